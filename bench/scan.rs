@@ -0,0 +1,59 @@
+//! Scan throughput of the built-in rules on a synthetic corpus
+//!
+//! Run with `cargo bench`; criterion reports MB/s for each benchmark and
+//! flags changes against the previous run.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use fastsecret::corpus::{Corpus, CorpusOptions};
+use fastsecret::scanner::Scanner;
+
+fn builtin_rules(c: &mut Criterion) {
+    let corpus = Corpus::generate(&CorpusOptions {
+        files: 50,
+        ..CorpusOptions::default()
+    });
+    let scanner = Scanner::builder().build().expect("built-in rules compile");
+
+    let mut group = c.benchmark_group("builtin-rules");
+    group.throughput(Throughput::Bytes(corpus.bytes()));
+    group.sample_size(20);
+    group.bench_function("scan", |b| {
+        b.iter(|| {
+            for (name, content) in &corpus.files {
+                let report = scanner
+                    .scan_bytes(name, content.as_bytes())
+                    .expect("in-memory scan");
+                black_box(report.findings);
+            }
+        })
+    });
+
+    // Secret-free content is the common case: most lines fail the prefilter
+    let clean = Corpus::generate(&CorpusOptions {
+        files: 50,
+        secrets_per_file: 0,
+        ..CorpusOptions::default()
+    });
+    group.throughput(Throughput::Bytes(clean.bytes()));
+    group.bench_function("scan-clean", |b| {
+        b.iter(|| {
+            for (name, content) in &clean.files {
+                let report = scanner
+                    .scan_bytes(name, content.as_bytes())
+                    .expect("in-memory scan");
+                black_box(report.findings);
+            }
+        })
+    });
+    group.finish();
+}
+
+fn rule_compilation(c: &mut Criterion) {
+    c.bench_function("compile-builtin-rules", |b| {
+        b.iter(|| black_box(Scanner::builder().build().expect("built-in rules compile")))
+    });
+}
+
+criterion_group!(benches, builtin_rules, rule_compilation);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+//! Generates the gRPC messages, service, and client of `fastsecret::grpc`
+//! from `proto/fastsecret.proto` when the `grpc` feature is enabled
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "grpc")]
+    {
+        // The bundled protoc keeps the build from needing one on the system
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        tonic_prost_build::compile_protos("proto/fastsecret.proto")?;
+    }
+    Ok(())
+}
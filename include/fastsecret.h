@@ -0,0 +1,131 @@
+/* fastsecret C API. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef FASTSECRET_H
+#define FASTSECRET_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Severity of a finding
+typedef enum FastsecretSeverity {
+  FASTSECRET_SEVERITY_LOW = 0,
+  FASTSECRET_SEVERITY_MEDIUM = 1,
+  FASTSECRET_SEVERITY_HIGH = 2,
+} FastsecretSeverity;
+
+// The findings from one scan
+typedef struct FastsecretFindings FastsecretFindings;
+
+// A scanner with its rules compiled, reusable across many scans
+typedef struct FastsecretScanner FastsecretScanner;
+
+// One finding; its strings are NUL-terminated UTF-8 owned by the
+// [`FastsecretFindings`] it came from
+typedef struct FastsecretFinding {
+  const char *rule_id;
+  const char *rule_name;
+  const char *file;
+  // The matched line with the secret masked
+  const char *snippet;
+  FastsecretSeverity severity;
+  // 1-based line number
+  size_t line;
+  // 1-based column where the secret starts
+  size_t column;
+  // 1-based column just past the end of the secret
+  size_t end_column;
+} FastsecretFinding;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a scanner with the built-in rules plus any custom rules given as
+// YAML (the `--rules` format); `rules_yaml` may be `NULL`
+//
+// Returns `NULL` on error.
+//
+// # Safety
+//
+// `rules_yaml` must be `NULL` or a valid NUL-terminated string.
+struct FastsecretScanner *fastsecret_scanner_new(const char *rules_yaml);
+
+// Free a scanner; `NULL` is ignored
+//
+// # Safety
+//
+// `scanner` must be `NULL` or a pointer from `fastsecret_scanner_new` that
+// has not been freed.
+void fastsecret_scanner_free(struct FastsecretScanner *scanner);
+
+// Scan `len` bytes at `data`, labelling findings with `filename` (which
+// also selects file-type handling such as `.env` variable names)
+//
+// `filename` may be `NULL` for `<input>`. Returns `NULL` on error.
+//
+// # Safety
+//
+// `scanner` must come from `fastsecret_scanner_new`; `data` must point to
+// `len` readable bytes (it may be `NULL` when `len` is 0); `filename` must
+// be `NULL` or a valid NUL-terminated string.
+struct FastsecretFindings *fastsecret_scan_buffer(const struct FastsecretScanner *scanner,
+                                                  const uint8_t *data,
+                                                  size_t len,
+                                                  const char *filename);
+
+// Number of findings; 0 for `NULL`
+//
+// # Safety
+//
+// `findings` must be `NULL` or a live pointer from a scan function.
+size_t fastsecret_findings_len(const struct FastsecretFindings *findings);
+
+// The finding at `index`, or `NULL` if out of range
+//
+// # Safety
+//
+// `findings` must be `NULL` or a live pointer from a scan function.
+const struct FastsecretFinding *fastsecret_findings_get(const struct FastsecretFindings *findings,
+                                                        size_t index);
+
+// All findings as a JSON array shaped like the CLI's JSON output
+//
+// Free the result with `fastsecret_string_free`. Returns `NULL` on error.
+//
+// # Safety
+//
+// `findings` must be a live pointer from a scan function.
+char *fastsecret_findings_to_json(const struct FastsecretFindings *findings);
+
+// Free findings returned by a scan function; `NULL` is ignored
+//
+// # Safety
+//
+// `findings` must be `NULL` or a pointer from a scan function that has not
+// been freed. Any [`FastsecretFinding`] borrowed from it becomes invalid.
+void fastsecret_findings_free(struct FastsecretFindings *findings);
+
+// Free a string returned by this library; `NULL` is ignored
+//
+// # Safety
+//
+// `s` must be `NULL` or a string from `fastsecret_findings_to_json` that
+// has not been freed.
+void fastsecret_string_free(char *s);
+
+// The message for the last error on this thread, or `NULL` if none
+//
+// The string is valid until the next failing call on this thread.
+const char *fastsecret_last_error(void);
+
+// The library version, e.g. `"0.1.0"`
+const char *fastsecret_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FASTSECRET_H */
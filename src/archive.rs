@@ -0,0 +1,249 @@
+//! Archive scanning
+//!
+//! Looks inside zip-based archives (zip, jar, war, whl, ...) and tarballs
+//! (tar, tar.gz, tgz) without extracting them to disk. Entries are
+//! decompressed in memory and scanned like regular files; findings are
+//! reported as `archive.zip!inner/path`. Nested archives are followed up to
+//! a depth limit, and size limits guard against decompression bombs.
+
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::Path;
+
+use crate::encoding;
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{
+    has_binary_extension, looks_binary, scan_binary, scan_lines, FileStats, Finding, ScanOptions,
+    SNIFF_LEN,
+};
+
+/// Limits applied while scanning archives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveOptions {
+    /// How many archives deep to follow nested archives
+    pub max_depth: usize,
+    /// Largest uncompressed entry to scan, in bytes
+    pub max_entry_size: u64,
+    /// Most uncompressed bytes to read from one top-level archive
+    pub max_total_size: u64,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        ArchiveOptions {
+            max_depth: 3,
+            max_entry_size: 64 << 20,
+            max_total_size: 512 << 20,
+        }
+    }
+}
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Detect an archive format from a file name's extension
+    pub fn from_name(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+        let has_ext = |exts: &[&str]| exts.iter().any(|e| lower.ends_with(e));
+
+        if has_ext(&[".tar.gz", ".tgz"]) {
+            Some(ArchiveKind::TarGz)
+        } else if has_ext(&[".tar"]) {
+            Some(ArchiveKind::Tar)
+        } else if has_ext(&[
+            ".zip", ".jar", ".war", ".ear", ".aar", ".apk", ".whl", ".nupkg",
+        ]) {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Scan every entry of an archive file
+pub(crate) fn scan_archive_file(
+    path: &Path,
+    kind: ArchiveKind,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    limits: &ArchiveOptions,
+    findings: &mut Vec<Finding>,
+) -> io::Result<FileStats> {
+    let mut scan = ArchiveScan::new(rule_set, options, limits, findings);
+    let name = path.display().to_string();
+    let file = File::open(path)?;
+    match kind {
+        ArchiveKind::Zip => scan.scan_zip(BufReader::new(file), &name, 0)?,
+        ArchiveKind::Tar => scan.scan_tar(BufReader::new(file), &name, 0)?,
+        ArchiveKind::TarGz => scan.scan_tar(GzDecoder::new(BufReader::new(file)), &name, 0)?,
+    }
+    Ok(scan.stats)
+}
+
+/// Scan every entry of a tar stream, naming them `name!entry/path`
+pub(crate) fn scan_tar_stream<R: Read>(
+    reader: R,
+    name: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    limits: &ArchiveOptions,
+    findings: &mut Vec<Finding>,
+) -> io::Result<FileStats> {
+    let mut scan = ArchiveScan::new(rule_set, options, limits, findings);
+    scan.scan_tar(reader, name, 0)?;
+    Ok(scan.stats)
+}
+
+/// State shared while walking one top-level archive
+struct ArchiveScan<'a> {
+    rule_set: &'a CompiledRuleSet,
+    options: &'a ScanOptions,
+    limits: &'a ArchiveOptions,
+    /// Uncompressed bytes left in the budget
+    remaining: u64,
+    stats: FileStats,
+    findings: &'a mut Vec<Finding>,
+}
+
+impl<'a> ArchiveScan<'a> {
+    fn new(
+        rule_set: &'a CompiledRuleSet,
+        options: &'a ScanOptions,
+        limits: &'a ArchiveOptions,
+        findings: &'a mut Vec<Finding>,
+    ) -> Self {
+        ArchiveScan {
+            rule_set,
+            options,
+            limits,
+            remaining: limits.max_total_size,
+            stats: FileStats::default(),
+            findings,
+        }
+    }
+
+    fn scan_zip<R: Read + Seek>(&mut self, reader: R, name: &str, depth: usize) -> io::Result<()> {
+        let mut zip = zip::ZipArchive::new(reader).map_err(io::Error::other)?;
+        for idx in 0..zip.len() {
+            let Ok(entry) = zip.by_index(idx) else {
+                continue;
+            };
+            if entry.is_dir() {
+                continue;
+            }
+            let inner = format!("{}!{}", name, entry.name());
+            let size = entry.size();
+            self.scan_entry(entry, &inner, size, depth)?;
+        }
+        Ok(())
+    }
+
+    fn scan_tar<R: Read>(&mut self, reader: R, name: &str, depth: usize) -> io::Result<()> {
+        let mut tar = tar::Archive::new(reader);
+        for entry in tar.entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let inner = format!("{}!{}", name, entry.path()?.display());
+            let size = entry.size();
+            self.scan_entry(entry, &inner, size, depth)?;
+        }
+        Ok(())
+    }
+
+    fn scan_entry<R: Read>(
+        &mut self,
+        reader: R,
+        name: &str,
+        size: u64,
+        depth: usize,
+    ) -> io::Result<()> {
+        let max_entry = self
+            .options
+            .max_file_size
+            .map_or(self.limits.max_entry_size, |m| {
+                m.min(self.limits.max_entry_size)
+            });
+        if size > max_entry || size > self.remaining {
+            self.skip(name, "too large");
+            return Ok(());
+        }
+
+        // Declared sizes can lie, so cap what is actually read too
+        let mut reader = reader.take(max_entry.min(self.remaining));
+
+        if let Some(kind) = ArchiveKind::from_name(name) {
+            if depth + 1 > self.limits.max_depth {
+                self.skip(name, "nested too deeply");
+                return Ok(());
+            }
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            self.remaining = self.remaining.saturating_sub(data.len() as u64);
+
+            // A corrupt nested archive shouldn't abort the outer one
+            let start = self.findings.len();
+            let nested = match kind {
+                ArchiveKind::Zip => self.scan_zip(Cursor::new(data), name, depth + 1),
+                ArchiveKind::Tar => self.scan_tar(Cursor::new(data), name, depth + 1),
+                ArchiveKind::TarGz => {
+                    self.scan_tar(GzDecoder::new(Cursor::new(data)), name, depth + 1)
+                }
+            };
+            if nested.is_err() {
+                self.findings.truncate(start);
+                self.skip(name, "unreadable archive");
+            }
+            return Ok(());
+        }
+
+        let mut sample = Vec::with_capacity(SNIFF_LEN);
+        (&mut reader)
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut sample)?;
+        let binary_extension = has_binary_extension(Path::new(name));
+        let detected = encoding::detect(&sample, binary_extension);
+        if detected.is_none() && looks_binary(&sample, binary_extension) {
+            if self.options.scan_binary {
+                let mut bytes = sample;
+                reader.read_to_end(&mut bytes)?;
+                let stats = scan_binary(&bytes, name, self.rule_set, self.options, self.findings);
+                self.stats.bytes += stats.bytes;
+                self.stats.lines += stats.lines;
+                self.remaining = self.remaining.saturating_sub(stats.bytes);
+            }
+            return Ok(());
+        }
+
+        let start = self.findings.len();
+        match scan_lines(
+            encoding::text_reader(sample, &mut reader, detected),
+            name,
+            self.rule_set,
+            self.options,
+            self.findings,
+        ) {
+            Ok(mut stats) => {
+                self.stats.bytes += stats.bytes;
+                self.stats.lines += stats.lines;
+                self.stats.errors.append(&mut stats.errors);
+                self.remaining = self.remaining.saturating_sub(stats.bytes);
+            }
+            // Binary content; skip the entry
+            Err(_) => self.findings.truncate(start),
+        }
+        Ok(())
+    }
+
+    fn skip(&self, name: &str, reason: &str) {
+        tracing::info!(file = name, "Skipping {} ({})", name, reason);
+    }
+}
@@ -0,0 +1,125 @@
+//! Async scanning for tokio services
+//!
+//! Behind the `tokio` feature, a [`Scanner`] can be driven from async code
+//! without blocking the runtime: scans run on tokio's blocking thread pool,
+//! and a directory scan can be consumed as a stream of findings that yields
+//! each file's findings as soon as that file is scanned.
+//!
+//! ```no_run
+//! use fastsecret::Scanner;
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let scanner = Scanner::builder().build()?;
+//! let mut findings = scanner.scan_path_stream("uploads/");
+//! while let Some(finding) = findings.next().await {
+//!     println!("{}:{} {}", finding.file, finding.line, finding.rule_id);
+//! }
+//! let report = findings.report().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use anyhow::{anyhow, Result};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+
+use crate::scanner::{Finding, ScanProgress, ScanReport, Scanner};
+
+/// Findings buffered between the scanning threads and the stream's reader
+/// before scanning waits for the reader to catch up
+const STREAM_BUFFER: usize = 256;
+
+impl Scanner {
+    /// Scan a file or directory on the blocking thread pool
+    pub async fn scan_path_async(&self, root: impl AsRef<Path>) -> Result<ScanReport> {
+        let scanner = self.clone();
+        let root = root.as_ref().to_path_buf();
+        join(tokio::task::spawn_blocking(move || scanner.scan_path(root))).await
+    }
+
+    /// Scan in-memory content on the blocking thread pool, labelling
+    /// findings with `name`
+    pub async fn scan_bytes_async(
+        &self,
+        name: impl Into<String>,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<ScanReport> {
+        let scanner = self.clone();
+        let name = name.into();
+        let bytes = bytes.into();
+        join(tokio::task::spawn_blocking(move || {
+            scanner.scan_bytes(&name, &bytes)
+        }))
+        .await
+    }
+
+    /// Scan a file or directory, yielding findings as each file is scanned
+    ///
+    /// Findings arrive in the order files finish, not in path order. Must be
+    /// called within a tokio runtime.
+    pub fn scan_path_stream(&self, root: impl AsRef<Path>) -> FindingStream {
+        let scanner = self.clone();
+        let root: PathBuf = root.as_ref().to_path_buf();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let task = tokio::task::spawn_blocking(move || {
+            scanner.scan_path_with_progress(root, &StreamProgress(tx))
+        });
+        FindingStream {
+            findings: ReceiverStream::new(rx),
+            task,
+        }
+    }
+}
+
+/// Findings of a running scan, from [`Scanner::scan_path_stream`]
+#[derive(Debug)]
+pub struct FindingStream {
+    findings: ReceiverStream<Finding>,
+    task: JoinHandle<Result<ScanReport>>,
+}
+
+impl FindingStream {
+    /// Wait for the scan to finish and return its report, with every
+    /// finding, the stats, and the files that couldn't be scanned
+    ///
+    /// Findings not yet read from the stream are dropped from it but are
+    /// still in the report.
+    pub async fn report(self) -> Result<ScanReport> {
+        // Let the scan finish rather than wait on a full channel
+        drop(self.findings);
+        join(self.task).await
+    }
+}
+
+impl Stream for FindingStream {
+    type Item = Finding;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Finding>> {
+        Pin::new(&mut self.findings).poll_next(cx)
+    }
+}
+
+/// Forwards each file's findings to a [`FindingStream`]
+struct StreamProgress(mpsc::Sender<Finding>);
+
+impl ScanProgress for StreamProgress {
+    fn findings(&self, _path: &Path, findings: &[Finding]) {
+        for finding in findings {
+            // The stream was dropped; the scan still runs to completion
+            if self.0.blocking_send(finding.clone()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn join(task: JoinHandle<Result<ScanReport>>) -> Result<ScanReport> {
+    task.await.map_err(|e| anyhow!("scan task failed: {}", e))?
+}
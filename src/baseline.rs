@@ -0,0 +1,205 @@
+//! Baseline files
+//!
+//! A baseline records the fingerprints of known findings so they can be
+//! suppressed in later scans, letting teams adopt fastsecret without first
+//! fixing every legacy finding.
+//!
+//! Each suppression can carry a `reason` and an `expires` date. Once that
+//! date passes the suppression no longer applies and the finding is reported
+//! again, so allowlisted secrets get revisited instead of being forgotten.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use crate::datetime::UtcDateTime;
+use crate::scanner::Finding;
+
+/// Bumped whenever the fingerprint format changes
+const BASELINE_VERSION: u32 = 2;
+
+/// A single suppressed finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub file: String,
+    pub line: usize,
+    /// Rule id
+    pub rule: String,
+    /// Why the finding is acceptable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Last day (`YYYY-MM-DD`, UTC) the suppression applies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+impl BaselineEntry {
+    /// Days from today until the suppression expires, negative once it has;
+    /// `None` if it never does
+    pub fn days_left(&self) -> Option<i64> {
+        let expires = UtcDateTime::parse_date(self.expires.as_deref()?)?;
+        Some(expires.days_since_epoch() - UtcDateTime::now().days_since_epoch())
+    }
+
+    /// Whether the expiry date has passed
+    pub fn is_expired(&self) -> bool {
+        self.days_left().is_some_and(|days| days < 0)
+    }
+}
+
+/// A set of known findings to suppress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Build a baseline covering every given finding
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        let mut seen = HashSet::new();
+        let entries = findings
+            .iter()
+            .filter_map(|f| {
+                let fingerprint = f.fingerprint();
+                seen.insert(fingerprint.clone()).then(|| BaselineEntry {
+                    fingerprint,
+                    file: f.file.clone(),
+                    line: f.line,
+                    rule: f.rule_id.clone(),
+                    reason: None,
+                    expires: None,
+                })
+            })
+            .collect();
+
+        Baseline {
+            version: BASELINE_VERSION,
+            findings: entries,
+        }
+    }
+
+    /// Load a baseline from a JSON file
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read baseline '{}'", path))?;
+        let baseline: Baseline = serde_json::from_str(&content)
+            .with_context(|| format!("invalid baseline file '{}'", path))?;
+        if baseline.version != BASELINE_VERSION {
+            bail!(
+                "baseline '{}' has version {} but version {} is required; \
+                 re-create it with `fastsecret baseline create`",
+                path,
+                baseline.version,
+                BASELINE_VERSION
+            );
+        }
+        for entry in &baseline.findings {
+            if let Some(expires) = &entry.expires {
+                if UtcDateTime::parse_date(expires).is_none() {
+                    bail!(
+                        "baseline '{}' has invalid expiry date '{}' for {}:{}; use YYYY-MM-DD",
+                        path,
+                        expires,
+                        entry.file,
+                        entry.line
+                    );
+                }
+            }
+        }
+        Ok(baseline)
+    }
+
+    /// Give every entry without a reason or expiry date the given ones
+    pub fn justify(&mut self, reason: Option<&str>, expires: Option<&str>) -> Result<()> {
+        if let Some(expires) = expires {
+            if UtcDateTime::parse_date(expires).is_none() {
+                bail!("invalid expiry date '{}'; use YYYY-MM-DD", expires);
+            }
+        }
+        for entry in &mut self.findings {
+            if entry.reason.is_none() {
+                entry.reason = reason.map(str::to_string);
+            }
+            if entry.expires.is_none() {
+                entry.expires = expires.map(str::to_string);
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the reasons and expiry dates of findings already in `previous`
+    pub fn carry_over(&mut self, previous: &Baseline) {
+        let known: HashMap<&str, &BaselineEntry> = previous
+            .findings
+            .iter()
+            .map(|e| (e.fingerprint.as_str(), e))
+            .collect();
+        for entry in &mut self.findings {
+            if let Some(old) = known.get(entry.fingerprint.as_str()) {
+                entry.reason = old.reason.clone();
+                entry.expires = old.expires.clone();
+            }
+        }
+    }
+
+    /// Entries that have expired or expire within `days` days, soonest first
+    pub fn expiring(&self, days: i64) -> Vec<(&BaselineEntry, i64)> {
+        let mut entries: Vec<(&BaselineEntry, i64)> = self
+            .findings
+            .iter()
+            .filter_map(|e| Some((e, e.days_left()?)))
+            .filter(|&(_, left)| left <= days)
+            .collect();
+        entries.sort_by_key(|&(e, left)| (left, e.file.as_str(), e.line));
+        entries
+    }
+
+    /// Entries that can never match a finding: malformed or repeated
+    /// fingerprints
+    pub fn problems(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut problems = Vec::new();
+        for entry in &self.findings {
+            let well_formed = entry.fingerprint.len() == 64
+                && entry.fingerprint.bytes().all(|b| b.is_ascii_hexdigit());
+            if !well_formed {
+                problems.push(format!(
+                    "malformed fingerprint for {}:{} [{}]",
+                    entry.file, entry.line, entry.rule
+                ));
+            } else if !seen.insert(entry.fingerprint.as_str()) {
+                problems.push(format!(
+                    "duplicate entry for {}:{} [{}]",
+                    entry.file, entry.line, entry.rule
+                ));
+            }
+        }
+        problems
+    }
+
+    /// Write the baseline as pretty-printed JSON
+    pub fn save(&self, path: &str) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content + "\n")
+            .with_context(|| format!("failed to write baseline '{}'", path))?;
+        Ok(())
+    }
+
+    /// Remove findings already present in the baseline, unless their
+    /// suppression has expired
+    pub fn filter(&self, findings: Vec<Finding>) -> Vec<Finding> {
+        let known: HashSet<&str> = self
+            .findings
+            .iter()
+            .filter(|e| !e.is_expired())
+            .map(|e| e.fingerprint.as_str())
+            .collect();
+        findings
+            .into_iter()
+            .filter(|f| !known.contains(f.fingerprint().as_str()))
+            .collect()
+    }
+}
@@ -0,0 +1,224 @@
+//! Scan result cache
+//!
+//! Remembers which files were scanned clean, keyed by path, modification
+//! time, size, and content hash, so a repeated scan can skip them. The
+//! cache is tied to a hash of the rules and scan options; changing either
+//! invalidates it. Files with findings are never cached and always rescanned,
+//! which keeps secrets out of the cache file.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{FileStats, ScanOptions};
+
+/// Bumped whenever the cache format or scanning semantics change
+const CACHE_VERSION: u32 = 1;
+
+/// Default directory for cache files: `$XDG_CACHE_HOME/fastsecret` or
+/// `~/.cache/fastsecret`
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".cache"))
+        })?;
+    Some(base.join("fastsecret"))
+}
+
+/// What is remembered about a file that scanned clean
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CacheEntry {
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    size: u64,
+    /// Hex SHA-256 of the file's content
+    hash: String,
+    bytes: u64,
+    lines: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    /// Hash of the rules and options the entries were produced with
+    ruleset: String,
+    files: HashMap<String, CacheEntry>,
+}
+
+/// Cache of clean files for one scan root
+#[derive(Debug)]
+pub(crate) struct ScanCache {
+    path: PathBuf,
+    ruleset: String,
+    previous: HashMap<String, CacheEntry>,
+    current: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl ScanCache {
+    /// Load the cache for `root` from `dir`, starting empty if it's missing,
+    /// unreadable, or was built with different rules
+    pub(crate) fn load(
+        dir: &Path,
+        root: &Path,
+        rule_set: &CompiledRuleSet,
+        options: &ScanOptions,
+    ) -> Self {
+        let canonical = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let key = hex_sha256(canonical.to_string_lossy().as_bytes());
+        let path = dir.join(format!("{}.json", &key[..16]));
+        let ruleset = ruleset_hash(rule_set, options);
+
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<CacheFile>(&data).ok())
+            .filter(|c| c.version == CACHE_VERSION && c.ruleset == ruleset)
+            .map(|c| c.files)
+            .unwrap_or_default();
+
+        ScanCache {
+            path,
+            ruleset,
+            previous,
+            current: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stats from the last scan of `file` if it is unchanged since then
+    pub(crate) fn lookup(&self, key: &str, file: &Path) -> Option<FileStats> {
+        let entry = self.previous.get(key)?;
+        let meta = fs::metadata(file).ok()?;
+        if meta.len() != entry.size {
+            return None;
+        }
+
+        let (secs, nanos) = mtime(&meta)?;
+        let entry = if (secs, nanos) == (entry.mtime_secs, entry.mtime_nanos) {
+            entry.clone()
+        } else {
+            // Touched (e.g. by a fresh checkout); trust it if the content matches
+            if hash_file(file).ok()? != entry.hash {
+                return None;
+            }
+            CacheEntry {
+                mtime_secs: secs,
+                mtime_nanos: nanos,
+                ..entry.clone()
+            }
+        };
+
+        let stats = FileStats {
+            bytes: entry.bytes,
+            lines: entry.lines,
+            errors: Vec::new(),
+        };
+        self.current.lock().unwrap().insert(key.to_string(), entry);
+        Some(stats)
+    }
+
+    /// Remember that `file` was scanned and had no findings
+    pub(crate) fn record_clean(&self, key: &str, file: &Path, stats: &FileStats) {
+        let Ok(meta) = fs::metadata(file) else {
+            return;
+        };
+        let (Some((secs, nanos)), Ok(hash)) = (mtime(&meta), hash_file(file)) else {
+            return;
+        };
+        let entry = CacheEntry {
+            mtime_secs: secs,
+            mtime_nanos: nanos,
+            size: meta.len(),
+            hash,
+            bytes: stats.bytes,
+            lines: stats.lines,
+        };
+        self.current.lock().unwrap().insert(key.to_string(), entry);
+    }
+
+    /// Write the entries recorded during this scan
+    pub(crate) fn save(self) -> io::Result<()> {
+        let cache = CacheFile {
+            version: CACHE_VERSION,
+            ruleset: self.ruleset,
+            files: self.current.into_inner().unwrap(),
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Write then rename so concurrent scans never see a partial file
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(&cache)?)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// Hash of everything that affects which findings a file produces
+fn ruleset_hash(rule_set: &CompiledRuleSet, options: &ScanOptions) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    for compiled in rule_set.rules() {
+        hasher.update(serde_json::to_vec(&compiled.rule).unwrap_or_default());
+    }
+    hasher.update(
+        format!(
+            "{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}",
+            options.ignore_rules,
+            options.only_tags,
+            options.skip_tags,
+            options.min_confidence,
+            options.entropy,
+            options.min_severity,
+            options.max_file_size,
+            options.archives,
+            options.scan_binary,
+            options.decode,
+            options.max_line_length,
+            options.syntax,
+            options.lockfiles.mode,
+            options.lockfiles.globs(),
+            options.detectors.cache_keys(),
+            options
+                .rule_scopes
+                .iter()
+                .map(|s| (s.paths(), &s.ignore_rules, &s.extra_rules))
+                .collect::<Vec<_>>()
+        )
+        .as_bytes(),
+    );
+    format!("{:x}", hasher.finalize())
+}
+
+fn mtime(meta: &fs::Metadata) -> Option<(u64, u32)> {
+    let since_epoch = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
@@ -0,0 +1,528 @@
+//! Object storage scanning
+//!
+//! Lists the objects under an `s3://bucket/prefix` or `gs://bucket/prefix`
+//! URL and streams each one through the scanner, so buckets full of config
+//! dumps and backups can be audited without copying them to disk first.
+//! Findings are reported as `s3://bucket/key`. Limits on the number of
+//! objects and on each object's size keep a scan of a huge bucket bounded.
+//!
+//! S3 requests are signed with the standard `AWS_*` environment variables
+//! and `AWS_ENDPOINT_URL` points at S3-compatible stores such as MinIO. GCS
+//! requests send `GOOGLE_OAUTH_ACCESS_TOKEN` as a bearer token. Without
+//! credentials only public buckets can be read.
+
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::encoding;
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{
+    has_binary_extension, looks_binary, scan_binary, scan_lines, Finding, ScanError, ScanErrorKind,
+    ScanOptions, SNIFF_LEN,
+};
+use crate::sigv4::{self, uri_encode};
+
+pub use crate::sigv4::Credentials as AwsCredentials;
+
+/// Objects requested per listing page
+const PAGE_SIZE: usize = 1000;
+
+/// Object storage services
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    S3,
+    Gcs,
+}
+
+/// A bucket, and optionally a key prefix within it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketUrl {
+    pub provider: Provider,
+    pub bucket: String,
+    pub prefix: String,
+}
+
+impl BucketUrl {
+    /// Parse `s3://bucket/prefix` or `gs://bucket/prefix`
+    pub fn parse(url: &str) -> Option<Self> {
+        let (provider, rest) = if let Some(rest) = url.strip_prefix("s3://") {
+            (Provider::S3, rest)
+        } else if let Some(rest) = url.strip_prefix("gs://") {
+            (Provider::Gcs, rest)
+        } else {
+            return None;
+        };
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return None;
+        }
+        Some(BucketUrl {
+            provider,
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// URL of one object in the bucket
+    pub fn object_url(&self, key: &str) -> String {
+        let scheme = match self.provider {
+            Provider::S3 => "s3",
+            Provider::Gcs => "gs",
+        };
+        format!("{}://{}/{}", scheme, self.bucket, key)
+    }
+}
+
+impl fmt::Display for BucketUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.object_url(&self.prefix))
+    }
+}
+
+/// Limits and credentials for bucket scans
+#[derive(Debug, Clone)]
+pub struct BucketOptions {
+    /// Most objects to scan; the rest of the listing is ignored
+    pub max_objects: usize,
+    /// Largest object to scan, in bytes
+    pub max_object_size: u64,
+    /// AWS region of S3 buckets
+    pub region: String,
+    /// Base URL of an S3-compatible or GCS-compatible service, used instead
+    /// of the public endpoints
+    pub endpoint: Option<String>,
+    /// Credentials for signing S3 requests
+    pub aws_credentials: Option<AwsCredentials>,
+    /// OAuth access token for GCS requests
+    pub gcs_token: Option<String>,
+    /// Timeout for each HTTP request
+    pub timeout: Duration,
+}
+
+impl Default for BucketOptions {
+    fn default() -> Self {
+        BucketOptions {
+            max_objects: 10_000,
+            max_object_size: 16 << 20,
+            region: "us-east-1".to_string(),
+            endpoint: None,
+            aws_credentials: None,
+            gcs_token: None,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl BucketOptions {
+    /// Defaults with the region, endpoint, and credentials read from the
+    /// usual AWS and Google environment variables
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let aws_credentials = match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+            (Some(key_id), Some(secret)) => Some(AwsCredentials {
+                key_id,
+                secret,
+                session_token: var("AWS_SESSION_TOKEN"),
+            }),
+            _ => None,
+        };
+        let defaults = BucketOptions::default();
+        BucketOptions {
+            region: var("AWS_REGION")
+                .or_else(|| var("AWS_DEFAULT_REGION"))
+                .unwrap_or(defaults.region.clone()),
+            endpoint: var("AWS_ENDPOINT_URL_S3").or_else(|| var("AWS_ENDPOINT_URL")),
+            aws_credentials,
+            gcs_token: var("GOOGLE_OAUTH_ACCESS_TOKEN"),
+            ..defaults
+        }
+    }
+}
+
+/// Findings from a bucket scan, with what was left out
+#[derive(Debug, Clone, Default)]
+pub struct BucketScan {
+    pub findings: Vec<Finding>,
+    pub objects_scanned: usize,
+    /// Objects skipped as too large, binary, or unreadable
+    pub objects_skipped: usize,
+    /// Objects, or parts of them, that couldn't be scanned: too large,
+    /// unreadable, or failed by a custom detector
+    pub errors: Vec<ScanError>,
+    /// Whether the listing had more objects than `max_objects`
+    pub truncated: bool,
+}
+
+/// An object found while listing
+#[derive(Debug, Clone)]
+struct Object {
+    key: String,
+    size: u64,
+}
+
+/// Outcome of scanning one object
+enum ObjectResult {
+    Scanned(Vec<Finding>, Vec<ScanError>),
+    Skipped,
+    Failed(ScanError),
+}
+
+/// List the objects under `url` and scan each of them
+///
+/// Objects are downloaded and scanned on `options.threads` threads, one
+/// stream at a time per thread, without being written to disk.
+pub fn scan_bucket(
+    url: &BucketUrl,
+    bucket: &BucketOptions,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<BucketScan> {
+    let client = Client::new(url, bucket);
+    let (objects, truncated) = client.list(&url.prefix, bucket.max_objects)?;
+
+    let max_size = options
+        .max_file_size
+        .map_or(bucket.max_object_size, |m| m.min(bucket.max_object_size));
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()?;
+    let results: Vec<ObjectResult> = pool.install(|| {
+        objects
+            .par_iter()
+            .map(|object| {
+                let name = url.object_url(&object.key);
+                if object.size > max_size {
+                    return ObjectResult::Failed(ScanError {
+                        file: name,
+                        kind: ScanErrorKind::TooLarge,
+                        message: format!(
+                            "{} bytes exceeds max object size of {} bytes",
+                            object.size, max_size
+                        ),
+                    });
+                }
+                match client.scan_object(object, &name, max_size, rule_set, options) {
+                    Ok(Some((findings, errors))) => ObjectResult::Scanned(findings, errors),
+                    Ok(None) => ObjectResult::Skipped,
+                    Err(e) => ObjectResult::Failed(ScanError {
+                        file: name,
+                        kind: ScanErrorKind::Unreadable,
+                        message: format!("{:#}", e),
+                    }),
+                }
+            })
+            .collect()
+    });
+
+    let mut scan = BucketScan {
+        truncated,
+        ..Default::default()
+    };
+    for result in results {
+        match result {
+            ObjectResult::Scanned(findings, errors) => {
+                scan.objects_scanned += 1;
+                scan.findings.extend(findings);
+                scan.errors.extend(errors);
+            }
+            ObjectResult::Skipped => scan.objects_skipped += 1,
+            ObjectResult::Failed(error) => {
+                scan.objects_skipped += 1;
+                scan.errors.push(error);
+            }
+        }
+    }
+    Ok(scan)
+}
+
+/// HTTP access to one bucket
+struct Client<'a> {
+    agent: ureq::Agent,
+    url: &'a BucketUrl,
+    options: &'a BucketOptions,
+}
+
+impl<'a> Client<'a> {
+    fn new(url: &'a BucketUrl, options: &'a BucketOptions) -> Self {
+        Client {
+            agent: ureq::AgentBuilder::new()
+                .timeout(options.timeout)
+                .user_agent(concat!("fastsecret/", env!("CARGO_PKG_VERSION")))
+                .build(),
+            url,
+            options,
+        }
+    }
+
+    /// Keys and sizes of up to `limit` objects under `prefix`, and whether
+    /// more were left out
+    fn list(&self, prefix: &str, limit: usize) -> Result<(Vec<Object>, bool)> {
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let (page, next) = match self.url.provider {
+                Provider::S3 => self.list_s3_page(prefix, token.as_deref())?,
+                Provider::Gcs => self.list_gcs_page(prefix, token.as_deref())?,
+            };
+            // Zero-byte keys ending in `/` are folder placeholders
+            objects.extend(page.into_iter().filter(|o| !o.key.ends_with('/')));
+            if objects.len() > limit {
+                objects.truncate(limit);
+                return Ok((objects, true));
+            }
+            match next {
+                Some(next) => token = Some(next),
+                None => return Ok((objects, false)),
+            }
+        }
+    }
+
+    fn list_s3_page(
+        &self,
+        prefix: &str,
+        token: Option<&str>,
+    ) -> Result<(Vec<Object>, Option<String>)> {
+        let mut query = Vec::new();
+        if let Some(token) = token {
+            query.push(format!("continuation-token={}", uri_encode(token, false)));
+        }
+        query.push("list-type=2".to_string());
+        query.push(format!("max-keys={}", PAGE_SIZE));
+        query.push(format!("prefix={}", uri_encode(prefix, false)));
+        let body = self
+            .s3_get("", &query.join("&"))?
+            .into_string()
+            .context("failed to read the bucket listing")?;
+
+        let objects = xml_elements(&body, "Contents")
+            .into_iter()
+            .filter_map(|contents| {
+                Some(Object {
+                    key: xml_text(contents, "Key")?,
+                    size: xml_text(contents, "Size")?.parse().ok()?,
+                })
+            })
+            .collect();
+        let next = match xml_text(&body, "IsTruncated").as_deref() {
+            Some("true") => xml_text(&body, "NextContinuationToken"),
+            _ => None,
+        };
+        Ok((objects, next))
+    }
+
+    fn list_gcs_page(
+        &self,
+        prefix: &str,
+        token: Option<&str>,
+    ) -> Result<(Vec<Object>, Option<String>)> {
+        let mut url = format!(
+            "{}/storage/v1/b/{}/o?fields=items(name,size),nextPageToken&maxResults={}&prefix={}",
+            self.gcs_base(),
+            uri_encode(&self.url.bucket, false),
+            PAGE_SIZE,
+            uri_encode(prefix, false)
+        );
+        if let Some(token) = token {
+            url.push_str(&format!("&pageToken={}", uri_encode(token, false)));
+        }
+        let body: serde_json::Value = self
+            .gcs_get(&url)?
+            .into_json()
+            .context("failed to read the bucket listing")?;
+
+        let objects = body["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| {
+                Some(Object {
+                    key: item["name"].as_str()?.to_string(),
+                    // The JSON API sends sizes as strings
+                    size: item["size"].as_str()?.parse().ok()?,
+                })
+            })
+            .collect();
+        let next = body["nextPageToken"].as_str().map(String::from);
+        Ok((objects, next))
+    }
+
+    /// Stream one object through the scanner, returning its findings and
+    /// the detectors that failed on it; `None` when it was skipped
+    fn scan_object(
+        &self,
+        object: &Object,
+        name: &str,
+        max_size: u64,
+        rule_set: &CompiledRuleSet,
+        options: &ScanOptions,
+    ) -> Result<Option<(Vec<Finding>, Vec<ScanError>)>> {
+        let options = &*options.for_path(&object.key);
+        let response = match self.url.provider {
+            Provider::S3 => self.s3_get(&uri_encode(&object.key, true), "")?,
+            Provider::Gcs => self.gcs_get(&format!(
+                "{}/storage/v1/b/{}/o/{}?alt=media",
+                self.gcs_base(),
+                uri_encode(&self.url.bucket, false),
+                uri_encode(&object.key, false)
+            ))?,
+        };
+        // The listed size can be stale, so cap what is actually read too
+        let mut reader = response.into_reader().take(max_size);
+
+        let mut findings = Vec::new();
+        let mut sample = Vec::with_capacity(SNIFF_LEN);
+        (&mut reader)
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut sample)?;
+        let binary_extension = has_binary_extension(Path::new(&object.key));
+        let detected = encoding::detect(&sample, binary_extension);
+        if detected.is_none() && looks_binary(&sample, binary_extension) {
+            if !options.scan_binary {
+                return Ok(None);
+            }
+            let mut bytes = sample;
+            reader.read_to_end(&mut bytes)?;
+            scan_binary(&bytes, name, rule_set, options, &mut findings);
+            return Ok(Some((findings, Vec::new())));
+        }
+        match scan_lines(
+            encoding::text_reader(sample, reader, detected),
+            name,
+            rule_set,
+            options,
+            &mut findings,
+        ) {
+            Ok(stats) => Ok(Some((findings, stats.errors))),
+            // Binary content past the sniffed sample
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// GET an S3 path (already encoded, relative to the bucket) with an
+    /// encoded query string sorted by key
+    fn s3_get(&self, path: &str, query: &str) -> Result<ureq::Response> {
+        let bucket = &self.url.bucket;
+        // Custom endpoints use path-style addressing, AWS virtual-hosted
+        let (base, path) = match &self.options.endpoint {
+            Some(endpoint) => (
+                endpoint.trim_end_matches('/').to_string(),
+                match path {
+                    "" => format!("/{}", uri_encode(bucket, false)),
+                    path => format!("/{}/{}", uri_encode(bucket, false), path),
+                },
+            ),
+            None => (
+                format!(
+                    "https://{}.s3.{}.amazonaws.com",
+                    bucket, self.options.region
+                ),
+                format!("/{}", path),
+            ),
+        };
+        let url = match query {
+            "" => format!("{}{}", base, path),
+            query => format!("{}{}?{}", base, path, query),
+        };
+
+        let mut request = self.agent.get(&url);
+        if let Some(credentials) = &self.options.aws_credentials {
+            let host = base.split_once("://").map_or(base.as_str(), |(_, h)| h);
+            let amz_date = sigv4::amz_date(SystemTime::now());
+            let payload_hash = sigv4::hex_sha256(b"");
+            let mut headers = vec![
+                ("host", host),
+                ("x-amz-content-sha256", payload_hash.as_str()),
+                ("x-amz-date", amz_date.as_str()),
+            ];
+            if let Some(token) = &credentials.session_token {
+                headers.push(("x-amz-security-token", token));
+            }
+            let authorization = sigv4::authorization(
+                credentials,
+                &self.options.region,
+                "s3",
+                &amz_date,
+                &sigv4::Request {
+                    method: "GET",
+                    path: &path,
+                    query,
+                    headers: &headers,
+                    payload_hash: &payload_hash,
+                },
+            );
+            for (name, value) in headers.iter().skip(1) {
+                request = request.set(name, value);
+            }
+            request = request.set("Authorization", &authorization);
+        }
+        self.send(request)
+    }
+
+    fn gcs_base(&self) -> String {
+        self.options
+            .endpoint
+            .as_deref()
+            .unwrap_or("https://storage.googleapis.com")
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    fn gcs_get(&self, url: &str) -> Result<ureq::Response> {
+        let mut request = self.agent.get(url);
+        if let Some(token) = &self.options.gcs_token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        self.send(request)
+    }
+
+    fn send(&self, request: ureq::Request) -> Result<ureq::Response> {
+        match request.call() {
+            Ok(response) if response.status() < 300 => Ok(response),
+            Ok(response) => bail!("{} returned HTTP {}", self.url, response.status()),
+            Err(ureq::Error::Status(code, response)) => {
+                let hint = match (self.url.provider, code) {
+                    (Provider::S3, 301) => {
+                        let region = response.header("x-amz-bucket-region").unwrap_or("?");
+                        format!("; the bucket is in region '{}', set AWS_REGION", region)
+                    }
+                    (_, 401 | 403) => "; check the credentials".to_string(),
+                    _ => String::new(),
+                };
+                bail!("{} returned HTTP {}{}", self.url, code, hint)
+            }
+            Err(e) => Err(e).with_context(|| format!("failed to reach {}", self.url)),
+        }
+    }
+}
+
+/// Bodies of every `<tag>...</tag>` element in `xml`, without nesting
+fn xml_elements<'x>(xml: &'x str, tag: &str) -> Vec<&'x str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let body = &rest[start + open.len()..];
+        let Some(end) = body.find(&close) else {
+            break;
+        };
+        elements.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    elements
+}
+
+/// Unescaped text of the first `<tag>` element in `xml`
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    let text = xml_elements(xml, tag).into_iter().next()?;
+    Some(
+        text.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
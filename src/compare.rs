@@ -0,0 +1,103 @@
+//! Comparing two scan reports
+//!
+//! `fastsecret compare old.json new.json` matches the findings of two JSON
+//! or NDJSON reports by fingerprint and sorts them into new, resolved, and
+//! persisting findings, so a nightly scan can be judged by what changed
+//! rather than by everything it found. A fingerprint ignores the line
+//! number, so a secret that merely moved still counts as persisting.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::scanner::{FindingSeverity, SeverityCounts};
+
+/// A finding as read back from a JSON report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportedFinding {
+    pub fingerprint: String,
+    pub file: String,
+    pub line: usize,
+    pub rule_id: String,
+    /// Display name of the rule
+    #[serde(rename = "rule")]
+    pub rule_name: String,
+    pub severity: FindingSeverity,
+    #[serde(default)]
+    pub snippet: String,
+}
+
+/// Read the findings of a report written with `--format json` or
+/// `--format ndjson`
+pub fn load_report(path: &str) -> Result<Vec<ReportedFinding>> {
+    let data = fs::read_to_string(path).with_context(|| format!("failed to read '{}'", path))?;
+    parse_report(&data).with_context(|| format!("'{}' isn't a JSON or NDJSON report", path))
+}
+
+/// Parse the findings of a JSON array or NDJSON report
+pub fn parse_report(data: &str) -> Result<Vec<ReportedFinding>> {
+    if data.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(data)?);
+    }
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| format!("invalid finding on line {}", i + 1))
+        })
+        .collect()
+}
+
+/// The difference between an older and a newer report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Comparison {
+    /// Findings only in the newer report
+    pub new: Vec<ReportedFinding>,
+    /// Findings only in the older report
+    pub resolved: Vec<ReportedFinding>,
+    /// Findings in both, as they appear in the newer report
+    pub persisting: Vec<ReportedFinding>,
+}
+
+impl Comparison {
+    /// Match the findings of `old` and `new` by fingerprint
+    ///
+    /// A fingerprint found twice in one report and once in the other
+    /// counts as one persisting finding and one new or resolved one.
+    pub fn new(old: Vec<ReportedFinding>, new: Vec<ReportedFinding>) -> Self {
+        let mut remaining: HashMap<String, usize> = HashMap::new();
+        for f in &old {
+            *remaining.entry(f.fingerprint.clone()).or_default() += 1;
+        }
+
+        let mut comparison = Comparison::default();
+        for f in new {
+            match remaining.get_mut(&f.fingerprint) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    comparison.persisting.push(f);
+                }
+                _ => comparison.new.push(f),
+            }
+        }
+        // The last occurrences of a fingerprint are the resolved ones
+        for f in old.into_iter().rev() {
+            if let Some(n) = remaining.get_mut(&f.fingerprint).filter(|n| **n > 0) {
+                *n -= 1;
+                comparison.resolved.push(f);
+            }
+        }
+        comparison.resolved.reverse();
+        comparison
+    }
+
+    /// Severity counts of the new findings
+    pub fn new_counts(&self) -> SeverityCounts {
+        let mut counts = SeverityCounts::default();
+        for f in &self.new {
+            counts.add(f.severity);
+        }
+        counts
+    }
+}
@@ -0,0 +1,126 @@
+//! Confidence scoring
+//!
+//! Estimates how likely a finding is to be a real credential rather than a
+//! placeholder, test value, or coincidental match. The score combines how
+//! specific the rule is, the randomness of the matched text, where the file
+//! lives, and telltale words like `example` or `dummy`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::entropy::shannon_entropy;
+use crate::rules::Rule;
+
+/// How likely a finding is to be a real secret, ordered from `Low` to `High`
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    /// Lowercase name, as in reports and config files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "high" => Ok(Confidence::High),
+            "medium" => Ok(Confidence::Medium),
+            "low" => Ok(Confidence::Low),
+            _ => Err(format!("Unknown confidence: {}", s)),
+        }
+    }
+}
+
+/// Words that mark a value as a placeholder or test data
+pub(crate) const PLACEHOLDER_MARKERS: &[&str] = &[
+    "example",
+    "sample",
+    "dummy",
+    "fake",
+    "test",
+    "placeholder",
+    "changeme",
+    "redacted",
+    "xxxx",
+    "your",
+];
+
+/// Path components of files that usually hold test or documentation data
+const TEST_PATH_MARKERS: &[&str] = &[
+    "test", "tests", "spec", "specs", "fixture", "fixtures", "testdata", "example", "examples",
+    "sample", "samples", "mock", "mocks", "docs",
+];
+
+/// Score a match of `rule` (or of the entropy engine, when `None`)
+pub fn score(rule: Option<&Rule>, secret: &str, path: &str) -> Confidence {
+    let mut score: i32 = match rule {
+        None => 35,
+        // A multi-line block such as a PEM key is unambiguous
+        Some(rule) if rule.multiline => 80,
+        Some(rule) => {
+            let lower = secret.to_ascii_lowercase();
+            let prefixed = rule
+                .keywords
+                .iter()
+                .any(|k| !k.is_empty() && lower.starts_with(&k.to_ascii_lowercase()));
+            // A vendor prefix like `ghp_` or `AKIA` in the secret itself
+            if prefixed {
+                75
+            } else {
+                55
+            }
+        }
+    };
+
+    let entropy = shannon_entropy(secret);
+    if entropy >= 4.0 {
+        score += 10;
+    } else if entropy < 3.0 {
+        score -= 20;
+    }
+
+    let lower = secret.to_ascii_lowercase();
+    if PLACEHOLDER_MARKERS.iter().any(|m| lower.contains(m)) {
+        score -= 30;
+    }
+    if is_test_path(path) {
+        score -= 20;
+    }
+
+    match score {
+        70.. => Confidence::High,
+        40..=69 => Confidence::Medium,
+        _ => Confidence::Low,
+    }
+}
+
+/// Whether a path looks like test, example, or documentation data
+fn is_test_path(path: &str) -> bool {
+    let path = path.to_ascii_lowercase().replace('\\', "/");
+    if path.ends_with(".md") || path.ends_with(".rst") {
+        return true;
+    }
+    path.split(['/', '!']).any(|component| {
+        let stem = component.split('.').next().unwrap_or(component);
+        TEST_PATH_MARKERS.contains(&component)
+            || stem.ends_with("_test")
+            || stem.starts_with("test_")
+            || component.contains(".test.")
+            || component.contains(".spec.")
+    })
+}
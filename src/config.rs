@@ -0,0 +1,323 @@
+//! Repository configuration
+//!
+//! Loads `fastsecret.toml` (or `.fastsecret.toml` / `.fastsecret.yml`)
+//! discovered by walking up from the scan root. Every setting is optional;
+//! command-line flags take precedence over values found here.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::confidence::Confidence;
+use crate::context::TestContextMode;
+use crate::lockfile::LockfileMode;
+use crate::notifications::PayloadFormat;
+use crate::policy::{Policy, SeverityOverride};
+use crate::report::OutputFormat;
+use crate::rules::RulesFormat;
+use crate::scanner::{parse_size, Dedup, FailOn, FindingSeverity, GroupBy, SortOrder};
+
+/// File names searched for in each directory, in order of preference
+pub const CONFIG_FILE_NAMES: &[&str] = &[
+    "fastsecret.toml",
+    ".fastsecret.toml",
+    ".fastsecret.yml",
+    ".fastsecret.yaml",
+];
+
+/// Parsed configuration file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub scan: ScanConfig,
+    pub rules: RulesConfig,
+    pub ignore: IgnoreConfig,
+    pub lockfiles: LockfilesConfig,
+    pub test_context: TestContextConfig,
+    pub notify: NotifyConfig,
+    pub policy: Policy,
+    /// Named settings selected with `--profile`, such as `dev` and `prod`
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Rule settings for parts of the tree
+    pub overrides: Vec<PathOverride>,
+    /// Directory containing the config file; relative paths resolve here
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+/// `[scan]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanConfig {
+    pub exit_on_secrets: Option<bool>,
+    /// Fail when findings at or above this severity exist
+    pub fail_on: Option<FailOn>,
+    /// Fail when files or directories couldn't be scanned
+    pub strict: Option<bool>,
+    pub verbose: Option<bool>,
+    pub format: Option<OutputFormat>,
+    /// Line written per finding with `format = "template"`
+    pub template: Option<String>,
+    /// Drop findings below this severity
+    pub min_severity: Option<FindingSeverity>,
+    /// Drop findings below this confidence
+    pub min_confidence: Option<Confidence>,
+    /// Demote or drop findings that look like placeholders
+    pub fp_filter: Option<bool>,
+    pub threads: Option<usize>,
+    pub entropy_threshold: Option<f64>,
+    pub redact: Option<bool>,
+    /// Lines of context shown before and after each finding
+    pub context: Option<usize>,
+    /// SQLite database each scan run is recorded in
+    pub record_db: Option<String>,
+    /// Skip files larger than this; a byte count or a string like `"10MB"`
+    #[serde(deserialize_with = "size")]
+    pub max_file_size: Option<u64>,
+    /// Look inside zip and tar archives
+    pub scan_archives: Option<bool>,
+    /// Match rules against the strings in binary files
+    pub scan_binary: Option<bool>,
+    /// Decode base64 and hex blobs and scan the decoded text
+    pub decode: Option<bool>,
+    /// Match longer lines in windows of this size; a byte count or a string
+    /// like `"64KB"`
+    #[serde(deserialize_with = "size")]
+    pub max_line_length: Option<u64>,
+    /// Resolve findings in minified files through their source maps
+    pub source_maps: Option<bool>,
+    /// Only report matches inside string literals of parsed source files
+    pub syntax: Option<bool>,
+    /// Read files through memory maps instead of heap buffers
+    pub mmap: Option<bool>,
+    /// Detector plugins compiled to WebAssembly
+    #[serde(deserialize_with = "one_or_many")]
+    pub plugins: Vec<String>,
+    /// Follow symbolic links while walking directories
+    pub follow_symlinks: Option<bool>,
+    /// How many directories deep to descend below the scan root
+    pub max_depth: Option<usize>,
+    /// Order to list findings in
+    pub sort: Option<SortOrder>,
+    /// Group findings by file or rule
+    pub group_by: Option<GroupBy>,
+    /// Report each location, or each distinct secret once
+    pub dedup: Option<Dedup>,
+    /// Most findings listed per file
+    pub max_findings_per_file: Option<usize>,
+    /// Most findings listed per rule
+    pub max_findings_per_rule: Option<usize>,
+    /// Add the salted `secret_sha256` of each secret to findings
+    pub hash_secrets: Option<bool>,
+    /// Salt for the `secret_sha256` of findings; prefer the environment
+    /// variable, since knowing the salt makes guessing secrets easier
+    pub secret_salt: Option<String>,
+    /// Only scan files matching these globs, relative to the scan root
+    pub include: Vec<String>,
+    /// `[profiles]` entry to apply when `--profile` isn't given
+    pub profile: Option<String>,
+}
+
+/// `[rules]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesConfig {
+    /// One or more custom rule files
+    #[serde(deserialize_with = "one_or_many")]
+    pub custom_rules: Vec<String>,
+    /// Format of the custom rule files
+    pub format: Option<RulesFormat>,
+    pub ignore_rules: Vec<String>,
+    /// Built-in rule packs to load; all of them when empty
+    pub packs: Vec<String>,
+    /// Only run rules carrying one of these tags
+    pub only_tags: Vec<String>,
+    /// Skip rules carrying any of these tags
+    pub skip_tags: Vec<String>,
+}
+
+/// `[ignore]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IgnoreConfig {
+    /// Extra directory names to skip wherever they appear
+    pub directories: Vec<String>,
+    /// Gitignore-style globs, relative to the scan root, to skip
+    pub paths: Vec<String>,
+}
+
+/// `[lockfiles]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LockfilesConfig {
+    /// How to scan recognized lockfiles and vendored dependencies
+    pub mode: Option<LockfileMode>,
+    /// Globs of files to skip
+    pub skip: Vec<String>,
+    /// Globs of files to scan with the restricted rule subset
+    pub restricted: Vec<String>,
+    /// Globs of files to scan in full, even if recognized as lockfiles
+    pub scan: Vec<String>,
+}
+
+/// `[test_context]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TestContextConfig {
+    /// Whether to tag or downgrade findings in test code
+    pub mode: Option<TestContextMode>,
+    /// Globs of test paths, replacing the built-in list
+    pub paths: Option<Vec<String>>,
+    /// Case-sensitive sample-value markers, replacing the built-in list
+    pub markers: Option<Vec<String>>,
+}
+
+/// A `[profiles.<name>]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Severity overrides applied after the `[[policy.severity]]` entries
+    pub severity: Vec<SeverityOverride>,
+}
+
+/// An `[[overrides]]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathOverride {
+    /// Globs of the files the section applies to, relative to the scan root
+    pub paths: Vec<String>,
+    /// Rules, by id or name, skipped in those files
+    pub ignore_rules: Vec<String>,
+    /// Custom rule files whose rules run only in those files
+    #[serde(deserialize_with = "one_or_many")]
+    pub extra_rules: Vec<String>,
+}
+
+/// `[notify]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Webhook to post findings to; prefer the environment variable for
+    /// URLs that embed a token
+    pub webhook: Option<String>,
+    /// Payload format; inferred from the URL when unset
+    pub format: Option<PayloadFormat>,
+    /// Only notify about findings at or above this severity
+    pub min_severity: Option<FindingSeverity>,
+}
+
+/// Accept either a single string or a list of strings
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(s) => vec![s],
+        OneOrMany::Many(v) => v,
+    })
+}
+
+/// Accept either a byte count or a human-readable size string
+fn size<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+
+    match Size::deserialize(deserializer)? {
+        Size::Bytes(n) => Ok(Some(n)),
+        Size::Text(s) => parse_size(&s).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+impl Config {
+    /// Load a config file, choosing the parser by extension
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config '{}'", path.display()))?;
+
+        let is_yaml = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yml") | Some("yaml")
+        );
+        let mut config: Config = if is_yaml {
+            serde_yaml::from_str(&content)
+                .with_context(|| format!("invalid config '{}'", path.display()))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("invalid config '{}'", path.display()))?
+        };
+
+        config.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    /// Find the nearest config file at or above `start`
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        let start = start.canonicalize().ok()?;
+        let dir = if start.is_file() {
+            start.parent()?.to_path_buf()
+        } else {
+            start
+        };
+
+        dir.ancestors()
+            .flat_map(|d| CONFIG_FILE_NAMES.iter().map(move |name| d.join(name)))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Load the nearest config file at or above `start`, if any
+    pub fn discover(start: &Path) -> Result<Option<Self>> {
+        Config::find(start).map(|p| Config::load(&p)).transpose()
+    }
+
+    /// The `[profiles]` entry called `name`
+    pub fn profile(&self, name: &str) -> Result<&ProfileConfig> {
+        self.profiles.get(name).with_context(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                format!(
+                    "unknown profile '{}': the config defines no [profiles]",
+                    name
+                )
+            } else {
+                format!(
+                    "unknown profile '{}' (the config defines {})",
+                    name,
+                    known.join(", ")
+                )
+            }
+        })
+    }
+
+    /// The `[policy]` section with the severity overrides of `profile`, if
+    /// any, added after its own so that they win
+    pub fn policy_for(&self, profile: Option<&str>) -> Result<Policy> {
+        let mut policy = self.policy.clone();
+        if let Some(name) = profile {
+            policy
+                .severity
+                .extend(self.profile(name)?.severity.iter().cloned());
+        }
+        Ok(policy)
+    }
+
+    /// Resolve a path from the config relative to the config file
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        self.base_dir.join(path)
+    }
+}
@@ -0,0 +1,131 @@
+//! Test-fixture context
+//!
+//! Secrets in test suites, fixtures, and examples are usually fake, but
+//! not always, so they shouldn't vanish from a report. Findings under paths
+//! like `tests/` or `fixtures/`, or on lines with markers like `EXAMPLE` or
+//! `dummy`, can instead be tagged `context: test` in their metadata, and
+//! optionally dropped to low confidence. A `[[policy.severity]]` entry with
+//! `context = "test"` then treats them differently.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::confidence::Confidence;
+use crate::scanner::Finding;
+
+/// Metadata key holding a finding's context
+pub const CONTEXT_KEY: &str = "context";
+
+/// Context value of findings in test code
+pub const TEST_CONTEXT: &str = "test";
+
+/// Globs of test, fixture, and example paths
+pub const DEFAULT_TEST_PATHS: &[&str] = &[
+    "**/test/**",
+    "**/tests/**",
+    "**/__tests__/**",
+    "**/spec/**",
+    "**/specs/**",
+    "**/fixture/**",
+    "**/fixtures/**",
+    "**/testdata/**",
+    "**/example/**",
+    "**/examples/**",
+    "**/*_test.*",
+    "**/*.test.*",
+    "**/*.spec.*",
+    "**/test_*",
+];
+
+/// Case-sensitive markers of sample values on a line
+pub const DEFAULT_TEST_MARKERS: &[&str] = &["EXAMPLE", "dummy", "DUMMY"];
+
+/// What to do with findings in test context
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestContextMode {
+    /// Leave them as they are
+    #[default]
+    Off,
+    /// Record `context: test` in their metadata
+    Tag,
+    /// Tag them and lower their confidence to low
+    Downgrade,
+}
+
+impl std::str::FromStr for TestContextMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(TestContextMode::Off),
+            "tag" => Ok(TestContextMode::Tag),
+            "downgrade" => Ok(TestContextMode::Downgrade),
+            _ => Err(format!(
+                "Unknown test context mode: {} (expected off, tag, or downgrade)",
+                s
+            )),
+        }
+    }
+}
+
+/// How findings in test code are recognized and treated
+#[derive(Debug, Clone)]
+pub struct TestContext {
+    pub mode: TestContextMode,
+    /// Globs tested against each finding's path, relative to the scan root
+    pub paths: Vec<String>,
+    /// Case-sensitive words that mark a line's value as a sample
+    pub markers: Vec<String>,
+}
+
+impl Default for TestContext {
+    fn default() -> Self {
+        TestContext {
+            mode: TestContextMode::Off,
+            paths: DEFAULT_TEST_PATHS.iter().map(|p| p.to_string()).collect(),
+            markers: DEFAULT_TEST_MARKERS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+}
+
+impl TestContext {
+    /// Tag, and with [`TestContextMode::Downgrade`] downgrade, the findings
+    /// in test context
+    pub fn apply(&self, findings: &mut [Finding]) -> Result<()> {
+        if self.mode == TestContextMode::Off {
+            return Ok(());
+        }
+        let globs = self.globs()?;
+        for finding in findings {
+            if !self.is_test(finding, &globs) {
+                continue;
+            }
+            finding
+                .metadata
+                .insert(CONTEXT_KEY.to_string(), TEST_CONTEXT.to_string());
+            if self.mode == TestContextMode::Downgrade {
+                finding.confidence = Confidence::Low;
+            }
+        }
+        Ok(())
+    }
+
+    fn globs(&self) -> Result<GlobSet> {
+        let mut globs = GlobSetBuilder::new();
+        for glob in &self.paths {
+            globs.add(Glob::new(glob).with_context(|| format!("invalid test path '{}'", glob))?);
+        }
+        Ok(globs.build()?)
+    }
+
+    fn is_test(&self, finding: &Finding, globs: &GlobSet) -> bool {
+        let path = finding.relative_file.as_deref().unwrap_or(&finding.file);
+        let path = path.replace('\\', "/");
+        globs.is_match(path.trim_start_matches("./"))
+            || self.markers.iter().any(|m| {
+                !m.is_empty() && (finding.snippet.contains(m) || finding.secret.contains(m))
+            })
+    }
+}
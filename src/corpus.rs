@@ -0,0 +1,214 @@
+//! Synthetic corpus for benchmarks
+//!
+//! Throughput numbers are only comparable on the same input, so the
+//! benchmarks and `fastsecret bench` scan a generated corpus rather than
+//! whatever checkout is at hand. It mixes source, config, and data files
+//! with the things that make matching expensive — long identifiers, hex
+//! hashes, base64 blobs, URLs — and plants a known number of real-looking
+//! credentials so a run can also check that detection still works. The
+//! same seed always produces the same files.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Shape of a generated corpus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorpusOptions {
+    /// Number of files
+    pub files: usize,
+    /// Approximate size of each file, in bytes
+    pub file_size: usize,
+    /// Credentials planted in each file
+    pub secrets_per_file: usize,
+    /// Seed for the generator; the same seed gives the same corpus
+    pub seed: u64,
+}
+
+impl Default for CorpusOptions {
+    fn default() -> Self {
+        CorpusOptions {
+            files: 200,
+            file_size: 64 << 10,
+            secrets_per_file: 1,
+            seed: 42,
+        }
+    }
+}
+
+/// A generated corpus held in memory
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    /// Relative path and content of each file
+    pub files: Vec<(String, String)>,
+    /// Credentials planted across all files
+    pub secrets: Vec<String>,
+}
+
+/// File kinds, by extension, cycled through so every corpus has a mix
+const EXTENSIONS: &[&str] = &["rs", "py", "js", "yaml", "json", "env", "md"];
+
+const WORDS: &[&str] = &[
+    "user", "account", "config", "server", "request", "response", "client", "session", "cache",
+    "handler", "value", "result", "buffer", "index", "stream", "token", "payload", "record",
+    "service", "region", "bucket", "message", "worker", "timeout", "retry", "version",
+];
+
+const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const ALNUM: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const HEX: &[u8] = b"0123456789abcdef";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// xorshift64* generator; fast, and stable across platforms and releases
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift
+        Rng(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn string(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len)
+            .map(|_| alphabet[self.below(alphabet.len())] as char)
+            .collect()
+    }
+
+    fn identifier(&mut self) -> String {
+        let parts = 1 + self.below(3);
+        (0..parts)
+            .map(|_| self.pick(WORDS))
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+}
+
+impl Corpus {
+    /// Generate a corpus in memory
+    pub fn generate(options: &CorpusOptions) -> Self {
+        let mut rng = Rng::new(options.seed);
+        let mut corpus = Corpus::default();
+        for i in 0..options.files {
+            let ext = EXTENSIONS[i % EXTENSIONS.len()];
+            let name = format!("dir{:02}/file{:04}.{}", i % 16, i, ext);
+
+            let mut content = String::with_capacity(options.file_size + 256);
+            let mut planted = 0;
+            while content.len() < options.file_size {
+                // Spread the secrets through the file
+                let due = options.secrets_per_file * content.len() / options.file_size.max(1);
+                if planted < options.secrets_per_file && planted <= due && rng.below(8) == 0 {
+                    let secret = secret(&mut rng);
+                    content.push_str(&assignment(ext, &rng.identifier(), &secret));
+                    corpus.secrets.push(secret);
+                    planted += 1;
+                } else {
+                    content.push_str(&filler_line(&mut rng, ext));
+                }
+                content.push('\n');
+            }
+            while planted < options.secrets_per_file {
+                let secret = secret(&mut rng);
+                content.push_str(&assignment(ext, &rng.identifier(), &secret));
+                content.push('\n');
+                corpus.secrets.push(secret);
+                planted += 1;
+            }
+            corpus.files.push((name, content));
+        }
+        corpus
+    }
+
+    /// Total size of the files, in bytes
+    pub fn bytes(&self) -> u64 {
+        self.files.iter().map(|(_, c)| c.len() as u64).sum()
+    }
+
+    /// Write the files under `dir`, creating directories as needed
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        for (name, content) in &self.files {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create '{}'", parent.display()))?;
+            }
+            fs::write(&path, content)
+                .with_context(|| format!("failed to write '{}'", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// A credential that a built-in rule reports
+fn secret(rng: &mut Rng) -> String {
+    match rng.below(5) {
+        0 => format!("AKIA{}", rng.string(BASE32, 16)),
+        1 => format!("sk_live_{}", rng.string(ALNUM, 24)),
+        2 => format!("AIza{}", rng.string(ALNUM, 35)),
+        3 => format!("SG.{}-{}", rng.string(ALNUM, 22), rng.string(ALNUM, 43)),
+        _ => format!(
+            "https://hooks.slack.com/services/T{}/B{}/{}",
+            rng.string(BASE32, 9),
+            rng.string(BASE32, 9),
+            rng.string(ALNUM, 24)
+        ),
+    }
+}
+
+/// A line assigning `value` to `name` in the syntax of `ext` files
+fn assignment(ext: &str, name: &str, value: &str) -> String {
+    match ext {
+        "rs" => format!("    let {} = \"{}\";", name, value),
+        "py" => format!("{} = \"{}\"", name, value),
+        "js" => format!("  const {} = '{}';", name, value),
+        "yaml" => format!("  {}: {}", name, value),
+        "json" => format!("  \"{}\": \"{}\",", name, value),
+        "env" => format!("{}={}", name.to_ascii_uppercase(), value),
+        _ => format!("Set `{}` to `{}`.", name, value),
+    }
+}
+
+/// A line of ordinary content for an `ext` file
+fn filler_line(rng: &mut Rng, ext: &str) -> String {
+    let name = rng.identifier();
+    let value = match rng.below(6) {
+        0 => rng.string(HEX, 40),
+        1 => {
+            let len = 24 + rng.below(40);
+            rng.string(BASE64, len)
+        }
+        2 => format!(
+            "https://{}.example.com/{}",
+            rng.pick(WORDS),
+            rng.identifier()
+        ),
+        3 => rng.below(100_000).to_string(),
+        _ => (0..3 + rng.below(8))
+            .map(|_| rng.pick(WORDS))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    match (ext, rng.below(4)) {
+        ("rs", 0) => format!("// {} {}", rng.pick(WORDS), value),
+        ("py", 0) | ("yaml", 0) | ("env", 0) => format!("# {} {}", rng.pick(WORDS), value),
+        ("js", 0) => format!("  {}.{}({});", rng.identifier(), rng.pick(WORDS), name),
+        ("md", _) => format!("The {} {} is {}.", rng.pick(WORDS), name, value),
+        _ => assignment(ext, &name, &value),
+    }
+}
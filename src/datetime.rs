@@ -0,0 +1,91 @@
+//! UTC calendar dates
+//!
+//! Just enough date handling for report timestamps, request signing, and
+//! baseline expiry dates, without pulling in a date library.
+
+use std::time::SystemTime;
+
+/// A UTC date and time with one-second precision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct UtcDateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl UtcDateTime {
+    pub(crate) fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    /// Times before the epoch clamp to it
+    pub(crate) fn from_system_time(time: SystemTime) -> Self {
+        let secs = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::from_unix(secs)
+    }
+
+    pub(crate) fn from_unix(secs: u64) -> Self {
+        let (days, rem) = (secs / 86_400, secs % 86_400);
+
+        // Civil date from days since the epoch (Howard Hinnant's algorithm)
+        let z = days as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        UtcDateTime {
+            year,
+            month: month as u32,
+            day: day as u32,
+            hour: (rem / 3600) as u32,
+            minute: (rem % 3600 / 60) as u32,
+            second: (rem % 60) as u32,
+        }
+    }
+
+    /// Parse a `YYYY-MM-DD` date, at midnight
+    pub(crate) fn parse_date(text: &str) -> Option<Self> {
+        let mut parts = text.splitn(3, '-');
+        let mut field = |len: usize| {
+            parts
+                .next()
+                .filter(|p| p.len() == len && p.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|p| p.parse::<u32>().ok())
+        };
+        let (year, month, day) = (field(4)?, field(2)?, field(2)?);
+        let date = UtcDateTime {
+            year: i64::from(year),
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        };
+        // Out-of-range days such as February 30 don't survive a round trip
+        let days = date.days_since_epoch();
+        (days >= 0 && Self::from_unix(days as u64 * 86_400) == date).then_some(date)
+    }
+
+    /// Days from 1970-01-01 to this date, ignoring the time of day
+    pub(crate) fn days_since_epoch(&self) -> i64 {
+        // Inverse of the civil date conversion in `from_unix`
+        let year = self.year - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (i64::from(self.month) + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+}
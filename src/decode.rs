@@ -0,0 +1,189 @@
+//! Decoding of base64- and hex-encoded blobs
+//!
+//! Secrets are often stored encoded: a base64 `.npmrc` in a CI variable, a
+//! hex-encoded key in a config file, or a base64 blob of JSON that itself
+//! holds a base64 token. With decoding enabled, long runs of base64 or hex
+//! characters are decoded and, when the result is text, scanned like any
+//! other line. Decoded text is searched again for encoded blobs up to a depth
+//! limit. Findings point at the encoded blob and carry an `encoded`
+//! metadata entry naming the encodings, outermost first (`base64`, or
+//! `base64+hex` for hex inside base64).
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{locate, looks_binary, scan_lines, Finding, ScanOptions};
+
+/// Limits for decoding encoded blobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Shortest run of encoded characters worth decoding
+    pub min_length: usize,
+    /// How many encodings deep to follow blobs inside decoded text
+    pub max_depth: usize,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            min_length: 20,
+            max_depth: 2,
+        }
+    }
+}
+
+/// Encodings that are recognized and decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Base64,
+    Hex,
+}
+
+impl Encoding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Base64 => "base64",
+            Encoding::Hex => "hex",
+        }
+    }
+}
+
+/// Runs of base64 (either alphabet) or hex characters
+fn candidate_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"[A-Za-z0-9+/_-]{8,}={0,2}").expect("valid regex"))
+}
+
+/// Encoded blobs in `line` at least `min_length` long, with their encoding
+pub fn find_encoded(line: &str, min_length: usize) -> Vec<(Range<usize>, Encoding)> {
+    candidate_regex()
+        .find_iter(line)
+        .filter(|m| m.len() >= min_length)
+        .filter_map(|m| {
+            let text = m.as_str();
+            let body = text.trim_end_matches('=');
+            let encoding = if body.len() == text.len()
+                && text.len() % 2 == 0
+                && text.bytes().all(|b| b.is_ascii_hexdigit())
+            {
+                Encoding::Hex
+            } else if body.contains(['+', '/']) && body.contains(['-', '_']) {
+                // Mixed alphabets aren't base64
+                return None;
+            } else {
+                Encoding::Base64
+            };
+            Some((m.range(), encoding))
+        })
+        .collect()
+}
+
+/// Decode `text`, returning `None` unless the result is readable text
+pub fn decode(text: &str, encoding: Encoding) -> Option<String> {
+    let bytes = match encoding {
+        Encoding::Hex => (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?,
+        Encoding::Base64 => {
+            let config = GeneralPurposeConfig::new()
+                .with_decode_padding_mode(DecodePaddingMode::Indifferent);
+            let alphabet = if text.contains(['-', '_']) {
+                &alphabet::URL_SAFE
+            } else {
+                &alphabet::STANDARD
+            };
+            GeneralPurpose::new(alphabet, config).decode(text).ok()?
+        }
+    };
+    // Decoded random bytes are almost never valid text
+    if bytes.is_empty() || looks_binary(&bytes, true) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Scan the decoded form of every encoded blob on a line
+///
+/// Findings take the line number and the columns of the blob they were
+/// decoded from. `encodings` lists the encodings already peeled off to
+/// reach `line`, and is empty for a line of the file itself.
+pub(crate) fn scan_encoded(
+    path_str: &str,
+    line_no: usize,
+    line: &str,
+    encodings: &[Encoding],
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(limits) = options.decode else {
+        return;
+    };
+    if encodings.len() >= limits.max_depth || line.len() < limits.min_length {
+        return;
+    }
+    // Decoded text is scanned without decoding; nested blobs are followed
+    // here so the depth stays bounded. Its positions aren't the file's, so
+    // the file's source map doesn't apply
+    let mut plain = None;
+
+    for (span, encoding) in find_encoded(line, limits.min_length) {
+        let Some(decoded) = decode(&line[span.clone()], encoding) else {
+            continue;
+        };
+        let mut chain = encodings.to_vec();
+        chain.push(encoding);
+
+        let mut decoded_findings = Vec::new();
+        if scan_lines(
+            decoded.as_bytes(),
+            path_str,
+            rule_set,
+            plain.get_or_insert_with(|| ScanOptions {
+                decode: None,
+                source_maps: false,
+                ..options.clone()
+            }),
+            &mut decoded_findings,
+        )
+        .is_err()
+        {
+            continue;
+        }
+        for (i, decoded_line) in decoded.lines().enumerate() {
+            scan_encoded(
+                path_str,
+                i + 1,
+                decoded_line,
+                &chain,
+                rule_set,
+                options,
+                &mut decoded_findings,
+            );
+        }
+
+        let (column, end_column, _) = locate(line, span.clone(), false);
+        let label = chain
+            .iter()
+            .map(|e| e.as_str())
+            .collect::<Vec<_>>()
+            .join("+");
+        for mut finding in decoded_findings {
+            finding.line = line_no;
+            finding.column = column;
+            finding.end_column = end_column;
+            // Nested findings already name their full chain
+            finding
+                .metadata
+                .entry("encoded".to_string())
+                .or_insert_with(|| label.clone());
+            findings.push(finding);
+        }
+    }
+}
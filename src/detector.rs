@@ -0,0 +1,296 @@
+//! Custom detectors
+//!
+//! Most credentials are caught by a regex, but some checks don't fit one:
+//! a parser for a structured config format, a statistical model, a
+//! provider SDK that recognizes its own key formats. A [`Detector`] sees
+//! each text file whole once it has been read and returns findings of its
+//! own, or an error, which is reported with the scan's other errors while
+//! the rest of the file is still scanned. Findings pass the same filters as rule matches — ignored rules, minimum
+//! severity and confidence, inline `fastsecret:ignore` comments — and end up
+//! in the same reports.
+//!
+//! The regex engine implements the trait too: [`RegexDetector`] runs an
+//! independent rule set, such as a team's private rules kept apart from
+//! the scanner's own, through the same matcher as built-in rules.
+//!
+//! ```
+//! use fastsecret::detector::{Detector, FileContext};
+//! use fastsecret::scanner::{Finding, FindingSeverity};
+//! use fastsecret::Scanner;
+//!
+//! /// Flags `INTERNAL-` tokens with a valid check digit
+//! struct InternalTokens;
+//!
+//! impl Detector for InternalTokens {
+//!     fn id(&self) -> &str {
+//!         "internal-tokens"
+//!     }
+//!
+//!     fn detect(&self, ctx: &FileContext) -> anyhow::Result<Vec<Finding>> {
+//!         let mut findings = Vec::new();
+//!         for (idx, line) in ctx.content.lines().enumerate() {
+//!             if let Some(start) = line.find("INTERNAL-") {
+//!                 let end = start + line[start..].find(' ').unwrap_or(line.len() - start);
+//!                 findings.extend(ctx.finding(
+//!                     idx + 1,
+//!                     start..end,
+//!                     "internal-token",
+//!                     "Internal Token",
+//!                     FindingSeverity::High,
+//!                 ));
+//!             }
+//!         }
+//!         Ok(findings)
+//!     }
+//! }
+//!
+//! let scanner = Scanner::builder().detector(InternalTokens).build()?;
+//! let report = scanner.scan_bytes("app.cfg", b"token = INTERNAL-8f3a2c\n")?;
+//! assert_eq!(report.findings[0].rule_id, "internal-token");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+
+use crate::confidence;
+use crate::metadata;
+use crate::rules::{CompiledRuleSet, Rule};
+use crate::scanner::{
+    drop_suppressed, locate, scan_line, Finding, FindingSeverity, MultilineBuffer, ScanError,
+    ScanErrorKind, ScanOptions,
+};
+
+/// A source of findings that runs alongside the rules
+pub trait Detector: Send + Sync {
+    /// Stable name of the detector
+    fn id(&self) -> &str;
+
+    /// What the detector adds to the scan cache key [default: its
+    /// [`id`](Self::id)]
+    ///
+    /// Cached results are thrown away when it changes, so it should change
+    /// whenever the detector would report different findings: include the
+    /// version, the rules, or the model it runs.
+    fn cache_key(&self) -> String {
+        self.id().to_string()
+    }
+
+    /// Findings in one text file, or why the detector couldn't check it
+    fn detect(&self, ctx: &FileContext) -> Result<Vec<Finding>>;
+}
+
+/// A file handed to a [`Detector`]
+#[derive(Debug, Clone, Copy)]
+pub struct FileContext<'a> {
+    /// Path of the file, as reported in findings
+    pub path: &'a str,
+    /// The file's text, lines separated by `\n`
+    pub content: &'a str,
+    /// Options of the running scan
+    pub options: &'a ScanOptions,
+}
+
+impl FileContext<'_> {
+    /// A finding for the secret at byte range `span` of line `line`
+    /// (1-based), located, redacted, and scored like a rule match
+    ///
+    /// Returns `None` if the line doesn't exist or `span` isn't within it
+    /// on character boundaries.
+    pub fn finding(
+        &self,
+        line: usize,
+        span: Range<usize>,
+        rule_id: &str,
+        rule_name: &str,
+        severity: FindingSeverity,
+    ) -> Option<Finding> {
+        let text = self.content.lines().nth(line.checked_sub(1)?)?;
+        let secret = text.get(span.clone())?;
+        let (column, end_column, snippet) = locate(text, span, self.options.redact);
+        Some(Finding {
+            file: self.path.to_string(),
+            line,
+            column,
+            end_column,
+            snippet,
+            context: Vec::new(),
+            rule_id: rule_id.to_string(),
+            rule_name: rule_name.to_string(),
+            severity,
+            confidence: confidence::score(None, secret, self.options.relative(self.path)),
+            remediation: None,
+            rule_source: None,
+            rule_version: None,
+            secret_sha256: None,
+            secret: secret.to_string(),
+            commit: None,
+            relative_file: None,
+            verification: None,
+            layer: None,
+            cell: None,
+            original: None,
+            other_locations: Vec::new(),
+            variable: None,
+            metadata: metadata::extract(secret),
+        })
+    }
+}
+
+/// Detectors registered on a scanner, run in registration order
+#[derive(Clone, Default)]
+pub struct DetectorRegistry {
+    detectors: Vec<Arc<dyn Detector>>,
+}
+
+impl fmt::Debug for DetectorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.ids()).finish()
+    }
+}
+
+impl DetectorRegistry {
+    /// Add a detector
+    pub fn register(&mut self, detector: impl Detector + 'static) {
+        self.detectors.push(Arc::new(detector));
+    }
+
+    /// Ids of the registered detectors
+    pub fn ids(&self) -> Vec<&str> {
+        self.detectors.iter().map(|d| d.id()).collect()
+    }
+
+    /// Cache keys of the registered detectors, see [`Detector::cache_key`]
+    pub fn cache_keys(&self) -> Vec<String> {
+        self.detectors.iter().map(|d| d.cache_key()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.detectors.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Detector> {
+        self.detectors.iter().map(|d| d.as_ref())
+    }
+}
+
+/// The regex rule engine as a [`Detector`], for running a rule set of its
+/// own next to a scanner's rules
+#[derive(Debug, Clone)]
+pub struct RegexDetector {
+    id: String,
+    rule_set: CompiledRuleSet,
+}
+
+impl RegexDetector {
+    /// Compile `rules` into a detector named `id`
+    ///
+    /// Rules with an invalid pattern are reported on stderr and skipped.
+    pub fn new(id: impl Into<String>, rules: &[Rule]) -> Result<Self> {
+        Ok(RegexDetector {
+            id: id.into(),
+            rule_set: CompiledRuleSet::new(rules)?,
+        })
+    }
+
+    pub fn rule_set(&self) -> &CompiledRuleSet {
+        &self.rule_set
+    }
+}
+
+impl Detector for RegexDetector {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The id and a hash of the rules
+    fn cache_key(&self) -> String {
+        let mut hasher = Sha256::new();
+        for compiled in self.rule_set.rules() {
+            hasher.update(serde_json::to_vec(&compiled.rule).unwrap_or_default());
+        }
+        format!("{}:{:x}", self.id, hasher.finalize())
+    }
+
+    fn detect(&self, ctx: &FileContext) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+        let mut multiline = self.rule_set.has_multiline().then(MultilineBuffer::default);
+        for (idx, line) in ctx.content.lines().enumerate() {
+            scan_line(
+                ctx.path,
+                idx + 1,
+                line,
+                &self.rule_set,
+                ctx.options,
+                &mut findings,
+            );
+            if let Some(buffer) = &mut multiline {
+                buffer.push(idx + 1, line);
+            }
+        }
+        if let Some(buffer) = &multiline {
+            buffer.scan(ctx.path, &self.rule_set, ctx.options, &mut findings);
+        }
+        Ok(findings)
+    }
+}
+
+/// Run the registered detectors over a file, adding the findings that
+/// survive the scan's filters to `findings[file_start..]` and the detectors
+/// that failed to `errors`
+pub(crate) fn scan_file(
+    content: &str,
+    file_start: usize,
+    path_str: &str,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+    errors: &mut Vec<ScanError>,
+) {
+    let ctx = FileContext {
+        path: path_str,
+        content,
+        options,
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    for detector in options.detectors.iter() {
+        let detected = match detector.detect(&ctx) {
+            Ok(detected) => detected,
+            Err(e) => {
+                errors.push(ScanError {
+                    file: path_str.to_string(),
+                    kind: ScanErrorKind::Detector,
+                    message: format!("detector {} failed: {:#}", detector.id(), e),
+                });
+                continue;
+            }
+        };
+        for finding in detected {
+            let ignored = options
+                .ignore_rules
+                .iter()
+                .any(|r| *r == finding.rule_id || *r == finding.rule_name);
+            if ignored
+                || finding.severity < options.min_severity
+                || finding.confidence < options.min_confidence
+            {
+                continue;
+            }
+            let idx = finding.line.saturating_sub(1);
+            let start = findings.len();
+            findings.push(finding);
+            if let Some(line) = lines.get(idx) {
+                let prev = idx.checked_sub(1).and_then(|i| lines.get(i)).copied();
+                drop_suppressed(findings, start, line, prev);
+            }
+        }
+    }
+    findings[file_start..].sort_by_key(|f| (f.line, f.column));
+}
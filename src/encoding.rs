@@ -0,0 +1,83 @@
+//! Text encodings other than UTF-8
+//!
+//! Windows tools write `.config` files and PowerShell scripts in UTF-16,
+//! and older sources are often Latin-1. Both would otherwise be skipped: the
+//! NUL bytes of UTF-16 look binary, and Latin-1 isn't valid UTF-8. Such
+//! files are recognized from their first bytes and transcoded to UTF-8
+//! while they are read, so rules match them like any other text.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use std::io::{BufRead, BufReader, Cursor, Read};
+
+/// Share of code units that must have a zero high byte for text without a
+/// BOM to be taken as UTF-16
+const UTF16_ZERO_RATIO: f64 = 0.9;
+
+/// The encoding of text starting with `sample`, if it isn't UTF-8
+///
+/// A byte order mark decides. Without one, mostly-ASCII UTF-16 is
+/// recognized by its zero bytes, and text that isn't valid UTF-8 but has no
+/// control characters is read as Latin-1 (Windows-1252). Files with a binary
+/// extension are only transcoded when they start with a BOM.
+pub(crate) fn detect(sample: &[u8], binary_extension: bool) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return (encoding != UTF_8).then_some(encoding);
+    }
+    if binary_extension {
+        return None;
+    }
+    if let Some(encoding) = detect_utf16(sample) {
+        return Some(encoding);
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => None,
+        // Cut off mid-character at the end of the sample
+        Err(e) if e.error_len().is_none() => None,
+        Err(_) => sample
+            .iter()
+            .all(|&b| b >= 0x20 || matches!(b, b'\t' | b'\n' | b'\r' | b'\x0c'))
+            .then_some(WINDOWS_1252),
+    }
+}
+
+/// UTF-16 without a BOM, recognized when nearly every code unit has a zero
+/// high byte and a nonzero low byte, as ASCII text does
+fn detect_utf16(sample: &[u8]) -> Option<&'static Encoding> {
+    let units = sample.len() / 2;
+    if units < 2 {
+        return None;
+    }
+    let zeros = |offset: usize| {
+        sample
+            .chunks_exact(2)
+            .filter(|unit| unit[offset] == 0)
+            .count() as f64
+            / units as f64
+    };
+    let (even, odd) = (zeros(0), zeros(1));
+    if odd >= UTF16_ZERO_RATIO && even <= 1.0 - UTF16_ZERO_RATIO {
+        Some(UTF_16LE)
+    } else if even >= UTF16_ZERO_RATIO && odd <= 1.0 - UTF16_ZERO_RATIO {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Read `sample` and then `rest` as UTF-8, transcoding from `encoding`
+pub(crate) fn text_reader<'a, R: Read + 'a>(
+    sample: Vec<u8>,
+    rest: R,
+    encoding: Option<&'static Encoding>,
+) -> Box<dyn BufRead + 'a> {
+    let reader = Cursor::new(sample).chain(rest);
+    match encoding {
+        Some(encoding) => Box::new(BufReader::new(
+            DecodeReaderBytesBuilder::new()
+                .encoding(Some(encoding))
+                .build(reader),
+        )),
+        None => Box::new(BufReader::new(reader)),
+    }
+}
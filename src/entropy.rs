@@ -0,0 +1,135 @@
+//! Entropy-based secret detection
+//!
+//! Complements the regex rules by flagging base64- and hex-looking tokens
+//! whose Shannon entropy is high enough that they are likely random keys
+//! rather than words or identifiers.
+
+use crate::confidence;
+use crate::metadata;
+use crate::scanner::{locate, Finding, FindingSeverity, ScanOptions};
+
+/// Rule id reported for findings produced by the entropy engine
+pub const ENTROPY_RULE_ID: &str = "high-entropy-string";
+
+/// Name reported for findings produced by the entropy engine
+pub const ENTROPY_RULE_NAME: &str = "High-Entropy String";
+
+/// Settings for the entropy engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyOptions {
+    /// Minimum Shannon entropy in bits per character
+    ///
+    /// Hex strings top out at 4.0 and base64 at 6.0.
+    pub threshold: f64,
+    /// Shortest token considered
+    pub min_length: usize,
+}
+
+impl Default for EntropyOptions {
+    fn default() -> Self {
+        EntropyOptions {
+            threshold: 4.5,
+            min_length: 20,
+        }
+    }
+}
+
+/// Shannon entropy of a string in bits per character
+pub fn shannon_entropy(s: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    let mut total = 0usize;
+    for b in s.bytes() {
+        counts[b as usize] += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return 0.0;
+    }
+
+    let len = total as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Characters that can appear in a base64, base64url, or hex token
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '_' | '-')
+}
+
+/// Split a line into candidate tokens with their byte offsets
+pub fn candidate_tokens(line: &str, min_length: usize) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in line
+        .char_indices()
+        .chain(std::iter::once((line.len(), ' ')))
+    {
+        match (is_token_char(c), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if i - s >= min_length {
+                    tokens.push((s, &line[s..i]));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Report high-entropy tokens on a single line
+pub(crate) fn scan_line_entropy(
+    path_str: &str,
+    line_no: usize,
+    line: &str,
+    entropy: &EntropyOptions,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    for (start, token) in candidate_tokens(line, entropy.min_length) {
+        if shannon_entropy(token) < entropy.threshold {
+            continue;
+        }
+        let confidence = confidence::score(None, token, options.relative(path_str));
+        if confidence < options.min_confidence {
+            continue;
+        }
+
+        let (column, end_column, snippet) =
+            locate(line, start..start + token.len(), options.redact);
+
+        findings.push(Finding {
+            file: path_str.to_string(),
+            line: line_no,
+            column,
+            end_column,
+            snippet,
+            context: Vec::new(),
+            rule_id: ENTROPY_RULE_ID.to_string(),
+            rule_name: ENTROPY_RULE_NAME.to_string(),
+            severity: FindingSeverity::Low,
+            confidence,
+            remediation: None,
+            rule_source: None,
+            rule_version: None,
+            secret_sha256: None,
+            secret: token.to_string(),
+            commit: None,
+            relative_file: None,
+            verification: None,
+            layer: None,
+            cell: None,
+            original: None,
+            other_locations: Vec::new(),
+            variable: None,
+            metadata: metadata::extract(token),
+        });
+    }
+}
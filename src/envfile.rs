@@ -0,0 +1,293 @@
+//! Variable names for findings in environment files
+//!
+//! Recognizes `.env` files, `environment:` blocks in YAML (as used by
+//! docker-compose), and the `data` / `stringData` of Kubernetes Secrets and
+//! ConfigMaps, and remembers which variable each line assigns. Findings on
+//! those lines are then tagged with the variable name, which tells the
+//! reader what the secret is for faster than a masked snippet does.
+//!
+//! The parsing is line based so it works on streamed input; it understands
+//! the common layouts rather than the full YAML or dotenv grammar.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::scanner::Finding;
+
+/// Kinds of file whose variables can be tracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    DotEnv,
+    Yaml,
+}
+
+impl FileKind {
+    fn from_name(name: &str) -> Option<Self> {
+        let file_name = Path::new(name).file_name()?.to_str()?.to_ascii_lowercase();
+        if file_name == ".env" || file_name.starts_with(".env.") || file_name.ends_with(".env") {
+            Some(FileKind::DotEnv)
+        } else if file_name.ends_with(".yml") || file_name.ends_with(".yaml") {
+            Some(FileKind::Yaml)
+        } else {
+            None
+        }
+    }
+}
+
+/// YAML block whose children are variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    /// docker-compose `environment:`
+    Environment,
+    /// Kubernetes `data:` or `stringData:`; only kept for Secrets and ConfigMaps
+    Data,
+}
+
+#[derive(Debug)]
+struct Block {
+    kind: BlockKind,
+    /// `data:` values are base64 encoded
+    encoded: bool,
+    indent: usize,
+    /// Indentation of the block's keys, once the first one is seen
+    child_indent: Option<usize>,
+    /// Key whose value continues on deeper-indented lines
+    current: Option<String>,
+}
+
+/// A variable assigned on a line
+#[derive(Debug)]
+struct Assignment {
+    name: String,
+    kind: BlockKind,
+    document: usize,
+}
+
+/// A base64 value under a Kubernetes `data:` key
+#[derive(Debug)]
+pub(crate) struct EncodedValue {
+    pub line: usize,
+    pub name: String,
+    pub value: String,
+    /// 1-based column of the value, and the column just past it
+    pub columns: (usize, usize),
+    document: usize,
+}
+
+/// Records which variable each line of a file assigns
+#[derive(Debug)]
+pub(crate) struct VariableTracker {
+    file_kind: FileKind,
+    assignments: HashMap<usize, Assignment>,
+    encoded: Vec<EncodedValue>,
+    // YAML state
+    block: Option<Block>,
+    document: usize,
+    document_kinds: HashMap<usize, String>,
+    // dotenv state: variable whose quoted value is still open, and its quote
+    open_quote: Option<(String, char)>,
+}
+
+impl VariableTracker {
+    /// A tracker for `name`, if it looks like a file with variables
+    pub(crate) fn for_path(name: &str) -> Option<Self> {
+        Some(VariableTracker {
+            file_kind: FileKind::from_name(name)?,
+            assignments: HashMap::new(),
+            encoded: Vec::new(),
+            block: None,
+            document: 0,
+            document_kinds: HashMap::new(),
+            open_quote: None,
+        })
+    }
+
+    /// Consume one line (1-based `line_no`, without the newline)
+    pub(crate) fn feed(&mut self, line_no: usize, line: &str) {
+        match self.file_kind {
+            FileKind::DotEnv => self.feed_dotenv(line_no, line),
+            FileKind::Yaml => self.feed_yaml(line_no, line),
+        }
+    }
+
+    /// Whether the file is YAML
+    pub(crate) fn is_yaml(&self) -> bool {
+        self.file_kind == FileKind::Yaml
+    }
+
+    /// Single-line values under `data:` in Secret documents
+    pub(crate) fn secret_data(&self) -> impl Iterator<Item = &EncodedValue> {
+        self.encoded
+            .iter()
+            .filter(|v| self.document_kinds.get(&v.document).map(String::as_str) == Some("Secret"))
+    }
+
+    /// Set `variable` on findings whose line assigns one
+    pub(crate) fn annotate(&self, findings: &mut [Finding]) {
+        for finding in findings {
+            let Some(assignment) = self.assignments.get(&finding.line) else {
+                continue;
+            };
+            let keep = match assignment.kind {
+                BlockKind::Environment => true,
+                BlockKind::Data => matches!(
+                    self.document_kinds
+                        .get(&assignment.document)
+                        .map(String::as_str),
+                    Some("Secret" | "ConfigMap")
+                ),
+            };
+            if keep {
+                finding.variable = Some(assignment.name.clone());
+            }
+        }
+    }
+
+    fn assign(&mut self, line_no: usize, name: &str, kind: BlockKind) {
+        self.assignments.insert(
+            line_no,
+            Assignment {
+                name: name.to_string(),
+                kind,
+                document: self.document,
+            },
+        );
+    }
+
+    /// Remember the inline value of `name: value` on a `data:` line
+    fn record_encoded(&mut self, line_no: usize, line: &str, name: &str) {
+        let Some(colon) = line.find(':') else {
+            return;
+        };
+        let after = &line[colon + 1..];
+        let value = after.trim();
+        let value = value
+            .strip_prefix(['"', '\''])
+            .and_then(|v| v.strip_suffix(['"', '\'']))
+            .unwrap_or(value);
+        // Block scalars and empty values have nothing inline
+        if value.is_empty() || value.starts_with(['|', '>', '#']) {
+            return;
+        }
+        let start = colon + 1 + after.find(value).unwrap_or(0);
+        let column = line[..start].chars().count() + 1;
+        self.encoded.push(EncodedValue {
+            line: line_no,
+            name: name.to_string(),
+            value: value.to_string(),
+            columns: (column, column + value.chars().count()),
+            document: self.document,
+        });
+    }
+
+    fn feed_dotenv(&mut self, line_no: usize, line: &str) {
+        if let Some((name, quote)) = self.open_quote.take() {
+            self.assign(line_no, &name, BlockKind::Environment);
+            if !line.contains(quote) {
+                self.open_quote = Some((name, quote));
+            }
+            return;
+        }
+
+        let trimmed = line.trim_start();
+        let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+        let Some((name, value)) = trimmed.split_once('=') else {
+            return;
+        };
+        let name = name.trim();
+        if !is_variable_name(name) {
+            return;
+        }
+        self.assign(line_no, name, BlockKind::Environment);
+
+        // A quoted value without its closing quote continues on later lines
+        let value = value.trim_start();
+        if let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+            if !value[1..].contains(quote) {
+                self.open_quote = Some((name.to_string(), quote));
+            }
+        }
+    }
+
+    fn feed_yaml(&mut self, line_no: usize, line: &str) {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            return;
+        }
+        if line.starts_with("---") {
+            self.document += 1;
+            self.block = None;
+            return;
+        }
+        let indent = line.len() - content.len();
+
+        if indent == 0 {
+            if let Some(kind) = content.strip_prefix("kind:") {
+                let kind = kind.trim().trim_matches(|c| c == '"' || c == '\'');
+                self.document_kinds.insert(self.document, kind.to_string());
+            }
+        }
+
+        if let Some(block) = &mut self.block {
+            if indent > block.indent {
+                let child_indent = *block.child_indent.get_or_insert(indent);
+                let kind = block.kind;
+                let encoded = block.encoded;
+                let name = if indent > child_indent {
+                    // Continuation of a multi-line value
+                    block.current.clone()
+                } else {
+                    let name = yaml_variable(content, kind);
+                    block.current = name.clone();
+                    if let (Some(name), true) = (&name, encoded) {
+                        self.record_encoded(line_no, line, name);
+                    }
+                    name
+                };
+                if let Some(name) = name {
+                    self.assign(line_no, &name, kind);
+                }
+                return;
+            }
+            self.block = None;
+        }
+
+        let key = content.strip_prefix("- ").unwrap_or(content);
+        let (kind, encoded) = match key.trim_end() {
+            "environment:" => (BlockKind::Environment, false),
+            "data:" => (BlockKind::Data, true),
+            "stringData:" => (BlockKind::Data, false),
+            _ => return,
+        };
+        self.block = Some(Block {
+            kind,
+            encoded,
+            indent,
+            child_indent: None,
+            current: None,
+        });
+    }
+}
+
+/// Variable name of a line inside a YAML variables block
+///
+/// Handles map entries (`KEY: value`) and, for `environment:`, list
+/// entries (`- KEY=value`).
+fn yaml_variable(content: &str, kind: BlockKind) -> Option<String> {
+    let name = match content.strip_prefix("- ") {
+        Some(item) if kind == BlockKind::Environment => {
+            let item = item.trim().trim_start_matches(['"', '\'']);
+            item.split_once('=').map_or(item, |(name, _)| name)
+        }
+        Some(_) => return None,
+        None => content.split_once(':')?.0,
+    };
+    let name = name.trim().trim_matches(|c| c == '"' || c == '\'');
+    is_variable_name(name).then(|| name.to_string())
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
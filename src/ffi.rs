@@ -0,0 +1,320 @@
+//! C bindings
+//!
+//! A small, stable C ABI for embedding the scanner in editors and other
+//! non-Rust hosts. The declarations live in `include/fastsecret.h`, which is
+//! generated with `cbindgen --config cbindgen.toml --output include/fastsecret.h`.
+//!
+//! # Ownership
+//!
+//! - Every pointer returned by a `fastsecret_*_new` or `fastsecret_scan_*`
+//!   function is owned by the caller and must be released with the matching
+//!   `fastsecret_*_free` function, exactly once.
+//! - Pointers returned by `fastsecret_findings_get` and the strings inside a
+//!   [`FastsecretFinding`] are borrowed from their [`FastsecretFindings`] and
+//!   stay valid until it is freed.
+//! - Strings returned by `fastsecret_findings_to_json` are owned by the
+//!   caller and must be released with `fastsecret_string_free`.
+//! - `fastsecret_last_error` and `fastsecret_version` return borrowed strings
+//!   that must not be freed.
+//!
+//! Functions that fail return `NULL` and record a message readable with
+//! `fastsecret_last_error` on the same thread. Secrets are always masked in
+//! snippets and JSON; the raw secret never crosses the boundary.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use anyhow::{anyhow, Result};
+
+use crate::report::json::JsonFinding;
+use crate::rules::parse_rule_file;
+use crate::scanner::{Finding, FindingSeverity, Scanner};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// A scanner with its rules compiled, reusable across many scans
+pub struct FastsecretScanner {
+    scanner: Scanner,
+}
+
+/// Severity of a finding
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FastsecretSeverity {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+}
+
+impl From<FindingSeverity> for FastsecretSeverity {
+    fn from(severity: FindingSeverity) -> Self {
+        match severity {
+            FindingSeverity::Low => FastsecretSeverity::Low,
+            FindingSeverity::Medium => FastsecretSeverity::Medium,
+            FindingSeverity::High => FastsecretSeverity::High,
+        }
+    }
+}
+
+/// One finding; its strings are NUL-terminated UTF-8 owned by the
+/// [`FastsecretFindings`] it came from
+#[repr(C)]
+#[derive(Debug)]
+pub struct FastsecretFinding {
+    pub rule_id: *const c_char,
+    pub rule_name: *const c_char,
+    pub file: *const c_char,
+    /// The matched line with the secret masked
+    pub snippet: *const c_char,
+    pub severity: FastsecretSeverity,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column where the secret starts
+    pub column: usize,
+    /// 1-based column just past the end of the secret
+    pub end_column: usize,
+}
+
+/// The findings from one scan
+pub struct FastsecretFindings {
+    findings: Vec<Finding>,
+    items: Vec<FastsecretFinding>,
+    // Backing storage for the pointers in `items`; CString contents don't
+    // move when the vector does
+    _strings: Vec<CString>,
+}
+
+impl FastsecretFindings {
+    fn new(findings: Vec<Finding>) -> Self {
+        let mut strings = Vec::with_capacity(findings.len() * 4);
+        let mut intern = |s: &str| {
+            let c = to_cstring(s);
+            let p = c.as_ptr();
+            strings.push(c);
+            p
+        };
+        let items = findings
+            .iter()
+            .map(|f| FastsecretFinding {
+                rule_id: intern(&f.rule_id),
+                rule_name: intern(&f.rule_name),
+                file: intern(&f.file),
+                snippet: intern(&f.snippet),
+                severity: f.severity.into(),
+                line: f.line,
+                column: f.column,
+                end_column: f.end_column,
+            })
+            .collect();
+        FastsecretFindings {
+            findings,
+            items,
+            _strings: strings,
+        }
+    }
+}
+
+/// Create a scanner with the built-in rules plus any custom rules given as
+/// YAML (the `--rules` format); `rules_yaml` may be `NULL`
+///
+/// Returns `NULL` on error.
+///
+/// # Safety
+///
+/// `rules_yaml` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_scanner_new(
+    rules_yaml: *const c_char,
+) -> *mut FastsecretScanner {
+    let rules = match opt_str(rules_yaml, "rules_yaml") {
+        Ok(rules) => rules,
+        Err(e) => return fail(e),
+    };
+    guard(move || {
+        let mut builder = Scanner::builder().redact(true).threads(1);
+        if let Some(yaml) = rules.filter(|y| !y.trim().is_empty()) {
+            builder = builder.add_rule_file(parse_rule_file(&yaml)?);
+        }
+        let scanner = builder.build()?;
+        Ok(Box::into_raw(Box::new(FastsecretScanner { scanner })))
+    })
+}
+
+/// Free a scanner; `NULL` is ignored
+///
+/// # Safety
+///
+/// `scanner` must be `NULL` or a pointer from `fastsecret_scanner_new` that
+/// has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_scanner_free(scanner: *mut FastsecretScanner) {
+    if !scanner.is_null() {
+        drop(Box::from_raw(scanner));
+    }
+}
+
+/// Scan `len` bytes at `data`, labelling findings with `filename` (which
+/// also selects file-type handling such as `.env` variable names)
+///
+/// `filename` may be `NULL` for `<input>`. Returns `NULL` on error.
+///
+/// # Safety
+///
+/// `scanner` must come from `fastsecret_scanner_new`; `data` must point to
+/// `len` readable bytes (it may be `NULL` when `len` is 0); `filename` must
+/// be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_scan_buffer(
+    scanner: *const FastsecretScanner,
+    data: *const u8,
+    len: usize,
+    filename: *const c_char,
+) -> *mut FastsecretFindings {
+    let Some(scanner) = scanner.as_ref() else {
+        return fail(anyhow!("scanner is NULL"));
+    };
+    let bytes: &[u8] = if len == 0 {
+        &[]
+    } else if data.is_null() {
+        return fail(anyhow!("data is NULL"));
+    } else {
+        std::slice::from_raw_parts(data, len)
+    };
+    let name = match opt_str(filename, "filename") {
+        Ok(name) => name.unwrap_or_else(|| "<input>".to_string()),
+        Err(e) => return fail(e),
+    };
+    guard(move || {
+        let report = scanner.scanner.scan_bytes(&name, bytes)?;
+        Ok(Box::into_raw(Box::new(FastsecretFindings::new(
+            report.findings,
+        ))))
+    })
+}
+
+/// Number of findings; 0 for `NULL`
+///
+/// # Safety
+///
+/// `findings` must be `NULL` or a live pointer from a scan function.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_findings_len(findings: *const FastsecretFindings) -> usize {
+    findings.as_ref().map_or(0, |f| f.items.len())
+}
+
+/// The finding at `index`, or `NULL` if out of range
+///
+/// # Safety
+///
+/// `findings` must be `NULL` or a live pointer from a scan function.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_findings_get(
+    findings: *const FastsecretFindings,
+    index: usize,
+) -> *const FastsecretFinding {
+    findings
+        .as_ref()
+        .and_then(|f| f.items.get(index))
+        .map_or(ptr::null(), |item| item as *const FastsecretFinding)
+}
+
+/// All findings as a JSON array shaped like the CLI's JSON output
+///
+/// Free the result with `fastsecret_string_free`. Returns `NULL` on error.
+///
+/// # Safety
+///
+/// `findings` must be a live pointer from a scan function.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_findings_to_json(
+    findings: *const FastsecretFindings,
+) -> *mut c_char {
+    let Some(findings) = findings.as_ref() else {
+        return fail(anyhow!("findings is NULL"));
+    };
+    guard(|| {
+        let items: Vec<JsonFinding> = findings.findings.iter().map(JsonFinding::from).collect();
+        Ok(to_cstring(&serde_json::to_string(&items)?).into_raw())
+    })
+}
+
+/// Free findings returned by a scan function; `NULL` is ignored
+///
+/// # Safety
+///
+/// `findings` must be `NULL` or a pointer from a scan function that has not
+/// been freed. Any [`FastsecretFinding`] borrowed from it becomes invalid.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_findings_free(findings: *mut FastsecretFindings) {
+    if !findings.is_null() {
+        drop(Box::from_raw(findings));
+    }
+}
+
+/// Free a string returned by this library; `NULL` is ignored
+///
+/// # Safety
+///
+/// `s` must be `NULL` or a string from `fastsecret_findings_to_json` that
+/// has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn fastsecret_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The message for the last error on this thread, or `NULL` if none
+///
+/// The string is valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn fastsecret_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// The library version, e.g. `"0.1.0"`
+#[no_mangle]
+pub extern "C" fn fastsecret_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Run `f`, turning errors and panics into a `NULL` result and a recorded
+/// error; unwinding across `extern "C"` would abort the host
+fn guard<T, F>(f: F) -> *mut T
+where
+    F: FnOnce() -> Result<*mut T>,
+{
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(p)) => {
+            LAST_ERROR.with(|e| *e.borrow_mut() = None);
+            p
+        }
+        Ok(Err(e)) => fail(e),
+        Err(_) => fail(anyhow!("internal error: fastsecret panicked")),
+    }
+}
+
+fn fail<T>(error: anyhow::Error) -> *mut T {
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(to_cstring(&format!("{:#}", error))));
+    ptr::null_mut()
+}
+
+unsafe fn opt_str(s: *const c_char, what: &str) -> Result<Option<String>> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(|s| Some(s.to_string()))
+        .map_err(|_| anyhow!("{} is not valid UTF-8", what))
+}
+
+/// Snippets can contain NUL bytes from binary-ish input; drop them rather
+/// than failing the whole scan
+fn to_cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
@@ -0,0 +1,671 @@
+//! Git integration
+//!
+//! Scans repository history by shelling out to the `git` binary and
+//! parsing its unified diff output, so no libgit2 build is required.
+//! Only added lines are matched, which means each finding is attributed
+//! to the commit that introduced the secret. History scans also cover commit
+//! messages, annotated tag messages, and git notes, which are reported under
+//! `<commit message>`, `<tag NAME>`, and `<git note>` at the SHA of the
+//! commit they belong to. Findings from a work tree scan can be attributed
+//! with `git blame`. It also installs the pre-commit and pre-push hooks that
+//! run fastsecret.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{
+    drop_suppressed, scan_line, scan_lines, Finding, MultilineBuffer, ScanOptions,
+};
+
+/// The commit a finding was introduced in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author: String,
+    pub date: String,
+}
+
+/// A line added by a diff hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedLine {
+    /// Path of the file in the new revision
+    pub path: String,
+    /// 1-based line number in the new revision
+    pub line: usize,
+    pub content: String,
+}
+
+/// Incremental parser for unified diff output
+///
+/// Feed it the output of `git diff` or `git log -p` one line at a time;
+/// it yields every added line along with its position in the new file.
+#[derive(Debug, Default)]
+pub struct DiffParser {
+    path: Option<String>,
+    next_line: usize,
+    in_hunk: bool,
+}
+
+impl DiffParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the current file, e.g. when a new commit starts
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Consume one line of diff output (without the trailing newline)
+    pub fn feed(&mut self, line: &str) -> Option<AddedLine> {
+        if self.in_hunk {
+            if let Some(content) = line.strip_prefix('+') {
+                let added = self.path.as_ref().map(|path| AddedLine {
+                    path: path.clone(),
+                    line: self.next_line,
+                    content: content.to_string(),
+                });
+                self.next_line += 1;
+                return added;
+            }
+            if line.starts_with(' ') {
+                self.next_line += 1;
+                return None;
+            }
+            if line.starts_with('-') || line.starts_with('\\') {
+                return None;
+            }
+            self.in_hunk = false;
+        }
+
+        if line.starts_with("diff ") {
+            self.path = None;
+        } else if let Some(target) = line.strip_prefix("+++ ") {
+            self.path = parse_target_path(target);
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            if let Some(start) = parse_hunk_start(header) {
+                self.next_line = start;
+                self.in_hunk = true;
+            }
+        }
+
+        None
+    }
+}
+
+/// Extract the new-side path from a `+++` header
+fn parse_target_path(target: &str) -> Option<String> {
+    let target = target.trim_end_matches('\t').trim_matches('"');
+    if target == "/dev/null" {
+        return None;
+    }
+    Some(target.strip_prefix("b/").unwrap_or(target).to_string())
+}
+
+/// Extract the new-side start line from a hunk header like `-1,2 +3,4 @@`
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let new_range = header.split_whitespace().find(|s| s.starts_with('+'))?;
+    let start = new_range[1..].split(',').next()?;
+    start.parse().ok()
+}
+
+/// Scan the full history of a git repository
+///
+/// Every commit reachable from any ref is diffed against its parent and
+/// the added lines are matched against the rule set, followed by the
+/// messages and notes found by [`scan_messages`].
+pub fn scan_history(
+    repo: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let mut findings = scan_git_diff(
+        repo,
+        &[
+            "log",
+            // Notes are scanned as notes, not as the blobs of the notes ref
+            "--exclude=refs/notes/*",
+            "--all",
+            "--reverse",
+            "-p",
+            "--format=%x00%H%x00%an <%ae>%x00%aI",
+        ],
+        rule_set,
+        options,
+    )?;
+    findings.extend(scan_messages(repo, rule_set, options)?);
+    Ok(findings)
+}
+
+/// Scan the commit messages, annotated tag messages, and notes of a
+/// repository
+///
+/// Findings are named `<commit message>`, `<tag NAME>`, or `<git note>`,
+/// with line numbers counted within the message. Their commit is the one the
+/// message describes, or the one the tag or note points at; a tag's author
+/// is its tagger.
+pub fn scan_messages(
+    repo: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+
+    let log = git_output(
+        repo,
+        &[
+            "log",
+            "--exclude=refs/notes/*",
+            "--all",
+            "--reverse",
+            "-z",
+            "--format=%H%x00%an <%ae>%x00%aI%x00%B%x00%N",
+        ],
+    )?;
+    let fields: Vec<&str> = log.split('\0').collect();
+    for record in fields.chunks_exact(5) {
+        let commit = CommitInfo {
+            sha: record[0].trim_start_matches('\n').to_string(),
+            author: record[1].to_string(),
+            date: record[2].to_string(),
+        };
+        scan_message(
+            "<commit message>",
+            record[3],
+            &commit,
+            rule_set,
+            options,
+            &mut findings,
+        )?;
+        scan_message(
+            "<git note>",
+            record[4],
+            &commit,
+            rule_set,
+            options,
+            &mut findings,
+        )?;
+    }
+
+    let tags = git_output(
+        repo,
+        &[
+            "for-each-ref",
+            "refs/tags",
+            "--format=%(objecttype)%00%(refname:short)%00%(*objectname)%00\
+             %(taggername) %(taggeremail)%00%(taggerdate:iso-strict)%00%(contents)%00",
+        ],
+    )?;
+    let fields: Vec<&str> = tags.split('\0').collect();
+    for record in fields.chunks_exact(6) {
+        // Lightweight tags have no message of their own
+        if record[0].trim_start_matches('\n') != "tag" {
+            continue;
+        }
+        let commit = CommitInfo {
+            sha: record[2].to_string(),
+            author: record[3].to_string(),
+            date: record[4].to_string(),
+        };
+        let name = format!("<tag {}>", record[1]);
+        scan_message(&name, record[5], &commit, rule_set, options, &mut findings)?;
+    }
+
+    Ok(findings)
+}
+
+/// Scan one message, attributing its findings to `commit`
+fn scan_message(
+    name: &str,
+    message: &str,
+    commit: &CommitInfo,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) -> Result<()> {
+    if message.trim().is_empty() {
+        return Ok(());
+    }
+    let start = findings.len();
+    scan_lines(message.as_bytes(), name, rule_set, options, findings)?;
+    for finding in &mut findings[start..] {
+        finding.commit = Some(commit.clone());
+    }
+    Ok(())
+}
+
+/// Attach the commit that last changed each finding's line, from `git blame`
+///
+/// Meant for findings in a work tree: those already carrying a commit, in
+/// files outside a repository, or on lines that aren't committed yet are
+/// left alone. Files that couldn't be blamed are logged.
+pub fn blame(findings: &mut [Finding]) {
+    let mut by_file: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, finding) in findings.iter().enumerate() {
+        if finding.commit.is_none() {
+            by_file.entry(finding.file.clone()).or_default().push(i);
+        }
+    }
+
+    for (file, indices) in by_file {
+        let path = Path::new(&file);
+        // Archive entries, stdin, and other names that aren't files
+        if !path.is_file() {
+            continue;
+        }
+        match blame_file(path, indices.iter().map(|&i| findings[i].line)) {
+            Ok(commits) => {
+                for i in indices {
+                    findings[i].commit = commits.get(&findings[i].line).cloned();
+                }
+            }
+            Err(e) => tracing::info!(file = %file, "Couldn't blame {}: {:#}", file, e),
+        }
+    }
+}
+
+/// The commit that last changed each of `lines` in the file at `path`
+fn blame_file(
+    path: &Path,
+    lines: impl Iterator<Item = usize>,
+) -> Result<HashMap<usize, CommitInfo>> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let dir = dir.to_string_lossy();
+    let name = path
+        .file_name()
+        .context("not a file")?
+        .to_string_lossy()
+        .into_owned();
+
+    let ranges: Vec<String> = lines
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|line| format!("-L{},{}", line, line))
+        .collect();
+    let mut args = vec!["blame", "--line-porcelain"];
+    args.extend(ranges.iter().map(String::as_str));
+    args.extend(["--", &name]);
+    let porcelain = git_output(&dir, &args)?;
+
+    // Each blamed line starts with `<sha> <orig line> <final line>`
+    let mut shas = HashMap::new();
+    for header in porcelain.lines().filter(|l| !l.starts_with('\t')) {
+        let mut parts = header.split(' ');
+        let (Some(sha), Some(_), Some(line)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let Ok(line) = line.parse::<usize>() else {
+            continue;
+        };
+        // Uncommitted lines blame to the all-zero SHA
+        if sha.len() == 40
+            && sha.bytes().all(|b| b.is_ascii_hexdigit())
+            && sha.bytes().any(|b| b != b'0')
+        {
+            shas.insert(line, sha.to_string());
+        }
+    }
+    if shas.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let unique: BTreeSet<&str> = shas.values().map(String::as_str).collect();
+    let mut args = vec![
+        "log",
+        "--no-walk=unsorted",
+        "--format=%H%x00%an <%ae>%x00%aI",
+    ];
+    args.extend(unique);
+    let commits: HashMap<String, CommitInfo> = git_output(&dir, &args)?
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\0');
+            let commit = CommitInfo {
+                sha: parts.next()?.to_string(),
+                author: parts.next()?.to_string(),
+                date: parts.next()?.to_string(),
+            };
+            Some((commit.sha.clone(), commit))
+        })
+        .collect();
+    Ok(shas
+        .into_iter()
+        .filter_map(|(line, sha)| Some((line, commits.get(&sha)?.clone())))
+        .collect())
+}
+
+/// Run a git command in `repo` and return its output
+fn git_output(repo: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed in '{}': {}", args[0], repo, stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Scan only the lines added in the staged changes (`git diff --cached`)
+///
+/// Line numbers refer to the staged version of each file, which makes
+/// this mode suitable for pre-commit hooks.
+pub fn scan_staged(
+    repo: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    scan_git_diff(repo, &["diff", "--cached"], rule_set, options)
+}
+
+/// Scan only the lines that `to` adds since it branched from `from`
+///
+/// Both are any revisions git understands (branches, tags, SHAs, `HEAD~3`).
+/// Like `git diff from...to`, `to` is compared with the merge base, so
+/// commits that landed on `from` after the branch was cut aren't reported.
+/// Line numbers refer to the files in `to`, which makes this the mode for
+/// pull request CI: `scan_diff(repo, "origin/main", "HEAD", ..)` reports only
+/// the secrets a branch introduces.
+pub fn scan_diff(
+    repo: &str,
+    from: &str,
+    to: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    for rev in [from, to] {
+        // Keep revisions from being read as options
+        if rev.is_empty() || rev.starts_with('-') {
+            bail!("invalid revision '{}'", rev);
+        }
+    }
+    // A tree, such as the pre-push hook's empty tree for a new history, has
+    // no merge base, so everything `to` holds is compared with it directly
+    let from_commit = format!("{}^{{commit}}", from);
+    if git_output(repo, &["rev-parse", "--verify", "--quiet", &from_commit]).is_err() {
+        return scan_git_diff(repo, &["diff", from, to], rule_set, options);
+    }
+    let range = format!("{}...{}", from, to);
+    scan_git_diff(repo, &["diff", &range], rule_set, options)
+}
+
+/// Run a git command producing a patch and scan its added lines
+///
+/// Lines of the form `\0<sha>\0<author>\0<date>` mark the start of a
+/// commit and are attached to the findings that follow.
+fn scan_git_diff(
+    repo: &str,
+    args: &[&str],
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "core.quotepath=off"])
+        .args(args)
+        .args([
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-textconv",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed to run git")?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .context("failed to capture git output")?;
+    let mut reader = BufReader::new(stdout);
+    let mut findings = Vec::new();
+    let mut parser = DiffParser::new();
+    let mut commit: Option<CommitInfo> = None;
+    let mut buf = Vec::new();
+
+    // Consecutive added lines of one file, for multi-line rules
+    let mut block = rule_set.has_multiline().then(MultilineBuffer::default);
+    let mut block_path = String::new();
+    // Options resolved for the path of the last added line
+    let mut scoped = (String::new(), Cow::Borrowed(options));
+    let flush = |block: &mut MultilineBuffer,
+                 path: &str,
+                 commit: &Option<CommitInfo>,
+                 findings: &mut Vec<Finding>| {
+        let start = findings.len();
+        block.scan(path, rule_set, &options.for_path(path), findings);
+        for finding in &mut findings[start..] {
+            finding.commit = commit.clone();
+        }
+        block.clear();
+    };
+
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(header) = line.strip_prefix('\0') {
+            if let Some(block) = &mut block {
+                flush(block, &block_path, &commit, &mut findings);
+            }
+            let mut parts = header.split('\0');
+            commit = Some(CommitInfo {
+                sha: parts.next().unwrap_or_default().to_string(),
+                author: parts.next().unwrap_or_default().to_string(),
+                date: parts.next().unwrap_or_default().to_string(),
+            });
+            parser.reset();
+            continue;
+        }
+
+        if let Some(added) = parser.feed(line) {
+            if scoped.0 != added.path {
+                scoped = (added.path.clone(), options.for_path(&added.path));
+            }
+            let start = findings.len();
+            scan_line(
+                &added.path,
+                added.line,
+                &added.content,
+                rule_set,
+                &scoped.1,
+                &mut findings,
+            );
+            drop_suppressed(&mut findings, start, &added.content, None);
+            for finding in &mut findings[start..] {
+                finding.commit = commit.clone();
+            }
+
+            if let Some(block) = &mut block {
+                let contiguous = block_path == added.path
+                    && block.last_line().is_some_and(|last| last + 1 == added.line);
+                if !contiguous {
+                    flush(block, &block_path, &commit, &mut findings);
+                    block_path = added.path.clone();
+                }
+                block.push(added.line, &added.content);
+                let start = findings.len();
+                block.slide(&added.path, rule_set, &scoped.1, &mut findings);
+                for finding in &mut findings[start..] {
+                    finding.commit = commit.clone();
+                }
+            }
+        }
+    }
+
+    if let Some(block) = &mut block {
+        flush(block, &block_path, &commit, &mut findings);
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("git {} failed in '{}' ({})", args[0], repo, status);
+    }
+
+    Ok(findings)
+}
+
+/// Git hook that `install_hook` can manage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    /// Scans staged changes before each commit
+    PreCommit,
+    /// Scans the commits being pushed
+    PrePush,
+}
+
+impl HookKind {
+    /// File name of the hook in the hooks directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrePush => "pre-push",
+        }
+    }
+
+    /// The hook script
+    pub fn script(self) -> String {
+        let body = match self {
+            HookKind::PreCommit => PRE_COMMIT_BODY,
+            HookKind::PrePush => PRE_PUSH_BODY,
+        };
+        format!(
+            "#!/bin/sh\n\
+             {}\n\
+             # Remove with `fastsecret install-hook --hook {} --uninstall`.\n\
+             if ! command -v fastsecret >/dev/null 2>&1; then\n\
+             \x20   echo \"fastsecret not found on PATH; skipping the secret scan\" >&2\n\
+             \x20   exit 0\n\
+             fi\n{}",
+            HOOK_MARKER,
+            self.file_name(),
+            body
+        )
+    }
+}
+
+impl std::str::FromStr for HookKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pre-commit" => Ok(HookKind::PreCommit),
+            "pre-push" => Ok(HookKind::PrePush),
+            _ => Err(format!(
+                "Unknown hook: {} (expected pre-commit or pre-push)",
+                s
+            )),
+        }
+    }
+}
+
+const PRE_COMMIT_BODY: &str = "exec fastsecret --staged --exit-on-secrets\n";
+
+/// Scans each pushed ref from what the remote already has; a new branch is
+/// scanned from the parent of its oldest commit not on any remote
+const PRE_PUSH_BODY: &str = r#"empty_tree=4b825dc642cb6eb9a060e54bf8d69288fbee4904
+status=0
+while read -r local_ref local_sha remote_ref remote_sha; do
+    case "$local_sha" in *[!0]*) ;; *) continue ;; esac  # deleted ref
+    case "$remote_sha" in
+        *[!0]*) from=$remote_sha ;;
+        *)
+            oldest=$(git rev-list --reverse "$local_sha" --not --remotes | head -n 1)
+            [ -z "$oldest" ] && continue
+            from=$(git rev-parse --verify --quiet "$oldest^") || from=$empty_tree
+            ;;
+    esac
+    fastsecret diff --from "$from" --to "$local_sha" --exit-on-secrets || status=1
+done
+exit $status
+"#;
+
+/// Line identifying hook scripts written by fastsecret
+const HOOK_MARKER: &str = "# Installed by fastsecret.";
+
+/// Directory git runs hooks from, honoring `core.hooksPath` and worktrees
+pub fn hooks_dir(repo: &str) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!("'{}' is not a git repository", repo);
+    }
+    let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(if dir.is_absolute() {
+        dir
+    } else {
+        Path::new(repo).join(dir)
+    })
+}
+
+/// Write the hook script into `repo`'s hooks directory
+///
+/// An existing hook that fastsecret didn't write is only replaced with
+/// `force`. Returns the path of the installed hook.
+pub fn install_hook(repo: &str, kind: HookKind, force: bool) -> Result<PathBuf> {
+    let dir = hooks_dir(repo)?;
+    let path = dir.join(kind.file_name());
+    if !force && path.exists() && !is_fastsecret_hook(&path) {
+        bail!(
+            "'{}' already exists and was not installed by fastsecret; use --force to replace it",
+            path.display()
+        );
+    }
+
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create '{}'", dir.display()))?;
+    fs::write(&path, kind.script())
+        .with_context(|| format!("failed to write '{}'", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Remove a hook installed by `install_hook`
+///
+/// Hooks fastsecret didn't write are left alone unless `force` is set.
+/// Returns the removed path, or `None` if there was no hook.
+pub fn uninstall_hook(repo: &str, kind: HookKind, force: bool) -> Result<Option<PathBuf>> {
+    let path = hooks_dir(repo)?.join(kind.file_name());
+    if !path.exists() {
+        return Ok(None);
+    }
+    if !force && !is_fastsecret_hook(&path) {
+        bail!(
+            "'{}' was not installed by fastsecret; use --force to remove it anyway",
+            path.display()
+        );
+    }
+    fs::remove_file(&path).with_context(|| format!("failed to remove '{}'", path.display()))?;
+    Ok(Some(path))
+}
+
+fn is_fastsecret_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|script| script.lines().any(|l| l == HOOK_MARKER))
+}
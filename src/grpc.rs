@@ -0,0 +1,234 @@
+//! gRPC scanning service and client
+//!
+//! Behind the `grpc` feature, `fastsecret grpc` serves the `Scanner`
+//! service of `proto/fastsecret.proto`: clients stream file contents in
+//! chunks and get findings streamed back as each file is scanned. Both
+//! directions go over HTTP/2 flow control, so a slow scanner pushes back on
+//! senders and a slow reader pushes back on scanning.
+//!
+//! The messages, service trait, and client are generated from the proto
+//! file at build time; [`ScanService`] implements the service.
+//!
+//! ```no_run
+//! use fastsecret::grpc::{chunks, ScannerClient};
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let mut client = ScannerClient::connect("http://scanner.internal:50051").await?;
+//! let upload = chunks("app/.env", std::fs::read("app/.env")?);
+//! let mut findings = client.scan(tokio_stream::iter(upload)).await?.into_inner();
+//! while let Some(finding) = findings.next().await {
+//!     let finding = finding?;
+//!     println!("{}:{} {}", finding.file, finding.line, finding.rule_id);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::confidence::Confidence;
+use crate::scanner::{self, FindingSeverity, Scanner};
+
+/// Protobuf messages, service, and client of the `fastsecret.v1` package
+pub mod proto {
+    tonic::include_proto!("fastsecret.v1");
+}
+
+pub use proto::scanner_client::ScannerClient;
+pub use proto::scanner_server::ScannerServer;
+pub use proto::{Chunk, Finding};
+
+/// Fully qualified name of the service
+pub const SERVICE_NAME: &str = "fastsecret.v1.Scanner";
+
+/// Size of the chunks [`chunks`] splits content into
+pub const CHUNK_SIZE: usize = 64 << 10;
+
+/// Findings buffered per call before scanning waits for the client
+const RESPONSE_BUFFER: usize = 64;
+
+/// Settings of the gRPC service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrpcOptions {
+    /// Largest file accepted, in bytes, summed over its chunks
+    pub max_file_size: u64,
+    /// Files a call may have started but not finished at once
+    pub max_open_files: usize,
+}
+
+impl Default for GrpcOptions {
+    fn default() -> Self {
+        GrpcOptions {
+            max_file_size: 64 << 20,
+            max_open_files: 64,
+        }
+    }
+}
+
+/// Split `content` into the chunks of one file, the last marked `eof`
+pub fn chunks(name: &str, content: impl AsRef<[u8]>) -> Vec<Chunk> {
+    let content = content.as_ref();
+    let mut chunks: Vec<Chunk> = content
+        .chunks(CHUNK_SIZE)
+        .map(|data| Chunk {
+            name: name.to_string(),
+            data: data.to_vec(),
+            eof: false,
+        })
+        .collect();
+    match chunks.last_mut() {
+        Some(last) => last.eof = true,
+        None => chunks.push(Chunk {
+            name: name.to_string(),
+            data: Vec::new(),
+            eof: true,
+        }),
+    }
+    chunks
+}
+
+impl From<&scanner::Finding> for Finding {
+    fn from(finding: &scanner::Finding) -> Self {
+        let severity = match finding.severity {
+            FindingSeverity::High => "high",
+            FindingSeverity::Medium => "medium",
+            FindingSeverity::Low => "low",
+        };
+        let confidence = match finding.confidence {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        };
+        Finding {
+            file: finding.file.clone(),
+            line: finding.line as u64,
+            column: finding.column as u64,
+            end_column: finding.end_column as u64,
+            snippet: finding.snippet.clone(),
+            rule_id: finding.rule_id.clone(),
+            rule_name: finding.rule_name.clone(),
+            severity: severity.to_string(),
+            confidence: confidence.to_string(),
+            fingerprint: finding.fingerprint(),
+            remediation: finding.remediation.clone(),
+            variable: finding.variable.clone(),
+            metadata: finding
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            rule_source: finding.rule_source.clone(),
+            rule_version: finding.rule_version.clone(),
+        }
+    }
+}
+
+/// Serve the `Scanner` service on `addr` until the process is killed
+pub async fn serve(scanner: Scanner, options: GrpcOptions, addr: SocketAddr) -> Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(ScannerServer::new(ScanService::new(scanner, options)))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+/// The `Scanner` service, to add to a [`tonic::transport::Server`] as a
+/// [`ScannerServer`]
+#[derive(Debug, Clone)]
+pub struct ScanService {
+    scanner: Arc<Scanner>,
+    options: GrpcOptions,
+}
+
+impl ScanService {
+    pub fn new(scanner: Scanner, options: GrpcOptions) -> Self {
+        ScanService {
+            scanner: Arc::new(scanner),
+            options,
+        }
+    }
+
+    /// Scan each file once its last chunk arrives, sending its findings
+    /// to `tx`
+    async fn scan_chunks(
+        &self,
+        mut chunks: Streaming<Chunk>,
+        tx: mpsc::Sender<Result<Finding, Status>>,
+    ) -> Result<(), Status> {
+        let mut open: HashMap<String, Vec<u8>> = HashMap::new();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+            if !open.contains_key(&chunk.name) && open.len() >= self.options.max_open_files {
+                return Err(Status::resource_exhausted(format!(
+                    "more than {} files open at once; finish one with eof before starting '{}'",
+                    self.options.max_open_files, chunk.name
+                )));
+            }
+            let content = open.entry(chunk.name.clone()).or_default();
+            if (content.len() + chunk.data.len()) as u64 > self.options.max_file_size {
+                return Err(Status::resource_exhausted(format!(
+                    "'{}' exceeds {} bytes",
+                    chunk.name, self.options.max_file_size
+                )));
+            }
+            content.extend_from_slice(&chunk.data);
+            if chunk.eof {
+                let content = open.remove(&chunk.name).unwrap_or_default();
+                self.scan_file(chunk.name, content, &tx).await?;
+            }
+        }
+        let mut rest: Vec<_> = open.into_iter().collect();
+        rest.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, content) in rest {
+            self.scan_file(name, content, &tx).await?;
+        }
+        Ok(())
+    }
+
+    async fn scan_file(
+        &self,
+        name: String,
+        content: Vec<u8>,
+        tx: &mpsc::Sender<Result<Finding, Status>>,
+    ) -> Result<(), Status> {
+        let scanner = Arc::clone(&self.scanner);
+        let report = tokio::task::spawn_blocking(move || scanner.scan_bytes(&name, &content))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| Status::invalid_argument(format!("{:#}", e)))?;
+        for finding in &report.findings {
+            tx.send(Ok(finding.into()))
+                .await
+                .map_err(|_| Status::cancelled("client went away"))?;
+        }
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl proto::scanner_server::Scanner for ScanService {
+    type ScanStream = ReceiverStream<Result<Finding, Status>>;
+
+    async fn scan(
+        &self,
+        request: Request<Streaming<Chunk>>,
+    ) -> Result<Response<Self::ScanStream>, Status> {
+        let (tx, rx) = mpsc::channel(RESPONSE_BUFFER);
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(status) = service.scan_chunks(request.into_inner(), tx.clone()).await {
+                let _ = tx.send(Err(status)).await;
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
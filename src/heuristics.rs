@@ -0,0 +1,215 @@
+//! False-positive heuristics
+//!
+//! An optional post-filter for findings that are obviously not real
+//! credentials: values named `example_token`, keys made of `xxxx` or
+//! `123456`, snippets quoted in documentation comments. Each finding gets a
+//! score from 0 to 100 built from cheap signals, and the score decides
+//! whether it is kept, demoted, or dropped. The score, the decision, and
+//! the signals that fired are recorded in the finding's `metadata` so a
+//! reviewer can see why a finding was downgraded.
+
+use std::collections::BTreeSet;
+
+use crate::confidence::{Confidence, PLACEHOLDER_MARKERS};
+use crate::scanner::{Finding, FindingSeverity};
+
+/// Score at which a finding is demoted one severity level
+pub const DEMOTE_SCORE: u32 = 40;
+
+/// Score at which a finding is dropped
+pub const DROP_SCORE: u32 = 70;
+
+/// Words in a variable name that mark its value as fake
+const FAKE_NAME_MARKERS: &[&str] = &[
+    "example",
+    "sample",
+    "dummy",
+    "fake",
+    "mock",
+    "test",
+    "placeholder",
+    "demo",
+    "template",
+];
+
+/// Line prefixes that start a comment
+const COMMENT_PREFIXES: &[&str] = &["#", "//", "/*", "*", "--", ";", "<!--", "rem "];
+
+/// Phrases that introduce an illustrative value
+const DOC_PHRASES: &[&str] = &["e.g.", "for example", "such as", "example:", "i.e."];
+
+/// What the filter does with a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Decision {
+    Keep,
+    /// Lower the severity one level and the confidence to low
+    Demote,
+    Drop,
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Decision::Keep => "keep",
+            Decision::Demote => "demote",
+            Decision::Drop => "drop",
+        })
+    }
+}
+
+/// How likely a finding is to be fake, and why
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Assessment {
+    /// 0 (no sign of a fake) to 100
+    pub score: u32,
+    /// The signals that raised the score
+    pub reasons: Vec<String>,
+}
+
+impl Assessment {
+    fn add(&mut self, points: u32, reason: String) {
+        self.score = (self.score + points).min(100);
+        self.reasons.push(reason);
+    }
+
+    pub fn decision(&self) -> Decision {
+        match self.score {
+            s if s >= DROP_SCORE => Decision::Drop,
+            s if s >= DEMOTE_SCORE => Decision::Demote,
+            _ => Decision::Keep,
+        }
+    }
+}
+
+/// Score a finding from its secret, the name it is assigned to, and the
+/// line it appears on
+pub fn assess(finding: &Finding) -> Assessment {
+    let mut assessment = Assessment::default();
+    let secret = finding.secret.trim();
+    let lower = secret.to_ascii_lowercase();
+
+    if let Some(word) = PLACEHOLDER_MARKERS.iter().find(|m| lower.contains(*m)) {
+        assessment.add(45, format!("placeholder word '{}'", word));
+    }
+
+    let name = finding
+        .variable
+        .clone()
+        .or_else(|| assigned_name(&finding.snippet, secret));
+    if let Some(name) = name {
+        let name = name.to_ascii_lowercase();
+        if let Some(word) = FAKE_NAME_MARKERS.iter().find(|m| name.contains(*m)) {
+            assessment.add(30, format!("variable name contains '{}'", word));
+        }
+    }
+
+    let line = finding.snippet.trim_start().to_ascii_lowercase();
+    if COMMENT_PREFIXES.iter().any(|p| line.starts_with(p)) {
+        assessment.add(15, "in a comment".to_string());
+    }
+    if let Some(phrase) = DOC_PHRASES.iter().find(|p| line.contains(*p)) {
+        assessment.add(20, format!("introduced by '{}'", phrase));
+    }
+
+    let chars: Vec<char> = secret.chars().collect();
+    let run = longest_run(&chars, |a, b| a == b);
+    if run >= 8 || (run >= 4 && run * 2 >= chars.len()) {
+        assessment.add(50, format!("{} repeated characters", run));
+    } else if run >= 4 {
+        assessment.add(25, format!("{} repeated characters", run));
+    }
+    let sequence = longest_run(&chars, |a, b| {
+        a.is_ascii_alphanumeric() && b as u32 == a as u32 + 1
+    });
+    if sequence >= 4 {
+        assessment.add(25, format!("{}-character sequence", sequence));
+    }
+    let distinct: BTreeSet<&char> = chars.iter().collect();
+    if chars.len() >= 12 && distinct.len() * 4 < chars.len() {
+        assessment.add(30, format!("only {} distinct characters", distinct.len()));
+    }
+
+    assessment
+}
+
+/// Score every finding, record the result in its metadata, and demote or
+/// drop the ones that look fake
+///
+/// Dropped findings are logged at debug level.
+pub fn filter(findings: Vec<Finding>) -> Vec<Finding> {
+    findings
+        .into_iter()
+        .filter_map(|mut finding| {
+            let assessment = assess(&finding);
+            let decision = assessment.decision();
+            finding
+                .metadata
+                .insert("fp_score".to_string(), assessment.score.to_string());
+            finding
+                .metadata
+                .insert("fp_decision".to_string(), decision.to_string());
+            if !assessment.reasons.is_empty() {
+                finding
+                    .metadata
+                    .insert("fp_reasons".to_string(), assessment.reasons.join(", "));
+            }
+
+            match decision {
+                Decision::Keep => Some(finding),
+                Decision::Demote => {
+                    finding.severity = match finding.severity {
+                        FindingSeverity::High => FindingSeverity::Medium,
+                        _ => FindingSeverity::Low,
+                    };
+                    finding.confidence = Confidence::Low;
+                    Some(finding)
+                }
+                Decision::Drop => {
+                    tracing::debug!(
+                        rule = %finding.rule_id,
+                        file = %finding.file,
+                        "Dropped likely false positive '{}' at {}:{} (score {}: {})",
+                        finding.rule_name,
+                        finding.file,
+                        finding.line,
+                        assessment.score,
+                        assessment.reasons.join(", ")
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Name assigned the value on a line like `name = "secret"` or
+/// `"name": "secret"`
+fn assigned_name(line: &str, secret: &str) -> Option<String> {
+    let before = &line[..line.find(secret)?];
+    let before = before.trim_end_matches(|c: char| {
+        c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '=' | ':' | '>' | '(' | ',')
+    });
+    let start = before
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let name = &before[start..];
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Length of the longest run of characters where each follows the one
+/// before it according to `next`
+fn longest_run(chars: &[char], next: impl Fn(char, char) -> bool) -> usize {
+    let mut longest = usize::from(!chars.is_empty());
+    let mut current = longest;
+    for pair in chars.windows(2) {
+        current = if next(pair[0], pair[1]) {
+            current + 1
+        } else {
+            1
+        };
+        longest = longest.max(current);
+    }
+    longest
+}
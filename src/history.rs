@@ -0,0 +1,256 @@
+//! Scan history in a local SQLite database
+//!
+//! `--record-db scans.db` appends each scan run and its findings to the
+//! database, and `fastsecret history` reads them back: when each secret was
+//! first and last seen, and how the number of findings changed over time.
+//! Findings are stored by fingerprint, rule, and location; the secrets
+//! themselves never reach the database.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::datetime::UtcDateTime;
+use crate::notifications::ScanContext;
+use crate::scanner::{Finding, FindingSeverity, SeverityCounts};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scans (
+    id INTEGER PRIMARY KEY,
+    repository TEXT NOT NULL,
+    git_ref TEXT,
+    git_commit TEXT,
+    recorded_at TEXT NOT NULL,
+    high INTEGER NOT NULL,
+    medium INTEGER NOT NULL,
+    low INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS findings (
+    scan_id INTEGER NOT NULL REFERENCES scans(id) ON DELETE CASCADE,
+    fingerprint TEXT NOT NULL,
+    rule_id TEXT NOT NULL,
+    rule_name TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    file TEXT NOT NULL,
+    line INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS scans_repository ON scans(repository, id);
+CREATE INDEX IF NOT EXISTS findings_scan ON findings(scan_id);
+";
+
+/// One recorded scan run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanRun {
+    pub id: i64,
+    pub repository: String,
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// UTC time of the run, `YYYY-MM-DDTHH:MM:SSZ`
+    pub recorded_at: String,
+    pub counts: SeverityCounts,
+}
+
+/// How one secret, identified by its fingerprint, showed up across runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecretHistory {
+    pub fingerprint: String,
+    pub rule_id: String,
+    /// Display name of the rule
+    #[serde(rename = "rule")]
+    pub rule_name: String,
+    pub severity: FindingSeverity,
+    /// File and line where the secret was last seen
+    pub file: String,
+    pub line: usize,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Number of runs that found the secret
+    pub scans: usize,
+    /// Whether the latest run of the repository still found it
+    pub open: bool,
+}
+
+/// A scan history database
+pub struct HistoryDb {
+    conn: Connection,
+}
+
+impl HistoryDb {
+    /// Open the database at `path`, creating it and its tables if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open history database '{}'", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("'{}' isn't a fastsecret history database", path.display()))?;
+        Ok(HistoryDb { conn })
+    }
+
+    /// Record a scan run that just finished, returning its id
+    pub fn record(&mut self, context: &ScanContext, findings: &[Finding]) -> Result<i64> {
+        self.record_at(context, findings, SystemTime::now())
+    }
+
+    /// Record a scan run made at `time`, returning its id
+    pub fn record_at(
+        &mut self,
+        context: &ScanContext,
+        findings: &[Finding],
+        time: SystemTime,
+    ) -> Result<i64> {
+        let counts = SeverityCounts::from_findings(findings);
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO scans (repository, git_ref, git_commit, recorded_at, high, medium, low)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                context.repository,
+                context.git_ref,
+                context.commit,
+                timestamp(UtcDateTime::from_system_time(time)),
+                counts.high as i64,
+                counts.medium as i64,
+                counts.low as i64,
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO findings (scan_id, fingerprint, rule_id, rule_name, severity, file, line)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for f in findings {
+                insert.execute(params![
+                    id,
+                    f.fingerprint(),
+                    f.rule_id,
+                    f.rule_name,
+                    f.severity.as_str(),
+                    f.relative_file.as_deref().unwrap_or(&f.file),
+                    f.line as i64,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Repositories with recorded runs, by name
+    pub fn repositories(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT repository FROM scans ORDER BY repository")?;
+        let names = stmt.query_map([], |row| row.get(0))?;
+        Ok(names.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// The repository of the most recent run, if any run was recorded
+    pub fn latest_repository(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT repository FROM scans ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Runs of `repository`, oldest first
+    pub fn runs(&self, repository: &str) -> Result<Vec<ScanRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, repository, git_ref, git_commit, recorded_at, high, medium, low
+             FROM scans WHERE repository = ?1 ORDER BY id",
+        )?;
+        let runs = stmt.query_map([repository], |row| {
+            Ok(ScanRun {
+                id: row.get(0)?,
+                repository: row.get(1)?,
+                git_ref: row.get(2)?,
+                commit: row.get(3)?,
+                recorded_at: row.get(4)?,
+                counts: SeverityCounts {
+                    high: row.get::<_, i64>(5)? as usize,
+                    medium: row.get::<_, i64>(6)? as usize,
+                    low: row.get::<_, i64>(7)? as usize,
+                },
+            })
+        })?;
+        Ok(runs.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Every secret found in `repository`, in the order they first appeared
+    pub fn secrets(&self, repository: &str) -> Result<Vec<SecretHistory>> {
+        let latest: Option<i64> = self.conn.query_row(
+            "SELECT MAX(id) FROM scans WHERE repository = ?1",
+            [repository],
+            |row| row.get(0),
+        )?;
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.recorded_at, f.fingerprint, f.rule_id, f.rule_name, f.severity,
+                    f.file, f.line
+             FROM findings f JOIN scans s ON s.id = f.scan_id
+             WHERE s.repository = ?1 ORDER BY s.id",
+        )?;
+        let mut rows = stmt.query([repository])?;
+
+        let mut secrets: Vec<SecretHistory> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        // Last run counted for each secret, so duplicates in a run count once
+        let mut last_scan: Vec<i64> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let scan_id: i64 = row.get(0)?;
+            let recorded_at: String = row.get(1)?;
+            let fingerprint: String = row.get(2)?;
+            let severity: String = row.get(5)?;
+            let severity = severity.parse().unwrap_or_default();
+            let line = row.get::<_, i64>(7)? as usize;
+            match index.get(&fingerprint) {
+                Some(&i) => {
+                    let secret = &mut secrets[i];
+                    if last_scan[i] != scan_id {
+                        last_scan[i] = scan_id;
+                        secret.scans += 1;
+                        secret.last_seen = recorded_at;
+                        secret.file = row.get(6)?;
+                        secret.line = line;
+                        secret.severity = severity;
+                    }
+                }
+                None => {
+                    index.insert(fingerprint.clone(), secrets.len());
+                    last_scan.push(scan_id);
+                    secrets.push(SecretHistory {
+                        fingerprint,
+                        rule_id: row.get(3)?,
+                        rule_name: row.get(4)?,
+                        severity,
+                        file: row.get(6)?,
+                        line,
+                        first_seen: recorded_at.clone(),
+                        last_seen: recorded_at,
+                        scans: 1,
+                        open: false,
+                    });
+                }
+            }
+        }
+        for (secret, scan_id) in secrets.iter_mut().zip(&last_scan) {
+            secret.open = Some(*scan_id) == latest;
+        }
+        Ok(secrets)
+    }
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ`, which sorts chronologically as text
+fn timestamp(t: UtcDateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
@@ -0,0 +1,330 @@
+//! Container image scanning
+//!
+//! Walks the filesystem of every layer in a Docker or OCI image tarball, as
+//! written by `docker save`, and scans the files it contains. Lower layers
+//! are scanned even when a later layer deletes or overwrites a file, since
+//! anyone who pulls the image can still read them. Findings are reported as
+//! `image:tag!path/in/layer` and carry the layer's digest and the Dockerfile
+//! instruction that created it.
+//!
+//! An image reference that isn't a local file is exported with the `docker`
+//! binary, pulling it first if needed.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::archive::{scan_tar_stream, ArchiveOptions};
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{Finding, ScanOptions};
+
+/// Largest metadata document (manifest, index, or config) that is read
+const MAX_METADATA_SIZE: u64 = 4 << 20;
+
+/// The image layer a finding was found in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayerInfo {
+    /// Digest of the uncompressed layer, as shown by `docker inspect`
+    pub digest: String,
+    /// Dockerfile instruction that created the layer, when recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+}
+
+/// `manifest.json` entry written by `docker save`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerManifest {
+    config: String,
+    layers: Vec<String>,
+}
+
+/// An OCI image index or image manifest
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OciDocument {
+    manifests: Vec<OciDescriptor>,
+    config: Option<OciDescriptor>,
+    layers: Vec<OciDescriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciDescriptor {
+    digest: String,
+}
+
+/// The parts of an image config that describe its layers
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ImageConfig {
+    rootfs: RootFs,
+    history: Vec<History>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RootFs {
+    diff_ids: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct History {
+    created_by: Option<String>,
+    empty_layer: bool,
+}
+
+/// Scan every layer of a container image
+///
+/// `reference` is either the path of an image tarball or an image name
+/// known to (or pullable by) the local Docker daemon.
+pub fn scan_image(
+    reference: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let path = Path::new(reference);
+    if path.is_file() {
+        let name = path
+            .file_name()
+            .map_or_else(|| reference.to_string(), |n| n.to_string_lossy().into());
+        return scan_image_tarball(path, &name, rule_set, options);
+    }
+
+    let export = ExportedImage::save(reference)?;
+    scan_image_tarball(&export.path, reference, rule_set, options)
+}
+
+/// Scan every layer of an image tarball, reporting files as `name!path`
+pub fn scan_image_tarball(
+    path: &Path,
+    name: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let open = || -> Result<tar::Archive<BufReader<File>>> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open image '{}'", path.display()))?;
+        Ok(tar::Archive::new(BufReader::new(file)))
+    };
+
+    // First pass: collect the JSON documents describing the image
+    let mut documents = HashMap::new();
+    for entry in open()?.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() || entry.size() > MAX_METADATA_SIZE {
+            continue;
+        }
+        let entry_path = normalize(&entry.path()?.to_string_lossy());
+        let mut data = Vec::new();
+        let mut head = [0u8; 1];
+        if entry.read(&mut head)? == 1 && matches!(head[0], b'{' | b'[') {
+            data.push(head[0]);
+            entry.read_to_end(&mut data)?;
+            documents.insert(entry_path, data);
+        }
+    }
+    let layers = resolve_layers(&documents)
+        .with_context(|| format!("'{}' is not a Docker or OCI image archive", path.display()))?;
+
+    // Nested archives inside layers are only followed with --scan-archives
+    let limits = ArchiveOptions {
+        max_depth: options.archives.map_or(0, |a| a.max_depth),
+        max_total_size: u64::MAX,
+        ..options.archives.unwrap_or_default()
+    };
+
+    // Second pass: scan the layers themselves
+    let mut findings = Vec::new();
+    for entry in open()?.entries()? {
+        let entry = entry?;
+        let entry_path = normalize(&entry.path()?.to_string_lossy());
+        let Some(layer) = layers.get(&entry_path) else {
+            continue;
+        };
+        tracing::debug!("Scanning layer {}", layer.digest);
+
+        let mut reader = BufReader::new(entry);
+        let start = findings.len();
+        let magic = reader.fill_buf()?;
+        let result = if magic.starts_with(&[0x1f, 0x8b]) {
+            scan_tar_stream(
+                GzDecoder::new(reader),
+                name,
+                rule_set,
+                options,
+                &limits,
+                &mut findings,
+            )
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            tracing::warn!(
+                "Skipping layer {}: zstd-compressed layers are not supported",
+                layer.digest
+            );
+            continue;
+        } else {
+            scan_tar_stream(reader, name, rule_set, options, &limits, &mut findings)
+        };
+        result.with_context(|| format!("failed to read layer {}", layer.digest))?;
+
+        for finding in &mut findings[start..] {
+            finding.relative_file = finding
+                .file
+                .strip_prefix(name)
+                .and_then(|f| f.strip_prefix('!'))
+                .map(str::to_string);
+            finding.layer = Some(layer.clone());
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Map each layer's path inside the tarball to its digest and instruction
+fn resolve_layers(documents: &HashMap<String, Vec<u8>>) -> Result<HashMap<String, LayerInfo>> {
+    let (config_path, layer_paths) = match documents.get("manifest.json") {
+        Some(data) => {
+            let manifests: Vec<DockerManifest> =
+                serde_json::from_slice(data).context("invalid manifest.json")?;
+            let Some(manifest) = manifests.into_iter().next() else {
+                bail!("manifest.json lists no images");
+            };
+            (manifest.config, manifest.layers)
+        }
+        None => {
+            let data = documents
+                .get("index.json")
+                .context("no manifest.json or index.json")?;
+            let mut document: OciDocument =
+                serde_json::from_slice(data).context("invalid index.json")?;
+
+            // Follow nested indexes (e.g. multi-platform images) to the first manifest
+            for _ in 0..8 {
+                if document.config.is_some() {
+                    break;
+                }
+                let Some(first) = document.manifests.first() else {
+                    bail!("index.json lists no manifests");
+                };
+                let blob = blob_path(&first.digest);
+                let data = documents
+                    .get(&blob)
+                    .with_context(|| format!("missing manifest blob '{}'", blob))?;
+                document = serde_json::from_slice(data)
+                    .with_context(|| format!("invalid manifest blob '{}'", blob))?;
+            }
+            let config = document.config.context("image manifest has no config")?;
+            (
+                blob_path(&config.digest),
+                document
+                    .layers
+                    .iter()
+                    .map(|l| blob_path(&l.digest))
+                    .collect(),
+            )
+        }
+    };
+
+    let config: ImageConfig = match documents.get(&normalize(&config_path)) {
+        Some(data) => serde_json::from_slice(data).context("invalid image config")?,
+        None => ImageConfig::default(),
+    };
+    let mut instructions = config
+        .history
+        .into_iter()
+        .filter(|h| !h.empty_layer)
+        .map(|h| h.created_by);
+
+    Ok(layer_paths
+        .into_iter()
+        .enumerate()
+        .map(|(idx, path)| {
+            let path = normalize(&path);
+            let digest = config
+                .rootfs
+                .diff_ids
+                .get(idx)
+                .cloned()
+                .unwrap_or_else(|| digest_from_path(&path));
+            let created_by = instructions.next().flatten().map(|s| clean_instruction(&s));
+            (path, LayerInfo { digest, created_by })
+        })
+        .collect())
+}
+
+/// Path of an OCI blob inside the image layout
+fn blob_path(digest: &str) -> String {
+    format!("blobs/{}", digest.replacen(':', "/", 1))
+}
+
+/// Best guess at a layer digest when the config doesn't list one
+fn digest_from_path(path: &str) -> String {
+    match path.strip_prefix("blobs/") {
+        Some(blob) => blob.replacen('/', ":", 1),
+        None => path.trim_end_matches("/layer.tar").to_string(),
+    }
+}
+
+/// Drop the shell wrapper Docker records around `RUN` instructions
+fn clean_instruction(created_by: &str) -> String {
+    let s = created_by.trim();
+    let s = s.strip_prefix("/bin/sh -c ").unwrap_or(s);
+    let s = s.strip_prefix("#(nop) ").unwrap_or(s);
+    s.trim().to_string()
+}
+
+fn normalize(path: &str) -> String {
+    path.trim_start_matches("./").to_string()
+}
+
+/// An image exported from the Docker daemon to a temporary tarball
+struct ExportedImage {
+    path: PathBuf,
+}
+
+impl ExportedImage {
+    /// Export `reference` with `docker save`, pulling it first if needed
+    fn save(reference: &str) -> Result<Self> {
+        let export = ExportedImage {
+            path: std::env::temp_dir().join(format!("fastsecret-image-{}.tar", std::process::id())),
+        };
+
+        if !docker(&["image", "inspect", reference], true)? {
+            tracing::info!("Pulling {}", reference);
+            if !docker(&["pull", reference], false)? {
+                bail!(
+                    "image '{}' not found locally and could not be pulled",
+                    reference
+                );
+            }
+        }
+
+        let path = export.path.to_string_lossy();
+        if !docker(&["save", "-o", &path, reference], false)? {
+            bail!("failed to export image '{}' with docker save", reference);
+        }
+        Ok(export)
+    }
+}
+
+impl Drop for ExportedImage {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Run a docker command, returning whether it succeeded
+fn docker(args: &[&str], quiet: bool) -> Result<bool> {
+    let mut command = Command::new("docker");
+    command.args(args).stdout(Stdio::null());
+    if quiet {
+        command.stderr(Stdio::null());
+    }
+    let status = command.status().context("failed to run docker")?;
+    Ok(status.success())
+}
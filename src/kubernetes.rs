@@ -0,0 +1,173 @@
+//! Kubernetes Secrets and Helm templates
+//!
+//! Values under a Secret's `data:` are base64 encoded, so rules never see
+//! the credential itself. Each value is decoded and scanned, and findings
+//! point at the manifest line with the key path (`data.api-key`) instead of
+//! the encoded snippet.
+//!
+//! Helm templates often carry a real credential as a fallback, as in
+//! `{{ .Values.db.password | default "hunter2" }}`. Lines are rendered with
+//! those literal defaults so rules that need the surrounding key still
+//! match.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::envfile::VariableTracker;
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{locate, scan_line, scan_lines, Finding, ScanOptions};
+
+/// Decode the `data:` values of Secret documents and scan them
+///
+/// Findings on the encoded text itself (from `findings[file_start..]`) are
+/// replaced by those from the decoded value. Values that aren't base64 or
+/// don't decode to text are left alone.
+pub(crate) fn scan_secret_data(
+    tracker: &VariableTracker,
+    file_start: usize,
+    path_str: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    for value in tracker.secret_data() {
+        let Some(text) = STANDARD
+            .decode(&value.value)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        else {
+            continue;
+        };
+        let key_path = format!("data.{}", value.name);
+
+        // A one-line value is scanned next to its key, like a stringData
+        // entry, so rules that look for an assignment still match
+        let single_line = !text.trim_end().contains('\n');
+        let decoded = if single_line {
+            format!("{}: {}", value.name, text.trim())
+        } else {
+            text
+        };
+        let mut decoded_findings = Vec::new();
+        if scan_lines(
+            decoded.as_bytes(),
+            path_str,
+            rule_set,
+            options,
+            &mut decoded_findings,
+        )
+        .is_err()
+        {
+            continue;
+        }
+
+        let mut file_findings = findings.split_off(file_start);
+        file_findings.retain(|f| !(f.line == value.line && value.value.contains(&f.secret)));
+        findings.append(&mut file_findings);
+
+        let (column, end_column) = value.columns;
+        for mut finding in decoded_findings {
+            finding.snippet = if single_line {
+                format!("data.{}", finding.snippet)
+            } else {
+                format!("{}: {}", key_path, finding.snippet)
+            };
+            finding.line = value.line;
+            finding.column = column;
+            finding.end_column = end_column;
+            finding.variable = Some(key_path.clone());
+            findings.push(finding);
+        }
+    }
+}
+
+/// Scan a Helm template line rendered with its literal defaults
+///
+/// Only adds findings the raw line didn't already produce (those after
+/// `start`); columns and snippets refer to the raw line.
+pub(crate) fn scan_helm_line(
+    path_str: &str,
+    line_no: usize,
+    line: &str,
+    start: usize,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(rendered) = render_helm_defaults(line) else {
+        return;
+    };
+    let mut rendered_findings = Vec::new();
+    scan_line(
+        path_str,
+        line_no,
+        &rendered,
+        rule_set,
+        options,
+        &mut rendered_findings,
+    );
+    for mut finding in rendered_findings {
+        let known = findings[start..]
+            .iter()
+            .any(|f| f.rule_id == finding.rule_id && f.secret == finding.secret);
+        // The default's text appears verbatim in the raw line
+        let Some(offset) = line.find(&finding.secret).filter(|_| !known) else {
+            continue;
+        };
+        let (column, end_column, snippet) =
+            locate(line, offset..offset + finding.secret.len(), options.redact);
+        finding.column = column;
+        finding.end_column = end_column;
+        finding.snippet = snippet;
+        findings.push(finding);
+    }
+}
+
+/// `line` with every `{{ ... }}` action that has a quoted `default`
+/// replaced by that default, or `None` if there is none
+fn render_helm_defaults(line: &str) -> Option<String> {
+    let mut rendered = String::with_capacity(line.len());
+    let mut rest = line;
+    let mut replaced = false;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open..].find("}}").map(|c| open + c + 2) else {
+            break;
+        };
+        rendered.push_str(&rest[..open]);
+        match quoted_default(&rest[open + 2..close - 2]) {
+            Some(default) => {
+                rendered.push_str(default);
+                replaced = true;
+            }
+            None => rendered.push_str(&rest[open..close]),
+        }
+        rest = &rest[close..];
+    }
+    rendered.push_str(rest);
+    replaced.then_some(rendered)
+}
+
+/// The quoted literal passed to `default` in a template action, as in
+/// `.Values.x | default "abc"` or `default "abc" .Values.x`
+fn quoted_default(action: &str) -> Option<&str> {
+    let mut rest = action;
+    while let Some(pos) = rest.find("default") {
+        let after = rest[pos + "default".len()..].trim_start();
+        let boundary = rest[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric() && c != '.' && c != '_');
+        if let Some(quote) = after
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\'' | '`'))
+        {
+            if boundary {
+                let literal = &after[1..];
+                return literal.find(quote).map(|end| &literal[..end]);
+            }
+        }
+        rest = &rest[pos + "default".len()..];
+    }
+    None
+}
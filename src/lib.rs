@@ -4,10 +4,129 @@
 //! - Fast regex-based secret pattern detection
 //! - Built-in rules for 50+ secret types
 //! - Pluggable custom rule support
+//! - A `Detector` trait for custom non-regex detectors
+//! - Sandboxed WebAssembly detector plugins behind the `plugins` feature
+//! - Built-in rules grouped into packs that can be selected and merged
+//! - Rule tags for running or skipping whole categories
+//! - Rules ignored or added for parts of a monorepo
 //! - Efficient file scanning and filtering
+//! - A cache that skips files unchanged since a clean scan
+//! - Variable names for secrets in `.env`, compose, and Kubernetes files
+//! - Terraform state and `.tfvars` values reported with their resource address
+//! - Lockfiles and vendored code scanned with a restricted rule subset
+//! - Jupyter notebook cells and outputs, reported by cell and line
+//! - Windowed matching of minified lines, with optional source map resolution
+//! - String-literal-aware scanning of source code behind the `syntax` feature
+//! - Scanning inside zip, jar, and tar archives
+//! - JSON / NDJSON, GitHub annotation, and GitLab reports for CI pipelines
+//! - Custom one-line-per-finding output templates
+//! - Shannon-entropy detection of random-looking tokens
+//! - Optional decoding of base64 and hex blobs before matching
+//! - Matching through percent-encoding and string escapes
+//! - A confidence score for each finding
+//! - Optional tagging or downgrading of findings in test code and fixtures
+//! - An optional filter that demotes or drops obvious fake secrets
+//! - Provider context such as AWS account ids and JWT issuers on findings
+//! - Repository configuration via `fastsecret.toml`
+//! - Git history scanning
+//! - Watch mode that rescans files as they change
+//! - A language server that shows findings as editor diagnostics
+//! - An HTTP scanning service behind the `server` feature
+//! - A streaming gRPC scanning service and client behind the `grpc` feature
+//! - Container image layer scanning
+//! - S3 and GCS bucket scanning behind the `cloud` feature
+//! - Remote repository scanning by URL
+//! - Scanning every repository of an organization in one run
+//! - Baselines for suppressing known findings
+//! - Comparison of two reports into new, resolved, and persisting findings
+//! - A SQLite history of scan runs and findings behind the `history` feature
+//! - A `[policy]` with fail conditions, per-path severities, and required rules
+//! - A reusable [`Scanner`] with scan statistics
+//! - An async scanning API with finding streams behind the `tokio` feature
+//! - Per-rule timings for finding slow custom rules
+//! - Warnings and detailed messages logged as text or JSON lines
+//! - Tracing spans over the scan pipeline, exported over OTLP behind the
+//!   `otel` feature
+//! - A synthetic corpus generator for throughput benchmarks
+//! - Optional verification of credentials with their provider
+//! - Webhook and Slack notifications about findings
+//! - Prometheus metrics for the HTTP service and watch mode
+//! - Python bindings behind the `python` feature
+//! - A C ABI with a generated header behind the `ffi` feature
+//! - WebAssembly bindings behind the `wasm` feature
 
+pub mod archive;
+#[cfg(feature = "tokio")]
+pub mod async_scan;
+pub mod baseline;
+pub mod cache;
+#[cfg(feature = "cloud")]
+pub mod cloud;
+pub mod compare;
+pub mod confidence;
+pub mod config;
+pub mod context;
+pub mod corpus;
+mod datetime;
+pub mod decode;
+pub mod detector;
+mod encoding;
+pub mod entropy;
+mod envfile;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod git;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod heuristics;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod image;
+mod kubernetes;
+pub mod lockfile;
+pub mod logging;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+pub mod metadata;
+pub mod metrics;
+mod normalize;
+pub mod notebook;
+pub mod notifications;
+pub mod org;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod policy;
+pub mod profile;
+#[cfg(feature = "python")]
+mod python;
+pub mod remote;
+pub mod report;
 pub mod rules;
 pub mod scanner;
+pub mod scope;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(any(feature = "verify", feature = "cloud"))]
+mod sigv4;
+pub mod sourcemap;
+#[cfg(feature = "syntax")]
+pub mod syntax;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+pub mod terraform;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+pub mod watch;
 
-pub use rules::{Rule, RuleSeverity};
-pub use scanner::{scan_path, Finding, FindingSeverity};
+pub use baseline::Baseline;
+pub use confidence::Confidence;
+pub use config::Config;
+pub use report::OutputFormat;
+pub use rules::{Allowlist, CompiledRule, CompiledRuleSet, Rule, RuleSeverity, RulesFormat};
+pub use scanner::{
+    scan_path, scan_path_with_options, scan_path_with_rule_set, Dedup, FailOn, Finding,
+    FindingSeverity, GroupBy, ScanError, ScanErrorKind, ScanOptions, ScanProgress, ScanReport,
+    ScanStats, ScanSummary, Scanner, ScannerBuilder, SeverityCounts, SortOrder,
+};
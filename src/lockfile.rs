@@ -0,0 +1,179 @@
+//! Lockfiles and vendored dependencies
+//!
+//! `package-lock.json`, `Cargo.lock`, `yarn.lock`, and `go.sum` are full of
+//! 40+ character hashes that look exactly like the bare tokens some rules
+//! match, and vendored dependencies under `vendor/` or `third_party/` are
+//! someone else's code. Such files are recognized by name and, by default,
+//! scanned with a restricted rule subset: only matches that carry a vendor
+//! prefix (`ghp_`, `AKIA`, `npm_`) or are multi-line blocks such as PEM keys
+//! are kept. They can also be skipped outright or scanned like any file,
+//! and globs choose the handling for other paths.
+
+use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::rules::CompiledRuleSet;
+use crate::scanner::Finding;
+
+/// File names of dependency lockfiles
+pub const LOCKFILE_NAMES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+    "Cargo.lock",
+    "go.sum",
+    "Gemfile.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "pdm.lock",
+    "composer.lock",
+    "packages.lock.json",
+    "gradle.lockfile",
+    "Podfile.lock",
+    "Package.resolved",
+    "pubspec.lock",
+    "mix.lock",
+    "flake.lock",
+    ".terraform.lock.hcl",
+];
+
+/// Directory names holding vendored third-party code
+pub const VENDOR_DIRS: &[&str] = &["vendor", "third_party", "third-party"];
+
+/// How a lockfile or vendored file is scanned, ordered from `Skip` to `Scan`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockfileMode {
+    /// Don't scan it
+    Skip,
+    /// Keep only findings with a vendor prefix or from multi-line rules
+    #[default]
+    Restricted,
+    /// Scan it like any other file
+    Scan,
+}
+
+impl std::str::FromStr for LockfileMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(LockfileMode::Skip),
+            "restricted" => Ok(LockfileMode::Restricted),
+            "scan" => Ok(LockfileMode::Scan),
+            _ => Err(format!(
+                "Unknown lockfile mode: {} (expected skip, restricted, or scan)",
+                s
+            )),
+        }
+    }
+}
+
+/// Which files count as lockfiles and how each is scanned
+#[derive(Debug, Clone, Default)]
+pub struct LockfileOptions {
+    /// Handling of recognized lockfiles and vendored files
+    pub mode: LockfileMode,
+    globs: Vec<(String, LockfileMode)>,
+    set: GlobSet,
+}
+
+impl LockfileOptions {
+    /// Options with `mode` for recognized files and per-path globs
+    ///
+    /// Globs match anywhere in a path (`vendor/**` also matches
+    /// `src/vendor/lib.js`) and win over the built-in recognition. When
+    /// globs for several modes match, `scan` wins over `restricted`, which
+    /// wins over `skip`, so a broad skip can be narrowed.
+    pub fn new(
+        mode: LockfileMode,
+        skip: &[String],
+        restricted: &[String],
+        scan: &[String],
+    ) -> Result<Self> {
+        let globs: Vec<(String, LockfileMode)> = [
+            (skip, LockfileMode::Skip),
+            (restricted, LockfileMode::Restricted),
+            (scan, LockfileMode::Scan),
+        ]
+        .into_iter()
+        .flat_map(|(globs, mode)| globs.iter().map(move |g| (g.clone(), mode)))
+        .collect();
+
+        let mut builder = GlobSetBuilder::new();
+        for (glob, _) in &globs {
+            let glob = glob.trim_start_matches("./");
+            let anywhere = if glob.starts_with('/') || glob.starts_with("**/") {
+                glob.trim_start_matches('/').to_string()
+            } else {
+                format!("**/{}", glob)
+            };
+            builder.add(Glob::new(&anywhere)?);
+        }
+        Ok(LockfileOptions {
+            mode,
+            globs,
+            set: builder.build()?,
+        })
+    }
+
+    /// Globs with their modes, in the order given
+    pub fn globs(&self) -> &[(String, LockfileMode)] {
+        &self.globs
+    }
+
+    /// How the file at `path`, relative to the scan root, is scanned
+    pub fn mode_for(&self, path: &str) -> LockfileMode {
+        let path = path.replace('\\', "/");
+        let matched = self.set.matches(path.trim_start_matches('/'));
+        if let Some(mode) = matched.iter().map(|&i| self.globs[i].1).max() {
+            return mode;
+        }
+        if is_lockfile(&path) || is_vendored(&path) {
+            self.mode
+        } else {
+            LockfileMode::Scan
+        }
+    }
+}
+
+/// Whether `path` names a dependency lockfile
+pub fn is_lockfile(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\', '!']).next().unwrap_or(path);
+    LOCKFILE_NAMES.contains(&name)
+}
+
+/// Whether `path` lies under a vendored dependency directory
+///
+/// Pass the path relative to the scan root, so a root that itself sits
+/// under a `vendor` directory doesn't count.
+pub fn is_vendored(path: &str) -> bool {
+    let mut components: Vec<&str> = path.split(['/', '\\', '!']).collect();
+    components.pop();
+    components.iter().any(|c| VENDOR_DIRS.contains(c))
+}
+
+/// Keep only the findings in `findings[file_start..]` that the restricted
+/// rule subset allows: multi-line rules, and matches that start with one of
+/// their rule's keywords
+pub(crate) fn restrict(findings: &mut Vec<Finding>, file_start: usize, rule_set: &CompiledRuleSet) {
+    let candidates = findings.split_off(file_start);
+    findings.extend(candidates.into_iter().filter(|f| {
+        rule_set
+            .rules()
+            .iter()
+            .find(|c| c.rule.id == f.rule_id)
+            .is_some_and(|c| {
+                let secret = f.secret.to_ascii_lowercase();
+                c.rule.multiline
+                    || c.rule
+                        .keywords
+                        .iter()
+                        .any(|k| !k.is_empty() && secret.starts_with(&k.to_ascii_lowercase()))
+            })
+    }));
+}
@@ -0,0 +1,138 @@
+//! Log messages on stderr
+//!
+//! Warnings such as an invalid custom rule or a file that couldn't be read,
+//! and the detailed messages of `--verbose`, are [`tracing`] events. [`init`]
+//! writes each one to stderr as a whole line, either as text or as a JSON
+//! object, so they never break into the findings and log pipelines can
+//! collect them:
+//!
+//! ```text
+//! {"timestamp":"2026-01-05T09:12:44.118204Z","level":"WARN","message":"./deploy/key.pem: Permission denied (os error 13)","file":"./deploy/key.pem","kind":"permission-denied","target":"fastsecret"}
+//! ```
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, Layer, Registry};
+
+/// How log messages are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// The message alone, prefixed by the level's symbol
+    #[default]
+    Text,
+    /// One JSON object per line with the timestamp, level, message, and
+    /// fields such as `file` or `rule`
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => anyhow::bail!("unknown log format '{}' (expected text or json)", s),
+        }
+    }
+}
+
+/// A layer installed next to the log writer, such as
+/// [`Telemetry::layer`](crate::telemetry::Telemetry::layer)
+pub type ExtraLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+type Inner = Layered<Option<ExtraLayer>, Registry>;
+
+/// The installed log writer
+pub struct LogHandle {
+    level: reload::Handle<LevelFilter, Inner>,
+}
+
+impl LogHandle {
+    /// Write messages up to `level` from now on
+    pub fn set_level(&self, level: LevelFilter) -> Result<()> {
+        self.level
+            .reload(level)
+            .context("the log subscriber is gone")
+    }
+}
+
+/// Write log messages up to `level` to stderr, alongside `extra`
+///
+/// Installs the global tracing subscriber, so it can be called once per
+/// process.
+pub fn init(level: LevelFilter, format: LogFormat, extra: Option<ExtraLayer>) -> Result<LogHandle> {
+    let (filter, handle) = reload::Layer::new(level);
+    let writer: Box<dyn Layer<Inner> + Send + Sync> = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .event_format(TextFormat)
+            .with_writer(std::io::stderr)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(false)
+            .with_writer(std::io::stderr)
+            .boxed(),
+    };
+    let subscriber = tracing_subscriber::registry()
+        .with(extra)
+        .with(writer.with_filter(filter));
+    tracing::subscriber::set_global_default(subscriber)
+        .context("a tracing subscriber is already installed")?;
+    Ok(LogHandle { level: handle })
+}
+
+/// The look of fastsecret's messages from before they were logged: warnings
+/// in yellow behind ⚠️, errors in red behind ✗, the rest indented
+struct TextFormat;
+
+impl<S, N> FormatEvent<S, N> for TextFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        // Fields such as `file` repeat what the message says
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        let line = match *event.metadata().level() {
+            Level::ERROR => format!("✗ {}", message).red(),
+            Level::WARN => format!("⚠️  {}", message).yellow(),
+            Level::INFO => format!("  {}", message).normal(),
+            _ => format!("  {}", message).dimmed(),
+        };
+        writeln!(writer, "{}", line)
+    }
+}
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.push_str(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Language server
+//!
+//! Speaks the Language Server Protocol so editors can show secrets inline.
+//! Open documents are rescanned on every change and their findings published
+//! as diagnostics; a quick fix inserts a `fastsecret:ignore[rule]` comment on
+//! the line above a finding. Diagnostics never include the secret itself.
+
+use anyhow::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, Request as LspRequest};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, InitializeResult, NumberOrString,
+    Position, PublishDiagnosticsParams, Range, ServerCapabilities, ServerInfo,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::scanner::{Finding, FindingSeverity, Scanner};
+
+/// `source` of every diagnostic this server publishes
+const SOURCE: &str = "fastsecret";
+
+/// Serve LSP over stdin and stdout until the client shuts the server down
+pub fn run_stdio(scanner: &Scanner) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection, scanner)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Serve LSP over `connection`: answer `initialize`, then handle messages
+/// until the client sends `shutdown`
+pub fn serve(connection: &Connection, scanner: &Scanner) -> Result<()> {
+    let capabilities = ServerCapabilities {
+        // Full sync keeps things simple; documents are rescanned whole anyway
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    };
+    let result = InitializeResult {
+        capabilities,
+        server_info: Some(ServerInfo {
+            name: SOURCE.to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    };
+    let (id, _params) = connection.initialize_start()?;
+    connection.initialize_finish(id, serde_json::to_value(result)?)?;
+
+    let mut server = Server {
+        connection,
+        scanner,
+        documents: HashMap::new(),
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    scanner: &'a Scanner,
+    /// Text of each open document
+    documents: HashMap<Url, String>,
+}
+
+impl Server<'_> {
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let document = params.text_document;
+                self.update(document.uri, document.text, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                // With full sync the last change holds the whole document
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(
+                        params.text_document.uri,
+                        change.text,
+                        Some(params.text_document.version),
+                    ),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.publish(uri, Vec::new(), None)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                match serde_json::from_value::<CodeActionParams>(request.params) {
+                    Ok(params) => Response::new_ok(request.id, self.code_actions(&params)),
+                    Err(e) => Response::new_err(
+                        request.id,
+                        ErrorCode::InvalidParams as i32,
+                        e.to_string(),
+                    ),
+                }
+            }
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {}", method),
+            ),
+        }
+    }
+
+    /// Store a document's new text and publish its findings
+    fn update(&mut self, uri: Url, text: String, version: Option<i32>) -> Result<()> {
+        let findings = self
+            .scanner
+            .scan_bytes(&document_name(&uri), text.as_bytes())?
+            .findings;
+        let lines: Vec<&str> = text.lines().collect();
+        let diagnostics = findings
+            .iter()
+            .map(|f| diagnostic(f, lines.get(f.line.saturating_sub(1)).copied()))
+            .collect();
+        self.documents.insert(uri.clone(), text);
+        self.publish(uri, diagnostics, version)
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<Diagnostic>, version: Option<i32>) -> Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        };
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))?;
+        Ok(())
+    }
+
+    /// A quick fix per fastsecret diagnostic that inserts an ignore comment
+    /// above its line
+    fn code_actions(&self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = &params.text_document.uri;
+        let (Some(text), Some((open, close))) =
+            (self.documents.get(uri), comment_syntax(&document_name(uri)))
+        else {
+            return Vec::new();
+        };
+
+        params
+            .context
+            .diagnostics
+            .iter()
+            .filter(|d| d.source.as_deref() == Some(SOURCE))
+            .filter_map(|d| {
+                let Some(NumberOrString::String(rule_id)) = &d.code else {
+                    return None;
+                };
+                let line = d.range.start.line;
+                let current = text.lines().nth(line as usize).unwrap_or("");
+                let indent = &current[..current.len() - current.trim_start().len()];
+                let comment = format!(
+                    "{}{} fastsecret:ignore[{}]{}\n",
+                    indent, open, rule_id, close
+                );
+                let edit = TextEdit {
+                    range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+                    new_text: comment,
+                };
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Ignore {} on this line", rule_id),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![d.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    ..CodeAction::default()
+                }))
+            })
+            .collect()
+    }
+}
+
+/// Diagnostic for a finding on `line`
+///
+/// Finding columns count characters; LSP positions count UTF-16 code units.
+fn diagnostic(finding: &Finding, line: Option<&str>) -> Diagnostic {
+    let utf16 = |column: usize| -> u32 {
+        line.map_or(column.saturating_sub(1), |line| {
+            line.chars()
+                .take(column.saturating_sub(1))
+                .map(char::len_utf16)
+                .sum()
+        }) as u32
+    };
+    let row = finding.line.saturating_sub(1) as u32;
+    Diagnostic {
+        range: Range::new(
+            Position::new(row, utf16(finding.column)),
+            Position::new(row, utf16(finding.end_column)),
+        ),
+        severity: Some(match finding.severity {
+            FindingSeverity::High => DiagnosticSeverity::ERROR,
+            FindingSeverity::Medium => DiagnosticSeverity::WARNING,
+            FindingSeverity::Low => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(finding.rule_id.clone())),
+        source: Some(SOURCE.to_string()),
+        message: format!("{} detected", finding.rule_name),
+        ..Diagnostic::default()
+    }
+}
+
+/// Path used to scan a document, so path allowlists and file-type handling
+/// apply as they do on disk
+fn document_name(uri: &Url) -> String {
+    uri.to_file_path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| uri.path().to_string())
+}
+
+/// Line comment delimiters for a file, or `None` if it can't have comments
+fn comment_syntax(name: &str) -> Option<(&'static str, &'static str)> {
+    let path = Path::new(name);
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if file_name.starts_with(".env")
+        || file_name.starts_with("dockerfile")
+        || file_name == "makefile"
+    {
+        return Some(("#", ""));
+    }
+    match extension.as_str() {
+        "json" | "lock" => None,
+        "py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "ps1" | "pl" | "r" | "yaml" | "yml"
+        | "toml" | "ini" | "cfg" | "conf" | "env" | "properties" | "tf" | "tfvars" | "hcl"
+        | "nix" | "cmake" | "mk" | "dockerfile" | "gitignore" => Some(("#", "")),
+        "sql" | "lua" | "hs" => Some(("--", "")),
+        "html" | "htm" | "xml" | "md" | "svg" | "vue" | "xaml" | "config" | "csproj" => {
+            Some(("<!--", " -->"))
+        }
+        "css" => Some(("/*", " */")),
+        "tex" | "erl" => Some(("%", "")),
+        "bat" | "cmd" => Some(("REM", "")),
+        _ => Some(("//", "")),
+    }
+}
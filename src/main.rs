@@ -1,111 +1,3161 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
 use colored::*;
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
-mod rules;
-mod scanner;
+use fastsecret::archive::ArchiveOptions;
+use fastsecret::baseline::Baseline;
+use fastsecret::cache;
+use fastsecret::compare::{self, Comparison, ReportedFinding};
+use fastsecret::confidence::Confidence;
+use fastsecret::config::Config;
+use fastsecret::context::{TestContext, TestContextMode};
+use fastsecret::corpus::{Corpus, CorpusOptions};
+use fastsecret::decode::DecodeOptions;
+use fastsecret::detector::DetectorRegistry;
+use fastsecret::entropy::EntropyOptions;
+use fastsecret::git::{self, HookKind};
+use fastsecret::heuristics;
+use fastsecret::image;
+use fastsecret::lockfile::{LockfileMode, LockfileOptions};
+use fastsecret::logging::{self, ExtraLayer, LogFormat, LogHandle};
+use fastsecret::notifications::PayloadFormat;
+use fastsecret::org::{self, Forge, OrgReport};
+use fastsecret::policy::{Condition, Policy};
+use fastsecret::profile::RuleProfile;
+use fastsecret::remote::{self, RemoteOptions};
+use fastsecret::report::{self, OutputFormat, Template};
+use fastsecret::rules::{self, CompiledRuleSet, Rule, RulesFormat};
+use fastsecret::scanner::{
+    self, Dedup, FailOn, Finding, FindingCaps, FindingSeverity, GroupBy, ScanError, ScanOptions,
+    ScanProgress, ScanStats, ScanSummary, Scanner, SeverityCounts, SortOrder,
+};
+use fastsecret::scope::RuleScope;
+#[cfg(feature = "otel")]
+use fastsecret::telemetry::Telemetry;
+use fastsecret::verify::VerificationStatus;
+use tracing_subscriber::filter::LevelFilter;
 
-use scanner::{scan_path, Finding};
+/// ⚡ Lightning-fast secrets scanner for source code.
+/// Detects leaked API keys, credentials, tokens, and private keys.
+#[derive(Parser, Debug)]
+#[command(
+    name = "fastsecret",
+    version = "0.1.0",
+    about = "⚡ Lightning-fast secrets scanner — catch leaked credentials before they go public",
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to scan (file or directory; the repository with --staged; - for stdin;
+    /// s3://bucket/prefix or gs://bucket/prefix with the `cloud` build feature)
+    #[arg(value_name = "PATH", required_unless_present_any = ["staged", "stdin"])]
+    path: Option<String>,
+
+    /// Scan only lines added in staged changes (for pre-commit hooks)
+    #[arg(long, conflicts_with = "stdin")]
+    staged: bool,
+
+    /// Scan text piped to standard input (same as PATH -)
+    #[arg(long, conflicts_with = "path")]
+    stdin: bool,
+
+    /// Name to report for findings read from standard input
+    #[arg(long, value_name = "NAME", default_value = "<stdin>")]
+    stdin_filename: String,
+
+    /// Most objects to scan in a bucket [default: 10000]
+    #[arg(long, value_name = "N")]
+    max_objects: Option<usize>,
+
+    /// Most detailed messages to log on stderr: off, error, warn, info, debug, or trace [default: warn, debug with --verbose]
+    #[arg(long, value_name = "LEVEL", global = true)]
+    log_level: Option<LevelFilter>,
+
+    /// Format of log messages: text or json (one object per line) [default: text]
+    #[arg(long, value_name = "FORMAT", global = true)]
+    log_format: Option<LogFormat>,
+
+    /// Export tracing spans to this OTLP/HTTP collector, e.g. http://localhost:4318 (needs the `otel` build feature)
+    #[arg(long, value_name = "URL", global = true)]
+    otlp_endpoint: Option<String>,
+
+    /// Most detailed spans to export: info (scans), debug (files), or trace (rule batches) [default: debug]
+    #[arg(long, value_name = "LEVEL", global = true, requires = "otlp_endpoint")]
+    otlp_level: Option<tracing::Level>,
+
+    #[command(flatten)]
+    scan: ScanArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Scan every commit in a git repository's history
+    GitHistory {
+        /// Repository to scan
+        #[arg(value_name = "REPO", default_value = ".")]
+        repo: String,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Scan only the lines a revision adds since it branched from another
+    Diff {
+        /// Revision to compare from, e.g. main or origin/main
+        #[arg(long, value_name = "REV")]
+        from: String,
+
+        /// Revision to compare to
+        #[arg(long, value_name = "REV", default_value = "HEAD")]
+        to: String,
+
+        /// Repository to scan
+        #[arg(value_name = "REPO", default_value = ".")]
+        repo: String,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Scan a directory, then rescan files as they change
+    Watch {
+        /// Directory to watch
+        #[arg(value_name = "PATH", default_value = ".")]
+        path: String,
+
+        /// Serve Prometheus metrics at http://ADDR/metrics, e.g. 127.0.0.1:9090
+        #[arg(long, value_name = "ADDR")]
+        metrics_listen: Option<String>,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Run a language server on stdio for inline editor diagnostics
+    Lsp {
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Serve scans over HTTP: POST /scan, GET /rules, GET /health, GET /metrics
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        listen: String,
+
+        /// Requests to handle at once [default: number of CPUs]
+        #[arg(long, value_name = "N")]
+        workers: Option<usize>,
+
+        /// Largest request body to accept, e.g. "64MB"
+        #[arg(long, value_name = "SIZE", value_parser = scanner::parse_size)]
+        max_body_size: Option<u64>,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Serve streaming scans over gRPC (see proto/fastsecret.proto)
+    Grpc {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:50051")]
+        listen: SocketAddr,
+
+        /// Files a call may stream at once before finishing one [default: 64]
+        #[arg(long, value_name = "N")]
+        max_open_files: Option<usize>,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Scan every layer of a Docker or OCI container image
+    Image {
+        /// Image name (exported with `docker save`) or path to an image tarball
+        #[arg(value_name = "IMAGE")]
+        image: String,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Clone a remote git repository into a temporary directory and scan it
+    Remote {
+        /// Repository URL, as accepted by `git clone`
+        #[arg(value_name = "URL")]
+        url: String,
+
+        /// Branch or tag to scan instead of the default branch
+        #[arg(long, value_name = "BRANCH")]
+        branch: Option<String>,
+
+        /// Commits of history to fetch (default: 1, or all with --history)
+        #[arg(long, value_name = "N")]
+        depth: Option<usize>,
+
+        /// Scan the fetched commit history instead of the checked-out files
+        #[arg(long)]
+        history: bool,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Scan many remote repositories, such as every repository of an organization
+    Org {
+        /// Repository URLs to scan
+        #[arg(value_name = "URL")]
+        urls: Vec<String>,
+
+        /// Read repository URLs from this file, one per line (`#` starts a comment)
+        #[arg(long, value_name = "FILE")]
+        repos_file: Option<String>,
+
+        /// Scan every repository of this GitHub organization (needs the `org` build feature)
+        #[arg(long, value_name = "ORG", conflicts_with = "gitlab")]
+        github: Option<String>,
+
+        /// Scan every repository of this GitLab group (needs the `org` build feature)
+        #[arg(long, value_name = "GROUP")]
+        gitlab: Option<String>,
+
+        /// API root for GitHub Enterprise or a self-hosted GitLab
+        #[arg(long, value_name = "URL")]
+        api_url: Option<String>,
+
+        /// API token for listing and cloning private repositories
+        #[arg(
+            long,
+            value_name = "TOKEN",
+            env = "FASTSECRET_FORGE_TOKEN",
+            hide_env_values = true
+        )]
+        token: Option<String>,
+
+        /// Repositories to clone and scan at once
+        #[arg(long, value_name = "N", default_value_t = org::DEFAULT_JOBS)]
+        jobs: usize,
+
+        /// Commits of history to fetch per repository (default: 1, or all with --history)
+        #[arg(long, value_name = "N")]
+        depth: Option<usize>,
+
+        /// Scan each repository's commit history instead of its checked-out files
+        #[arg(long)]
+        history: bool,
+
+        /// Write each repository's report and an aggregate summary as JSON to this directory
+        #[arg(long, value_name = "DIR")]
+        report_dir: Option<String>,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Manage baseline files of known findings
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineCommand,
+    },
+
+    /// Compare two JSON reports and list new, resolved, and persisting findings
+    Compare {
+        /// Earlier report, written with --format json or ndjson
+        #[arg(value_name = "OLD")]
+        old: String,
+
+        /// Later report
+        #[arg(value_name = "NEW")]
+        new: String,
+
+        /// Output format: text or json
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: OutputFormat,
+
+        /// Exit with 2 when new findings at or above this severity exist, or never [default: low]
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on: Option<FailOn>,
+
+        /// List the persisting findings too, not just how many there are
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Show scan runs and secrets recorded with --record-db (needs the `history` build feature)
+    History {
+        /// History database
+        #[arg(value_name = "DB")]
+        db: String,
+
+        /// Repository to show [default: the one scanned last]
+        #[arg(long, value_name = "NAME")]
+        repo: Option<String>,
+
+        /// Show this many of the latest runs
+        #[arg(long, value_name = "N", default_value_t = 10)]
+        runs: usize,
+
+        /// Only list secrets found by the latest run
+        #[arg(long)]
+        open: bool,
+
+        /// Output format: text or json
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Install (or remove) a git hook that scans before each commit or push
+    InstallHook {
+        /// Repository to install the hook in
+        #[arg(value_name = "REPO", default_value = ".")]
+        repo: String,
+
+        /// Hook to install: pre-commit (scans staged changes) or pre-push
+        #[arg(long, value_name = "HOOK", default_value = "pre-commit")]
+        hook: HookKind,
+
+        /// Replace (or remove) an existing hook not written by fastsecret
+        #[arg(long)]
+        force: bool,
+
+        /// Remove the hook instead of installing it
+        #[arg(long)]
+        uninstall: bool,
+    },
+
+    /// Inspect the detection rules
+    Rules {
+        #[command(subcommand)]
+        action: RulesCommand,
+    },
+
+    /// Check the config, rules, baseline, and scan scope without scanning
+    Doctor {
+        /// Path a scan would start from
+        #[arg(value_name = "PATH", default_value = ".")]
+        path: String,
+
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+
+    /// Measure scan throughput on a synthetic corpus, or write the corpus out
+    Bench {
+        /// Write the corpus to this directory instead of scanning it
+        #[arg(long, value_name = "DIR")]
+        generate: Option<String>,
+
+        /// Number of files in the corpus
+        #[arg(long, value_name = "N", default_value_t = CorpusOptions::default().files)]
+        files: usize,
+
+        /// Size of each file, in KiB
+        #[arg(long, value_name = "KIB", default_value_t = CorpusOptions::default().file_size >> 10)]
+        size: usize,
+
+        /// Seed for the generator; the same seed gives the same corpus
+        #[arg(long, value_name = "N", default_value_t = CorpusOptions::default().seed)]
+        seed: u64,
+
+        /// Exit with code 1 if throughput is below this many MB/s
+        #[arg(long, value_name = "MBPS")]
+        min_throughput: Option<f64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum BaselineCommand {
+    /// Scan a path and record every finding in a new baseline file
+    Create {
+        /// Baseline file to write
+        #[arg(value_name = "OUTPUT")]
+        file: String,
+
+        /// Path to scan (file or directory)
+        #[arg(value_name = "PATH", default_value = ".")]
+        path: String,
+
+        /// Why the recorded findings are acceptable
+        #[arg(long, value_name = "TEXT")]
+        reason: Option<String>,
+
+        /// Last day (YYYY-MM-DD) the recorded findings stay suppressed
+        #[arg(long, value_name = "DATE")]
+        expires: Option<String>,
+
+        #[command(flatten)]
+        scan: Box<ScanArgs>,
+    },
+
+    /// List suppressions that have expired or expire soon
+    Audit {
+        /// Baseline file to audit
+        #[arg(value_name = "BASELINE")]
+        baseline: String,
+
+        /// Also list suppressions expiring within this many days
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        within: u32,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum RulesCommand {
+    /// List the built-in and custom rules a scan would run
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = RulesOutput::Table)]
+        format: RulesOutput,
+
+        #[command(flatten)]
+        rules: RuleArgs,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+
+    /// Show which rules match a sample string or file, with capture groups
+    Test {
+        /// Text to match against every rule
+        #[arg(long, value_name = "TEXT", required_unless_present = "file")]
+        sample: Option<String>,
+
+        /// File whose content is matched against every rule
+        #[arg(long, value_name = "FILE", conflicts_with = "sample")]
+        file: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = RulesOutput::Table)]
+        format: RulesOutput,
+
+        #[command(flatten)]
+        rules: RuleArgs,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+
+    /// Compile rules and check their examples and negative examples
+    Validate {
+        /// Rule files to check [default: the built-in rules and the config's custom rules]
+        #[arg(value_name = "FILE")]
+        files: Vec<String>,
+
+        /// Format of the rule files: yaml or gitleaks [default: yaml]
+        #[arg(long, value_name = "FORMAT")]
+        rules_format: Option<RulesFormat>,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum RulesOutput {
+    Table,
+    Json,
+}
+
+/// Options shared by every scanning mode
+#[derive(clap::Args, Debug)]
+struct ScanArgs {
+    #[command(flatten)]
+    rules: RuleArgs,
+
+    /// Exit with code 2 if secrets found (for CI/CD); same as --fail-on low
+    #[arg(long)]
+    exit_on_secrets: bool,
+
+    /// Exit with code 2 if findings at or above this severity exist: low, medium, high, or never
+    #[arg(long, value_name = "SEVERITY")]
+    fail_on: Option<FailOn>,
+
+    /// Exit with code 2 if this condition holds, e.g. "high > 0 || medium > 5" (overrides --fail-on)
+    #[arg(long, value_name = "EXPR")]
+    fail_if: Option<Condition>,
+
+    /// Apply the severity remaps of this [profiles] entry from the config, e.g. dev or prod
+    #[arg(long, value_name = "NAME", env = "FASTSECRET_PROFILE")]
+    profile: Option<String>,
+
+    /// Exit with code 1 if any file, directory, or bucket object couldn't be scanned (git and image scans stop at their first error instead)
+    #[arg(long)]
+    strict: bool,
+
+    /// Time each rule and print the slowest ones after the scan (bypasses the cache)
+    #[arg(long)]
+    profile_rules: bool,
+
+    /// Verbose output (show all matches)
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Mask secrets in output (default unless --verbose)
+    #[arg(long, conflicts_with = "no_redact")]
+    redact: bool,
+
+    /// Show secrets unmasked in output
+    #[arg(long)]
+    no_redact: bool,
+
+    /// Show N lines before and after each finding, with secrets masked
+    #[arg(short = 'C', long, value_name = "N")]
+    context: Option<usize>,
+
+    /// Print only a one-line summary, e.g. `findings=2 high=1 medium=1 low=0 exit=2`
+    #[arg(long, conflicts_with = "output")]
+    summary_only: bool,
+
+    /// Print only the summary line and no progress or statistics; --output still writes the report
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Output format: text, json, ndjson, html, github, gitlab, or template [default: text]
+    #[arg(long, value_name = "FORMAT")]
+    format: Option<OutputFormat>,
+
+    /// Line written per finding with --format template, e.g. '{{file}}:{{line}} {{rule_id}}'
+    #[arg(long, value_name = "TEMPLATE")]
+    template: Option<String>,
+
+    /// Sort findings by file, severity, or rule [default: scan order]
+    #[arg(long, value_name = "ORDER")]
+    sort: Option<SortOrder>,
+
+    /// Group findings by file or rule
+    #[arg(long, value_name = "GROUP")]
+    group_by: Option<GroupBy>,
+
+    /// Add each secret's salted SHA-256 to reports as secret_sha256, to correlate secrets across scans
+    #[arg(long)]
+    hash_secrets: bool,
+
+    /// Salt for --hash-secrets; use the same one wherever secrets are correlated
+    #[arg(
+        long,
+        value_name = "SALT",
+        env = "FASTSECRET_SECRET_SALT",
+        hide_env_values = true
+    )]
+    secret_salt: Option<String>,
+
+    /// Report each location (by-location) or each distinct secret once with all its locations (by-secret)
+    #[arg(long, value_name = "MODE")]
+    dedup: Option<Dedup>,
+
+    /// Show at most N findings per file; the rest are counted but not listed
+    #[arg(long, value_name = "N")]
+    max_findings_per_file: Option<usize>,
+
+    /// Show at most N findings per rule; the rest are counted but not listed
+    #[arg(long, value_name = "N")]
+    max_findings_per_rule: Option<usize>,
+
+    /// Write the report to this file instead of stdout (not for text)
+    #[arg(long, value_name = "FILE")]
+    output: Option<String>,
+
+    /// Only report findings at or above this severity: low, medium, or high
+    #[arg(long, value_name = "SEVERITY")]
+    min_severity: Option<FindingSeverity>,
+
+    /// Only report findings at or above this confidence: low, medium, or high
+    #[arg(long, value_name = "CONFIDENCE")]
+    min_confidence: Option<Confidence>,
+
+    /// Demote or drop findings that look like placeholders or sample values
+    #[arg(long)]
+    fp_filter: bool,
+
+    /// Findings in tests, fixtures, and examples: off, tag (context: test), or downgrade to low confidence
+    #[arg(long, value_name = "MODE")]
+    test_context: Option<TestContextMode>,
+
+    /// Number of scanning threads (defaults to one per CPU)
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Also report tokens whose Shannon entropy is at least this many bits per character
+    #[arg(long, value_name = "BITS")]
+    entropy_threshold: Option<f64>,
+
+    /// Skip files larger than this size, e.g. 10MB (no limit by default)
+    #[arg(long, value_name = "SIZE", value_parser = scanner::parse_size)]
+    max_file_size: Option<u64>,
+
+    /// Only scan files matching this glob, relative to the scan root (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Skip files matching this glob, relative to the scan root (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Look inside zip, jar, and tar archives instead of skipping them
+    #[arg(long)]
+    scan_archives: bool,
+
+    /// Match rules against the printable strings in binary files instead of skipping them
+    #[arg(long)]
+    scan_binary: bool,
+
+    /// Decode base64 and hex blobs (nested up to 2 deep) and scan the decoded text
+    #[arg(long)]
+    decode: bool,
+
+    /// Match lines longer than this, e.g. 64KB, in overlapping windows (no limit by default)
+    #[arg(long, value_name = "SIZE", value_parser = scanner::parse_size)]
+    max_line_length: Option<u64>,
+
+    /// Report findings in minified files at their original source through source maps
+    #[arg(long)]
+    source_maps: bool,
+
+    /// Parse Python, JavaScript, TypeScript, Go, and Rust files and only report matches in string literals
+    #[arg(long)]
+    syntax: bool,
+
+    /// Read files through memory maps instead of copying them into memory (needs the `mmap` build feature)
+    #[arg(long)]
+    mmap: bool,
+
+    /// Run a detector plugin compiled to WebAssembly; repeatable (needs the `plugins` build feature)
+    #[arg(long = "plugin", value_name = "FILE")]
+    plugins: Vec<String>,
+
+    /// How to scan lockfiles and vendored dependencies: skip, restricted, or scan [default: restricted]
+    #[arg(long, value_name = "MODE")]
+    lockfiles: Option<LockfileMode>,
+
+    /// Directory for the scan cache [default: ~/.cache/fastsecret]
+    #[arg(long, value_name = "DIR", conflicts_with = "no_cache")]
+    cache_dir: Option<String>,
+
+    /// Rescan every file instead of skipping files unchanged since a clean scan
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Don't show a progress bar while scanning
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Don't respect .gitignore, .ignore, or .fastsecretignore files
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Follow symbolic links to files and directories (each file is still scanned once)
+    #[arg(long)]
+    follow_symlinks: bool,
+
+    /// Descend at most this many directories below the scan root (0 = only the root's files)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Check matched credentials with their provider (needs the `verify` build feature)
+    #[arg(long)]
+    verify: bool,
+
+    /// Attach the commit, author, and date that last changed each finding's line (git blame)
+    #[arg(long)]
+    blame: bool,
+
+    /// Suppress findings recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<String>,
+
+    /// Post findings to this webhook URL (needs the `webhook` build feature)
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "FASTSECRET_NOTIFY_WEBHOOK",
+        hide_env_values = true
+    )]
+    notify_webhook: Option<String>,
+
+    /// Webhook payload: json or slack [default: slack for hooks.slack.com URLs, else json]
+    #[arg(long, value_name = "FORMAT")]
+    notify_format: Option<PayloadFormat>,
+
+    /// Only notify when findings at or above this severity exist [default: low]
+    #[arg(long, value_name = "SEVERITY")]
+    notify_on: Option<FindingSeverity>,
+
+    /// Record the scan run and its findings in this SQLite database (needs the `history` build feature)
+    #[arg(long, value_name = "FILE")]
+    record_db: Option<String>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+/// Options that choose which rules are loaded and run
+#[derive(clap::Args, Debug)]
+struct RuleArgs {
+    /// Load custom rules from YAML file
+    #[arg(long, value_name = "FILE")]
+    rules: Option<String>,
+
+    /// Format of the --rules file: yaml or gitleaks [default: yaml]
+    #[arg(long, value_name = "FORMAT")]
+    rules_format: Option<RulesFormat>,
+
+    /// Only load these built-in rule packs, e.g. aws,pki (comma-separated)
+    #[arg(long, visible_alias = "enable-pack", value_name = "PACKS")]
+    rule_packs: Option<String>,
+
+    /// Ignore specific rules by id or name (comma-separated)
+    #[arg(long, value_name = "RULES")]
+    ignore_rules: Option<String>,
+
+    /// Only run rules with one of these tags, e.g. payment,pki (comma-separated)
+    #[arg(long, value_name = "TAGS")]
+    only_tags: Option<String>,
+
+    /// Skip rules with any of these tags, e.g. generic (comma-separated)
+    #[arg(long, value_name = "TAGS")]
+    skip_tags: Option<String>,
+}
+
+/// Options for finding the config file
+#[derive(clap::Args, Debug)]
+struct ConfigArgs {
+    /// Use this config file instead of searching for fastsecret.toml
+    #[arg(long, value_name = "FILE", conflicts_with = "no_config")]
+    config: Option<String>,
+
+    /// Ignore any fastsecret.toml / .fastsecret.yml config file
+    #[arg(long)]
+    no_config: bool,
+}
+
+/// Effective settings after merging the config file with CLI flags
+struct Settings {
+    scanner: Scanner,
+    format: OutputFormat,
+    /// Line format of `--format template`
+    template: Option<Template>,
+    output: Option<String>,
+    sort: Option<SortOrder>,
+    group_by: Option<GroupBy>,
+    dedup: Dedup,
+    /// How many findings are shown per file and per rule
+    caps: FindingCaps,
+    /// Salt for each finding's `secret_sha256`, when secrets are hashed
+    secret_salt: Option<String>,
+    fail_on: FailOn,
+    /// Severity overrides, fail condition, and required rules
+    policy: Policy,
+    /// Lowest severity reported, checked after the policy's overrides
+    min_severity: FindingSeverity,
+    baseline: Option<String>,
+    verify: bool,
+    /// Attribute work tree findings with `git blame`
+    blame: bool,
+    /// Fail the scan when files couldn't be scanned
+    strict: bool,
+    /// Run the false-positive heuristics over the findings
+    fp_filter: bool,
+    /// How findings in test code are tagged or downgraded
+    test_context: TestContext,
+    /// Show a progress bar and final statistics on stderr
+    progress: bool,
+    /// Print the summary line instead of the findings
+    summary_only: bool,
+    /// Don't print status messages on stderr
+    quiet: bool,
+    notify: Option<NotifySettings>,
+    record: Option<RecordSettings>,
+}
+
+/// Where and when to post findings
+#[cfg_attr(not(feature = "webhook"), allow(dead_code))]
+struct NotifySettings {
+    url: String,
+    format: PayloadFormat,
+    min_severity: FindingSeverity,
+    /// Scan root, used to describe the repository in the payload
+    root: String,
+}
+
+/// Builds without the `otel` feature never export spans
+#[cfg(not(feature = "otel"))]
+type Telemetry = ();
+
+/// Where to record the scan run
+#[cfg_attr(not(feature = "history"), allow(dead_code))]
+struct RecordSettings {
+    db: PathBuf,
+    /// Scan root, used to name the repository the run belongs to
+    root: String,
+}
+
+/// Log writer whose level follows `--verbose`; unset when `--log-level`
+/// chose the level
+static VERBOSE_LOG: OnceLock<LogHandle> = OnceLock::new();
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    #[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+    let telemetry = init_logging(&cli)?;
+
+    // Set when some repositories of an org scan couldn't be scanned, or
+    // with --strict some files or bucket objects
+    let mut incomplete = false;
+
+    // Files scanned is only known when scanning files or stdin
+    let (findings, settings, files_scanned) = match cli.command {
+        Some(Command::GitHistory { repo, scan }) => {
+            let settings = prepare_scan(&scan, &repo)?;
+            let scanner = &settings.scanner;
+            (
+                git::scan_history(&repo, scanner.rule_set(), scanner.options())?,
+                settings,
+                None,
+            )
+        }
+        Some(Command::Diff {
+            from,
+            to,
+            repo,
+            scan,
+        }) => {
+            let settings = prepare_scan(&scan, &repo)?;
+            let scanner = &settings.scanner;
+            (
+                git::scan_diff(&repo, &from, &to, scanner.rule_set(), scanner.options())?,
+                settings,
+                None,
+            )
+        }
+        Some(Command::Watch {
+            path,
+            metrics_listen,
+            scan,
+        }) => {
+            let settings = prepare_scan(&scan, &path)?;
+            return run_watch(&path, metrics_listen.as_deref(), &settings);
+        }
+        Some(Command::Lsp { scan }) => {
+            let settings = prepare_scan(&scan, ".")?;
+            return run_lsp(&settings);
+        }
+        Some(Command::Serve {
+            listen,
+            workers,
+            max_body_size,
+            scan,
+        }) => {
+            let settings = prepare_scan(&scan, ".")?;
+            return run_serve(&listen, workers, max_body_size, settings);
+        }
+        Some(Command::Grpc {
+            listen,
+            max_open_files,
+            scan,
+        }) => {
+            let settings = prepare_scan(&scan, ".")?;
+            return run_grpc(listen, max_open_files, settings);
+        }
+        Some(Command::Doctor { path, scan }) => return run_doctor(&path, &scan),
+        Some(Command::Image { image, scan }) => {
+            let settings = prepare_scan(&scan, ".")?;
+            let scanner = &settings.scanner;
+            (
+                image::scan_image(&image, scanner.rule_set(), scanner.options())?,
+                settings,
+                None,
+            )
+        }
+        Some(Command::Remote {
+            url,
+            branch,
+            depth,
+            history,
+            scan,
+        }) => {
+            let settings = prepare_scan(&scan, ".")?;
+            let scanner = &settings.scanner;
+            let remote = RemoteOptions {
+                branch,
+                depth,
+                history,
+            };
+            (
+                remote::scan_remote(&url, &remote, scanner.rule_set(), scanner.options())?,
+                settings,
+                None,
+            )
+        }
+        Some(Command::Org {
+            mut urls,
+            repos_file,
+            github,
+            gitlab,
+            api_url,
+            token,
+            jobs,
+            depth,
+            history,
+            report_dir,
+            scan,
+        }) => {
+            if let Some(path) = repos_file {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read '{}'", path))?;
+                urls.extend(
+                    text.lines()
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                        .map(String::from),
+                );
+            }
+            let forge = match (github, gitlab) {
+                (Some(org), _) => Some((Forge::Github, org)),
+                (_, Some(group)) => Some((Forge::Gitlab, group)),
+                (None, None) => None,
+            };
+            if let Some((forge, org)) = forge {
+                urls.extend(list_org_repos(
+                    forge,
+                    &org,
+                    token.as_deref(),
+                    api_url.as_deref(),
+                )?);
+            }
+            if urls.is_empty() {
+                anyhow::bail!(
+                    "no repositories to scan; pass URLs, --repos-file, --github, or --gitlab"
+                );
+            }
+
+            let settings = prepare_scan(&scan, ".")?;
+            let scanner = &settings.scanner;
+            let remote = RemoteOptions {
+                branch: None,
+                depth,
+                history,
+            };
+            let report =
+                org::scan_repos(&urls, &remote, scanner.rule_set(), scanner.options(), jobs)?;
+            if !settings.quiet {
+                print_org_report(&report);
+            }
+            if let Some(dir) = report_dir {
+                write_org_reports(&report, Path::new(&dir))?;
+            }
+            incomplete = report.failed().next().is_some();
+            (report.into_findings(), settings, None)
+        }
+        Some(Command::Baseline {
+            action:
+                BaselineCommand::Create {
+                    file,
+                    path,
+                    reason,
+                    expires,
+                    scan,
+                },
+        }) => {
+            let settings = prepare_scan(&scan, &path)?;
+            let report = settings.scanner.scan_path(&path)?;
+            let mut baseline = Baseline::from_findings(&report.findings);
+            // Re-creating a baseline keeps the justifications already given
+            if let Ok(previous) = Baseline::load(&file) {
+                baseline.carry_over(&previous);
+            }
+            baseline.justify(reason.as_deref(), expires.as_deref())?;
+            baseline.save(&file)?;
+            println!(
+                "{}",
+                format!(
+                    "✓ Wrote {} finding(s) to baseline '{}'",
+                    baseline.findings.len(),
+                    file
+                )
+                .green()
+            );
+            return Ok(());
+        }
+        Some(Command::InstallHook {
+            repo,
+            hook,
+            force,
+            uninstall,
+        }) => {
+            if uninstall {
+                match git::uninstall_hook(&repo, hook, force)? {
+                    Some(path) => println!("{}", format!("✓ Removed '{}'", path.display()).green()),
+                    None => println!("No {} hook installed", hook.file_name()),
+                }
+            } else {
+                let path = git::install_hook(&repo, hook, force)?;
+                println!(
+                    "{}",
+                    format!(
+                        "✓ Installed {} hook at '{}'",
+                        hook.file_name(),
+                        path.display()
+                    )
+                    .green()
+                );
+            }
+            return Ok(());
+        }
+        Some(Command::Baseline {
+            action: BaselineCommand::Audit { baseline, within },
+        }) => return run_baseline_audit(&baseline, within),
+        Some(Command::Rules { action }) => return run_rules(action),
+        Some(Command::Compare {
+            old,
+            new,
+            format,
+            fail_on,
+            verbose,
+        }) => return run_compare(&old, &new, format, fail_on, verbose),
+        Some(Command::History {
+            db,
+            repo,
+            runs,
+            open,
+            format,
+        }) => return run_history(&db, repo, runs, open, format),
+        Some(Command::Bench {
+            generate,
+            files,
+            size,
+            seed,
+            min_throughput,
+        }) => {
+            let options = CorpusOptions {
+                files,
+                file_size: size << 10,
+                seed,
+                ..CorpusOptions::default()
+            };
+            return run_bench(&options, generate.as_deref(), min_throughput);
+        }
+        None if cli.staged => {
+            let repo = cli.path.unwrap_or_else(|| ".".to_string());
+            let settings = prepare_scan(&cli.scan, &repo)?;
+            let scanner = &settings.scanner;
+            (
+                git::scan_staged(&repo, scanner.rule_set(), scanner.options())?,
+                settings,
+                None,
+            )
+        }
+        None if cli.stdin || cli.path.as_deref() == Some("-") => {
+            let settings = prepare_scan(&cli.scan, ".")?;
+            let report = settings
+                .scanner
+                .scan_reader(&cli.stdin_filename, std::io::stdin().lock())?;
+            print_scan_errors(&report.errors, &settings);
+            incomplete = settings.strict && !report.errors.is_empty();
+            (report.findings, settings, Some(report.stats.files_scanned))
+        }
+        None if cli.path.as_deref().is_some_and(is_bucket_url) => {
+            let url = cli.path.expect("PATH is required");
+            let settings = prepare_scan(&cli.scan, ".")?;
+            let (findings, objects_scanned, errors) =
+                scan_bucket(&url, cli.max_objects, &settings)?;
+            print_scan_errors(&errors, &settings);
+            incomplete = settings.strict && !errors.is_empty();
+            (findings, settings, Some(objects_scanned))
+        }
+        None => {
+            let path = cli.path.expect("PATH is required");
+            let settings = prepare_scan(&cli.scan, &path)?;
+            let report = if settings.progress {
+                let progress = ProgressReporter::new();
+                settings.scanner.scan_path_with_progress(&path, &progress)?
+            } else {
+                settings.scanner.scan_path(&path)?
+            };
+            if settings.progress || settings.scanner.options().verbose {
+                print_stats(&report.stats);
+            }
+            print_scan_errors(&report.errors, &settings);
+            incomplete = settings.strict && !report.errors.is_empty();
+            (report.findings, settings, Some(report.stats.files_scanned))
+        }
+    };
+
+    if let Some(profile) = settings.scanner.profile() {
+        print_rule_profile(profile, settings.scanner.options().verbose);
+    }
+    let findings = if settings.fp_filter {
+        heuristics::filter(findings)
+    } else {
+        findings
+    };
+    let mut findings = match &settings.baseline {
+        Some(path) => {
+            let baseline = Baseline::load(path)?;
+            let expired = baseline.findings.iter().filter(|e| e.is_expired()).count();
+            if expired > 0 && !settings.quiet {
+                tracing::warn!(
+                    "{} suppression(s) in '{}' have expired; \
+                     run `fastsecret baseline audit {}`",
+                    expired,
+                    path,
+                    path
+                );
+            }
+            baseline.filter(findings)
+        }
+        None => findings,
+    };
+    settings.test_context.apply(&mut findings)?;
+    settings.policy.apply_severity(&mut findings)?;
+    // Downgraded and remapped findings face the thresholds again
+    let min_confidence = settings.scanner.options().min_confidence;
+    findings.retain(|f| f.severity >= settings.min_severity && f.confidence >= min_confidence);
+    let mut findings = scanner::dedup_findings(findings, settings.dedup);
+    if let Some(salt) = &settings.secret_salt {
+        scanner::hash_secrets(&mut findings, salt);
+    }
+    if let Some(order) = settings.sort {
+        scanner::sort_findings(&mut findings, order);
+    }
+    if let Some(by) = settings.group_by {
+        // Keep each group together in every output format
+        findings = scanner::group_findings(&findings, by)
+            .into_iter()
+            .flat_map(|(_, group)| group)
+            .cloned()
+            .collect();
+    }
+
+    if settings.blame {
+        git::blame(&mut findings);
+    }
+    if settings.verify {
+        run_verification(&mut findings, &settings)?;
+    }
+    if let Some(notify) = &settings.notify {
+        send_notification(&findings, notify)?;
+    }
+    if let Some(record) = &settings.record {
+        record_scan(&findings, record)?;
+    }
+
+    let exit_code = report_findings(&findings, files_scanned, &settings)?;
+    // process::exit skips destructors; flush exported spans first
+    #[cfg(feature = "otel")]
+    drop(telemetry);
+    process::exit(if incomplete {
+        exit_code.max(1)
+    } else {
+        exit_code
+    });
+}
+
+/// List expired and soon-to-expire baseline suppressions, exiting with 1
+/// if any have expired
+fn run_baseline_audit(path: &str, within: u32) -> Result<()> {
+    let baseline = Baseline::load(path)?;
+    let entries = baseline.expiring(i64::from(within));
+    for (entry, days) in &entries {
+        let when = match days {
+            d if *d < 0 => format!("expired {} day(s) ago", -d).red(),
+            0 => "expires today".yellow(),
+            d => format!("expires in {} day(s)", d).yellow(),
+        };
+        println!(
+            "  [{}: {}] {} ({}) [{}]",
+            entry.file,
+            entry.line,
+            when,
+            entry.expires.as_deref().unwrap_or_default(),
+            entry.rule
+        );
+        if let Some(reason) = &entry.reason {
+            println!("{}", format!("      reason: {}", reason).dimmed());
+        }
+    }
+
+    let total = baseline.findings.len();
+    let expired = entries.iter().filter(|(_, days)| *days < 0).count();
+    let no_reason = baseline
+        .findings
+        .iter()
+        .filter(|e| e.reason.is_none())
+        .count();
+    let no_expiry = baseline
+        .findings
+        .iter()
+        .filter(|e| e.expires.is_none())
+        .count();
+    println!(
+        "{} suppression(s): {} expired, {} expiring within {} day(s), {} without a reason, {} without an expiry date",
+        total,
+        expired,
+        entries.len() - expired,
+        within,
+        no_reason,
+        no_expiry
+    );
+    if expired > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Compare two reports, exiting with 2 if the newer one has new findings at
+/// or above `fail_on`
+fn run_compare(
+    old: &str,
+    new: &str,
+    format: OutputFormat,
+    fail_on: Option<FailOn>,
+    verbose: bool,
+) -> Result<()> {
+    let comparison = Comparison::new(compare::load_report(old)?, compare::load_report(new)?);
+    let fail_on = fail_on.unwrap_or(FailOn::Severity(FindingSeverity::Low));
+    let failed = fail_on.should_fail(&comparison.new_counts());
+
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(std::io::stdout().lock(), &comparison)?;
+            println!();
+        }
+        OutputFormat::Text => {
+            let list = |title: ColoredString, findings: &[ReportedFinding]| {
+                println!("{} {}", title, format!("({})", findings.len()).dimmed());
+                for f in findings {
+                    let severity = match f.severity {
+                        FindingSeverity::High => "HIGH".red().bold(),
+                        FindingSeverity::Medium => "MEDIUM".yellow().bold(),
+                        FindingSeverity::Low => "LOW".cyan(),
+                    };
+                    println!(
+                        "  {} {} {} {} {} {}",
+                        format!("[{}: {}]", f.file, f.line).bright_blue(),
+                        severity,
+                        "—".dimmed(),
+                        f.rule_name.bold(),
+                        format!("[{}]", f.rule_id).dimmed(),
+                        format!("({})", f.snippet).dimmed()
+                    );
+                }
+            };
+            list("🆕 New".red().bold(), &comparison.new);
+            list("✅ Resolved".green().bold(), &comparison.resolved);
+            if verbose {
+                list("Persisting".bold(), &comparison.persisting);
+            }
+            println!(
+                "\n{} new, {} resolved, {} persisting.",
+                comparison.new.len(),
+                comparison.resolved.len(),
+                comparison.persisting.len()
+            );
+        }
+        _ => anyhow::bail!("compare writes text or json"),
+    }
+
+    if failed {
+        process::exit(2);
+    }
+    Ok(())
+}
+
+/// Print the trend of recorded runs and when each secret was seen
+#[cfg(feature = "history")]
+fn run_history(
+    db: &str,
+    repo: Option<String>,
+    runs: usize,
+    open: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    use fastsecret::history::{HistoryDb, ScanRun, SecretHistory};
+    use std::cmp::Ordering;
+
+    if !Path::new(db).is_file() {
+        anyhow::bail!("history database '{}' not found", db);
+    }
+    let db = HistoryDb::open(db)?;
+    let Some(repo) = repo.map_or_else(|| db.latest_repository(), |r| Ok(Some(r)))? else {
+        println!("No scan runs recorded yet");
+        return Ok(());
+    };
+    let all_runs = db.runs(&repo)?;
+    if all_runs.is_empty() {
+        let known = db.repositories()?;
+        anyhow::bail!(
+            "no runs recorded for '{}'; the database has {}",
+            repo,
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        );
+    }
+    let shown = &all_runs[all_runs.len().saturating_sub(runs)..];
+    let mut secrets = db.secrets(&repo)?;
+    if open {
+        secrets.retain(|s| s.open);
+    }
+
+    match format {
+        OutputFormat::Json => {
+            #[derive(serde::Serialize)]
+            struct History<'a> {
+                repository: &'a str,
+                runs: &'a [ScanRun],
+                secrets: &'a [SecretHistory],
+            }
+            let history = History {
+                repository: &repo,
+                runs: shown,
+                secrets: &secrets,
+            };
+            serde_json::to_writer_pretty(std::io::stdout().lock(), &history)?;
+            println!();
+        }
+        OutputFormat::Text => {
+            println!(
+                "{} {}",
+                format!("📈 {}", repo).bold(),
+                format!("({} run(s) recorded)", all_runs.len()).dimmed()
+            );
+            let mut previous = all_runs
+                .len()
+                .checked_sub(shown.len() + 1)
+                .map(|i| &all_runs[i]);
+            for run in shown {
+                let total = run.counts.total();
+                let mut line = format!(
+                    "  {}  {:>4} finding(s) {}",
+                    run.recorded_at,
+                    total,
+                    format!(
+                        "({} high, {} medium, {} low)",
+                        run.counts.high, run.counts.medium, run.counts.low
+                    )
+                    .dimmed()
+                );
+                if let Some(before) = previous.map(|p| p.counts.total()) {
+                    let change = match total.cmp(&before) {
+                        Ordering::Greater => format!("+{}", total - before).red(),
+                        Ordering::Less => format!("-{}", before - total).green(),
+                        Ordering::Equal => "±0".dimmed(),
+                    };
+                    line.push_str(&format!("  {}", change));
+                }
+                let short = |c: &String| c[..c.len().min(7)].to_string();
+                let revision = match (&run.git_ref, &run.commit) {
+                    (Some(r), Some(c)) => Some(format!("{}@{}", r, short(c))),
+                    (None, Some(c)) => Some(short(c)),
+                    (Some(r), None) => Some(r.clone()),
+                    (None, None) => None,
+                };
+                if let Some(revision) = revision {
+                    line.push_str(&format!("  {}", revision.dimmed()));
+                }
+                println!("{}", line);
+                previous = Some(run);
+            }
+
+            let open_count = secrets.iter().filter(|s| s.open).count();
+            println!(
+                "\n{} {}",
+                "Secrets".bold(),
+                format!(
+                    "({} open, {} resolved)",
+                    open_count,
+                    secrets.len() - open_count
+                )
+                .dimmed()
+            );
+            for s in &secrets {
+                let severity = match s.severity {
+                    FindingSeverity::High => "HIGH".red().bold(),
+                    FindingSeverity::Medium => "MEDIUM".yellow().bold(),
+                    FindingSeverity::Low => "LOW".cyan(),
+                };
+                let state = if s.open {
+                    "open".red()
+                } else {
+                    "resolved".green()
+                };
+                println!(
+                    "  {} {} {} {} {} {}",
+                    format!("[{}: {}]", s.file, s.line).bright_blue(),
+                    severity,
+                    "—".dimmed(),
+                    s.rule_name.bold(),
+                    format!("[{}]", s.rule_id).dimmed(),
+                    state
+                );
+                println!(
+                    "{}",
+                    format!(
+                        "      first seen {}, last seen {}, in {} run(s)",
+                        s.first_seen, s.last_seen, s.scans
+                    )
+                    .dimmed()
+                );
+            }
+        }
+        _ => anyhow::bail!("history writes text or json"),
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "history"))]
+fn run_history(
+    _db: &str,
+    _repo: Option<String>,
+    _runs: usize,
+    _open: bool,
+    _format: OutputFormat,
+) -> Result<()> {
+    anyhow::bail!("`fastsecret history` requires fastsecret to be built with the `history` feature")
+}
+
+/// Findings of `fastsecret doctor`, printed as they are made
+#[derive(Default)]
+struct Doctor {
+    failed: bool,
+    warnings: usize,
+}
+
+impl Doctor {
+    fn section(&self, title: &str) {
+        println!("\n{}", title.bold());
+    }
+
+    fn ok(&self, message: impl std::fmt::Display) {
+        println!("  {} {}", "✓".green(), message);
+    }
+
+    fn warn(&mut self, message: impl std::fmt::Display) {
+        self.warnings += 1;
+        println!("  {} {}", "⚠".yellow(), message);
+    }
+
+    fn fail(&mut self, message: impl std::fmt::Display) {
+        self.failed = true;
+        println!("  {} {}", "✗".red(), message);
+    }
+}
+
+/// Check everything a scan of `path` depends on, exiting with 1 if the
+/// scan would fail to start
+fn run_doctor(path: &str, args: &ScanArgs) -> Result<()> {
+    let mut doctor = Doctor::default();
+
+    doctor.section("Config");
+    let config_file = match &args.config.config {
+        _ if args.config.no_config => None,
+        Some(file) => Some(PathBuf::from(file)),
+        None => Config::find(Path::new(path)),
+    };
+    let config = match config_file {
+        None if args.config.no_config => {
+            doctor.ok("not loaded (--no-config)");
+            Config::default()
+        }
+        None => {
+            doctor.ok("no config file found; using defaults");
+            Config::default()
+        }
+        Some(file) => match Config::load(&file) {
+            Ok(config) => {
+                doctor.ok(format!("{} is valid", file.display()));
+                config
+            }
+            Err(e) => {
+                doctor.fail(format!("{:#}", e));
+                Config::default()
+            }
+        },
+    };
+    if let Some(profile) = args.profile.as_deref().or(config.scan.profile.as_deref()) {
+        match config.profile(profile) {
+            Ok(entries) => doctor.ok(format!(
+                "profile '{}' remaps {} severity rule(s)",
+                profile,
+                entries.severity.len()
+            )),
+            Err(e) => doctor.fail(e),
+        }
+    }
+
+    doctor.section("Rules");
+    let rules_format = args
+        .rules
+        .rules_format
+        .or(config.rules.format)
+        .unwrap_or_default();
+    for file in custom_rule_files(&args.rules, &config) {
+        match rules::load_rule_file(&file, rules_format) {
+            Ok(custom) => doctor.ok(format!(
+                "{}: {} rule(s), {} override(s)",
+                file,
+                custom.rules.len(),
+                custom.overrides.len()
+            )),
+            Err(e) => doctor.fail(format!("{}: {:#}", file, e)),
+        }
+    }
+    let rules = match load_rules(&args.rules, &config) {
+        Ok(rules) => rules,
+        Err(e) => {
+            doctor.fail(format!("{:#}", e));
+            Vec::new()
+        }
+    };
+    let problems = rules::check_rules(&rules);
+    for problem in &problems {
+        doctor.fail(problem);
+    }
+    if problems.is_empty() {
+        let started = Instant::now();
+        match CompiledRuleSet::new(&rules) {
+            Ok(rule_set) => {
+                doctor.ok(format!(
+                    "{} rule(s) compiled in {:.0?}",
+                    rule_set.len(),
+                    started.elapsed()
+                ));
+                let filters = rule_filters(&args.rules, &config);
+                let disabled = rules.iter().filter(|r| !filters.rule_enabled(r)).count();
+                if disabled > 0 {
+                    doctor.ok(format!(
+                        "{} rule(s) disabled by ignore lists or tag filters",
+                        disabled
+                    ));
+                }
+                for rule in config.policy.missing_rules(&rule_set, &filters) {
+                    doctor.fail(format!("required rule '{}' would not run", rule));
+                }
+            }
+            Err(e) => doctor.fail(format!("{:#}", e)),
+        }
+    }
+
+    doctor.section("Rule sources");
+    let mut sources: Vec<(&str, usize, Vec<&str>)> = Vec::new();
+    for rule in &rules {
+        let source = rule.source.as_deref().unwrap_or("unknown");
+        let idx = match sources.iter().position(|(s, ..)| *s == source) {
+            Some(idx) => idx,
+            None => {
+                sources.push((source, 0, Vec::new()));
+                sources.len() - 1
+            }
+        };
+        let (_, count, versions) = &mut sources[idx];
+        *count += 1;
+        if let Some(version) = rule.version.as_deref() {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+    }
+    let width = sources.iter().map(|(s, ..)| s.len()).max().unwrap_or(0);
+    for (source, count, versions) in &sources {
+        let versions = if versions.is_empty() {
+            "unversioned".to_string()
+        } else {
+            versions.join(", ")
+        };
+        println!(
+            "  {:<width$}  {:>4} rule(s)  {}",
+            source,
+            count,
+            versions.dimmed()
+        );
+    }
+
+    doctor.section("Baseline");
+    match &args.baseline {
+        None => doctor.ok("none given"),
+        Some(file) => match Baseline::load(file) {
+            Ok(baseline) => {
+                doctor.ok(format!(
+                    "{}: {} suppression(s)",
+                    file,
+                    baseline.findings.len()
+                ));
+                for problem in baseline.problems() {
+                    doctor.warn(problem);
+                }
+                let expired = baseline.findings.iter().filter(|e| e.is_expired()).count();
+                if expired > 0 {
+                    doctor.warn(format!(
+                        "{} suppression(s) have expired; run `fastsecret baseline audit {}`",
+                        expired, file
+                    ));
+                }
+            }
+            Err(e) => doctor.fail(format!("{:#}", e)),
+        },
+    }
+
+    doctor.section("Scan scope");
+    if !Path::new(path).exists() {
+        doctor.fail(format!("'{}' does not exist", path));
+    } else if doctor.failed {
+        println!("  skipped until the problems above are fixed");
+    } else {
+        match prepare_scan(args, path) {
+            Ok(settings) => {
+                let (files, errors) = settings.scanner.files(path)?;
+                let mut sizes: Vec<(&PathBuf, u64)> = files
+                    .iter()
+                    .map(|f| (f, std::fs::metadata(f).map_or(0, |m| m.len())))
+                    .collect();
+                let total: u64 = sizes.iter().map(|(_, size)| size).sum();
+                if files.is_empty() {
+                    doctor.warn(format!("no files to scan under '{}'", path));
+                } else {
+                    doctor.ok(format!(
+                        "{} file(s), {} under '{}'",
+                        files.len(),
+                        HumanBytes(total),
+                        path
+                    ));
+                }
+                if let Some(max) = settings.scanner.options().max_file_size {
+                    let too_large = sizes.iter().filter(|(_, size)| *size > max).count();
+                    if too_large > 0 {
+                        doctor.warn(format!(
+                            "{} file(s) exceed the {} max file size and will be skipped",
+                            too_large,
+                            HumanBytes(max)
+                        ));
+                    }
+                }
+                sizes.sort_by_key(|&(f, size)| (std::cmp::Reverse(size), f));
+                for (file, size) in sizes.iter().take(3) {
+                    println!(
+                        "    {}",
+                        format!("{} ({})", file.display(), HumanBytes(*size)).dimmed()
+                    );
+                }
+                if !errors.is_empty() {
+                    doctor.warn(format!(
+                        "{} path(s) can't be read, e.g. {}",
+                        errors.len(),
+                        errors[0]
+                    ));
+                }
+            }
+            Err(e) => doctor.fail(format!("{:#}", e)),
+        }
+    }
+
+    println!();
+    if doctor.failed {
+        println!("{}", "✗ Fix the problems above before scanning".red());
+        process::exit(1);
+    }
+    match doctor.warnings {
+        0 => println!("{}", "✓ Ready to scan".green()),
+        n => println!(
+            "{}",
+            format!("✓ Ready to scan, with {} warning(s)", n).green()
+        ),
+    }
+    Ok(())
+}
+
+fn run_bench(
+    options: &CorpusOptions,
+    generate: Option<&str>,
+    min_throughput: Option<f64>,
+) -> Result<()> {
+    let corpus = Corpus::generate(options);
+    if let Some(dir) = generate {
+        corpus.write_to(Path::new(dir))?;
+        println!(
+            "{}",
+            format!(
+                "✓ Wrote {} files ({}) with {} planted secrets to '{}'",
+                corpus.files.len(),
+                HumanBytes(corpus.bytes()),
+                corpus.secrets.len(),
+                dir
+            )
+            .green()
+        );
+        return Ok(());
+    }
+
+    let scanner = Scanner::builder().build()?;
+    let started = Instant::now();
+    let mut found = HashSet::new();
+    for (name, content) in &corpus.files {
+        let report = scanner.scan_bytes(name, content.as_bytes())?;
+        found.extend(report.findings.into_iter().map(|f| f.secret));
+    }
+    let elapsed = started.elapsed();
+    let throughput = corpus.bytes() as f64 / 1e6 / elapsed.as_secs_f64().max(f64::EPSILON);
+
+    println!(
+        "Scanned {} files ({}) in {:.2?}: {:.1} MB/s",
+        corpus.files.len(),
+        HumanBytes(corpus.bytes()),
+        elapsed,
+        throughput
+    );
+    let missed = corpus
+        .secrets
+        .iter()
+        .filter(|s| !found.contains(*s))
+        .count();
+    if missed > 0 {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠ {} of {} planted secrets were not found",
+                missed,
+                corpus.secrets.len()
+            )
+            .yellow()
+        );
+    }
+    if let Some(min) = min_throughput {
+        if throughput < min {
+            eprintln!(
+                "{}",
+                format!("✗ Throughput is below the {:.1} MB/s target", min).red()
+            );
+            process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+fn run_rules(action: RulesCommand) -> Result<()> {
+    match action {
+        RulesCommand::List {
+            format,
+            rules,
+            config,
+        } => {
+            let config = load_config(&config, ".")?;
+            let filters = rule_filters(&rules, &config);
+            let rules: Vec<Rule> = load_rules(&rules, &config)?
+                .into_iter()
+                .chain(rules::parser_rules())
+                .filter(|r| filters.rule_enabled(r))
+                .collect();
+            match format {
+                RulesOutput::Json => {
+                    println!("{}", serde_json::to_string_pretty(&rules)?);
+                }
+                RulesOutput::Table => print_rule_table(&rules),
+            }
+        }
+        RulesCommand::Test {
+            sample,
+            file,
+            format,
+            rules,
+            config,
+        } => {
+            let (name, text) = match (sample, file) {
+                (Some(sample), _) => ("<sample>".to_string(), sample),
+                (None, Some(file)) => {
+                    let text = std::fs::read_to_string(&file)
+                        .with_context(|| format!("failed to read '{}'", file))?;
+                    (file, text)
+                }
+                (None, None) => unreachable!("clap requires --sample or --file"),
+            };
+
+            let config = load_config(&config, ".")?;
+            let filters = rule_filters(&rules, &config);
+            let rules: Vec<Rule> = load_rules(&rules, &config)?
+                .into_iter()
+                .filter(|r| filters.rule_enabled(r))
+                .collect();
+            let rule_set = CompiledRuleSet::new(&rules)?;
+
+            let results: Vec<(&rules::CompiledRule, Vec<rules::RuleMatch>)> = rule_set
+                .rules()
+                .iter()
+                .map(|compiled| (compiled, compiled.explain(&text, &name)))
+                .filter(|(_, matches)| !matches.is_empty())
+                .collect();
+            let accepted = results
+                .iter()
+                .any(|(_, matches)| matches.iter().any(|m| m.rejected.is_none()));
+
+            match format {
+                RulesOutput::Json => {
+                    let json: Vec<serde_json::Value> = results
+                        .iter()
+                        .map(|(compiled, matches)| {
+                            serde_json::json!({
+                                "id": compiled.rule.id,
+                                "name": compiled.rule.name,
+                                "severity": compiled.rule.severity,
+                                "matches": matches,
+                            })
+                        })
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&json)?);
+                }
+                RulesOutput::Table => print_rule_matches(&text, &results),
+            }
+            if !accepted {
+                process::exit(1);
+            }
+        }
+        RulesCommand::Validate {
+            files,
+            rules_format,
+            config,
+        } => {
+            let config = load_config(&config, ".")?;
+            let rules_format = rules_format.or(config.rules.format).unwrap_or_default();
+
+            // Without files, check everything a scan would load
+            let mut sources: Vec<(String, Vec<Rule>)> = Vec::new();
+            let files = if files.is_empty() {
+                sources.push(("built-in".to_string(), rules::load_builtin_rules()));
+                config
+                    .rules
+                    .custom_rules
+                    .iter()
+                    .map(|p| config.resolve(p).display().to_string())
+                    .collect()
+            } else {
+                files
+            };
+
+            let mut failed = false;
+            for file in files {
+                match rules::load_rule_file(&file, rules_format) {
+                    Ok(custom) => {
+                        // Overrides are checked as the built-in rules they produce
+                        let mut builtin = rules::load_builtin_rules();
+                        for rule_override in &custom.overrides {
+                            if !rule_override.apply(&mut builtin) {
+                                println!(
+                                    "{} {}: {}: no rule with this id to override",
+                                    "✗".red(),
+                                    file,
+                                    rule_override.target
+                                );
+                                failed = true;
+                            }
+                        }
+                        builtin.retain(|r| custom.overridden_ids().any(|id| r.is_named(id)));
+                        builtin.extend(custom.rules);
+                        sources.push((file, builtin));
+                    }
+                    Err(e) => {
+                        println!("{} {}: {:#}", "✗".red(), file, e);
+                        failed = true;
+                    }
+                }
+            }
+
+            let mut total = 0;
+            let mut examples = 0;
+            for (source, rules) in &sources {
+                total += rules.len();
+                examples += rules
+                    .iter()
+                    .map(|r| r.examples.len() + r.negative_examples.len())
+                    .sum::<usize>();
+                for problem in rules::validate_rules(rules) {
+                    println!("{} {}: {}", "✗".red(), source, problem);
+                    failed = true;
+                }
+            }
+
+            if failed {
+                process::exit(1);
+            }
+            println!(
+                "{}",
+                format!("✓ {} rule(s) valid, {} example(s) passed", total, examples).green()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print the matches found by `rules test`
+fn print_rule_matches(text: &str, results: &[(&rules::CompiledRule, Vec<rules::RuleMatch>)]) {
+    if results.is_empty() {
+        println!("{}", "No rules matched.".yellow());
+        return;
+    }
+
+    // 1-based line and column of a byte offset
+    let position = |offset: usize| {
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+        (line, column)
+    };
+
+    for (compiled, matches) in results {
+        let rule = &compiled.rule;
+        println!(
+            "{} {} {}",
+            rule.id.bold(),
+            format!("({})", rule.name).dimmed(),
+            String::from(rule.severity)
+        );
+        for m in matches {
+            let (line, column) = position(m.start);
+            let status = match &m.rejected {
+                None => "✓".green(),
+                Some(_) => "✗".red(),
+            };
+            println!(
+                "  {} line {}, column {} [{}..{}]: {}",
+                status, line, column, m.start, m.end, m.text
+            );
+            for (index, group) in m.groups.iter().enumerate().map(|(i, g)| (i + 1, g)) {
+                let label = match group.as_ref().and_then(|g| g.name.as_deref()) {
+                    Some(name) => format!("group {} <{}>", index, name),
+                    None => format!("group {}", index),
+                };
+                let secret = if index == m.secret_group {
+                    " (secret)".cyan().to_string()
+                } else {
+                    String::new()
+                };
+                match group {
+                    Some(g) => println!(
+                        "      {}{} [{}..{}]: {}",
+                        label, secret, g.start, g.end, g.text
+                    ),
+                    None => println!("      {}: {}", label, "no match".dimmed()),
+                }
+            }
+            if m.secret_group == 0 {
+                println!("      {}", "whole match is the secret".dimmed());
+            }
+            if let Some(reason) = &m.rejected {
+                println!("      {} {}", "dropped:".yellow(), reason);
+            }
+        }
+    }
+}
 
-/// ⚡ Lightning-fast secrets scanner for source code.
-/// Detects leaked API keys, credentials, tokens, and private keys.
-#[derive(Parser, Debug)]
-#[command(
-    name = "fastsecret",
-    version = "0.1.0",
-    about = "⚡ Lightning-fast secrets scanner — catch leaked credentials before they go public",
-    long_about = None
-)]
-struct Args {
-    /// Path to scan (file or directory)
-    #[arg(value_name = "PATH")]
-    path: String,
+/// Print rules as an aligned table, one per line
+fn print_rule_table(rules: &[Rule]) {
+    const PATTERN_WIDTH: usize = 40;
 
-    /// Load custom rules from YAML file
-    #[arg(long, value_name = "FILE")]
-    rules: Option<String>,
+    let tags: Vec<String> = rules.iter().map(|r| r.tags.join(",")).collect();
+    let sources: Vec<&str> = rules
+        .iter()
+        .map(|r| r.source.as_deref().unwrap_or("-"))
+        .collect();
+    let patterns: Vec<String> = rules
+        .iter()
+        .map(|r| {
+            if r.pattern.is_empty() {
+                "(parsed)".to_string()
+            } else if r.pattern.chars().count() > PATTERN_WIDTH {
+                let cut: String = r.pattern.chars().take(PATTERN_WIDTH - 1).collect();
+                format!("{}…", cut)
+            } else {
+                r.pattern.clone()
+            }
+        })
+        .collect();
+    let id_width = rules
+        .iter()
+        .map(|r| r.id.len())
+        .chain([2])
+        .max()
+        .unwrap_or(2);
+    let tag_width = tags.iter().map(String::len).chain([4]).max().unwrap_or(4);
+    let source_width = sources
+        .iter()
+        .map(|s| s.len())
+        .chain([6])
+        .max()
+        .unwrap_or(6);
+    let pattern_width = patterns
+        .iter()
+        .map(|p| p.chars().count())
+        .chain([7])
+        .max()
+        .unwrap_or(7);
 
-    /// Ignore specific rules (comma-separated)
-    #[arg(long, value_name = "RULES")]
-    ignore_rules: Option<String>,
+    println!(
+        "{}",
+        format!(
+            "{:<id_width$}  {:<8}  {:<tag_width$}  {:<source_width$}  {:<pattern_width$}  DESCRIPTION",
+            "ID", "SEVERITY", "TAGS", "SOURCE", "PATTERN"
+        )
+        .bold()
+    );
+    for (((rule, tags), source), pattern) in rules.iter().zip(&tags).zip(&sources).zip(&patterns) {
+        let severity = format!("{:<8}", String::from(rule.severity));
+        let severity = match rule.severity {
+            rules::RuleSeverity::High => severity.red(),
+            rules::RuleSeverity::Medium => severity.yellow(),
+            rules::RuleSeverity::Low => severity.blue(),
+        };
+        println!(
+            "{:<id_width$}  {}  {:<tag_width$}  {:<source_width$}  {:<pattern_width$}  {}",
+            rule.id,
+            severity,
+            tags,
+            source,
+            pattern,
+            rule.description.as_deref().unwrap_or(&rule.name)
+        );
+    }
+    println!("\n{} rule(s)", rules.len());
+}
 
-    /// Exit with code 2 if secrets found (for CI/CD)
-    #[arg(long)]
-    exit_on_secrets: bool,
+/// Load the config file, if any, for a scan rooted at `root`
+fn load_config(args: &ConfigArgs, root: &str) -> Result<Config> {
+    if args.no_config {
+        return Ok(Config::default());
+    }
+    let config = match &args.config {
+        Some(path) => Some(Config::load(Path::new(path))?),
+        None => Config::discover(Path::new(root))?,
+    };
+    Ok(config.unwrap_or_default())
+}
 
-    /// Verbose output (show all matches)
-    #[arg(short, long)]
-    verbose: bool,
+/// Split a comma-separated flag value, dropping empty entries
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Custom rule files to load, from `--rules` or the config
+fn custom_rule_files(args: &RuleArgs, config: &Config) -> Vec<String> {
+    match &args.rules {
+        Some(path) => vec![path.clone()],
+        None => config
+            .rules
+            .custom_rules
+            .iter()
+            .map(|p| config.resolve(p).display().to_string())
+            .collect(),
+    }
+}
 
-    // Parse ignore rules
-    let ignore_set = args
-        .ignore_rules
-        .as_ref()
-        .map(|s| {
-            s.split(',')
-                .map(|r| r.trim().to_string())
-                .collect::<Vec<_>>()
-        })
+/// Load the built-in packs and custom rule files chosen by the config file
+/// and CLI flags
+fn load_rules(args: &RuleArgs, config: &Config) -> Result<Vec<Rule>> {
+    let custom_rule_files = custom_rule_files(args, config);
+
+    let rules_format = args
+        .rules_format
+        .or(config.rules.format)
         .unwrap_or_default();
 
-    // Load rules
-    let mut rules = rules::load_builtin_rules();
-    if let Some(rules_path) = args.rules {
-        match rules::load_custom_rules(&rules_path) {
+    let packs = match &args.rule_packs {
+        Some(s) => split_list(s),
+        None => config.rules.packs.clone(),
+    };
+    let mut rules = if packs.is_empty() {
+        rules::load_builtin_rules()
+    } else {
+        rules::load_rule_packs(&packs)?
+    };
+    for rules_path in &custom_rule_files {
+        match rules::load_rule_file(rules_path, rules_format) {
             Ok(custom) => {
-                if args.verbose {
-                    eprintln!("✓ Loaded {} custom rules", custom.len());
+                tracing::info!(
+                    "✓ Loaded {} custom rules and {} overrides",
+                    custom.rules.len(),
+                    custom.overrides.len()
+                );
+                for shadowed in custom.shadowed_builtins(&rules) {
+                    tracing::warn!(
+                        rule = %shadowed.id,
+                        "'{}' redefines built-in rule '{}' ({}); \
+                         use `override: {}` to change it instead",
+                        rules_path,
+                        shadowed.id,
+                        shadowed.source.as_deref().unwrap_or_default(),
+                        shadowed.id
+                    );
+                }
+                for target in custom.merge_into(&mut rules) {
+                    tracing::warn!(
+                        rule = %target,
+                        "'{}' overrides rule '{}', which isn't loaded",
+                        rules_path,
+                        target
+                    );
                 }
-                rules.extend(custom);
             }
             Err(e) => {
-                eprintln!(
-                    "{}",
-                    format!(
-                        "⚠️  Failed to load custom rules from '{}': {}",
-                        rules_path, e
-                    )
-                    .yellow()
+                tracing::warn!("Failed to load custom rules from '{}': {}", rules_path, e);
+            }
+        }
+    }
+    Ok(rules)
+}
+
+/// Scopes for the `[[overrides]]` sections of the config, adding the rules
+/// of their `extra_rules` files to `rules`
+fn load_rule_scopes(
+    args: &RuleArgs,
+    config: &Config,
+    rules: &mut Vec<Rule>,
+) -> Result<Vec<RuleScope>> {
+    let rules_format = args
+        .rules_format
+        .or(config.rules.format)
+        .unwrap_or_default();
+    // A file shared by several sections is loaded once
+    let mut loaded: HashMap<String, Vec<String>> = HashMap::new();
+    let mut scopes = Vec::with_capacity(config.overrides.len());
+    for (i, section) in config.overrides.iter().enumerate() {
+        if section.paths.is_empty() {
+            anyhow::bail!("[[overrides]] entry {} has no paths", i + 1);
+        }
+        let mut extra_rules = Vec::new();
+        for file in &section.extra_rules {
+            let file = config.resolve(file).display().to_string();
+            if let Some(ids) = loaded.get(&file) {
+                extra_rules.extend(ids.iter().cloned());
+                continue;
+            }
+            let custom = match rules::load_rule_file(&file, rules_format) {
+                Ok(custom) => custom,
+                Err(e) => {
+                    tracing::warn!("Failed to load custom rules from '{}': {}", file, e);
+                    continue;
+                }
+            };
+            if !custom.overrides.is_empty() {
+                tracing::warn!(
+                    "'{}' is loaded for [[overrides]], where `override:` entries aren't supported; ignoring them",
+                    file
                 );
             }
+            let mut ids = Vec::new();
+            for rule in custom.rules {
+                if rules.iter().any(|r| r.id == rule.id) {
+                    tracing::warn!(
+                        rule = %rule.id,
+                        "'{}' defines rule '{}', which is already loaded; \
+                         rules for [[overrides]] need ids of their own",
+                        file,
+                        rule.id
+                    );
+                    continue;
+                }
+                ids.push(rule.id.clone());
+                rules.push(rule);
+            }
+            extra_rules.extend(ids.iter().cloned());
+            loaded.insert(file, ids);
+        }
+        scopes.push(
+            RuleScope::new(&section.paths, section.ignore_rules.clone(), extra_rules)
+                .with_context(|| format!("invalid [[overrides]] entry {}", i + 1))?,
+        );
+    }
+    Ok(scopes)
+}
+
+/// Options holding the ignore list and tag filters from the config file and
+/// CLI flags
+fn rule_filters(args: &RuleArgs, config: &Config) -> ScanOptions {
+    let ignore_rules = match &args.ignore_rules {
+        Some(s) => split_list(s),
+        None => config.rules.ignore_rules.clone(),
+    };
+    let only_tags = match &args.only_tags {
+        Some(s) => split_list(s),
+        None => config.rules.only_tags.clone(),
+    };
+    let skip_tags = match &args.skip_tags {
+        Some(s) => split_list(s),
+        None => config.rules.skip_tags.clone(),
+    };
+    ScanOptions {
+        ignore_rules,
+        only_tags,
+        skip_tags,
+        ..ScanOptions::default()
+    }
+}
+
+/// Load the WebAssembly detector plugins passed with --plugin
+#[cfg(feature = "plugins")]
+fn load_plugins(paths: &[PathBuf]) -> Result<DetectorRegistry> {
+    use fastsecret::detector::Detector;
+
+    let mut registry = DetectorRegistry::default();
+    for path in paths {
+        let plugin = fastsecret::plugin::WasmDetector::load(path)?;
+        tracing::info!("Loaded plugin {}", plugin.id());
+        registry.register(plugin);
+    }
+    Ok(registry)
+}
+
+#[cfg(not(feature = "plugins"))]
+fn load_plugins(paths: &[PathBuf]) -> Result<DetectorRegistry> {
+    if !paths.is_empty() {
+        anyhow::bail!("--plugin requires fastsecret to be built with the `plugins` feature");
+    }
+    Ok(DetectorRegistry::default())
+}
+
+/// Load rules and build scan settings from the config file and CLI flags
+///
+/// CLI flags take precedence over values from the config file.
+fn prepare_scan(args: &ScanArgs, root: &str) -> Result<Settings> {
+    let config = load_config(&args.config, root)?;
+    let verbose = args.verbose || config.scan.verbose.unwrap_or(false);
+    if let Some(log) = VERBOSE_LOG.get().filter(|_| verbose) {
+        log.set_level(LevelFilter::DEBUG)?;
+    }
+    let mut rules = load_rules(&args.rules, &config)?;
+    let rule_scopes = load_rule_scopes(&args.rules, &config, &mut rules)?;
+    let problems = rules::check_rules(&rules);
+    if !problems.is_empty() {
+        let list: Vec<String> = problems.iter().map(|p| format!("  {}", p)).collect();
+        anyhow::bail!(
+            "{} rule problem(s) found; fix them or run `fastsecret rules validate`:\n{}",
+            problems.len(),
+            list.join("\n")
+        );
+    }
+
+    let redact = if args.redact {
+        true
+    } else if args.no_redact {
+        false
+    } else {
+        config.scan.redact.unwrap_or(!verbose)
+    };
+
+    let syntax = args.syntax || config.scan.syntax.unwrap_or(false);
+    if syntax && !cfg!(feature = "syntax") {
+        anyhow::bail!("--syntax requires fastsecret to be built with the `syntax` feature");
+    }
+    let mmap = args.mmap || config.scan.mmap.unwrap_or(false);
+    if mmap && !cfg!(feature = "mmap") {
+        anyhow::bail!("--mmap requires fastsecret to be built with the `mmap` feature");
+    }
+    let plugins: Vec<PathBuf> = if args.plugins.is_empty() {
+        config
+            .scan
+            .plugins
+            .iter()
+            .map(|p| config.resolve(p))
+            .collect()
+    } else {
+        args.plugins.iter().map(PathBuf::from).collect()
+    };
+
+    let options = ScanOptions {
+        verbose,
+        threads: args.threads.or(config.scan.threads).unwrap_or(0),
+        no_ignore: args.no_ignore,
+        redact,
+        min_severity: args
+            .min_severity
+            .or(config.scan.min_severity)
+            .unwrap_or_default(),
+        min_confidence: args
+            .min_confidence
+            .or(config.scan.min_confidence)
+            .unwrap_or_default(),
+        skip_dirs: config.ignore.directories.clone(),
+        include: if args.include.is_empty() {
+            config.scan.include.clone()
+        } else {
+            args.include.clone()
+        },
+        exclude: config
+            .ignore
+            .paths
+            .iter()
+            .chain(&args.exclude)
+            .cloned()
+            .collect(),
+        max_file_size: args.max_file_size.or(config.scan.max_file_size),
+        cache_dir: if args.no_cache || args.profile_rules {
+            None
+        } else {
+            args.cache_dir
+                .as_ref()
+                .map(PathBuf::from)
+                .or_else(cache::default_cache_dir)
+        },
+        archives: (args.scan_archives || config.scan.scan_archives.unwrap_or(false))
+            .then(ArchiveOptions::default),
+        scan_binary: args.scan_binary || config.scan.scan_binary.unwrap_or(false),
+        decode: (args.decode || config.scan.decode.unwrap_or(false)).then(DecodeOptions::default),
+        max_line_length: args
+            .max_line_length
+            .or(config.scan.max_line_length)
+            .map(|len| len as usize),
+        source_maps: args.source_maps || config.scan.source_maps.unwrap_or(false),
+        context: args.context.or(config.scan.context).unwrap_or(0),
+        syntax,
+        mmap,
+        detectors: load_plugins(&plugins)?,
+        rule_scopes,
+        lockfiles: LockfileOptions::new(
+            args.lockfiles.or(config.lockfiles.mode).unwrap_or_default(),
+            &config.lockfiles.skip,
+            &config.lockfiles.restricted,
+            &config.lockfiles.scan,
+        )
+        .context("invalid glob in [lockfiles]")?,
+        follow_symlinks: args.follow_symlinks || config.scan.follow_symlinks.unwrap_or(false),
+        max_depth: args.max_depth.or(config.scan.max_depth),
+        entropy: args
+            .entropy_threshold
+            .or(config.scan.entropy_threshold)
+            .map(|threshold| EntropyOptions {
+                threshold,
+                ..EntropyOptions::default()
+            }),
+        ..rule_filters(&args.rules, &config)
+    };
+
+    // --fail-on wins over --exit-on-secrets, which means "fail on anything"
+    let exit_on_secrets = args.exit_on_secrets || config.scan.exit_on_secrets.unwrap_or(false);
+    // An unsalted hash of a short secret is easy to reverse
+    let secret_salt = if args.hash_secrets || config.scan.hash_secrets.unwrap_or(false) {
+        let salt = args
+            .secret_salt
+            .clone()
+            .or_else(|| config.scan.secret_salt.clone());
+        Some(salt.context(
+            "--hash-secrets needs a salt; set --secret-salt, FASTSECRET_SECRET_SALT, or secret_salt under [scan]",
+        )?)
+    } else {
+        None
+    };
+    let fail_on = args
+        .fail_on
+        .or(config.scan.fail_on)
+        .unwrap_or(if exit_on_secrets {
+            FailOn::Severity(FindingSeverity::Low)
+        } else {
+            FailOn::Never
+        });
+
+    let profile = args.profile.as_deref().or(config.scan.profile.as_deref());
+    let policy = config.policy_for(profile)?;
+
+    let format = args.format.or(config.scan.format).unwrap_or_default();
+    if args.output.is_some() && format == OutputFormat::Text {
+        anyhow::bail!("--output needs a report format such as --format json");
+    }
+    if args.template.is_some() && format != OutputFormat::Template {
+        anyhow::bail!("--template needs --format template");
+    }
+    let template = match (format, args.template.as_ref().or(config.scan.template.as_ref())) {
+        (OutputFormat::Template, Some(template)) => {
+            Some(template.parse::<Template>().context("invalid --template")?)
+        }
+        (OutputFormat::Template, None) => anyhow::bail!(
+            "--format template needs a --template, e.g. --template '{{{{file}}}}:{{{{line}}}} {{{{rule_id}}}}'"
+        ),
+        _ => None,
+    };
+    let notify = args
+        .notify_webhook
+        .clone()
+        .or_else(|| config.notify.webhook.clone())
+        .map(|url| NotifySettings {
+            format: args
+                .notify_format
+                .or(config.notify.format)
+                .unwrap_or_else(|| PayloadFormat::for_url(&url)),
+            min_severity: args
+                .notify_on
+                .or(config.notify.min_severity)
+                .unwrap_or_default(),
+            root: root.to_string(),
+            url,
+        });
+    if notify.is_some() && !cfg!(feature = "webhook") {
+        anyhow::bail!(
+            "--notify-webhook requires fastsecret to be built with the `webhook` feature"
+        );
+    }
+    let record = args
+        .record_db
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| config.scan.record_db.as_deref().map(|p| config.resolve(p)))
+        .map(|db| RecordSettings {
+            db,
+            root: root.to_string(),
+        });
+    if record.is_some() && !cfg!(feature = "history") {
+        anyhow::bail!("--record-db requires fastsecret to be built with the `history` feature");
+    }
+
+    // A remap can raise a finding past --min-severity, so then findings are
+    // held to it only once the policy has run
+    let min_severity = options.min_severity;
+    let raises = policy.severity.iter().any(|o| o.severity >= min_severity);
+    let rule_set = CompiledRuleSet::new(&rules)?;
+    let options = ScanOptions {
+        profile: args
+            .profile_rules
+            .then(|| Arc::new(RuleProfile::new(&rule_set))),
+        min_severity: if raises {
+            FindingSeverity::Low
+        } else {
+            min_severity
+        },
+        ..options
+    };
+
+    Ok(Settings {
+        scanner: Scanner::new(rule_set, options),
+        format,
+        template,
+        output: args.output.clone(),
+        sort: args.sort.or(config.scan.sort),
+        group_by: args.group_by.or(config.scan.group_by),
+        dedup: args.dedup.or(config.scan.dedup).unwrap_or_default(),
+        caps: FindingCaps {
+            per_file: args
+                .max_findings_per_file
+                .or(config.scan.max_findings_per_file),
+            per_rule: args
+                .max_findings_per_rule
+                .or(config.scan.max_findings_per_rule),
+        },
+        secret_salt,
+        fail_on,
+        policy: Policy {
+            fail_if: args.fail_if.clone().or(policy.fail_if),
+            ..policy
+        },
+        min_severity,
+        baseline: args.baseline.clone(),
+        verify: args.verify,
+        blame: args.blame,
+        strict: args.strict || config.scan.strict.unwrap_or(false),
+        fp_filter: args.fp_filter || config.scan.fp_filter.unwrap_or(false),
+        test_context: {
+            let defaults = TestContext::default();
+            TestContext {
+                mode: args
+                    .test_context
+                    .or(config.test_context.mode)
+                    .unwrap_or_default(),
+                paths: config.test_context.paths.clone().unwrap_or(defaults.paths),
+                markers: config
+                    .test_context
+                    .markers
+                    .clone()
+                    .unwrap_or(defaults.markers),
+            }
+        },
+        progress: !args.no_progress && !verbose && !args.quiet && std::io::stderr().is_terminal(),
+        summary_only: args.summary_only || args.quiet,
+        quiet: args.quiet,
+        notify,
+        record,
+    })
+}
+
+/// Print findings in the requested format and return the exit code
+fn report_findings(
+    findings: &[Finding],
+    files_scanned: Option<usize>,
+    settings: &Settings,
+) -> Result<i32> {
+    let counts = SeverityCounts::from_findings(findings);
+    let scanner = &settings.scanner;
+    let violations = settings
+        .policy
+        .violations(findings, scanner.rule_set(), scanner.options());
+    // A fail condition replaces the severity threshold
+    let threshold_failed =
+        settings.policy.fail_if.is_none() && settings.fail_on.should_fail(&counts);
+    let exit_code = if threshold_failed || !violations.is_empty() {
+        2
+    } else {
+        0
+    };
+    if !settings.quiet {
+        for violation in &violations {
+            eprintln!("{}", format!("✗ Policy: {}", violation).red());
         }
     }
 
-    // Perform scan
-    let findings = scan_path(&args.path, &rules, &ignore_set, args.verbose)?;
+    // Counts and policies above cover every finding; caps only trim the list
+    let (shown, suppressed) = scanner::cap_findings(findings.to_vec(), settings.caps);
+    if settings.summary_only {
+        // Only --quiet allows --output alongside the summary
+        if settings.output.is_some() {
+            write_report(&shown, settings)?;
+        }
+        let summary = ScanSummary {
+            files_scanned,
+            ..ScanSummary::from_findings(findings)
+        };
+        println!("{} exit={}", summary, exit_code);
+        return Ok(exit_code);
+    }
+
+    if settings.format != OutputFormat::Text {
+        write_report(&shown, settings)?;
+        if suppressed.total() > 0 && !settings.quiet {
+            tracing::warn!(
+                "{} more finding(s) suppressed by --max-findings-per-file or --max-findings-per-rule",
+                suppressed.total()
+            );
+        }
+        return Ok(exit_code);
+    }
 
-    // Display results
     if findings.is_empty() {
         println!(
             "{}",
             "✅ No secrets detected.  You're safe! ".green().bold()
         );
-        process::exit(0);
     } else {
         println!("{}", "🚨 Possible secrets found:".red().bold());
-        display_findings(&findings);
+        let verbose = settings.scanner.options().verbose;
+        match settings.group_by {
+            Some(by) => {
+                for (key, group) in scanner::group_findings(&shown, by) {
+                    println!("\n{} {}", key.bold(), format!("({})", group.len()).dimmed());
+                    display_findings(group, verbose);
+                }
+            }
+            None => display_findings(&shown, verbose),
+        }
+        for (file, n) in &suppressed.by_file {
+            println!(
+                "  {}",
+                format!(
+                    "… and {} more in {} suppressed (--max-findings-per-file)",
+                    n, file
+                )
+                .dimmed()
+            );
+        }
+        for (rule, n) in &suppressed.by_rule {
+            println!(
+                "  {}",
+                format!(
+                    "… and {} more {} finding(s) suppressed (--max-findings-per-rule)",
+                    n, rule
+                )
+                .dimmed()
+            );
+        }
 
-        let count = findings.len();
+        let hidden = match suppressed.total() {
+            0 => String::new(),
+            n => format!(", {} not shown", n),
+        };
         println!(
             "\n{}",
-            format!("Found {} potential secret(s).", count).red().bold()
+            format!(
+                "Found {} potential secret(s) ({} high, {} medium, {} low){}.",
+                counts.total(),
+                counts.high,
+                counts.medium,
+                counts.low,
+                hidden
+            )
+            .red()
+            .bold()
+        );
+    }
+
+    Ok(exit_code)
+}
+
+/// Write findings in a report format to `--output` or stdout
+fn write_report(findings: &[Finding], settings: &Settings) -> Result<()> {
+    let writer: Box<dyn Write> = match &settings.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).with_context(|| format!("failed to create '{}'", path))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    match settings.format {
+        OutputFormat::Json => report::write_json(findings, writer)?,
+        OutputFormat::Ndjson => report::write_ndjson(findings, writer)?,
+        OutputFormat::Html => report::write_html(findings, writer)?,
+        OutputFormat::Github => report::write_github(findings, writer)?,
+        OutputFormat::Gitlab => report::write_gitlab(findings, writer)?,
+        OutputFormat::Template => {
+            let template = settings.template.as_ref().expect("checked by prepare_scan");
+            report::write_template(findings, template, writer)?
+        }
+        OutputFormat::Text => unreachable!(),
+    }
+    if let Some(path) = settings.output.as_ref().filter(|_| !settings.quiet) {
+        eprintln!(
+            "{}",
+            format!("✓ Wrote {} finding(s) to '{}'", findings.len(), path).green()
+        );
+    }
+    Ok(())
+}
+
+/// Watch a directory and print findings as files change
+#[cfg(feature = "watch")]
+fn run_watch(path: &str, metrics_listen: Option<&str>, settings: &Settings) -> Result<()> {
+    use fastsecret::metrics::{self, Metrics};
+    use fastsecret::watch::{self, WatchEvent};
+
+    let metrics = Arc::new(Metrics::new());
+    if let Some(addr) = metrics_listen {
+        let addr = metrics::serve(Arc::clone(&metrics), addr)?;
+        if !settings.quiet {
+            eprintln!("Serving metrics on http://{}/metrics", addr);
+        }
+    }
+
+    let baseline = settings
+        .baseline
+        .as_deref()
+        .map(Baseline::load)
+        .transpose()?;
+    let verbose = settings.scanner.options().verbose;
+    let known = |findings: Vec<Finding>| match &baseline {
+        Some(baseline) => baseline.filter(findings),
+        None => findings,
+    };
+
+    watch::watch_with_metrics(
+        &settings.scanner,
+        Path::new(path),
+        Duration::from_millis(300),
+        &metrics,
+        |event| {
+            match event {
+                WatchEvent::Initial(report) => {
+                    let findings = known(report.findings);
+                    if findings.is_empty() {
+                        println!("{}", "✅ No secrets detected.".green().bold());
+                    } else {
+                        println!("{}", "🚨 Possible secrets found:".red().bold());
+                        display_findings(&findings, verbose);
+                    }
+                    println!(
+                        "{}",
+                        format!("👀 Watching {} for changes (Ctrl-C to stop)", path).dimmed()
+                    );
+                }
+                WatchEvent::Changed {
+                    path,
+                    new,
+                    resolved,
+                } => {
+                    let new = known(new);
+                    if !new.is_empty() {
+                        println!(
+                            "{}",
+                            format!("🚨 New secret(s) in {}:", path.display())
+                                .red()
+                                .bold()
+                        );
+                        display_findings(&new, verbose);
+                    }
+                    if !resolved.is_empty() {
+                        println!(
+                            "{}",
+                            format!(
+                                "✓ {} finding(s) resolved in {}",
+                                resolved.len(),
+                                path.display()
+                            )
+                            .green()
+                        );
+                    }
+                }
+            }
+            true
+        },
+    )
+}
+
+#[cfg(not(feature = "watch"))]
+fn run_watch(_path: &str, _metrics_listen: Option<&str>, _settings: &Settings) -> Result<()> {
+    anyhow::bail!("watch mode requires fastsecret to be built with the `watch` feature")
+}
+
+/// Serve LSP on stdio; stdout belongs to the protocol, so nothing else is printed
+#[cfg(feature = "lsp")]
+fn run_lsp(settings: &Settings) -> Result<()> {
+    fastsecret::lsp::run_stdio(&settings.scanner)
+}
+
+#[cfg(not(feature = "lsp"))]
+fn run_lsp(_settings: &Settings) -> Result<()> {
+    anyhow::bail!("the language server requires fastsecret to be built with the `lsp` feature")
+}
+
+/// Serve scans over HTTP until the process is killed
+#[cfg(feature = "server")]
+fn run_serve(
+    listen: &str,
+    workers: Option<usize>,
+    max_body_size: Option<u64>,
+    settings: Settings,
+) -> Result<()> {
+    use fastsecret::server::{Server, ServerOptions};
+
+    let defaults = ServerOptions::default();
+    let options = ServerOptions {
+        workers: workers.unwrap_or(defaults.workers),
+        max_body_size: max_body_size.unwrap_or(defaults.max_body_size),
+    };
+    let rules = settings.scanner.rule_set().rules().len();
+    let server = Server::bind(listen, settings.scanner, options)?;
+    if !settings.quiet {
+        let addr = server
+            .local_addr()
+            .map_or_else(|| listen.to_string(), |a| a.to_string());
+        eprintln!("Listening on http://{} with {} rules", addr, rules);
+    }
+    server.run();
+    Ok(())
+}
+
+#[cfg(not(feature = "server"))]
+fn run_serve(
+    _listen: &str,
+    _workers: Option<usize>,
+    _max_body_size: Option<u64>,
+    _settings: Settings,
+) -> Result<()> {
+    anyhow::bail!("the HTTP server requires fastsecret to be built with the `server` feature")
+}
+
+/// Serve streaming scans over gRPC until the process is killed
+#[cfg(feature = "grpc")]
+fn run_grpc(listen: SocketAddr, max_open_files: Option<usize>, settings: Settings) -> Result<()> {
+    use fastsecret::grpc::{self, GrpcOptions};
+
+    let defaults = GrpcOptions::default();
+    let options = GrpcOptions {
+        max_file_size: settings
+            .scanner
+            .options()
+            .max_file_size
+            .unwrap_or(defaults.max_file_size),
+        max_open_files: max_open_files.unwrap_or(defaults.max_open_files),
+    };
+    if !settings.quiet {
+        eprintln!(
+            "Serving {} on {} with {} rules",
+            grpc::SERVICE_NAME,
+            listen,
+            settings.scanner.rule_set().rules().len()
+        );
+    }
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(grpc::serve(settings.scanner, options, listen))
+}
+
+#[cfg(not(feature = "grpc"))]
+fn run_grpc(
+    _listen: SocketAddr,
+    _max_open_files: Option<usize>,
+    _settings: Settings,
+) -> Result<()> {
+    anyhow::bail!("the gRPC server requires fastsecret to be built with the `grpc` feature")
+}
+
+/// Clone URLs of every repository in a GitHub organization or GitLab group
+#[cfg(feature = "org")]
+fn list_org_repos(
+    forge: Forge,
+    org: &str,
+    token: Option<&str>,
+    api_url: Option<&str>,
+) -> Result<Vec<String>> {
+    org::list_repos(forge, org, token, api_url)
+}
+
+#[cfg(not(feature = "org"))]
+fn list_org_repos(
+    _forge: Forge,
+    _org: &str,
+    _token: Option<&str>,
+    _api_url: Option<&str>,
+) -> Result<Vec<String>> {
+    anyhow::bail!("--github and --gitlab require fastsecret to be built with the `org` feature")
+}
+
+fn is_bucket_url(path: &str) -> bool {
+    path.starts_with("s3://") || path.starts_with("gs://")
+}
+
+/// Scan the objects in a bucket, returning findings and objects scanned
+#[cfg(feature = "cloud")]
+fn scan_bucket(
+    url: &str,
+    max_objects: Option<usize>,
+    settings: &Settings,
+) -> Result<(Vec<Finding>, usize, Vec<ScanError>)> {
+    use fastsecret::cloud::{self, BucketOptions, BucketUrl};
+
+    let bucket_url =
+        BucketUrl::parse(url).with_context(|| format!("invalid bucket URL '{}'", url))?;
+    let defaults = BucketOptions::from_env();
+    let bucket = BucketOptions {
+        max_objects: max_objects.unwrap_or(defaults.max_objects),
+        ..defaults
+    };
+    let scanner = &settings.scanner;
+    let scan = cloud::scan_bucket(&bucket_url, &bucket, scanner.rule_set(), scanner.options())?;
+    if scan.truncated && !settings.quiet {
+        tracing::warn!(
+            "Stopped after {} objects; raise --max-objects to scan the rest",
+            bucket.max_objects
+        );
+    }
+    if settings.progress || scanner.options().verbose {
+        eprintln!(
+            "{}",
+            format!(
+                "Scanned {} object(s) in {} ({} skipped)",
+                scan.objects_scanned, bucket_url, scan.objects_skipped
+            )
+            .dimmed()
+        );
+    }
+    Ok((scan.findings, scan.objects_scanned, scan.errors))
+}
+
+#[cfg(not(feature = "cloud"))]
+fn scan_bucket(
+    _url: &str,
+    _max_objects: Option<usize>,
+    _settings: &Settings,
+) -> Result<(Vec<Finding>, usize, Vec<ScanError>)> {
+    anyhow::bail!("scanning buckets requires fastsecret to be built with the `cloud` feature")
+}
+
+/// Print one line per repository of an org scan, then the totals
+fn print_org_report(report: &OrgReport) {
+    for repo in &report.repos {
+        match &repo.error {
+            Some(error) => eprintln!("  {} {}: {}", "✗".red(), repo.name, error),
+            None => eprintln!("  {} {}: {}", "✓".green(), repo.name, repo.summary()),
+        }
+    }
+    let failed = report.failed().count();
+    eprintln!(
+        "{}",
+        format!(
+            "Scanned {} of {} repositories; {}",
+            report.repos.len() - failed,
+            report.repos.len(),
+            report.summary()
+        )
+        .dimmed()
+    );
+}
+
+/// Write `<org>_<repo>.json` per repository plus `summary.json` to `dir`
+fn write_org_reports(report: &OrgReport, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create '{}'", dir.display()))?;
+    let mut summaries = Vec::new();
+    for repo in &report.repos {
+        let path = dir.join(format!("{}.json", repo.name.replace('/', "_")));
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create '{}'", path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), repo)?;
+        summaries.push(serde_json::json!({
+            "name": repo.name,
+            "url": repo.url,
+            "summary": repo.summary(),
+            "error": repo.error,
+        }));
+    }
+    let summary = serde_json::json!({
+        "repositories": summaries,
+        "summary": report.summary(),
+    });
+    std::fs::write(
+        dir.join("summary.json"),
+        serde_json::to_string_pretty(&summary)?,
+    )?;
+    Ok(())
+}
+
+/// Check findings with their providers
+#[cfg(feature = "verify")]
+fn run_verification(findings: &mut [Finding], settings: &Settings) -> Result<()> {
+    use fastsecret::verify;
+
+    let verifiers = verify::builtin_verifiers(std::time::Duration::from_secs(10));
+    verify::verify_findings(
+        findings,
+        settings.scanner.rule_set(),
+        &verifiers,
+        &verify::VerifyOptions::default(),
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "verify"))]
+fn run_verification(_findings: &mut [Finding], _settings: &Settings) -> Result<()> {
+    anyhow::bail!("--verify requires fastsecret to be built with the `verify` feature")
+}
+
+/// Post findings at or above the notification threshold to the webhook
+///
+/// A failed delivery is reported as a warning; it doesn't change the exit code.
+#[cfg(feature = "webhook")]
+fn send_notification(findings: &[Finding], notify: &NotifySettings) -> Result<()> {
+    use fastsecret::notifications::{self, ScanContext};
+
+    let findings: Vec<Finding> = findings
+        .iter()
+        .filter(|f| f.severity >= notify.min_severity)
+        .cloned()
+        .collect();
+    if findings.is_empty() {
+        return Ok(());
+    }
+    let context = ScanContext::detect(&notify.root);
+    let payload = notifications::build_payload(notify.format, &context, &findings);
+    if let Err(e) = notifications::send_webhook(&notify.url, &payload, Duration::from_secs(10)) {
+        tracing::warn!("{:#}", e);
+    }
+    Ok(())
+}
+
+/// `prepare_scan` rejects `--notify-webhook` without the `webhook` feature
+#[cfg(not(feature = "webhook"))]
+fn send_notification(_findings: &[Finding], _notify: &NotifySettings) -> Result<()> {
+    Ok(())
+}
+
+/// Append the run to the history database
+///
+/// A failure to record is reported as a warning; it doesn't change the exit code.
+#[cfg(feature = "history")]
+fn record_scan(findings: &[Finding], record: &RecordSettings) -> Result<()> {
+    use fastsecret::history::HistoryDb;
+    use fastsecret::notifications::ScanContext;
+
+    let context = ScanContext::detect(&record.root);
+    if let Err(e) = HistoryDb::open(&record.db).and_then(|mut db| db.record(&context, findings)) {
+        tracing::warn!("{:#}", e);
+    }
+    Ok(())
+}
+
+/// `prepare_scan` rejects `--record-db` without the `history` feature
+#[cfg(not(feature = "history"))]
+fn record_scan(_findings: &[Finding], _record: &RecordSettings) -> Result<()> {
+    Ok(())
+}
+
+/// Log messages on stderr, and export spans if `--otlp-endpoint` asks for
+/// it, returning the span exporter
+fn init_logging(cli: &Cli) -> Result<Option<Telemetry>> {
+    let (telemetry, spans) = cli
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| init_telemetry(endpoint, cli.otlp_level))
+        .transpose()?
+        .unzip();
+    let log = logging::init(
+        cli.log_level.unwrap_or(LevelFilter::WARN),
+        cli.log_format.unwrap_or_default(),
+        spans,
+    )?;
+    if cli.log_level.is_none() {
+        let _ = VERBOSE_LOG.set(log);
+    }
+    Ok(telemetry)
+}
+
+/// Export the scan's spans to an OTLP collector
+#[cfg(feature = "otel")]
+fn init_telemetry(
+    endpoint: &str,
+    level: Option<tracing::Level>,
+) -> Result<(Telemetry, ExtraLayer)> {
+    let telemetry = Telemetry::new(endpoint)?;
+    let layer = Box::new(telemetry.layer(level.unwrap_or(tracing::Level::DEBUG)));
+    Ok((telemetry, layer))
+}
+
+#[cfg(not(feature = "otel"))]
+fn init_telemetry(
+    _endpoint: &str,
+    _level: Option<tracing::Level>,
+) -> Result<(Telemetry, ExtraLayer)> {
+    anyhow::bail!("--otlp-endpoint requires fastsecret to be built with the `otel` feature")
+}
+
+/// Progress bar on stderr showing files scanned and the current path
+struct ProgressReporter {
+    bar: ProgressBar,
+}
+
+impl ProgressReporter {
+    fn new() -> Self {
+        let bar = ProgressBar::new_spinner();
+        bar.set_message("Collecting files...");
+        bar.enable_steady_tick(Duration::from_millis(100));
+        ProgressReporter { bar }
+    }
+}
+
+impl ScanProgress for ProgressReporter {
+    fn start(&self, total_files: usize) {
+        self.bar.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{bar:30.cyan/blue}] {pos}/{len} files {wide_msg:.dim}",
+            )
+            .expect("valid progress template")
+            .progress_chars("=> "),
+        );
+        self.bar.set_length(total_files as u64);
+        self.bar.set_message("");
+    }
+
+    fn file_done(&self, path: &Path) {
+        self.bar.set_message(path.display().to_string());
+        self.bar.inc(1);
+    }
+
+    fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Print scan statistics to stderr
+fn print_stats(stats: &ScanStats) {
+    let counts = &stats.severity_counts;
+    eprintln!(
+        "{}",
+        format!(
+            "Scanned {} file(s), {} line(s), {} in {:.2?} ({} skipped, {} cached); \
+             {} high, {} medium, {} low finding(s)",
+            stats.files_scanned,
+            stats.lines_scanned,
+            HumanBytes(stats.bytes_scanned),
+            stats.duration,
+            stats.files_skipped,
+            stats.files_cached,
+            counts.high,
+            counts.medium,
+            counts.low
+        )
+        .dimmed()
+    );
+}
+
+/// Rules listed by `--profile-rules` without `--verbose`
+const PROFILE_TOP: usize = 20;
+
+/// Print how long each rule took, slowest first
+fn print_rule_profile(profile: &RuleProfile, verbose: bool) {
+    let timings = profile.timings();
+    let total: Duration = timings.iter().map(|t| t.time).sum();
+    eprintln!("{}", "Rule profile (slowest first):".bold());
+    eprintln!(
+        "{}",
+        format!(
+            "  {:>10}  {:>6}  {:>8}  {:>6}  rule",
+            "time", "share", "matches", "files"
+        )
+        .dimmed()
+    );
+    let shown = if verbose { timings.len() } else { PROFILE_TOP };
+    for timing in timings.iter().take(shown) {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            timing.time.as_secs_f64() / total.as_secs_f64() * 100.0
+        };
+        eprintln!(
+            "  {:>10}  {:>5.1}%  {:>8}  {:>6}  {} [{}]",
+            format!("{:.2?}", timing.time),
+            share,
+            timing.matches,
+            timing.files,
+            timing.rule_name,
+            timing.rule_id
         );
+    }
+    if timings.len() > shown {
+        eprintln!(
+            "{}",
+            format!(
+                "  ... and {} more (use --verbose to list all)",
+                timings.len() - shown
+            )
+            .dimmed()
+        );
+    }
+}
 
-        if args.exit_on_secrets {
-            process::exit(2);
+/// Warn about files that couldn't be scanned, listing them in verbose or
+/// strict mode
+fn print_scan_errors(errors: &[ScanError], settings: &Settings) {
+    if errors.is_empty() || settings.quiet {
+        return;
+    }
+    let list = settings.strict || settings.scanner.options().verbose;
+    tracing::warn!(
+        "Couldn't scan {} path(s){}",
+        errors.len(),
+        if list {
+            ":"
         } else {
-            process::exit(0);
+            "; run with --verbose to list them"
+        }
+    );
+    if list {
+        for error in errors {
+            tracing::warn!(file = %error.file, kind = error.kind.as_str(), "{}", error);
         }
     }
 }
 
 /// Display findings with color and formatting
-fn display_findings(findings: &[Finding]) {
+///
+/// Verbose mode adds each finding's fingerprint.
+fn display_findings<'a>(findings: impl IntoIterator<Item = &'a Finding>, verbose: bool) {
     for f in findings {
         let severity_display = match f.severity {
             scanner::FindingSeverity::High => "HIGH".red().bold(),
@@ -113,19 +3163,139 @@ fn display_findings(findings: &[Finding]) {
             scanner::FindingSeverity::Low => "LOW".cyan(),
         };
 
-        let snippet = if f.snippet.len() > 80 {
-            format!("{}...", &f.snippet[..77])
+        let snippet = if f.snippet.chars().count() > 80 {
+            format!("{}...", f.snippet.chars().take(77).collect::<String>())
         } else {
             f.snippet.clone()
         };
 
+        let location = match &f.commit {
+            Some(commit) => format!(
+                "[{}: {} @ {}]",
+                f.file,
+                f.line,
+                &commit.sha[..commit.sha.len().min(8)]
+            ),
+            None => format!("[{}: {}]", f.file, f.line),
+        };
+
+        let verification = match f.verification {
+            Some(VerificationStatus::Verified) => format!(" {}", "VERIFIED".red().bold()),
+            Some(VerificationStatus::Invalid) => format!(" {}", "invalid".dimmed()),
+            Some(VerificationStatus::Unknown) => format!(" {}", "unverified".dimmed()),
+            None => String::new(),
+        };
+
+        let confidence = match f.confidence {
+            Confidence::Low => format!(" {}", "(low confidence)".dimmed()),
+            Confidence::Medium | Confidence::High => String::new(),
+        };
+
+        let variable = match &f.variable {
+            Some(name) => format!(" in {}", name.bold()),
+            None => String::new(),
+        };
+
         println!(
-            "  {} {} {} {} ({})",
-            format!("[{}: {}]", f.file, f.line).bright_blue(),
+            "  {} {}{}{} {} {} {}{} ({})",
+            location.bright_blue(),
             severity_display,
+            confidence,
+            verification,
             "—".dimmed(),
             f.rule_name.bold(),
+            format!("[{}]", f.rule_id).dimmed(),
+            variable,
             snippet.dimmed()
         );
+
+        if !f.context.is_empty() {
+            let width = f
+                .context
+                .iter()
+                .map(|c| c.line)
+                .chain([f.line])
+                .max()
+                .unwrap_or(0)
+                .to_string()
+                .len();
+            let (before, after) = f
+                .context
+                .split_at(f.context.partition_point(|c| c.line < f.line));
+            for c in before {
+                println!(
+                    "      {}",
+                    format!("{:>width$} │ {}", c.line, c.text).dimmed()
+                );
+            }
+            println!("      {:>width$} │ {}", f.line, f.snippet);
+            for c in after {
+                println!(
+                    "      {}",
+                    format!("{:>width$} │ {}", c.line, c.text).dimmed()
+                );
+            }
+        }
+
+        if let Some(commit) = &f.commit {
+            println!(
+                "      {}",
+                format!("introduced by {} on {}", commit.author, commit.date).dimmed()
+            );
+        }
+        if let Some(layer) = &f.layer {
+            let digest = layer.digest.trim_start_matches("sha256:");
+            let layer_line = match &layer.created_by {
+                Some(created_by) => format!(
+                    "in layer {} — {}",
+                    &digest[..digest.len().min(12)],
+                    created_by
+                ),
+                None => format!("in layer {}", &digest[..digest.len().min(12)]),
+            };
+            println!("      {}", layer_line.dimmed());
+        }
+        if let Some(cell) = &f.cell {
+            println!("      {}", cell.to_string().dimmed());
+        }
+        if let Some(original) = &f.original {
+            println!("      {}", format!("from {}", original).dimmed());
+        }
+        if !f.other_locations.is_empty() {
+            const SHOWN: usize = 5;
+            let mut places: Vec<String> = f
+                .other_locations
+                .iter()
+                .take(SHOWN)
+                .map(ToString::to_string)
+                .collect();
+            if f.other_locations.len() > SHOWN {
+                places.push(format!("{} more", f.other_locations.len() - SHOWN));
+            }
+            println!(
+                "      {}",
+                format!("also in {}", places.join(", ")).dimmed()
+            );
+        }
+        if verbose {
+            println!(
+                "      {}",
+                format!("fingerprint {}", f.fingerprint()).dimmed()
+            );
+            if let Some(source) = &f.rule_source {
+                let version = f
+                    .rule_version
+                    .as_deref()
+                    .map(|v| format!(" {}", v))
+                    .unwrap_or_default();
+                println!(
+                    "      {}",
+                    format!("rule from {}{}", source, version).dimmed()
+                );
+            }
+            if let Some(remediation) = &f.remediation {
+                println!("      {} {}", "fix:".green(), remediation);
+            }
+        }
     }
 }
@@ -0,0 +1,69 @@
+//! Finding metadata
+//!
+//! Provider-specific context decoded from a matched secret: the AWS account
+//! an access key belongs to, whether a Stripe key is live or test, a JWT's
+//! issuer and algorithm, and the secret's entropy. It lands in a finding's
+//! `metadata` map, which callers may extend with their own entries.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::collections::BTreeMap;
+
+use crate::entropy::shannon_entropy;
+use crate::rules::validators::aws_account_id;
+
+/// Metadata for a matched secret
+pub fn extract(secret: &str) -> BTreeMap<String, String> {
+    let mut metadata = BTreeMap::new();
+    metadata.insert(
+        "entropy".to_string(),
+        format!("{:.2}", shannon_entropy(secret)),
+    );
+
+    if let Some(account) = aws_account_id(secret) {
+        metadata.insert("aws_account_id".to_string(), format!("{:012}", account));
+    }
+    if let Some(mode) = stripe_mode(secret) {
+        metadata.insert("stripe_mode".to_string(), mode.to_string());
+    }
+    if let Some((header, claims)) = decode_jwt(secret) {
+        let text = |value: &serde_json::Value, key: &str| {
+            value.get(key).and_then(|v| v.as_str()).map(str::to_string)
+        };
+        if let Some(alg) = text(&header, "alg") {
+            metadata.insert("jwt_algorithm".to_string(), alg);
+        }
+        if let Some(iss) = text(&claims, "iss") {
+            metadata.insert("jwt_issuer".to_string(), iss);
+        }
+    }
+    metadata
+}
+
+/// `live` or `test` for Stripe secret, restricted, and publishable keys
+fn stripe_mode(secret: &str) -> Option<&'static str> {
+    let rest = ["sk_", "rk_", "pk_"]
+        .iter()
+        .find_map(|prefix| secret.strip_prefix(prefix))?;
+    if rest.starts_with("live_") {
+        Some("live")
+    } else if rest.starts_with("test_") {
+        Some("test")
+    } else {
+        None
+    }
+}
+
+/// Header and claims of a JWT, if `token` is one with JSON in both
+fn decode_jwt(token: &str) -> Option<(serde_json::Value, serde_json::Value)> {
+    let mut parts = token.trim().split('.');
+    let (header, claims) = (parts.next()?, parts.next()?);
+    if !header.starts_with("eyJ") {
+        return None;
+    }
+    let decode = |part: &str| {
+        let bytes = URL_SAFE_NO_PAD.decode(part.trim_end_matches('=')).ok()?;
+        serde_json::from_slice::<serde_json::Value>(&bytes).ok()
+    };
+    Some((decode(header)?, decode(claims)?))
+}
@@ -0,0 +1,213 @@
+//! Prometheus metrics for long-running modes
+//!
+//! `fastsecret serve` answers `GET /metrics`, and `fastsecret watch
+//! --metrics-listen ADDR` serves the same page on a port of its own, in the
+//! Prometheus text format:
+//!
+//! - `fastsecret_scans_total`: scan requests, initial scans, and rescans
+//! - `fastsecret_findings_total{severity, rule}`: findings detected
+//! - `fastsecret_scan_errors_total{kind}`: files that couldn't be scanned,
+//!   by [`ScanErrorKind`](crate::ScanErrorKind), and rejected scan requests
+//!   (`kind="request"`)
+//! - `fastsecret_scan_duration_seconds`: a histogram of scan times
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::scanner::{Finding, FindingSeverity};
+
+/// Content type of the text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds of the scan duration buckets, in seconds
+const DURATION_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 30.0, 120.0,
+];
+
+/// Counters shared by the threads of a server or watcher
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counters: Mutex<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    scans: u64,
+    findings: BTreeMap<(FindingSeverity, String), u64>,
+    errors: BTreeMap<String, u64>,
+    /// Scans per duration bucket, not cumulative; the last slot is `+Inf`
+    durations: [u64; DURATION_BUCKETS.len() + 1],
+    duration_sum: f64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a finished scan, how long it took, and what it found
+    pub fn record_scan(&self, duration: Duration, findings: &[Finding]) {
+        let mut counters = self.lock();
+        counters.scans += 1;
+        let secs = duration.as_secs_f64();
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|le| secs <= *le)
+            .unwrap_or(DURATION_BUCKETS.len());
+        counters.durations[bucket] += 1;
+        counters.duration_sum += secs;
+        for f in findings {
+            *counters
+                .findings
+                .entry((f.severity, f.rule_id.clone()))
+                .or_default() += 1;
+        }
+    }
+
+    /// Count a file or request that couldn't be scanned
+    pub fn record_error(&self, kind: &str) {
+        *self.lock().errors.entry(kind.to_string()).or_default() += 1;
+    }
+
+    /// The metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let counters = self.lock();
+        let mut out = String::new();
+
+        header(&mut out, "fastsecret_scans_total", "counter", "Scans run.");
+        let _ = writeln!(out, "fastsecret_scans_total {}", counters.scans);
+
+        header(
+            &mut out,
+            "fastsecret_findings_total",
+            "counter",
+            "Findings detected, by severity and rule.",
+        );
+        for ((severity, rule), n) in &counters.findings {
+            let _ = writeln!(
+                out,
+                "fastsecret_findings_total{{severity=\"{}\",rule=\"{}\"}} {}",
+                severity.as_str(),
+                escape(rule),
+                n
+            );
+        }
+
+        header(
+            &mut out,
+            "fastsecret_scan_errors_total",
+            "counter",
+            "Files or requests that couldn't be scanned, by kind.",
+        );
+        for (kind, n) in &counters.errors {
+            let _ = writeln!(
+                out,
+                "fastsecret_scan_errors_total{{kind=\"{}\"}} {}",
+                escape(kind),
+                n
+            );
+        }
+
+        header(
+            &mut out,
+            "fastsecret_scan_duration_seconds",
+            "histogram",
+            "Time taken by each scan.",
+        );
+        let mut cumulative = 0;
+        for (i, n) in counters.durations.iter().enumerate() {
+            cumulative += n;
+            let le = DURATION_BUCKETS
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), |le| le.to_string());
+            let _ = writeln!(
+                out,
+                "fastsecret_scan_duration_seconds_bucket{{le=\"{}\"}} {}",
+                le, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "fastsecret_scan_duration_seconds_sum {}",
+            counters.duration_sum
+        );
+        let _ = writeln!(
+            out,
+            "fastsecret_scan_duration_seconds_count {}",
+            counters.scans
+        );
+        out
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        // Counters stay consistent even if a holder panicked
+        self.counters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Serve `GET /metrics` on `addr` from a background thread, returning the
+/// address listened on (port 0 picks a free one)
+pub fn serve(metrics: Arc<Metrics>, addr: &str) -> Result<SocketAddr> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("failed to listen on {}", addr))?;
+    let local = listener.local_addr()?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A client that hangs up only loses its own response
+            let _ = respond(&metrics, stream);
+        }
+    });
+    Ok(local)
+}
+
+fn respond(metrics: &Metrics, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new((&stream).take(16 << 10));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers; nothing in them changes the answer
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && !line.trim_end().is_empty() {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let (status, content_type, body) = match (method, path.split('?').next()) {
+        ("GET", Some("/metrics")) => ("200 OK", CONTENT_TYPE, metrics.render()),
+        (_, Some("/metrics")) => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".to_string(),
+        ),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
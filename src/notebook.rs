@@ -0,0 +1,210 @@
+//! Jupyter notebook scanning
+//!
+//! A `.ipynb` file is JSON in which each cell's source, and each output's
+//! text, is an array of strings. Matching the raw JSON misses secrets
+//! behind escaped quotes or split across source lines, and reports lines of
+//! the JSON rather than of the cell. Notebooks are parsed instead: each
+//! cell's source and text outputs are scanned as plain text, and findings
+//! carry the cell and the line within it, along with the line of the
+//! `.ipynb` file that holds it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::collections::{HashMap, HashSet};
+
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{scan_lines, Finding, ScanOptions};
+
+/// Where in a notebook a finding was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct NotebookCell {
+    /// 1-based position of the cell in the notebook
+    pub index: usize,
+    /// 1-based line within the cell's source or output text
+    pub line: usize,
+    /// 1-based position of the output, when the secret is in a cell's output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<usize>,
+}
+
+impl std::fmt::Display for NotebookCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.output {
+            Some(output) => write!(
+                f,
+                "in output {} of cell {}, line {}",
+                output, self.index, self.line
+            ),
+            None => write!(f, "in cell {}, line {}", self.index, self.line),
+        }
+    }
+}
+
+/// Whether `path` names a Jupyter notebook
+pub(crate) fn is_notebook(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".ipynb")
+}
+
+#[derive(Deserialize)]
+struct Notebook<'a> {
+    #[serde(borrow)]
+    cells: Vec<Cell<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Cell<'a> {
+    #[serde(borrow, default)]
+    source: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    outputs: Vec<Output<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Output<'a> {
+    /// Stream output
+    #[serde(borrow, default)]
+    text: Option<&'a RawValue>,
+    /// Rich output, keyed by MIME type
+    #[serde(borrow, default)]
+    data: HashMap<String, &'a RawValue>,
+    /// Error output
+    #[serde(borrow, default)]
+    traceback: Option<&'a RawValue>,
+}
+
+/// The text of a cell's source or of one output, with the file offset of
+/// each string it was joined from
+struct Block {
+    text: String,
+    /// Start of each piece within `text`, and of its JSON literal in the file
+    pieces: Vec<(usize, usize)>,
+    output: Option<usize>,
+}
+
+impl Block {
+    fn new(output: Option<usize>) -> Self {
+        Block {
+            text: String::new(),
+            pieces: Vec::new(),
+            output,
+        }
+    }
+
+    /// Append a JSON string or array of strings; other values are ignored
+    fn push(&mut self, content: &str, raw: &RawValue) {
+        let pieces: Vec<&RawValue> = match raw.get().trim_start().as_bytes().first() {
+            Some(b'[') => serde_json::from_str(raw.get()).unwrap_or_default(),
+            Some(b'"') => vec![raw],
+            _ => return,
+        };
+        for piece in pieces {
+            let Ok(text) = serde_json::from_str::<String>(piece.get()) else {
+                continue;
+            };
+            // Borrowed raw values point into `content`
+            let offset = piece.get().as_ptr() as usize - content.as_ptr() as usize;
+            self.pieces.push((self.text.len(), offset));
+            self.text.push_str(&text);
+        }
+    }
+}
+
+/// Scan the cells of a notebook, replacing the findings the line scan made
+/// on the JSON of those cells (from `findings[file_start..]`)
+///
+/// Leaves the findings alone if `content` isn't a notebook.
+pub(crate) fn scan_notebook(
+    content: &str,
+    file_start: usize,
+    path_str: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    let Ok(notebook) = serde_json::from_str::<Notebook>(content) else {
+        return;
+    };
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let file_line = |offset: usize| line_starts.partition_point(|&s| s <= offset);
+
+    let mut cell_findings = Vec::new();
+    let mut cell_lines = HashSet::new();
+    for (index, cell) in notebook.cells.iter().enumerate() {
+        let mut blocks = Vec::new();
+        if let Some(source) = cell.source {
+            let mut block = Block::new(None);
+            block.push(content, source);
+            blocks.push(block);
+        }
+        for (i, output) in cell.outputs.iter().enumerate() {
+            let mut block = Block::new(Some(i + 1));
+            for raw in [output.text, output.traceback].into_iter().flatten() {
+                block.push(content, raw);
+            }
+            let mut mime_types: Vec<&String> = output
+                .data
+                .keys()
+                .filter(|m| m.starts_with("text/") || m.ends_with("json"))
+                .collect();
+            mime_types.sort();
+            for mime in mime_types {
+                block.push(content, output.data[mime]);
+            }
+            blocks.push(block);
+        }
+
+        for block in blocks {
+            cell_lines.extend(block.pieces.iter().map(|&(_, offset)| file_line(offset)));
+            let mut found = Vec::new();
+            if scan_lines(
+                block.text.as_bytes(),
+                path_str,
+                rule_set,
+                options,
+                &mut found,
+            )
+            .is_err()
+            {
+                continue;
+            }
+            for mut finding in found {
+                // The piece the finding's line starts in
+                let line_start = block
+                    .text
+                    .split_inclusive('\n')
+                    .take(finding.line - 1)
+                    .map(str::len)
+                    .sum::<usize>();
+                let piece = block
+                    .pieces
+                    .partition_point(|&(start, _)| start <= line_start);
+                let (_, offset) = block.pieces[piece.saturating_sub(1)];
+
+                finding.cell = Some(NotebookCell {
+                    index: index + 1,
+                    line: finding.line,
+                    output: block.output,
+                });
+                finding.line = file_line(offset);
+                // Columns point into the JSON string when the secret is
+                // written there without escapes
+                let file_text = &content[line_starts[finding.line - 1]..];
+                let file_text = file_text.split('\n').next().unwrap_or_default();
+                if let Some(pos) = file_text.find(&finding.secret) {
+                    finding.column = file_text[..pos].chars().count() + 1;
+                    finding.end_column = finding.column + finding.secret.chars().count();
+                }
+                cell_findings.push(finding);
+            }
+        }
+    }
+
+    // Findings on the raw JSON of a cell are replaced; anything elsewhere,
+    // such as in notebook metadata, stays
+    let mut file_findings = findings.split_off(file_start);
+    file_findings.retain(|f| !cell_lines.contains(&f.line));
+    findings.append(&mut file_findings);
+    findings.append(&mut cell_findings);
+}
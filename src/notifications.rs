@@ -0,0 +1,221 @@
+//! Webhook notifications
+//!
+//! Posts a summary of a scan's findings to a webhook, either as a generic
+//! JSON document or as a Slack message, so scheduled scans can alert a
+//! security channel. Payloads carry the repository, ref, and commit plus one
+//! entry per finding with the secret masked; snippets are never sent.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::process::Command;
+
+use crate::scanner::{redact_secret, Finding, FindingSeverity, SeverityCounts};
+
+/// Findings listed in a Slack message before the rest are summarized
+const SLACK_MAX_FINDINGS: usize = 20;
+
+/// Shape of the webhook payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    /// A JSON document with the scan context and findings
+    #[default]
+    Json,
+    /// A Slack incoming-webhook message
+    Slack,
+}
+
+impl std::str::FromStr for PayloadFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(PayloadFormat::Json),
+            "slack" => Ok(PayloadFormat::Slack),
+            _ => Err(format!("Unknown notification format: {}", s)),
+        }
+    }
+}
+
+impl PayloadFormat {
+    /// Slack for `hooks.slack.com` URLs, JSON otherwise
+    pub fn for_url(url: &str) -> Self {
+        if url.contains("://hooks.slack.com/") {
+            PayloadFormat::Slack
+        } else {
+            PayloadFormat::Json
+        }
+    }
+}
+
+/// Where the scanned code came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ScanContext {
+    /// Repository name, e.g. `owner/repo`, or the scanned directory's name
+    pub repository: String,
+    /// Branch or tag, when the scan root is in a git checkout
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl ScanContext {
+    /// Describe the code at `root` using git metadata when it is available
+    pub fn detect(root: &str) -> Self {
+        let git = |args: &[&str]| -> Option<String> {
+            let dir = if Path::new(root).is_dir() {
+                Path::new(root)
+            } else {
+                Path::new(root).parent()?
+            };
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(args)
+                .output()
+                .ok()?;
+            let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !text.is_empty()).then_some(text)
+        };
+
+        let repository = git(&["config", "--get", "remote.origin.url"])
+            .map(|url| repo_name(&url))
+            .or_else(|| {
+                let top = git(&["rev-parse", "--show-toplevel"])?;
+                Some(Path::new(&top).file_name()?.to_string_lossy().into_owned())
+            })
+            .or_else(|| {
+                let path = Path::new(root).canonicalize().ok()?;
+                Some(path.file_name()?.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| root.to_string());
+
+        ScanContext {
+            repository,
+            git_ref: git(&["rev-parse", "--abbrev-ref", "HEAD"]).filter(|r| r != "HEAD"),
+            commit: git(&["rev-parse", "HEAD"]),
+        }
+    }
+}
+
+/// `owner/repo` from a remote URL, without credentials or `.git`
+fn repo_name(url: &str) -> String {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    // https://host/owner/repo, ssh://git@host/owner/repo, git@host:owner/repo
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or(rest, |(_, path)| path),
+        None => url.split_once(':').map_or(url, |(_, path)| path),
+    };
+    path.to_string()
+}
+
+/// Build the payload announcing `findings`
+pub fn build_payload(format: PayloadFormat, context: &ScanContext, findings: &[Finding]) -> Value {
+    match format {
+        PayloadFormat::Json => json_payload(context, findings),
+        PayloadFormat::Slack => slack_payload(context, findings),
+    }
+}
+
+fn json_payload(context: &ScanContext, findings: &[Finding]) -> Value {
+    let counts = SeverityCounts::from_findings(findings);
+    let items: Vec<Value> = findings
+        .iter()
+        .map(|f| {
+            json!({
+                "rule_id": f.rule_id,
+                "rule": f.rule_name,
+                "severity": f.severity,
+                "file": f.relative_file.as_deref().unwrap_or(&f.file),
+                "line": f.line,
+                "secret": redact_secret(&f.secret),
+                "fingerprint": f.fingerprint(),
+                "commit": f.commit.as_ref().map(|c| &c.sha),
+            })
+        })
+        .collect();
+    json!({
+        "tool": "fastsecret",
+        "version": env!("CARGO_PKG_VERSION"),
+        "context": context,
+        "total": counts.total(),
+        "counts": counts,
+        "findings": items,
+    })
+}
+
+fn slack_payload(context: &ScanContext, findings: &[Finding]) -> Value {
+    let counts = SeverityCounts::from_findings(findings);
+    let mut location = format!("*{}*", context.repository);
+    if let Some(git_ref) = &context.git_ref {
+        location.push_str(&format!(" on `{}`", git_ref));
+    }
+    if let Some(commit) = &context.commit {
+        location.push_str(&format!(" at `{}`", &commit[..commit.len().min(12)]));
+    }
+    let summary = format!(
+        ":rotating_light: fastsecret found {} potential secret(s) in {} ({} high, {} medium, {} low)",
+        counts.total(),
+        location,
+        counts.high,
+        counts.medium,
+        counts.low
+    );
+
+    let mut lines: Vec<String> = findings
+        .iter()
+        .take(SLACK_MAX_FINDINGS)
+        .map(|f| {
+            format!(
+                "• *{}* `{}` in `{}:{}` ({})",
+                severity_label(f.severity),
+                f.rule_id,
+                f.relative_file.as_deref().unwrap_or(&f.file),
+                f.line,
+                redact_secret(&f.secret)
+            )
+        })
+        .collect();
+    if findings.len() > SLACK_MAX_FINDINGS {
+        lines.push(format!("…and {} more", findings.len() - SLACK_MAX_FINDINGS));
+    }
+
+    json!({
+        "text": summary,
+        "blocks": [
+            { "type": "section", "text": { "type": "mrkdwn", "text": summary } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": lines.join("\n") } },
+        ],
+    })
+}
+
+fn severity_label(severity: FindingSeverity) -> &'static str {
+    match severity {
+        FindingSeverity::High => "HIGH",
+        FindingSeverity::Medium => "MEDIUM",
+        FindingSeverity::Low => "LOW",
+    }
+}
+
+/// POST `payload` as JSON to `url`
+#[cfg(feature = "webhook")]
+pub fn send_webhook(url: &str, payload: &Value, timeout: std::time::Duration) -> Result<()> {
+    use anyhow::Context;
+
+    ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .user_agent(concat!("fastsecret/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .post(url)
+        .send_json(payload)
+        // The URL often embeds a token, so keep it out of the error
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => anyhow::anyhow!("webhook returned HTTP {}", code),
+            ureq::Error::Transport(t) => anyhow::anyhow!("webhook request failed: {}", t.kind()),
+        })
+        .context("failed to send notification")?;
+    Ok(())
+}
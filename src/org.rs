@@ -0,0 +1,240 @@
+//! Organization-wide scanning
+//!
+//! Scans many remote repositories with one rule set, a few at a time. Each
+//! repository is cloned and scanned on its own, so one that fails to clone
+//! is reported as an error without stopping the rest. The repositories can
+//! be given as URLs or, with the `org` feature, listed from a GitHub
+//! organization or GitLab group.
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::remote::{redact_url, scan_remote, RemoteOptions};
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{Finding, ScanOptions, ScanSummary};
+
+/// Repositories cloned and scanned at once when no limit is given
+pub const DEFAULT_JOBS: usize = 4;
+
+/// The outcome of scanning one repository
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoScan {
+    /// Short name such as `org/repo`
+    pub name: String,
+    /// Clone URL, with any credentials masked
+    pub url: String,
+    /// Findings with paths relative to the repository root
+    pub findings: Vec<Finding>,
+    /// Why the repository couldn't be scanned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RepoScan {
+    pub fn summary(&self) -> ScanSummary {
+        ScanSummary::from_findings(&self.findings)
+    }
+}
+
+/// The outcome of scanning every repository, in the order they were given
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrgReport {
+    pub repos: Vec<RepoScan>,
+}
+
+impl OrgReport {
+    /// Counts over all repositories
+    pub fn summary(&self) -> ScanSummary {
+        self.repos
+            .iter()
+            .map(RepoScan::summary)
+            .fold(ScanSummary::default(), |total, s| ScanSummary {
+                findings: total.findings + s.findings,
+                high: total.high + s.high,
+                medium: total.medium + s.medium,
+                low: total.low + s.low,
+                files_scanned: None,
+            })
+    }
+
+    /// Repositories that couldn't be scanned
+    pub fn failed(&self) -> impl Iterator<Item = &RepoScan> {
+        self.repos.iter().filter(|r| r.error.is_some())
+    }
+
+    /// Every finding, with its path prefixed by the repository name as
+    /// `org/repo!path/in/repo`
+    pub fn into_findings(self) -> Vec<Finding> {
+        self.repos
+            .into_iter()
+            .flat_map(|repo| {
+                let name = repo.name;
+                repo.findings.into_iter().map(move |mut f| {
+                    f.file = format!("{}!{}", name, f.file);
+                    f.relative_file = Some(f.file.clone());
+                    f
+                })
+            })
+            .collect()
+    }
+}
+
+/// Short name for a repository URL: its last two path segments without a
+/// `.git` suffix
+pub fn repo_name(url: &str) -> String {
+    let url = redact_url(url);
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        // scp-like `git@host:org/repo.git`
+        None => url.rsplit_once(':').map_or(url.as_str(), |(_, path)| path),
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments.len() {
+        0 => url.clone(),
+        n => segments[n.saturating_sub(2)..].join("/"),
+    }
+}
+
+/// Clone and scan each repository in `urls`, at most `jobs` at a time
+///
+/// A repository that can't be cloned or scanned gets an `error` instead of
+/// failing the whole run.
+pub fn scan_repos(
+    urls: &[String],
+    remote: &RemoteOptions,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    jobs: usize,
+) -> Result<OrgReport> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.max(1))
+        .build()?;
+    let repos = pool.install(|| {
+        urls.par_iter()
+            .map(|url| {
+                let (findings, error) = match scan_remote(url, remote, rule_set, options) {
+                    Ok(findings) => (findings, None),
+                    Err(e) => (Vec::new(), Some(format!("{:#}", e))),
+                };
+                tracing::info!("Scanned {}", redact_url(url));
+                RepoScan {
+                    name: repo_name(url),
+                    url: redact_url(url),
+                    findings,
+                    error,
+                }
+            })
+            .collect()
+    });
+    Ok(OrgReport { repos })
+}
+
+/// Where an organization's repositories are hosted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    Github,
+    Gitlab,
+}
+
+impl Forge {
+    /// API root used when none is given
+    pub fn default_api_url(self) -> &'static str {
+        match self {
+            Forge::Github => "https://api.github.com",
+            Forge::Gitlab => "https://gitlab.com/api/v4",
+        }
+    }
+}
+
+/// Clone URLs of every repository in a GitHub organization or GitLab group
+///
+/// With a token, private repositories are listed too and the URLs carry the
+/// token so they can be cloned. Archived repositories are skipped.
+#[cfg(feature = "org")]
+pub fn list_repos(
+    forge: Forge,
+    org: &str,
+    token: Option<&str>,
+    api_url: Option<&str>,
+) -> Result<Vec<String>> {
+    use anyhow::{bail, Context};
+    use serde_json::Value;
+
+    const PER_PAGE: usize = 100;
+
+    let api = api_url
+        .unwrap_or(forge.default_api_url())
+        .trim_end_matches('/');
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(30))
+        .user_agent(concat!("fastsecret/", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    let mut urls = Vec::new();
+    for page in 1.. {
+        let mut request = match forge {
+            Forge::Github => agent.get(&format!(
+                "{}/orgs/{}/repos?per_page={}&page={}",
+                api, org, PER_PAGE, page
+            )),
+            Forge::Gitlab => agent.get(&format!(
+                "{}/groups/{}/projects?include_subgroups=true&per_page={}&page={}",
+                api,
+                org.replace('/', "%2F"),
+                PER_PAGE,
+                page
+            )),
+        };
+        if let Some(token) = token {
+            request = match forge {
+                Forge::Github => request.set("Authorization", &format!("Bearer {}", token)),
+                Forge::Gitlab => request.set("PRIVATE-TOKEN", token),
+            };
+        }
+        let repos: Vec<Value> = match request.call() {
+            Ok(response) => response.into_json()?,
+            Err(ureq::Error::Status(code, _)) => {
+                bail!("listing repositories of '{}' returned HTTP {}", org, code)
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to list repositories of '{}'", org))
+            }
+        };
+
+        let url_key = match forge {
+            Forge::Github => "clone_url",
+            Forge::Gitlab => "http_url_to_repo",
+        };
+        for repo in &repos {
+            if repo["archived"].as_bool() == Some(true) {
+                continue;
+            }
+            if let Some(url) = repo[url_key].as_str() {
+                urls.push(match token {
+                    Some(token) => with_token(forge, url, token),
+                    None => url.to_string(),
+                });
+            }
+        }
+        if repos.len() < PER_PAGE {
+            break;
+        }
+    }
+    Ok(urls)
+}
+
+/// `url` with `token` added as HTTPS credentials
+#[cfg(feature = "org")]
+fn with_token(forge: Forge, url: &str, token: &str) -> String {
+    let user = match forge {
+        Forge::Github => "x-access-token",
+        Forge::Gitlab => "oauth2",
+    };
+    match url.strip_prefix("https://") {
+        Some(rest) => format!("https://{}:{}@{}", user, token, rest),
+        None => url.to_string(),
+    }
+}
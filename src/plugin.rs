@@ -0,0 +1,298 @@
+//! Detector plugins compiled to WebAssembly
+//!
+//! Behind the `plugins` feature, `--plugin detect_custom.wasm` loads a
+//! [`Detector`] from a WebAssembly module, so a team can ship proprietary
+//! checks as a binary without forking the crate or rebuilding fastsecret.
+//! Plugins run in an interpreter sandbox: they see only the file they are
+//! handed, have no filesystem, network, clock or environment access, and
+//! are stopped when they exceed an instruction budget ([`FUEL_PER_FILE`]
+//! by default) or [`MAX_MEMORY`] bytes of memory.
+//!
+//! # Host interface, version 1
+//!
+//! A plugin exports:
+//!
+//! - `memory`, its linear memory
+//! - `fastsecret_abi_version() -> i32`, returning [`ABI_VERSION`]
+//! - `fastsecret_alloc(len: i32) -> i32`, returning a buffer of `len`
+//!   bytes the host writes the path and content into
+//! - `fastsecret_detect(path_ptr: i32, path_len: i32, content_ptr: i32,
+//!   content_len: i32) -> i64`, returning the location of its result as
+//!   `ptr << 32 | len`
+//!
+//! The result is a UTF-8 JSON array of matches, each with a 1-based
+//! `line`, the `start` and `end` byte offsets of the secret within that
+//! line, a `rule_id`, and optionally a `rule_name` and a `severity`
+//! (`high` by default):
+//!
+//! ```json
+//! [{"line": 3, "start": 8, "end": 40, "rule_id": "acme-token", "severity": "high"}]
+//! ```
+//!
+//! The host locates, redacts and scores each match like a rule match. A
+//! plugin may import one function, `fastsecret.log(ptr: i32, len: i32)`,
+//! whose UTF-8 messages are logged at info level; modules importing
+//! anything else are rejected when loaded. Each file gets a fresh instance,
+//! so no state carries over between files.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use wasmi::{
+    Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, TypedFunc,
+};
+
+use crate::detector::{Detector, FileContext};
+use crate::scanner::{Finding, FindingSeverity};
+
+/// Version of the host interface this build implements
+pub const ABI_VERSION: i32 = 1;
+
+/// Instructions, roughly, a plugin may execute per file by default
+pub const FUEL_PER_FILE: u64 = 1 << 32;
+
+/// Largest linear memory a plugin may grow to, in bytes
+pub const MAX_MEMORY: usize = 256 << 20;
+
+/// Module of the functions plugins may import
+const HOST_MODULE: &str = "fastsecret";
+
+/// A match reported by a plugin
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PluginMatch {
+    line: usize,
+    start: usize,
+    end: usize,
+    rule_id: String,
+    #[serde(default)]
+    rule_name: Option<String>,
+    #[serde(default = "default_severity")]
+    severity: FindingSeverity,
+}
+
+fn default_severity() -> FindingSeverity {
+    FindingSeverity::High
+}
+
+/// Per-instance state the host functions see
+struct HostState {
+    limits: StoreLimits,
+    log: Option<String>,
+}
+
+/// A detector running a WebAssembly plugin
+pub struct WasmDetector {
+    id: String,
+    /// SHA-256 of the module
+    hash: String,
+    fuel: u64,
+    engine: Engine,
+    module: Module,
+    linker: Linker<HostState>,
+}
+
+impl WasmDetector {
+    /// Load and check the plugin at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let wasm = std::fs::read(path)
+            .with_context(|| format!("failed to read plugin {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "plugin".to_string());
+        Self::from_bytes(&name, &wasm)
+            .with_context(|| format!("failed to load plugin {}", path.display()))
+    }
+
+    /// Load a plugin from the bytes of a module
+    ///
+    /// The detector's id is `name` followed by a short hash of the module,
+    /// and its cache key holds the whole hash, so a rebuilt plugin doesn't
+    /// reuse cached results of the old one.
+    pub fn from_bytes(name: &str, wasm: &[u8]) -> Result<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|e| anyhow!("invalid module: {}", e))?;
+        for import in module.imports() {
+            if import.module() != HOST_MODULE || import.name() != "log" {
+                bail!(
+                    "imports {}.{}, which isn't part of the host interface",
+                    import.module(),
+                    import.name()
+                );
+            }
+        }
+
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap(
+                HOST_MODULE,
+                "log",
+                |caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    if caller.data().log.is_none() {
+                        return;
+                    }
+                    let message = read_memory(&caller, ptr, len)
+                        .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+                    if let (Some(message), Some(id)) = (message, &caller.data().log) {
+                        tracing::info!(plugin = %id, "[{}] {}", id, message);
+                    }
+                },
+            )
+            .map_err(|e| anyhow!("{}", e))?;
+
+        let hash = format!("{:x}", Sha256::digest(wasm));
+        let detector = WasmDetector {
+            id: format!("{}@{}", name, &hash[..12]),
+            hash,
+            fuel: FUEL_PER_FILE,
+            engine,
+            module,
+            linker,
+        };
+        let (mut store, instance) = detector.instantiate(false)?;
+        let version = instance
+            .get_typed_func::<(), i32>(&store, "fastsecret_abi_version")
+            .map_err(|e| anyhow!("missing export fastsecret_abi_version: {}", e))?
+            .call(&mut store, ())
+            .map_err(|e| anyhow!("fastsecret_abi_version failed: {}", e))?;
+        if version != ABI_VERSION {
+            bail!(
+                "built for host interface version {}, but this fastsecret implements version {}",
+                version,
+                ABI_VERSION
+            );
+        }
+        Exports::get(&store, instance)?;
+        Ok(detector)
+    }
+
+    /// Instructions, roughly, the plugin may execute per file [default:
+    /// [`FUEL_PER_FILE`]]
+    pub fn fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// A fresh instance; `log` passes the plugin's log messages on
+    fn instantiate(&self, log: bool) -> Result<(Store<HostState>, Instance)> {
+        let state = HostState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY)
+                .instances(1)
+                .build(),
+            log: log.then(|| self.id.clone()),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.fuel).map_err(|e| anyhow!("{}", e))?;
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| anyhow!("failed to instantiate: {}", e))?;
+        Ok((store, instance))
+    }
+
+    /// Run the plugin over one file
+    fn run(&self, ctx: &FileContext) -> Result<Vec<PluginMatch>> {
+        let (mut store, instance) = self.instantiate(true)?;
+        let Exports {
+            memory,
+            alloc,
+            detect,
+        } = Exports::get(&store, instance)?;
+
+        let mut args = Vec::with_capacity(4);
+        for input in [ctx.path.as_bytes(), ctx.content.as_bytes()] {
+            let len = i32::try_from(input.len()).context("file is too large for a plugin")?;
+            let ptr = alloc
+                .call(&mut store, len)
+                .map_err(|e| anyhow!("fastsecret_alloc failed: {}", e))?;
+            memory
+                .write(&mut store, ptr as u32 as usize, input)
+                .map_err(|e| anyhow!("fastsecret_alloc returned an invalid buffer: {}", e))?;
+            args.extend([ptr, len]);
+        }
+        let result = detect
+            .call(&mut store, (args[0], args[1], args[2], args[3]))
+            .map_err(|e| anyhow!("fastsecret_detect failed: {}", e))?;
+
+        let (ptr, len) = ((result as u64 >> 32) as u32, result as u32);
+        let output = memory
+            .data(&store)
+            .get(ptr as usize..ptr as usize + len as usize)
+            .ok_or_else(|| anyhow!("fastsecret_detect returned an out-of-bounds result"))?;
+        serde_json::from_slice(output).context("fastsecret_detect returned invalid JSON")
+    }
+}
+
+/// The exports a plugin is driven through
+struct Exports {
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    detect: TypedFunc<(i32, i32, i32, i32), i64>,
+}
+
+impl Exports {
+    fn get(store: &Store<HostState>, instance: Instance) -> Result<Self> {
+        Ok(Exports {
+            memory: instance
+                .get_memory(store, "memory")
+                .ok_or_else(|| anyhow!("missing export memory"))?,
+            alloc: instance
+                .get_typed_func(store, "fastsecret_alloc")
+                .map_err(|e| anyhow!("missing export fastsecret_alloc: {}", e))?,
+            detect: instance
+                .get_typed_func(store, "fastsecret_detect")
+                .map_err(|e| anyhow!("missing export fastsecret_detect: {}", e))?,
+        })
+    }
+}
+
+impl Detector for WasmDetector {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The id, a hash of the whole module, and the fuel limit, which
+    /// decides where a long scan stops
+    fn cache_key(&self) -> String {
+        format!("{}:{}:{}", self.id, self.hash, self.fuel)
+    }
+
+    fn detect(&self, ctx: &FileContext) -> Result<Vec<Finding>> {
+        let matches = self.run(ctx)?;
+        Ok(matches
+            .into_iter()
+            .filter_map(|m| {
+                let rule_name = m.rule_name.as_deref().unwrap_or(&m.rule_id);
+                let finding =
+                    ctx.finding(m.line, m.start..m.end, &m.rule_id, rule_name, m.severity);
+                if finding.is_none() {
+                    tracing::info!(
+                        plugin = %self.id,
+                        "Plugin {} reported {}:{} at bytes {}..{}, which isn't in the file",
+                        self.id, ctx.path, m.line, m.start, m.end
+                    );
+                }
+                finding
+            })
+            .collect())
+    }
+}
+
+/// `len` bytes at `ptr` of the calling plugin's memory
+fn read_memory<'a>(caller: &'a Caller<'_, HostState>, ptr: i32, len: i32) -> Option<&'a [u8]> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let start = ptr as u32 as usize;
+    memory
+        .data(caller)
+        .get(start..start.checked_add(len as u32 as usize)?)
+}
@@ -0,0 +1,447 @@
+//! Scan policy
+//!
+//! The `[policy]` section of `fastsecret.toml` decides what a scan's
+//! findings mean for CI, beyond a single `fail_on` threshold:
+//!
+//! ```toml
+//! [policy]
+//! fail_if = "high > 0 || medium > 5"
+//! required_rules = ["aws-access-key-id", "github-pat"]
+//!
+//! [[policy.severity]]
+//! paths = ["tests/**", "**/fixtures/**"]
+//! severity = "low"
+//!
+//! [[policy.severity]]
+//! context = "test"
+//! severity = "low"
+//! ```
+//!
+//! Severity overrides are applied to the findings before they are reported;
+//! the condition and the required rules are then checked to compute the
+//! exit code.
+
+use anyhow::{bail, Context, Result};
+use globset::{Glob, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::context::CONTEXT_KEY;
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{Finding, FindingSeverity, ScanOptions, SeverityCounts};
+use crate::verify::VerificationStatus;
+
+/// `[policy]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Fail the scan when this condition holds, instead of per `fail_on`
+    pub fail_if: Option<Condition>,
+    /// Change the severity of findings in matching paths
+    pub severity: Vec<SeverityOverride>,
+    /// Rules, by id or name, that must be enabled for the scan to pass
+    pub required_rules: Vec<String>,
+}
+
+/// A `[[policy.severity]]` entry
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeverityOverride {
+    /// Globs tested against each finding's path, relative to the scan root;
+    /// all paths when empty
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Only change findings tagged with this context, such as `test`
+    #[serde(default)]
+    pub context: Option<String>,
+    /// Only change findings of these rules, by id or name; all when empty
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Severity the findings get
+    pub severity: FindingSeverity,
+}
+
+impl Policy {
+    /// Apply the severity overrides to `findings`
+    ///
+    /// When several entries match a finding, the last one wins.
+    pub fn apply_severity(&self, findings: &mut [Finding]) -> Result<()> {
+        for entry in &self.severity {
+            let mut globs = GlobSetBuilder::new();
+            for glob in &entry.paths {
+                globs.add(
+                    Glob::new(glob).with_context(|| format!("invalid policy path '{}'", glob))?,
+                );
+            }
+            let globs = globs.build()?;
+            for finding in findings.iter_mut() {
+                let path = finding.relative_file.as_deref().unwrap_or(&finding.file);
+                let path = path.replace('\\', "/");
+                let rule_matches = entry.rules.is_empty()
+                    || entry
+                        .rules
+                        .iter()
+                        .any(|r| *r == finding.rule_id || *r == finding.rule_name);
+                let path_matches =
+                    entry.paths.is_empty() || globs.is_match(path.trim_start_matches("./"));
+                let context_matches = entry
+                    .context
+                    .as_ref()
+                    .is_none_or(|context| finding.metadata.get(CONTEXT_KEY) == Some(context));
+                if rule_matches && path_matches && context_matches {
+                    finding.severity = entry.severity;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Required rules that the scan didn't run, because they weren't loaded
+    /// or were filtered out
+    pub fn missing_rules(&self, rule_set: &CompiledRuleSet, options: &ScanOptions) -> Vec<&str> {
+        self.required_rules
+            .iter()
+            .filter(|key| {
+                !rule_set
+                    .rules()
+                    .iter()
+                    .any(|c| c.rule.is_named(key) && options.rule_enabled(&c.rule))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Why the scan fails the policy; empty when it passes
+    ///
+    /// Without `fail_if` only required rules are checked, and the caller's
+    /// `fail_on` threshold decides about the findings.
+    pub fn violations(
+        &self,
+        findings: &[Finding],
+        rule_set: &CompiledRuleSet,
+        options: &ScanOptions,
+    ) -> Vec<String> {
+        let mut violations: Vec<String> = self
+            .missing_rules(rule_set, options)
+            .into_iter()
+            .map(|rule| format!("required rule '{}' did not run", rule))
+            .collect();
+        if let Some(condition) = &self.fail_if {
+            let counts = Counts::from_findings(findings);
+            if condition.evaluate(&counts) {
+                violations.push(format!("fail_if \"{}\" holds ({})", condition, counts));
+            }
+        }
+        violations
+    }
+}
+
+/// The numbers a condition can refer to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    /// Findings confirmed live by `--verify`
+    pub verified: usize,
+}
+
+impl Counts {
+    /// Tally a list of findings
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        let severity = SeverityCounts::from_findings(findings);
+        Counts {
+            high: severity.high,
+            medium: severity.medium,
+            low: severity.low,
+            verified: findings
+                .iter()
+                .filter(|f| f.verification == Some(VerificationStatus::Verified))
+                .count(),
+        }
+    }
+
+    fn get(&self, var: Var) -> u64 {
+        (match var {
+            Var::High => self.high,
+            Var::Medium => self.medium,
+            Var::Low => self.low,
+            Var::Total => self.high + self.medium + self.low,
+            Var::Verified => self.verified,
+        }) as u64
+    }
+}
+
+impl std::fmt::Display for Counts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "high={} medium={} low={} verified={}",
+            self.high, self.medium, self.low, self.verified
+        )
+    }
+}
+
+/// A condition over finding counts, like `high > 0 || (medium > 5)`
+///
+/// Variables are `high`, `medium`, `low`, `total`, and `verified`. They can
+/// be added with `+` and compared with `>`, `>=`, `<`, `<=`, `==`, and `!=`;
+/// comparisons combine with `&&`, `||`, `!`, and parentheses. A bare number
+/// or variable holds when it isn't zero.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    /// Whether the condition holds for these counts
+    pub fn evaluate(&self, counts: &Counts) -> bool {
+        self.expr.eval(counts) != 0
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl std::str::FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s).map_err(|e| format!("invalid condition '{}': {}", s, e))?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser
+            .parse()
+            .map_err(|e| format!("invalid condition '{}': {}", s, e))?;
+        Ok(Condition {
+            source: s.trim().to_string(),
+            expr,
+        })
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Var {
+    High,
+    Medium,
+    Low,
+    Total,
+    Verified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Num(u64),
+    Var(Var),
+    Add(Box<Expr>, Box<Expr>),
+    Cmp(CmpOp, Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Numbers for arithmetic, 0 or 1 for comparisons and logic
+    fn eval(&self, counts: &Counts) -> u64 {
+        match self {
+            Expr::Num(n) => *n,
+            Expr::Var(var) => counts.get(*var),
+            Expr::Add(a, b) => a.eval(counts).saturating_add(b.eval(counts)),
+            Expr::Cmp(op, a, b) => {
+                let (a, b) = (a.eval(counts), b.eval(counts));
+                let holds = match op {
+                    CmpOp::Gt => a > b,
+                    CmpOp::Ge => a >= b,
+                    CmpOp::Lt => a < b,
+                    CmpOp::Le => a <= b,
+                    CmpOp::Eq => a == b,
+                    CmpOp::Ne => a != b,
+                };
+                holds as u64
+            }
+            Expr::Not(a) => (a.eval(counts) == 0) as u64,
+            Expr::And(a, b) => (a.eval(counts) != 0 && b.eval(counts) != 0) as u64,
+            Expr::Or(a, b) => (a.eval(counts) != 0 || b.eval(counts) != 0) as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Num(u64),
+    Ident(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+const OPERATORS: &[&str] = &["&&", "||", ">=", "<=", "==", "!=", ">", "<", "!", "+"];
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Num(rest[..len].parse().context("number too large")?));
+            len
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            len
+        } else if c == '(' {
+            tokens.push(Token::Open);
+            1
+        } else if c == ')' {
+            tokens.push(Token::Close);
+            1
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            op.len()
+        } else {
+            bail!("unexpected '{}'", c);
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, loosest binding first: `||`, `&&`, `!`, comparison,
+/// `+`, then numbers, variables, and parentheses
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(&mut self) -> Result<Expr> {
+        let expr = self.or()?;
+        match self.tokens.get(self.pos) {
+            None => Ok(expr),
+            Some(token) => bail!("unexpected {}", describe(token)),
+        }
+    }
+
+    fn eat(&mut self, op: &'static str) -> bool {
+        if self.tokens.get(self.pos) == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.sum()?;
+        let op = match self.tokens.get(self.pos) {
+            Some(Token::Op(">")) => CmpOp::Gt,
+            Some(Token::Op(">=")) => CmpOp::Ge,
+            Some(Token::Op("<")) => CmpOp::Lt,
+            Some(Token::Op("<=")) => CmpOp::Le,
+            Some(Token::Op("==")) => CmpOp::Eq,
+            Some(Token::Op("!=")) => CmpOp::Ne,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Cmp(op, Box::new(left), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut expr = self.atom()?;
+        while self.eat("+") {
+            expr = Expr::Add(Box::new(expr), Box::new(self.atom()?));
+        }
+        Ok(expr)
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            bail!("unexpected end of condition");
+        };
+        self.pos += 1;
+        match token {
+            Token::Num(n) => Ok(Expr::Num(n)),
+            Token::Ident(name) => {
+                let var = match name.as_str() {
+                    "high" => Var::High,
+                    "medium" => Var::Medium,
+                    "low" => Var::Low,
+                    "total" => Var::Total,
+                    "verified" => Var::Verified,
+                    _ => bail!(
+                        "unknown variable '{}' (expected high, medium, low, total, or verified)",
+                        name
+                    ),
+                };
+                Ok(Expr::Var(var))
+            }
+            Token::Open => {
+                let expr = self.or()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    bail!("missing ')'");
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            token => bail!("unexpected {}", describe(&token)),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Num(n) => format!("'{}'", n),
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Op(op) => format!("'{}'", op),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
@@ -0,0 +1,127 @@
+//! Rule performance profiling
+//!
+//! A slow custom rule — a long `.{0,500}` span, a pattern with no literal
+//! to anchor on — can dominate a scan's run time. With profiling on, every
+//! single-line rule's regex is timed against every line on its own, rather
+//! than only the lines the shared prefilter passes it, and the time spent
+//! extracting matches is added on top, along with how many matches each
+//! rule made and in how many files. Profiling slows the scan down; the
+//! numbers are for comparing rules with each other.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::rules::CompiledRuleSet;
+
+/// Per-rule timings collected during a scan
+#[derive(Debug)]
+pub struct RuleProfile {
+    /// Id and name of each rule, in rule set order
+    rules: Vec<(String, String)>,
+    index: HashMap<String, usize>,
+    nanos: Vec<AtomicU64>,
+    matches: Vec<AtomicU64>,
+    /// Rule index and file of every file a rule matched in
+    files: Mutex<HashSet<(usize, String)>>,
+}
+
+/// How one rule performed during a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTiming {
+    pub rule_id: String,
+    pub rule_name: String,
+    /// Time spent running the rule's regex
+    pub time: Duration,
+    /// Regex matches, including ones dropped by allowlists and validators
+    pub matches: u64,
+    /// Files with at least one match
+    pub files: usize,
+}
+
+impl RuleProfile {
+    /// An empty profile for the rules of `rule_set`
+    pub fn new(rule_set: &CompiledRuleSet) -> Self {
+        let rules: Vec<(String, String)> = rule_set
+            .rules()
+            .iter()
+            .map(|c| (c.rule.id.clone(), c.rule.name.clone()))
+            .collect();
+        RuleProfile {
+            index: rules
+                .iter()
+                .enumerate()
+                .map(|(i, (id, _))| (id.clone(), i))
+                .collect(),
+            nanos: rules.iter().map(|_| AtomicU64::new(0)).collect(),
+            matches: rules.iter().map(|_| AtomicU64::new(0)).collect(),
+            files: Mutex::new(HashSet::new()),
+            rules,
+        }
+    }
+
+    /// Time each single-line rule that applies to `path` against `text`
+    ///
+    /// Rules of other rule sets, such as a [`RegexDetector`]'s, are timed
+    /// under their id if the profiled set has a rule with it.
+    ///
+    /// [`RegexDetector`]: crate::detector::RegexDetector
+    pub(crate) fn time_line(&self, rule_set: &CompiledRuleSet, text: &str, path: &str) {
+        for compiled in rule_set.rules() {
+            if compiled.rule.multiline || !compiled.applies_to(path) {
+                continue;
+            }
+            let Some(&idx) = self.index.get(&compiled.rule.id) else {
+                continue;
+            };
+            let started = Instant::now();
+            let _ = compiled.regex.is_match(text);
+            self.add_time(idx, started.elapsed());
+        }
+    }
+
+    /// Record time spent extracting `matches` matches of a rule in `path`
+    pub(crate) fn record(&self, rule_id: &str, elapsed: Duration, matches: u64, path: &str) {
+        let Some(&idx) = self.index.get(rule_id) else {
+            return;
+        };
+        self.add_time(idx, elapsed);
+        if matches > 0 {
+            self.matches[idx].fetch_add(matches, Ordering::Relaxed);
+            if let Ok(mut files) = self.files.lock() {
+                files.insert((idx, path.to_string()));
+            }
+        }
+    }
+
+    fn add_time(&self, idx: usize, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.nanos[idx].fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Timings of the rules that ran, slowest first
+    pub fn timings(&self) -> Vec<RuleTiming> {
+        let mut files = vec![0; self.rules.len()];
+        if let Ok(touched) = self.files.lock() {
+            for (idx, _) in touched.iter() {
+                files[*idx] += 1;
+            }
+        }
+        let mut timings: Vec<RuleTiming> = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(idx, (id, name))| RuleTiming {
+                rule_id: id.clone(),
+                rule_name: name.clone(),
+                time: Duration::from_nanos(self.nanos[idx].load(Ordering::Relaxed)),
+                matches: self.matches[idx].load(Ordering::Relaxed),
+                files: files[idx],
+            })
+            .filter(|t| !t.time.is_zero())
+            .collect();
+        timings.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.rule_id.cmp(&b.rule_id)));
+        timings
+    }
+}
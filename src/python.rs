@@ -0,0 +1,142 @@
+//! Python bindings
+//!
+//! A PyO3 extension module, published as `fastsecret-py` and imported as
+//! `fastsecret`, so Python tooling can embed the scanner instead of shelling
+//! out to the CLI. Findings come back as plain dicts shaped like the JSON
+//! output; rules as dicts shaped like the YAML rule format. Build it with
+//! `maturin build --features python` (see `pyproject.toml`).
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::report::json::JsonFinding;
+use crate::rules::{load_builtin_rules, load_rule_file, CustomRules, RulesFormat};
+use crate::scanner::{Finding, FindingSeverity, Scanner};
+
+/// Scanner for the given options; custom rules are added to the built-ins
+fn build_scanner(
+    rules: Option<&str>,
+    rules_format: &str,
+    ignore_rules: Vec<String>,
+    min_severity: &str,
+    redact: bool,
+    threads: usize,
+) -> PyResult<Scanner> {
+    let min_severity: FindingSeverity = min_severity.parse().map_err(PyValueError::new_err)?;
+    let mut builder = Scanner::builder()
+        .ignore(ignore_rules)
+        .min_severity(min_severity)
+        .redact(redact)
+        .threads(threads);
+    if let Some(path) = rules {
+        builder = builder.add_rule_file(read_rules(path, rules_format)?);
+    }
+    builder.build().map_err(runtime_error)
+}
+
+fn read_rules(path: &str, format: &str) -> PyResult<CustomRules> {
+    let format: RulesFormat = format.parse().map_err(PyValueError::new_err)?;
+    load_rule_file(path, format).map_err(runtime_error)
+}
+
+/// Convert serializable values to Python objects through `json.loads`
+fn to_python<T: serde::Serialize>(py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_string(value).map_err(runtime_error)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn findings_to_python(py: Python<'_>, findings: &[Finding]) -> PyResult<Py<PyAny>> {
+    let items: Vec<JsonFinding> = findings.iter().map(JsonFinding::from).collect();
+    to_python(py, &items)
+}
+
+fn runtime_error(e: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+#[pymodule(name = "fastsecret")]
+mod fastsecret_py {
+    use super::*;
+
+    /// Scan a file or directory and return a list of finding dicts
+    #[allow(clippy::too_many_arguments)] // Python keyword arguments
+    #[pyfunction]
+    #[pyo3(signature = (
+        path,
+        rules = None,
+        rules_format = "yaml",
+        ignore_rules = Vec::new(),
+        min_severity = "low",
+        redact = true,
+        threads = 0,
+    ))]
+    fn scan_path(
+        py: Python<'_>,
+        path: &str,
+        rules: Option<&str>,
+        rules_format: &str,
+        ignore_rules: Vec<String>,
+        min_severity: &str,
+        redact: bool,
+        threads: usize,
+    ) -> PyResult<Py<PyAny>> {
+        let scanner = build_scanner(
+            rules,
+            rules_format,
+            ignore_rules,
+            min_severity,
+            redact,
+            threads,
+        )?;
+        // Release the GIL so other Python threads run while we scan
+        let report = py
+            .detach(|| scanner.scan_path(path))
+            .map_err(runtime_error)?;
+        findings_to_python(py, &report.findings)
+    }
+
+    /// Scan a string and return a list of finding dicts
+    #[allow(clippy::too_many_arguments)] // Python keyword arguments
+    #[pyfunction]
+    #[pyo3(signature = (
+        content,
+        filename = "<string>",
+        rules = None,
+        rules_format = "yaml",
+        ignore_rules = Vec::new(),
+        min_severity = "low",
+        redact = true,
+    ))]
+    fn scan_string(
+        py: Python<'_>,
+        content: &str,
+        filename: &str,
+        rules: Option<&str>,
+        rules_format: &str,
+        ignore_rules: Vec<String>,
+        min_severity: &str,
+        redact: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let scanner = build_scanner(rules, rules_format, ignore_rules, min_severity, redact, 1)?;
+        let report = scanner
+            .scan_bytes(filename, content.as_bytes())
+            .map_err(runtime_error)?;
+        findings_to_python(py, &report.findings)
+    }
+
+    /// Load rules as a list of dicts: the built-in rules, or those in `path`
+    #[pyfunction]
+    #[pyo3(signature = (path = None, format = "yaml"))]
+    fn load_rules(py: Python<'_>, path: Option<&str>, format: &str) -> PyResult<Py<PyAny>> {
+        let rules = match path {
+            Some(path) => read_rules(path, format)?.rules,
+            None => load_builtin_rules(),
+        };
+        to_python(py, &rules)
+    }
+
+    #[pymodule_init]
+    fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        m.add("__version__", env!("CARGO_PKG_VERSION"))
+    }
+}
@@ -0,0 +1,147 @@
+//! Remote repository scanning
+//!
+//! Clones a repository by URL into a temporary directory with the `git`
+//! binary, scans it, and deletes the clone again. Tree scans use a shallow
+//! clone of a single branch, so auditing a large third-party repository only
+//! downloads its latest snapshot; history scans fetch as much history as
+//! asked for. Findings report paths relative to the repository root.
+
+use anyhow::{bail, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::git;
+use crate::rules::CompiledRuleSet;
+use crate::scanner::{scan_path_with_rule_set, Finding, ScanOptions};
+
+/// What to clone and scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteOptions {
+    /// Branch or tag to check out instead of the default branch
+    pub branch: Option<String>,
+    /// Commits of history to fetch; defaults to 1 for tree scans and the
+    /// full history for history scans
+    pub depth: Option<usize>,
+    /// Scan every fetched commit instead of the checked-out files
+    pub history: bool,
+}
+
+/// A repository cloned into a temporary directory, deleted on drop
+#[derive(Debug)]
+pub struct RemoteClone {
+    path: PathBuf,
+}
+
+impl RemoteClone {
+    /// Clone `url` into a new temporary directory
+    pub fn clone(url: &str, remote: &RemoteOptions) -> Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        // Keep the URL and branch from being read as options
+        if url.is_empty() || url.starts_with('-') {
+            bail!("invalid repository URL '{}'", redact_url(url));
+        }
+        if let Some(branch) = remote.branch.as_deref() {
+            if branch.is_empty() || branch.starts_with('-') {
+                bail!("invalid branch '{}'", branch);
+            }
+        }
+
+        let clone = RemoteClone {
+            path: std::env::temp_dir().join(format!(
+                "fastsecret-remote-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            )),
+        };
+
+        let depth = remote.depth.or((!remote.history).then_some(1));
+        let mut command = Command::new("git");
+        command
+            // Never run commands through the `ext::` transport
+            .args(["-c", "protocol.ext.allow=never", "clone", "--quiet"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        if let Some(depth) = depth {
+            command.arg(format!("--depth={}", depth.max(1)));
+        }
+        match &remote.branch {
+            Some(branch) => {
+                command.args(["--single-branch", "--branch", branch]);
+            }
+            // History scans cover every branch, which --depth would drop
+            None if remote.history => {
+                command.arg("--no-single-branch");
+            }
+            None => {}
+        }
+        let output = command.arg("--").arg(url).arg(&clone.path).output()?;
+        if !output.status.success() {
+            // git may echo the URL, which can embed a token
+            let stderr = String::from_utf8_lossy(&output.stderr).replace(url, &redact_url(url));
+            bail!(
+                "failed to clone '{}': {}",
+                redact_url(url),
+                stderr.trim().lines().last().unwrap_or("git clone failed")
+            );
+        }
+        Ok(clone)
+    }
+
+    /// Directory holding the working tree
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RemoteClone {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Clone the repository at `url` and scan it
+///
+/// Scans the checked-out files, or with `remote.history` every fetched
+/// commit. The clone is deleted before returning.
+pub fn scan_remote(
+    url: &str,
+    remote: &RemoteOptions,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let clone = RemoteClone::clone(url, remote)?;
+    let root = clone.path().to_string_lossy().into_owned();
+    if remote.history {
+        return git::scan_history(&root, rule_set, options);
+    }
+
+    // A throwaway clone would only fill the cache with dead entries
+    let options = ScanOptions {
+        cache_dir: None,
+        ..options.clone()
+    };
+    let mut findings = scan_path_with_rule_set(&root, rule_set, &options)?;
+    for f in &mut findings {
+        if let Some(relative) = f.relative_file.clone() {
+            f.file = relative;
+        }
+    }
+    Ok(findings)
+}
+
+/// `url` with any user name and password replaced by `***`
+pub fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let host_start = rest.find('/').unwrap_or(rest.len());
+    match rest[..host_start].rfind('@') {
+        Some(at) => format!("{}://***@{}", scheme, &rest[at + 1..]),
+        None => url.to_string(),
+    }
+}
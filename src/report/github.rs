@@ -0,0 +1,48 @@
+//! GitHub Actions workflow commands
+//!
+//! One `::error`, `::warning`, or `::notice` command per finding, which the
+//! Actions runner turns into inline annotations on the pull request diff.
+//! Messages name the rule, never the secret.
+
+use anyhow::Result;
+use std::io::Write;
+
+use crate::scanner::{Finding, FindingSeverity};
+
+/// Write findings as GitHub Actions annotation commands
+pub fn write_github<W: Write>(findings: &[Finding], mut writer: W) -> Result<()> {
+    for f in findings {
+        let level = match f.severity {
+            FindingSeverity::High => "error",
+            FindingSeverity::Medium => "warning",
+            FindingSeverity::Low => "notice",
+        };
+        // Annotations only attach to paths relative to the repository root
+        let file = f.relative_file.as_deref().unwrap_or(&f.file);
+        let file = file.replace('\\', "/");
+        writeln!(
+            writer,
+            "::{} file={},line={},col={},endColumn={},title={}::{}",
+            level,
+            escape_property(file.trim_start_matches("./")),
+            f.line,
+            f.column,
+            f.end_column,
+            escape_property(&format!("fastsecret: {}", f.rule_id)),
+            escape_data(&format!("{} detected ({})", f.rule_name, f.rule_id)),
+        )?;
+    }
+    Ok(())
+}
+
+/// Escape a command message
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a command property value, which also can't contain `:` or `,`
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
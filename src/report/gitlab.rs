@@ -0,0 +1,192 @@
+//! GitLab Secret Detection report
+//!
+//! The `gl-secret-detection-report.json` format (schema 15.x) that GitLab
+//! reads from a job's `artifacts:reports:secret_detection`, which feeds the
+//! Security Dashboard and merge request widgets.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+use crate::datetime::UtcDateTime;
+use crate::scanner::{Finding, FindingSeverity};
+
+/// Report schema version this output follows
+pub const GITLAB_SCHEMA_VERSION: &str = "15.2.1";
+
+/// Commit recorded for findings outside git history; GitLab requires one
+const NO_COMMIT: &str = "0000000";
+
+#[derive(Serialize)]
+struct Report<'a> {
+    version: &'static str,
+    vulnerabilities: Vec<Vulnerability<'a>>,
+    scan: Scan,
+}
+
+#[derive(Serialize)]
+struct Vulnerability<'a> {
+    id: String,
+    category: &'static str,
+    name: &'a str,
+    description: String,
+    severity: &'static str,
+    solution: &'a str,
+    scanner: Tool,
+    location: Location<'a>,
+    identifiers: Vec<Identifier<'a>>,
+    raw_source_code_extract: &'a str,
+}
+
+#[derive(Serialize)]
+struct Location<'a> {
+    file: String,
+    start_line: usize,
+    end_line: usize,
+    commit: Commit<'a>,
+}
+
+#[derive(Serialize)]
+struct Commit<'a> {
+    sha: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Identifier<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: String,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct Scan {
+    analyzer: Tool,
+    scanner: Tool,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    start_time: String,
+    end_time: String,
+    status: &'static str,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    id: &'static str,
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vendor: Option<Vendor>,
+}
+
+#[derive(Serialize)]
+struct Vendor {
+    name: &'static str,
+}
+
+impl Tool {
+    fn new(detailed: bool) -> Self {
+        Tool {
+            id: "fastsecret",
+            name: "fastsecret",
+            version: detailed.then_some(env!("CARGO_PKG_VERSION")),
+            vendor: detailed.then_some(Vendor { name: "fastsecret" }),
+        }
+    }
+}
+
+/// Write findings as a GitLab Secret Detection report
+pub fn write_gitlab<W: Write>(findings: &[Finding], mut writer: W) -> Result<()> {
+    let now = timestamp(UtcDateTime::now());
+    let report = Report {
+        version: GITLAB_SCHEMA_VERSION,
+        vulnerabilities: findings.iter().map(vulnerability).collect(),
+        scan: Scan {
+            analyzer: Tool::new(true),
+            scanner: Tool::new(true),
+            kind: "secret_detection",
+            start_time: now.clone(),
+            end_time: now,
+            status: "success",
+        },
+    };
+    serde_json::to_writer_pretty(&mut writer, &report)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn vulnerability(f: &Finding) -> Vulnerability<'_> {
+    let file = f
+        .relative_file
+        .as_deref()
+        .unwrap_or(&f.file)
+        .replace('\\', "/");
+    Vulnerability {
+        id: uuid_from_fingerprint(&f.fingerprint()),
+        category: "secret_detection",
+        name: &f.rule_name,
+        description: format!("{} detected by rule {}", f.rule_name, f.rule_id),
+        severity: match f.severity {
+            FindingSeverity::High => "High",
+            FindingSeverity::Medium => "Medium",
+            FindingSeverity::Low => "Low",
+        },
+        solution: f
+            .remediation
+            .as_deref()
+            .unwrap_or("Revoke and rotate this secret, then remove it from the repository."),
+        scanner: Tool::new(false),
+        location: Location {
+            file: file.trim_start_matches("./").to_string(),
+            start_line: f.line,
+            end_line: f.line,
+            commit: match &f.commit {
+                Some(commit) => Commit {
+                    sha: &commit.sha,
+                    author: Some(&commit.author),
+                    date: Some(&commit.date),
+                },
+                None => Commit {
+                    sha: NO_COMMIT,
+                    author: None,
+                    date: None,
+                },
+            },
+        },
+        identifiers: vec![Identifier {
+            kind: "fastsecret_rule_id",
+            name: format!("fastsecret rule {}", f.rule_id),
+            value: &f.rule_id,
+        }],
+        raw_source_code_extract: &f.snippet,
+    }
+}
+
+/// Stable name-based (version 5 style) UUID, so GitLab tracks a finding
+/// across pipelines
+fn uuid_from_fingerprint(fingerprint: &str) -> String {
+    let hex = &fingerprint[..32];
+    let variant = u8::from_str_radix(&hex[16..17], 16).unwrap_or(0) & 0x3 | 0x8;
+    format!(
+        "{}-{}-5{}-{:x}{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[13..16],
+        variant,
+        &hex[17..20],
+        &hex[20..32]
+    )
+}
+
+/// `YYYY-MM-DDTHH:MM:SS`, the timestamp format the schema requires
+fn timestamp(t: UtcDateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
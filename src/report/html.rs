@@ -0,0 +1,316 @@
+//! Standalone HTML report
+//!
+//! A single self-contained page (no scripts or external assets) with a
+//! severity summary, findings grouped by rule and by file, and collapsible
+//! snippets. Secrets are always masked, whatever the scan's redaction
+//! setting, so the page can be attached to CI runs or emailed safely.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::scanner::{redact_secret, Finding, FindingSeverity, SeverityCounts};
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem auto; max-width: 1100px; color: #1f2328; padding: 0 1rem; }
+h1 { font-size: 1.6rem; margin-bottom: 0.2rem; }
+h2 { font-size: 1.2rem; margin-top: 2rem; border-bottom: 1px solid #d0d7de; padding-bottom: 0.3rem; }
+.muted { color: #656d76; }
+.summary { display: flex; gap: 1rem; margin: 1.5rem 0; }
+.card { border: 1px solid #d0d7de; border-radius: 6px; padding: 0.8rem 1.2rem; min-width: 7rem; }
+.card .count { font-size: 1.8rem; font-weight: 600; }
+.chart { margin: 1rem 0; }
+.bar-row { display: flex; align-items: center; gap: 0.6rem; margin: 0.3rem 0; }
+.bar-label { width: 5rem; text-align: right; }
+.bar-track { flex: 1; background: #f6f8fa; border-radius: 4px; height: 1.1rem; }
+.bar { height: 100%; border-radius: 4px; }
+.high { background: #cf222e; color: #fff; }
+.medium { background: #bf8700; color: #fff; }
+.low { background: #0969da; color: #fff; }
+.badge { font-size: 0.75rem; font-weight: 600; padding: 0.1rem 0.4rem; border-radius: 4px; }
+details.group { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.5rem 0; padding: 0.4rem 0.8rem; }
+details.group > summary { cursor: pointer; font-weight: 600; }
+ul { list-style: none; padding-left: 0.5rem; }
+li { margin: 0.3rem 0; }
+li details summary { cursor: pointer; }
+.remediation { margin: 0.5rem 0; padding: 0.4rem 0.6rem; background: #fff8c5; border-radius: 6px; font-size: 0.9rem; }
+code, pre { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 0.85rem; }
+pre { background: #f6f8fa; padding: 0.6rem; border-radius: 6px; overflow-x: auto; white-space: pre-wrap; word-break: break-all; }
+";
+
+/// Write findings as a standalone HTML page
+pub fn write_html<W: Write>(findings: &[Finding], mut writer: W) -> Result<()> {
+    let counts = SeverityCounts::from_findings(findings);
+    let mut by_rule: BTreeMap<(&str, &str), Vec<&Finding>> = BTreeMap::new();
+    let mut by_file: BTreeMap<&str, Vec<&Finding>> = BTreeMap::new();
+    for f in findings {
+        by_rule
+            .entry((f.rule_id.as_str(), f.rule_name.as_str()))
+            .or_default()
+            .push(f);
+        by_file.entry(f.file.as_str()).or_default().push(f);
+    }
+
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(
+        writer,
+        "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">"
+    )?;
+    writeln!(writer, "<title>fastsecret report</title>")?;
+    writeln!(writer, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(writer, "<h1>fastsecret report</h1>")?;
+    writeln!(
+        writer,
+        "<p class=\"muted\">{} finding(s) in {} file(s) &middot; fastsecret {}</p>",
+        counts.total(),
+        by_file.len(),
+        env!("CARGO_PKG_VERSION")
+    )?;
+
+    // Summary cards and severity chart
+    writeln!(writer, "<div class=\"summary\">")?;
+    for (label, count) in [
+        ("Total", counts.total()),
+        ("High", counts.high),
+        ("Medium", counts.medium),
+        ("Low", counts.low),
+    ] {
+        writeln!(
+            writer,
+            "<div class=\"card\"><div class=\"count\">{}</div><div class=\"muted\">{}</div></div>",
+            count, label
+        )?;
+    }
+    writeln!(writer, "</div>")?;
+
+    writeln!(writer, "<div class=\"chart\">")?;
+    let max = counts.high.max(counts.medium).max(counts.low).max(1);
+    for severity in [
+        FindingSeverity::High,
+        FindingSeverity::Medium,
+        FindingSeverity::Low,
+    ] {
+        let count = counts.get(severity);
+        writeln!(
+            writer,
+            "<div class=\"bar-row\"><span class=\"bar-label\">{}</span>\
+             <div class=\"bar-track\"><div class=\"bar {}\" style=\"width: {:.1}%\"></div></div>\
+             <span>{}</span></div>",
+            severity_label(severity),
+            severity_class(severity),
+            count as f64 * 100.0 / max as f64,
+            count
+        )?;
+    }
+    writeln!(writer, "</div>")?;
+
+    if findings.is_empty() {
+        writeln!(writer, "<p>✅ No secrets detected.</p>")?;
+    } else {
+        let mut rules: Vec<_> = by_rule.into_iter().collect();
+        rules.sort_by_key(|(_, group)| std::cmp::Reverse(group.len()));
+        writeln!(writer, "<h2>By rule</h2>")?;
+        for ((id, name), group) in rules {
+            let source = match (&group[0].rule_source, &group[0].rule_version) {
+                (Some(source), Some(version)) => format!(
+                    " <span class=\"muted\">{} {}</span>",
+                    escape(source),
+                    escape(version)
+                ),
+                (Some(source), None) => {
+                    format!(" <span class=\"muted\">{}</span>", escape(source))
+                }
+                _ => String::new(),
+            };
+            let title = format!(
+                "{} <code class=\"muted\">{}</code>{}",
+                escape(name),
+                escape(id),
+                source
+            );
+            let remediation = group[0].remediation.as_deref();
+            write_group(&mut writer, &title, remediation, &group, |f| {
+                format!("{}:{}", f.file, f.line)
+            })?;
+        }
+
+        let mut files: Vec<_> = by_file.into_iter().collect();
+        files.sort_by_key(|(_, group)| std::cmp::Reverse(group.len()));
+        writeln!(writer, "<h2>By file</h2>")?;
+        for (file, group) in files {
+            let title = format!("<code>{}</code>", escape(file));
+            write_group(&mut writer, &title, None, &group, |f| {
+                format!("line {} — {}", f.line, f.rule_name)
+            })?;
+        }
+    }
+
+    writeln!(writer, "</body>\n</html>")?;
+    Ok(())
+}
+
+/// Write one collapsible group of findings, with rotation guidance if any
+fn write_group<W: Write>(
+    writer: &mut W,
+    title: &str,
+    remediation: Option<&str>,
+    group: &[&Finding],
+    label: impl Fn(&Finding) -> String,
+) -> Result<()> {
+    let worst = group
+        .iter()
+        .map(|f| f.severity)
+        .max()
+        .unwrap_or(FindingSeverity::Low);
+    writeln!(
+        writer,
+        "<details class=\"group\"><summary><span class=\"badge {}\">{}</span> {} \
+         <span class=\"muted\">({})</span></summary>\n<ul>",
+        severity_class(worst),
+        severity_label(worst),
+        title,
+        group.len()
+    )?;
+    if let Some(remediation) = remediation {
+        writeln!(
+            writer,
+            "<p class=\"remediation\"><strong>Remediation:</strong> {}</p>",
+            escape(remediation)
+        )?;
+    }
+    for f in group {
+        let variable = f
+            .variable
+            .as_deref()
+            .map(|v| {
+                format!(
+                    " <span class=\"muted\">in</span> <code>{}</code>",
+                    escape(v)
+                )
+            })
+            .unwrap_or_default();
+        let others = match f.other_locations.len() {
+            0 => String::new(),
+            n => format!(
+                " <span class=\"muted\" title=\"{}\">and {} other location(s)</span>",
+                escape(
+                    &f.other_locations
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                n
+            ),
+        };
+        writeln!(
+            writer,
+            "<li><details><summary><span class=\"badge {}\">{}</span> {}{}{}</summary>\
+             <pre>{}</pre></details></li>",
+            severity_class(f.severity),
+            severity_label(f.severity),
+            escape(&label(f)),
+            variable,
+            others,
+            escape(&excerpt(f))
+        )?;
+    }
+    writeln!(writer, "</ul></details>")?;
+    Ok(())
+}
+
+/// The finding's masked snippet, between its context lines when it has any
+fn excerpt(finding: &Finding) -> String {
+    let snippet = redacted_snippet(finding);
+    if finding.context.is_empty() {
+        return snippet;
+    }
+    let width = finding
+        .context
+        .iter()
+        .map(|c| c.line)
+        .chain([finding.line])
+        .max()
+        .unwrap_or(0)
+        .to_string()
+        .len();
+    let mut lines: Vec<(usize, &str)> = finding
+        .context
+        .iter()
+        .map(|c| (c.line, c.text.as_str()))
+        .collect();
+    lines.push((finding.line, &snippet));
+    lines.sort_by_key(|&(line, _)| line);
+    lines
+        .iter()
+        .map(|(line, text)| format!("{:>width$} │ {}", line, text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The finding's snippet with its secret masked, even if the scan didn't
+fn redacted_snippet(finding: &Finding) -> String {
+    let secret = finding.secret.as_str();
+    if secret.is_empty() {
+        return finding.snippet.clone();
+    }
+    if finding.snippet.contains(secret) {
+        return finding.snippet.replace(secret, &redact_secret(secret));
+    }
+
+    // Multi-line secrets only show the part on the finding's line
+    let mut snippet = finding.snippet.clone();
+    for part in secret.lines().map(str::trim).filter(|p| p.len() >= 8) {
+        snippet = snippet.replace(part, &redact_secret(part));
+    }
+    if snippet != finding.snippet {
+        return snippet;
+    }
+
+    // Long snippets are cut short, possibly in the middle of the secret
+    if let Some(cut) = finding.snippet.strip_suffix("...") {
+        for (end, _) in secret.char_indices().rev() {
+            let prefix = &secret[..end];
+            if end >= 4 && cut.ends_with(prefix) {
+                let masked: String = redact_secret(secret)
+                    .chars()
+                    .take(prefix.chars().count())
+                    .collect();
+                return format!("{}{}...", &cut[..cut.len() - prefix.len()], masked);
+            }
+        }
+    }
+    finding.snippet.clone()
+}
+
+fn severity_label(severity: FindingSeverity) -> &'static str {
+    match severity {
+        FindingSeverity::High => "HIGH",
+        FindingSeverity::Medium => "MEDIUM",
+        FindingSeverity::Low => "LOW",
+    }
+}
+
+fn severity_class(severity: FindingSeverity) -> &'static str {
+    match severity {
+        FindingSeverity::High => "high",
+        FindingSeverity::Medium => "medium",
+        FindingSeverity::Low => "low",
+    }
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
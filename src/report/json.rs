@@ -0,0 +1,41 @@
+//! JSON and NDJSON output
+
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+use crate::scanner::Finding;
+
+/// A finding as it appears in JSON output
+#[derive(Serialize)]
+pub(crate) struct JsonFinding<'a> {
+    #[serde(flatten)]
+    finding: &'a Finding,
+    fingerprint: String,
+}
+
+impl<'a> From<&'a Finding> for JsonFinding<'a> {
+    fn from(finding: &'a Finding) -> Self {
+        JsonFinding {
+            finding,
+            fingerprint: finding.fingerprint(),
+        }
+    }
+}
+
+/// Write findings as a pretty-printed JSON array
+pub fn write_json<W: Write>(findings: &[Finding], mut writer: W) -> Result<()> {
+    let items: Vec<JsonFinding> = findings.iter().map(JsonFinding::from).collect();
+    serde_json::to_writer_pretty(&mut writer, &items)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Write findings as newline-delimited JSON, one object per line
+pub fn write_ndjson<W: Write>(findings: &[Finding], mut writer: W) -> Result<()> {
+    for finding in findings {
+        serde_json::to_writer(&mut writer, &JsonFinding::from(finding))?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
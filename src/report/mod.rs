@@ -0,0 +1,59 @@
+//! Report formatting
+//!
+//! Renders scan findings in machine-readable formats so CI pipelines can
+//! consume results without scraping terminal output, and as an HTML page
+//! for people. GitHub Actions annotations put findings inline on pull
+//! requests, and the GitLab report feeds its Security Dashboard. Templates
+//! write one line per finding in a shape of the user's choosing.
+
+use serde::Deserialize;
+
+pub mod github;
+pub mod gitlab;
+pub mod html;
+pub mod json;
+pub mod template;
+
+pub use github::write_github;
+pub use gitlab::write_gitlab;
+pub use html::write_html;
+pub use json::{write_json, write_ndjson};
+pub use template::{write_template, Template};
+
+/// Output format for scan results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Colored, human-readable terminal output
+    #[default]
+    Text,
+    /// A single JSON array of findings
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// A standalone HTML page
+    Html,
+    /// GitHub Actions workflow commands (inline PR annotations)
+    Github,
+    /// GitLab Secret Detection report (Security Dashboard, MR widgets)
+    Gitlab,
+    /// One line per finding from a `--template`
+    Template,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "html" => Ok(OutputFormat::Html),
+            "github" => Ok(OutputFormat::Github),
+            "gitlab" => Ok(OutputFormat::Gitlab),
+            "template" => Ok(OutputFormat::Template),
+            _ => Err(format!("Unknown output format: {}", s)),
+        }
+    }
+}
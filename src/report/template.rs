@@ -0,0 +1,208 @@
+//! Custom line templates
+//!
+//! `--format template --template '{{file}}:{{line}} {{rule_id}} {{severity}}'`
+//! writes one line per finding in whatever shape the downstream tool
+//! expects. A placeholder names a finding field, as in JSON reports, and may
+//! pass it through filters: `{{severity | upper}}`, `{{file | json}}`.
+//! `\n` and `\t` in the template stand for a newline and a tab.
+
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::scanner::Finding;
+
+/// Fields a placeholder can name
+const FIELDS: &[&str] = &[
+    "file",
+    "line",
+    "column",
+    "end_column",
+    "rule_id",
+    "rule",
+    "severity",
+    "confidence",
+    "fingerprint",
+    "snippet",
+    "secret_sha256",
+    "variable",
+    "commit",
+    "author",
+    "remediation",
+    "rule_source",
+    "rule_version",
+];
+
+/// A parsed `--template`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field {
+        name: &'static str,
+        filters: Vec<Filter>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    Upper,
+    Lower,
+    /// A JSON string, or a bare number for numeric fields
+    Json,
+}
+
+/// A field's value before filters
+enum Value {
+    Text(String),
+    Number(usize),
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(unescape(&rest[..start])));
+            }
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                bail!(
+                    "unclosed '{{{{' at column {} of the template",
+                    source.len() - rest.len() + start + 1
+                );
+            };
+            parts.push(placeholder(&after[..end])?);
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(unescape(rest)));
+        }
+        Ok(Template { parts })
+    }
+}
+
+impl Template {
+    /// The template filled in with one finding
+    pub fn render(&self, finding: &Finding) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field { name, filters } => {
+                    let mut value = value(finding, name);
+                    for filter in filters {
+                        value = filter.apply(value);
+                    }
+                    match value {
+                        Value::Text(text) => out.push_str(&text),
+                        Value::Number(n) => out.push_str(&n.to_string()),
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Filter {
+    fn apply(self, value: Value) -> Value {
+        match (self, value) {
+            (Filter::Upper, Value::Text(s)) => Value::Text(s.to_uppercase()),
+            (Filter::Lower, Value::Text(s)) => Value::Text(s.to_lowercase()),
+            (Filter::Json, Value::Text(s)) => Value::Text(serde_json::Value::String(s).to_string()),
+            (_, value) => value,
+        }
+    }
+}
+
+/// Write one rendered line per finding
+pub fn write_template<W: Write>(
+    findings: &[Finding],
+    template: &Template,
+    mut writer: W,
+) -> Result<()> {
+    for finding in findings {
+        writeln!(writer, "{}", template.render(finding))?;
+    }
+    Ok(())
+}
+
+/// Parse the inside of `{{ ... }}`: a field name, then `|`-separated filters
+fn placeholder(inner: &str) -> Result<Part> {
+    let mut names = inner.split('|').map(str::trim);
+    let field = names.next().unwrap_or_default();
+    let Some(&name) = FIELDS.iter().find(|f| **f == field) else {
+        bail!(
+            "unknown template field '{}'; expected one of: {}",
+            field,
+            FIELDS.join(", ")
+        );
+    };
+    let filters = names
+        .map(|filter| match filter {
+            "upper" => Ok(Filter::Upper),
+            "lower" => Ok(Filter::Lower),
+            "json" => Ok(Filter::Json),
+            _ => bail!(
+                "unknown template filter '{}'; expected upper, lower, or json",
+                filter
+            ),
+        })
+        .collect::<Result<_>>()?;
+    Ok(Part::Field { name, filters })
+}
+
+fn value(f: &Finding, name: &str) -> Value {
+    let text = |s: Option<&String>| Value::Text(s.cloned().unwrap_or_default());
+    match name {
+        "file" => Value::Text(f.file.clone()),
+        "line" => Value::Number(f.line),
+        "column" => Value::Number(f.column),
+        "end_column" => Value::Number(f.end_column),
+        "rule_id" => Value::Text(f.rule_id.clone()),
+        "rule" => Value::Text(f.rule_name.clone()),
+        "severity" => Value::Text(f.severity.as_str().to_string()),
+        "confidence" => Value::Text(f.confidence.as_str().to_string()),
+        "fingerprint" => Value::Text(f.fingerprint()),
+        "snippet" => Value::Text(f.snippet.clone()),
+        "secret_sha256" => text(f.secret_sha256.as_ref()),
+        "variable" => text(f.variable.as_ref()),
+        "commit" => text(f.commit.as_ref().map(|c| &c.sha)),
+        "author" => text(f.commit.as_ref().map(|c| &c.author)),
+        "remediation" => text(f.remediation.as_ref()),
+        "rule_source" => text(f.rule_source.as_ref()),
+        "rule_version" => text(f.rule_version.as_ref()),
+        _ => unreachable!("placeholders only name known fields"),
+    }
+}
+
+/// Replace `\n`, `\t`, and `\\`, which are awkward to type in a shell
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
@@ -0,0 +1,120 @@
+//! Import of gitleaks TOML rule sets
+//!
+//! Translates the parts of a `gitleaks.toml` that fastsecret understands:
+//! rule regexes, secret groups, keywords, entropy, and allowlists (both the
+//! per-rule and the global ones). Path-only rules, which flag a file merely
+//! for existing, have no fastsecret equivalent and are skipped with a
+//! warning.
+
+use anyhow::Context;
+use serde::Deserialize;
+use std::fs;
+
+use super::{rule_id_from_name, Allowlist, Rule};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GitleaksConfig {
+    rules: Vec<GitleaksRule>,
+    allowlist: Option<GitleaksAllowlist>,
+    allowlists: Vec<GitleaksAllowlist>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GitleaksRule {
+    id: String,
+    description: String,
+    regex: Option<String>,
+    secret_group: Option<usize>,
+    entropy: Option<f64>,
+    keywords: Vec<String>,
+    tags: Vec<String>,
+    path: Option<String>,
+    allowlist: Option<GitleaksAllowlist>,
+    allowlists: Vec<GitleaksAllowlist>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GitleaksAllowlist {
+    regexes: Vec<String>,
+    paths: Vec<String>,
+    stopwords: Vec<String>,
+}
+
+impl GitleaksAllowlist {
+    fn to_allowlist(&self) -> Allowlist {
+        Allowlist {
+            regexes: self
+                .regexes
+                .iter()
+                .cloned()
+                .chain(self.stopwords.iter().map(|w| regex::escape(w)))
+                .collect(),
+            paths: Vec::new(),
+            path_regexes: self.paths.clone(),
+        }
+    }
+}
+
+/// Load rules from a gitleaks TOML config file
+pub fn load_gitleaks_config(path: &str) -> anyhow::Result<Vec<Rule>> {
+    let content = fs::read_to_string(path)?;
+    let config: GitleaksConfig =
+        toml::from_str(&content).with_context(|| format!("invalid gitleaks config '{}'", path))?;
+
+    let mut global = Allowlist::default();
+    for allowlist in config.allowlist.iter().chain(&config.allowlists) {
+        global.extend(&allowlist.to_allowlist());
+    }
+
+    let mut rules = Vec::with_capacity(config.rules.len());
+    for gl in config.rules {
+        let name = if gl.description.is_empty() {
+            gl.id.clone()
+        } else {
+            gl.description.clone()
+        };
+
+        let Some(pattern) = gl.regex else {
+            tracing::warn!(
+                rule = %name,
+                "Skipping gitleaks rule '{}': path-only rules are not supported",
+                name
+            );
+            continue;
+        };
+        if gl.path.is_some() {
+            tracing::warn!(
+                rule = %name,
+                "gitleaks rule '{}' is limited to certain paths; applying it to all files",
+                name
+            );
+        }
+
+        let mut allowlist = global.clone();
+        for a in gl.allowlist.iter().chain(&gl.allowlists) {
+            allowlist.extend(&a.to_allowlist());
+        }
+
+        rules.push(Rule {
+            id: if gl.id.is_empty() {
+                rule_id_from_name(&name)
+            } else {
+                gl.id
+            },
+            name,
+            pattern,
+            secret_group: gl.secret_group.filter(|&g| g > 0),
+            keywords: gl.keywords,
+            tags: gl.tags,
+            description: (!gl.description.is_empty()).then_some(gl.description),
+            entropy: gl.entropy,
+            allowlist,
+            ..Default::default()
+        });
+    }
+
+    Ok(rules)
+}
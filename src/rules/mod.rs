@@ -0,0 +1,715 @@
+//! Secret detection rules
+//!
+//! Built-in rules for common secrets (AWS, Stripe, OpenAI, etc.), grouped
+//! into packs
+//! Support for custom rules loaded from YAML files or gitleaks configs
+
+pub mod gitleaks;
+pub mod overrides;
+pub mod packs;
+pub mod validate;
+pub mod validators;
+
+pub use gitleaks::load_gitleaks_config;
+pub use overrides::{parse_rule_file, CustomRules, RuleOverride};
+pub use packs::{builtin_pack, builtin_packs, load_rule_packs, RulePack, BUILTIN_PACK_NAMES};
+pub use validate::{check_rules, validate_rules, RuleProblem};
+pub use validators::{CustomValidator, CustomValidators, Validator};
+
+use aho_corasick::AhoCorasick;
+use anyhow::Context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs;
+
+use crate::entropy::shannon_entropy;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    High,
+    #[default]
+    Medium,
+    Low,
+}
+
+impl std::str::FromStr for RuleSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "high" => Ok(RuleSeverity::High),
+            "medium" => Ok(RuleSeverity::Medium),
+            "low" => Ok(RuleSeverity::Low),
+            _ => Err(format!("Unknown severity: {}", s)),
+        }
+    }
+}
+
+impl From<RuleSeverity> for String {
+    fn from(sev: RuleSeverity) -> Self {
+        match sev {
+            RuleSeverity::High => "high".to_string(),
+            RuleSeverity::Medium => "medium".to_string(),
+            RuleSeverity::Low => "low".to_string(),
+        }
+    }
+}
+
+/// A secret detection rule with regex pattern and metadata
+///
+/// When the pattern has a capture group, the first group (or the one named
+/// by `secret_group`) is treated as the secret; otherwise the whole match is.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Rule {
+    /// Stable machine-readable identifier, e.g. `aws-access-key-id`
+    ///
+    /// Used for ignoring, suppressing, and baselining. Custom rules without
+    /// an id get one derived from their name.
+    #[serde(default)]
+    pub id: String,
+    /// Human-readable name for display
+    pub name: String,
+    pub pattern: String,
+    /// Capture group holding the secret, when it isn't the first one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_group: Option<usize>,
+    /// Literal strings, one of which must appear for the pattern to match
+    ///
+    /// Matched case-insensitively. Lines containing none of them skip regex
+    /// evaluation for this rule entirely.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default = "default_severity")]
+    pub severity: RuleSeverity,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How to rotate or revoke a leaked secret, e.g. where to revoke a token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+    /// Minimum Shannon entropy (bits per character) the secret must have
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<f64>,
+    /// Match the pattern against the whole file instead of line by line
+    ///
+    /// Use `\s` or `(?s)` in the pattern to span newlines. Findings are
+    /// reported at the line where the match starts.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiline: bool,
+    /// Match the pattern case-insensitively, like a leading `(?i)`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub case_insensitive: bool,
+    /// Let `.` match newlines too, like a leading `(?s)`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dot_all: bool,
+    /// Drop matches longer than this many bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_match_len: Option<usize>,
+    /// Known false positives to suppress
+    #[serde(default, skip_serializing_if = "Allowlist::is_empty")]
+    pub allowlist: Allowlist,
+    /// Structural check each secret must pass, e.g. `luhn`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator: Option<Validator>,
+    /// Name of the verifier that can check matches against the provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+    /// Categories for selecting rules, e.g. `cloud`, `payment`, `pki`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Globs naming the files the rule runs on, e.g. `*.tf` or `Dockerfile`
+    ///
+    /// Each glob is tested against the file name and the whole path. A rule
+    /// without any runs on every file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_patterns: Vec<String>,
+    /// Strings the rule must report, checked by `fastsecret rules validate`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    /// Strings the rule must not report
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negative_examples: Vec<String>,
+    /// Where the rule comes from: `builtin/<pack>` for built-in rules, and
+    /// for custom rules the file they were loaded from unless it names a
+    /// source itself, such as the owning team
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Version of the rule's definition; built-in rules carry the
+    /// fastsecret version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Rule {
+    /// The pattern with the rule's regex flags applied
+    pub fn effective_pattern(&self) -> Cow<'_, str> {
+        let flags = match (self.case_insensitive, self.dot_all) {
+            (false, false) => return Cow::Borrowed(&self.pattern),
+            (true, false) => "(?i)",
+            (false, true) => "(?s)",
+            (true, true) => "(?is)",
+        };
+        Cow::Owned(format!("{}{}", flags, self.pattern))
+    }
+}
+
+/// Largest compiled size, in bytes, of any one rule's regex
+///
+/// The regex engine matches in linear time, but a pattern like `\w{1000}`
+/// compiles to a huge program that slows every line down. Rules over the
+/// limit fail to compile instead.
+pub const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Deepest nesting of groups and repetitions a pattern may use
+pub const REGEX_NEST_LIMIT: u32 = 100;
+
+/// Compile a rule or allowlist regex within the global limits
+fn build_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .nest_limit(REGEX_NEST_LIMIT)
+        .build()
+}
+
+/// False-positive filters attached to a rule
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Allowlist {
+    /// Regexes tested against the secret; any hit suppresses the match
+    pub regexes: Vec<String>,
+    /// Globs tested against the file path; any hit suppresses the rule there
+    pub paths: Vec<String>,
+    /// Regexes tested against the file path, for rules imported from gitleaks
+    pub path_regexes: Vec<String>,
+}
+
+impl Allowlist {
+    /// Whether the allowlist has no entries
+    pub fn is_empty(&self) -> bool {
+        self.regexes.is_empty() && self.paths.is_empty() && self.path_regexes.is_empty()
+    }
+
+    /// Add every entry of `other` to this allowlist
+    pub fn extend(&mut self, other: &Allowlist) {
+        self.regexes.extend(other.regexes.iter().cloned());
+        self.paths.extend(other.paths.iter().cloned());
+        self.path_regexes.extend(other.path_regexes.iter().cloned());
+    }
+}
+
+fn default_severity() -> RuleSeverity {
+    RuleSeverity::Medium
+}
+
+impl Rule {
+    /// Whether `key` refers to this rule, by id or by display name
+    pub fn is_named(&self, key: &str) -> bool {
+        self.id == key || self.name == key
+    }
+
+    /// Whether the rule carries any of `tags`, compared case-insensitively
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        self.tags
+            .iter()
+            .any(|t| tags.iter().any(|wanted| t.eq_ignore_ascii_case(wanted)))
+    }
+
+    /// Whether the rule is one of fastsecret's own
+    pub fn is_builtin(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|s| s.starts_with(BUILTIN_SOURCE_PREFIX))
+    }
+}
+
+/// Derive a rule id from a display name
+///
+/// Lowercases the name and joins alphanumeric runs with dashes, so
+/// `"MyCompany API Token (v2)"` becomes `"mycompany-api-token-v2"`.
+pub fn rule_id_from_name(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Prefix of the `source` of built-in rules, followed by the pack name
+pub const BUILTIN_SOURCE_PREFIX: &str = "builtin/";
+
+/// Load built-in secret detection rules
+///
+/// This is every built-in pack merged together; see [`load_rule_packs`] to
+/// load only some of them.
+pub fn load_builtin_rules() -> Vec<Rule> {
+    let mut all = RulePack::default();
+    for pack in builtin_packs() {
+        all.merge(pack);
+    }
+    all.into_rules()
+}
+
+/// Rules whose findings come from parsing files rather than a pattern
+///
+/// They have no pattern and aren't compiled; scans check them against the
+/// ignore list and tag filters like any other rule, and `rules list` shows
+/// them.
+pub fn parser_rules() -> Vec<Rule> {
+    vec![
+        crate::terraform::rule(),
+        #[cfg(feature = "syntax")]
+        crate::syntax::rule(),
+    ]
+}
+
+/// Load custom rules from a YAML file
+pub fn load_custom_rules(path: &str) -> anyhow::Result<Vec<Rule>> {
+    parse_custom_rules(&fs::read_to_string(path)?)
+}
+
+/// Parse custom rules from YAML text
+///
+/// Only the new rules are returned; use [`parse_rule_file`] to also get the
+/// overrides of existing rules.
+pub fn parse_custom_rules(yaml: &str) -> anyhow::Result<Vec<Rule>> {
+    Ok(parse_rule_file(yaml)?.rules)
+}
+
+/// File format of a custom rules file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RulesFormat {
+    /// fastsecret's own YAML list of rules
+    #[default]
+    Yaml,
+    /// A gitleaks TOML config
+    Gitleaks,
+}
+
+impl std::str::FromStr for RulesFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yaml" => Ok(RulesFormat::Yaml),
+            "gitleaks" => Ok(RulesFormat::Gitleaks),
+            _ => Err(format!("Unknown rules format: {}", s)),
+        }
+    }
+}
+
+/// Load custom rules from a file in the given format
+///
+/// Only the new rules are returned; use [`load_rule_file`] to also get the
+/// overrides of existing rules.
+pub fn load_rules(path: &str, format: RulesFormat) -> anyhow::Result<Vec<Rule>> {
+    Ok(load_rule_file(path, format)?.rules)
+}
+
+/// Load a custom rules file in the given format, with its overrides
+///
+/// Only YAML files can override rules; gitleaks configs just add rules.
+/// Rules that don't name a `source` get `path`.
+pub fn load_rule_file(path: &str, format: RulesFormat) -> anyhow::Result<CustomRules> {
+    let mut custom = match format {
+        RulesFormat::Yaml => parse_rule_file(&fs::read_to_string(path)?)?,
+        RulesFormat::Gitleaks => CustomRules {
+            rules: load_gitleaks_config(path)?,
+            overrides: Vec::new(),
+        },
+    };
+    for rule in &mut custom.rules {
+        rule.source.get_or_insert_with(|| path.to_string());
+    }
+    Ok(custom)
+}
+
+/// A rule paired with its compiled regex
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub rule: Rule,
+    pub regex: Regex,
+    allow_regexes: Vec<Regex>,
+    allow_paths: GlobSet,
+    allow_path_regexes: Vec<Regex>,
+    /// Compiled `file_patterns`, `None` when the rule runs on every file
+    file_patterns: Option<GlobSet>,
+    custom_validator: Option<CustomValidator>,
+}
+
+impl CompiledRule {
+    /// Compile a rule's pattern and allowlist
+    pub fn new(rule: &Rule) -> anyhow::Result<Self> {
+        CompiledRule::with_validators(rule, &CustomValidators::new())
+    }
+
+    /// Compile a rule, resolving a custom validator from `validators`
+    pub fn with_validators(rule: &Rule, validators: &CustomValidators) -> anyhow::Result<Self> {
+        let custom_validator = match &rule.validator {
+            Some(Validator::Custom(name)) => Some(
+                validators
+                    .get(name)
+                    .cloned()
+                    .with_context(|| format!("unknown custom validator '{}'", name))?,
+            ),
+            _ => None,
+        };
+
+        let regex = build_regex(&rule.effective_pattern()).context("invalid pattern")?;
+
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|r| build_regex(r).with_context(|| format!("invalid allowlist regex '{}'", r)))
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+        let allow_regexes = compile(&rule.allowlist.regexes)?;
+        let allow_path_regexes = compile(&rule.allowlist.path_regexes)?;
+
+        let mut paths = GlobSetBuilder::new();
+        for glob in &rule.allowlist.paths {
+            paths.add(
+                Glob::new(glob).with_context(|| format!("invalid allowlist path '{}'", glob))?,
+            );
+        }
+
+        let file_patterns = if rule.file_patterns.is_empty() {
+            None
+        } else {
+            let mut globs = GlobSetBuilder::new();
+            for glob in &rule.file_patterns {
+                globs.add(
+                    Glob::new(glob).with_context(|| format!("invalid file pattern '{}'", glob))?,
+                );
+            }
+            Some(globs.build()?)
+        };
+
+        Ok(CompiledRule {
+            rule: rule.clone(),
+            regex,
+            allow_regexes,
+            allow_paths: paths.build()?,
+            allow_path_regexes,
+            file_patterns,
+            custom_validator,
+        })
+    }
+
+    /// Whether `secret` passes the rule's validator (if it has one)
+    pub fn is_valid(&self, secret: &str) -> bool {
+        match (&self.rule.validator, &self.custom_validator) {
+            (None, _) => true,
+            (Some(Validator::Custom(_)), Some(custom)) => custom.check(secret),
+            (Some(validator), _) => validator.check(secret),
+        }
+    }
+
+    /// Whether a match of `len` bytes is within the rule's `max_match_len`
+    pub fn within_max_len(&self, len: usize) -> bool {
+        self.rule.max_match_len.is_none_or(|max| len <= max)
+    }
+
+    /// Whether the rule runs on the file at `path`, relative to the scan
+    /// root, per its `file_patterns`
+    ///
+    /// Paths in angle brackets, like `<sample>`, stand for text that isn't
+    /// a file and match every rule. Inside an archive (`app.zip!conf/x.tf`)
+    /// the entry's name is tested.
+    pub fn applies_to(&self, path: &str) -> bool {
+        let Some(globs) = &self.file_patterns else {
+            return true;
+        };
+        if path.starts_with('<') && path.ends_with('>') {
+            return true;
+        }
+        let path = path.trim_start_matches("./");
+        let name = path.rsplit(['/', '\\', '!']).next().unwrap_or(path);
+        globs.is_match(name) || globs.is_match(path)
+    }
+
+    /// Whether a match of `secret` in `path`, relative to the scan root, is
+    /// allowlisted
+    pub fn is_allowed(&self, path: &str, secret: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        self.allow_regexes.iter().any(|r| r.is_match(secret))
+            || (!self.allow_paths.is_empty() && self.allow_paths.is_match(path))
+            || self.allow_path_regexes.iter().any(|r| r.is_match(path))
+    }
+
+    /// Every match of the pattern in `text`, with capture groups and the
+    /// reason a scan would drop it, if any
+    ///
+    /// Unlike a scan this matches the whole text at once and doesn't skip it
+    /// when keywords are missing; that is reported as a rejection instead.
+    pub fn explain(&self, text: &str, path: &str) -> Vec<RuleMatch> {
+        let lower = text.to_ascii_lowercase();
+        let has_keyword = self.rule.keywords.is_empty()
+            || self
+                .rule
+                .keywords
+                .iter()
+                .any(|k| lower.contains(&k.to_ascii_lowercase()));
+        let names: Vec<Option<&str>> = self.regex.capture_names().collect();
+
+        self.regex
+            .captures_iter(text)
+            .map(|caps| {
+                let whole = caps.get(0).expect("group 0 always matches");
+                // Same fallback as the scanner: the whole match if the
+                // secret group didn't take part
+                let wanted = self.rule.secret_group.unwrap_or(1);
+                let secret_group = if caps.get(wanted).is_some() {
+                    wanted
+                } else {
+                    0
+                };
+                let secret = caps.get(secret_group).map_or("", |m| m.as_str());
+
+                let rejected = if !self.applies_to(path) {
+                    Some(format!(
+                        "the rule only runs on files matching {}",
+                        self.rule.file_patterns.join(", ")
+                    ))
+                } else if !has_keyword {
+                    Some("none of the rule's keywords appear in the text".to_string())
+                } else if !self.within_max_len(whole.len()) {
+                    Some(format!(
+                        "match is {} bytes, longer than the rule's max_match_len of {}",
+                        whole.len(),
+                        self.rule.max_match_len.unwrap_or_default()
+                    ))
+                } else if let Some(min) = self
+                    .rule
+                    .entropy
+                    .filter(|&min| shannon_entropy(secret) < min)
+                {
+                    Some(format!(
+                        "entropy {:.2} is below the rule's minimum of {}",
+                        shannon_entropy(secret),
+                        min
+                    ))
+                } else if !self.is_valid(secret) {
+                    Some(format!(
+                        "fails the {} validator",
+                        self.rule
+                            .validator
+                            .as_ref()
+                            .expect("only rules with validators fail")
+                    ))
+                } else if self.is_allowed(path, secret) {
+                    Some("allowlisted".to_string())
+                } else {
+                    None
+                };
+
+                RuleMatch {
+                    start: whole.start(),
+                    end: whole.end(),
+                    text: whole.as_str().to_string(),
+                    groups: (1..caps.len())
+                        .map(|index| {
+                            caps.get(index).map(|m| GroupMatch {
+                                index,
+                                name: names[index].map(str::to_string),
+                                start: m.start(),
+                                end: m.end(),
+                                text: m.as_str().to_string(),
+                            })
+                        })
+                        .collect(),
+                    secret_group,
+                    rejected,
+                }
+            })
+            .collect()
+    }
+}
+
+/// One match of a rule's pattern, as explained by [`CompiledRule::explain`]
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleMatch {
+    /// Byte offset of the whole match
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Capture groups after group 0; `None` for groups that didn't take part
+    pub groups: Vec<Option<GroupMatch>>,
+    /// Group reported as the secret (0 is the whole match)
+    pub secret_group: usize,
+    /// Why a scan would drop the match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected: Option<String>,
+}
+
+/// A capture group within a [`RuleMatch`]
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupMatch {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// A set of rules compiled once and reused across every scanned file
+///
+/// Rules with `keywords` are gated by a single Aho-Corasick search over the
+/// line, so their regexes only run when one of their keywords is present.
+/// The remaining rules are combined into a `RegexSet` so a line can be
+/// tested against all of them in a single pass. Only rules that pass either
+/// check are then evaluated individually.
+#[derive(Debug, Clone)]
+pub struct CompiledRuleSet {
+    rules: Vec<CompiledRule>,
+    /// Combined patterns of the rules without keywords
+    set: RegexSet,
+    /// Rule index for each pattern in `set`
+    set_rules: Vec<usize>,
+    /// Keywords of every rule that has them
+    keywords: Option<AhoCorasick>,
+    /// Rule index for each pattern in `keywords`
+    keyword_rules: Vec<usize>,
+    /// Indices of rules that match across lines
+    multiline: Vec<usize>,
+}
+
+impl CompiledRuleSet {
+    /// Compile a list of rules
+    ///
+    /// Rules with an invalid pattern or allowlist are reported on stderr
+    /// and skipped.
+    pub fn new(rules: &[Rule]) -> anyhow::Result<Self> {
+        CompiledRuleSet::with_validators(rules, &CustomValidators::new())
+    }
+
+    /// Compile a list of rules, resolving custom validators from `validators`
+    pub fn with_validators(rules: &[Rule], validators: &CustomValidators) -> anyhow::Result<Self> {
+        let mut compiled = Vec::with_capacity(rules.len());
+        for rule in rules {
+            match CompiledRule::with_validators(rule, validators) {
+                Ok(c) => compiled.push(c),
+                Err(e) => tracing::warn!(rule = %rule.id, "Invalid rule '{}': {:#}", rule.name, e),
+            }
+        }
+
+        let mut set_rules = Vec::new();
+        let mut keyword_rules = Vec::new();
+        let mut words = Vec::new();
+        let mut multiline = Vec::new();
+        for (idx, c) in compiled.iter().enumerate() {
+            if c.rule.multiline {
+                multiline.push(idx);
+                continue;
+            }
+            if c.rule.keywords.is_empty() {
+                set_rules.push(idx);
+            }
+            for word in &c.rule.keywords {
+                words.push(word.as_str());
+                keyword_rules.push(idx);
+            }
+        }
+
+        let set = RegexSetBuilder::new(
+            set_rules
+                .iter()
+                .map(|&i| compiled[i].rule.effective_pattern()),
+        )
+        .size_limit(REGEX_SIZE_LIMIT.saturating_mul(set_rules.len().max(1)))
+        .nest_limit(REGEX_NEST_LIMIT)
+        .build()?;
+        let keywords = if words.is_empty() {
+            None
+        } else {
+            Some(
+                AhoCorasick::builder()
+                    .ascii_case_insensitive(true)
+                    .build(&words)?,
+            )
+        };
+
+        Ok(CompiledRuleSet {
+            rules: compiled,
+            set,
+            set_rules,
+            keywords,
+            keyword_rules,
+            multiline,
+        })
+    }
+
+    /// All successfully compiled rules
+    pub fn rules(&self) -> &[CompiledRule] {
+        &self.rules
+    }
+
+    /// Number of compiled rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether the set contains no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether any rule matches across lines
+    pub fn has_multiline(&self) -> bool {
+        !self.multiline.is_empty()
+    }
+
+    /// Rules that match across lines, in rule order
+    pub fn multiline_rules(&self) -> impl Iterator<Item = &CompiledRule> {
+        self.multiline.iter().map(move |&idx| &self.rules[idx])
+    }
+
+    /// Single-line rules whose pattern matches somewhere in `text`, in rule order
+    pub fn matching<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a CompiledRule> + 'a {
+        self.matching_in(text, None)
+    }
+
+    /// Single-line rules that run on the file at `path` and whose pattern
+    /// matches somewhere in `text`, in rule order
+    ///
+    /// Rules whose `file_patterns` exclude the file are dropped before their
+    /// regex runs.
+    pub fn matching_file<'a>(
+        &'a self,
+        text: &str,
+        path: &str,
+    ) -> impl Iterator<Item = &'a CompiledRule> + 'a {
+        self.matching_in(text, Some(path))
+    }
+
+    fn matching_in<'a>(
+        &'a self,
+        text: &str,
+        path: Option<&str>,
+    ) -> impl Iterator<Item = &'a CompiledRule> + 'a {
+        let applies = |idx: usize| path.is_none_or(|p| self.rules[idx].applies_to(p));
+        let mut hit = vec![false; self.rules.len()];
+        for idx in self.set.matches(text).into_iter() {
+            let rule = self.set_rules[idx];
+            hit[rule] = applies(rule);
+        }
+        if let Some(keywords) = &self.keywords {
+            let mut checked = vec![false; self.rules.len()];
+            for m in keywords.find_overlapping_iter(text) {
+                let idx = self.keyword_rules[m.pattern().as_usize()];
+                if !checked[idx] {
+                    checked[idx] = true;
+                    hit[idx] = applies(idx) && self.rules[idx].regex.is_match(text);
+                }
+            }
+        }
+
+        self.rules
+            .iter()
+            .zip(hit)
+            .filter_map(|(rule, hit)| hit.then_some(rule))
+    }
+}
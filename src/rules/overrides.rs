@@ -0,0 +1,189 @@
+//! Overrides of built-in rules
+//!
+//! A custom rules file can tune a built-in rule instead of redefining it: an
+//! entry with an `override:` key names the rule to change and lists only the
+//! fields to replace, and `enabled: false` drops the rule entirely.
+//!
+//! ```yaml
+//! - override: generic-high-entropy-secret
+//!   severity: low
+//!   allowlist:
+//!     paths: ['**/fixtures/**']
+//! - override: slack-webhook-url
+//!   enabled: false
+//! ```
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::{rule_id_from_name, Allowlist, Rule, RuleSeverity};
+
+/// Changes to one existing rule, found by id or display name
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleOverride {
+    /// Id or display name of the rule to change
+    #[serde(rename = "override")]
+    pub target: String,
+    /// `false` removes the rule
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// New pattern; set `keywords` too if the old ones no longer apply
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub secret_group: Option<usize>,
+    #[serde(default)]
+    pub keywords: Option<Vec<String>>,
+    #[serde(default)]
+    pub severity: Option<RuleSeverity>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub remediation: Option<String>,
+    #[serde(default)]
+    pub entropy: Option<f64>,
+    #[serde(default)]
+    pub case_insensitive: Option<bool>,
+    #[serde(default)]
+    pub max_match_len: Option<usize>,
+    /// Entries added to the rule's allowlist
+    #[serde(default)]
+    pub allowlist: Option<Allowlist>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Replaces the rule's `file_patterns`; an empty list runs it everywhere
+    #[serde(default)]
+    pub file_patterns: Option<Vec<String>>,
+}
+
+impl RuleOverride {
+    /// Apply the override to `rules`, returning whether it matched a rule
+    pub fn apply(&self, rules: &mut Vec<Rule>) -> bool {
+        let Some(idx) = rules.iter().position(|r| r.is_named(&self.target)) else {
+            return false;
+        };
+        if self.enabled == Some(false) {
+            rules.remove(idx);
+            return true;
+        }
+
+        let rule = &mut rules[idx];
+        if let Some(name) = &self.name {
+            rule.name = name.clone();
+        }
+        if let Some(pattern) = &self.pattern {
+            rule.pattern = pattern.clone();
+        }
+        if self.secret_group.is_some() {
+            rule.secret_group = self.secret_group;
+        }
+        if let Some(keywords) = &self.keywords {
+            rule.keywords = keywords.clone();
+        }
+        if let Some(severity) = self.severity {
+            rule.severity = severity;
+        }
+        if self.description.is_some() {
+            rule.description = self.description.clone();
+        }
+        if self.remediation.is_some() {
+            rule.remediation = self.remediation.clone();
+        }
+        if self.entropy.is_some() {
+            rule.entropy = self.entropy;
+        }
+        if let Some(case_insensitive) = self.case_insensitive {
+            rule.case_insensitive = case_insensitive;
+        }
+        if self.max_match_len.is_some() {
+            rule.max_match_len = self.max_match_len;
+        }
+        if let Some(allowlist) = &self.allowlist {
+            rule.allowlist.extend(allowlist);
+        }
+        if let Some(tags) = &self.tags {
+            rule.tags = tags.clone();
+        }
+        if let Some(file_patterns) = &self.file_patterns {
+            rule.file_patterns = file_patterns.clone();
+        }
+        true
+    }
+}
+
+/// The contents of a custom rules file: new rules plus overrides of
+/// existing ones
+#[derive(Debug, Clone, Default)]
+pub struct CustomRules {
+    pub rules: Vec<Rule>,
+    pub overrides: Vec<RuleOverride>,
+}
+
+impl CustomRules {
+    /// Apply the overrides to `base`, then add the new rules, each replacing
+    /// any rule in `base` with the same id
+    ///
+    /// Returns the targets of overrides that matched no rule.
+    pub fn merge_into(self, base: &mut Vec<Rule>) -> Vec<String> {
+        let unmatched = self
+            .overrides
+            .into_iter()
+            .filter(|o| !o.apply(base))
+            .map(|o| o.target)
+            .collect();
+        for rule in self.rules {
+            match base.iter_mut().find(|r| r.id == rule.id) {
+                Some(existing) => *existing = rule,
+                None => base.push(rule),
+            }
+        }
+        unmatched
+    }
+
+    /// Built-in rules in `base` that new rules of the same id would replace
+    ///
+    /// Redefining a built-in rule silently changes what its id means across
+    /// teams; an `override:` entry states the intent.
+    pub fn shadowed_builtins<'a>(&self, base: &'a [Rule]) -> Vec<&'a Rule> {
+        base.iter()
+            .filter(|r| r.is_builtin() && self.rules.iter().any(|new| new.id == r.id))
+            .collect()
+    }
+
+    /// Ids of the overridden rules that are still enabled
+    pub fn overridden_ids(&self) -> impl Iterator<Item = &str> {
+        self.overrides
+            .iter()
+            .filter(|o| o.enabled != Some(false))
+            .map(|o| o.target.as_str())
+    }
+}
+
+/// Parse a custom rules file from YAML text
+///
+/// Entries with an `override:` key become [`RuleOverride`]s; all others
+/// are new rules.
+pub fn parse_rule_file(yaml: &str) -> anyhow::Result<CustomRules> {
+    let entries: Vec<serde_yaml::Mapping> = serde_yaml::from_str(yaml)?;
+    let mut custom = CustomRules::default();
+    for (i, entry) in entries.into_iter().enumerate() {
+        let is_override = entry.contains_key("override");
+        let value = serde_yaml::Value::Mapping(entry);
+        if is_override {
+            let entry: RuleOverride = serde_yaml::from_value(value)
+                .with_context(|| format!("invalid override in entry {}", i + 1))?;
+            custom.overrides.push(entry);
+        } else {
+            let mut rule: Rule = serde_yaml::from_value(value)
+                .with_context(|| format!("invalid rule in entry {}", i + 1))?;
+            if rule.id.is_empty() {
+                rule.id = rule_id_from_name(&rule.name);
+            }
+            custom.rules.push(rule);
+        }
+    }
+    Ok(custom)
+}
@@ -0,0 +1,133 @@
+//! Rule validation
+//!
+//! Checks rule definitions the way `fastsecret rules validate` reports them:
+//! every pattern and allowlist must compile, ids must be unique, the secret
+//! group must exist, and each rule's `examples` must be reported by a scan
+//! while its `negative_examples` must not.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{CompiledRule, Rule};
+
+/// Path passed to allowlists when matching examples
+const EXAMPLE_PATH: &str = "<example>";
+
+/// Something wrong with one rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleProblem {
+    pub rule_id: String,
+    pub message: String,
+}
+
+impl fmt::Display for RuleProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.rule_id, self.message)
+    }
+}
+
+/// Check that every rule compiles within the regex limits and is usable,
+/// without running its examples
+///
+/// This is the pass run before a scan starts, so a broken custom rule stops
+/// the scan instead of being skipped.
+pub fn check_rules(rules: &[Rule]) -> Vec<RuleProblem> {
+    let mut problems = Vec::new();
+    for rule in rules {
+        check_rule(rule, &mut |message| {
+            problems.push(problem_for(rule, message))
+        });
+    }
+    problems
+}
+
+/// Validate rules, returning every problem found (empty when all are valid)
+pub fn validate_rules(rules: &[Rule]) -> Vec<RuleProblem> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+
+    for rule in rules {
+        let mut problem = |message: String| problems.push(problem_for(rule, message));
+
+        if rule.id.is_empty() {
+            problem("rule has no id".to_string());
+        } else if !seen.insert(rule.id.as_str()) {
+            problem("duplicate rule id".to_string());
+        }
+
+        let Some(compiled) = check_rule(rule, &mut problem) else {
+            continue;
+        };
+
+        for (i, example) in rule.examples.iter().enumerate() {
+            let matches = compiled.explain(example, EXAMPLE_PATH);
+            if matches.iter().any(|m| m.rejected.is_none()) {
+                continue;
+            }
+            let message = match matches.iter().find_map(|m| m.rejected.as_deref()) {
+                Some(reason) => format!(
+                    "example {} {:?} matched but would be dropped: {}",
+                    i + 1,
+                    example,
+                    reason
+                ),
+                None => format!("example {} {:?} doesn't match", i + 1, example),
+            };
+            problem(message);
+        }
+
+        for (i, example) in rule.negative_examples.iter().enumerate() {
+            if let Some(m) = compiled
+                .explain(example, EXAMPLE_PATH)
+                .into_iter()
+                .find(|m| m.rejected.is_none())
+            {
+                problem(format!(
+                    "negative example {} {:?} matches {:?} at bytes {}..{}",
+                    i + 1,
+                    example,
+                    m.text,
+                    m.start,
+                    m.end
+                ));
+            }
+        }
+    }
+    problems
+}
+
+fn problem_for(rule: &Rule, message: String) -> RuleProblem {
+    RuleProblem {
+        rule_id: if rule.id.is_empty() {
+            rule.name.clone()
+        } else {
+            rule.id.clone()
+        },
+        message,
+    }
+}
+
+/// Compile a rule and check its settings, returning it if it compiled
+fn check_rule(rule: &Rule, problem: &mut impl FnMut(String)) -> Option<CompiledRule> {
+    let compiled = match CompiledRule::new(rule) {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            problem(format!("{:#}", e));
+            return None;
+        }
+    };
+
+    let groups = compiled.regex.captures_len() - 1;
+    if let Some(group) = rule.secret_group {
+        if group > groups {
+            problem(format!(
+                "secret_group {} doesn't exist; the pattern has {} capture group(s)",
+                group, groups
+            ));
+        }
+    }
+    if rule.max_match_len == Some(0) {
+        problem("max_match_len must be at least 1".to_string());
+    }
+    Some(compiled)
+}
@@ -0,0 +1,296 @@
+//! Structural validators for matched secrets
+//!
+//! Many credentials carry more structure than a regex can check: GitHub
+//! tokens end in a CRC32 checksum, AWS key ids are base32 with a known set
+//! of prefixes, card numbers satisfy the Luhn check, Azure SAS tokens sign
+//! with a 32-byte HMAC. A rule's `validator`
+//! runs on each secret after the regex matches, and matches that fail it
+//! are dropped as false positives.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Check applied to a rule's secret after its pattern matches
+///
+/// Written in rule files as `luhn`, `base62-crc32`, `aws-account-decode`,
+/// `azure-account-key`, `azure-sas`, `azure-devops-pat`, or `custom:NAME`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Validator {
+    /// Digits (spaces and dashes allowed) that pass the Luhn checksum
+    Luhn,
+    /// `prefix_` + body whose last 6 characters are the base62 CRC32 of the
+    /// rest, as in GitHub tokens
+    Base62Crc32,
+    /// An AWS access key id that decodes to an account id
+    AwsAccountDecode,
+    /// Base64 of a 64-byte key, as Azure Storage and Cosmos DB account keys
+    AzureAccountKey,
+    /// An Azure SAS query string with a service version, permissions or a
+    /// stored policy, and a signature that decodes to 32 bytes
+    AzureSas,
+    /// An Azure DevOps personal access token in either format
+    AzureDevopsPat,
+    /// A function registered under this name with
+    /// [`ScannerBuilder::validator`](crate::ScannerBuilder::validator)
+    Custom(String),
+}
+
+impl Validator {
+    /// Run a built-in validator; custom validators always fail here since
+    /// they need a registered function
+    pub fn check(&self, secret: &str) -> bool {
+        match self {
+            Validator::Luhn => luhn(secret),
+            Validator::Base62Crc32 => base62_crc32(secret),
+            Validator::AwsAccountDecode => aws_account_id(secret).is_some(),
+            Validator::AzureAccountKey => azure_account_key(secret),
+            Validator::AzureSas => azure_sas(secret),
+            Validator::AzureDevopsPat => azure_devops_pat(secret),
+            Validator::Custom(_) => false,
+        }
+    }
+}
+
+impl std::str::FromStr for Validator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(name) = s.strip_prefix("custom:") {
+            return Ok(Validator::Custom(name.trim().to_string()));
+        }
+        match s.to_lowercase().as_str() {
+            "luhn" => Ok(Validator::Luhn),
+            "base62-crc32" => Ok(Validator::Base62Crc32),
+            "aws-account-decode" => Ok(Validator::AwsAccountDecode),
+            "azure-account-key" => Ok(Validator::AzureAccountKey),
+            "azure-sas" => Ok(Validator::AzureSas),
+            "azure-devops-pat" => Ok(Validator::AzureDevopsPat),
+            _ => Err(format!("Unknown validator: {}", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Validator {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Validator> for String {
+    fn from(validator: Validator) -> Self {
+        validator.to_string()
+    }
+}
+
+impl fmt::Display for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Validator::Luhn => write!(f, "luhn"),
+            Validator::Base62Crc32 => write!(f, "base62-crc32"),
+            Validator::AwsAccountDecode => write!(f, "aws-account-decode"),
+            Validator::AzureAccountKey => write!(f, "azure-account-key"),
+            Validator::AzureSas => write!(f, "azure-sas"),
+            Validator::AzureDevopsPat => write!(f, "azure-devops-pat"),
+            Validator::Custom(name) => write!(f, "custom:{}", name),
+        }
+    }
+}
+
+/// A validator function for rules declaring `validator: custom:NAME`
+#[derive(Clone)]
+pub struct CustomValidator(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl CustomValidator {
+    pub fn new(check: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        CustomValidator(Arc::new(check))
+    }
+
+    pub fn check(&self, secret: &str) -> bool {
+        (self.0)(secret)
+    }
+}
+
+impl fmt::Debug for CustomValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomValidator(..)")
+    }
+}
+
+/// Custom validators by name
+pub type CustomValidators = HashMap<String, CustomValidator>;
+
+/// Whether `number` passes the Luhn checksum
+pub fn luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_digit(10))
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+    if !(12..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Whether a `prefix_body` token ends in the base62-encoded CRC32 of the
+/// rest of its body
+pub fn base62_crc32(token: &str) -> bool {
+    let body = token.rsplit_once('_').map_or(token, |(_, body)| body);
+    if body.len() <= 6 || !body.is_ascii() {
+        return false;
+    }
+    let (payload, checksum) = body.split_at(body.len() - 6);
+    encode_base62(crc32fast::hash(payload.as_bytes()), 6) == checksum
+}
+
+/// Encode `value` in base62, left-padded with zeros to `width`
+fn encode_base62(mut value: u32, width: usize) -> String {
+    let mut digits = Vec::new();
+    while value > 0 {
+        digits.push(BASE62[(value % 62) as usize]);
+        value /= 62;
+    }
+    while digits.len() < width {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("base62 digits are ASCII")
+}
+
+/// Four-letter prefixes of AWS access key ids
+const AWS_KEY_PREFIXES: &[&str] = &[
+    "AKIA", "ASIA", "ABIA", "ACCA", "AGPA", "AIDA", "AIPA", "ANPA", "ANVA", "APKA", "AROA",
+];
+
+/// The account id encoded in an AWS access key id, if `key` is one
+///
+/// The 16 characters after the prefix are base32; the account id sits in
+/// bits 1 through 40 of the first six decoded bytes.
+pub fn aws_account_id(key: &str) -> Option<u64> {
+    if key.len() != 20 || !AWS_KEY_PREFIXES.contains(&key.get(..4)?) {
+        return None;
+    }
+
+    let mut bits: u128 = 0;
+    for c in key[4..].bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        bits = (bits << 5) | u128::from(value);
+    }
+    // 16 characters are 80 bits; the first 48 hold the account id
+    let first_six = (bits >> 32) as u64;
+    Some((first_six & 0x7fff_ffff_ff80) >> 7)
+}
+
+/// Whether `key` is the base64 of a 64-byte Azure account key
+pub fn azure_account_key(key: &str) -> bool {
+    STANDARD.decode(key).is_ok_and(|bytes| bytes.len() == 64)
+}
+
+/// Whether `query` is an Azure shared access signature
+///
+/// Parameters may be separated by `&` or an HTML-escaped `&amp;`. The
+/// `sv` service version must be a date, the `sig` signature (usually
+/// percent-encoded) must decode to a 32-byte HMAC-SHA256, and there must be
+/// `sp` permissions or an `si` stored access policy to grant them.
+pub fn azure_sas(query: &str) -> bool {
+    let params: HashMap<&str, &str> = query
+        .trim_start_matches('?')
+        .split('&')
+        .map(|p| p.strip_prefix("amp;").unwrap_or(p))
+        .filter_map(|p| p.split_once('='))
+        .collect();
+    let version = params.get("sv").is_some_and(|v| {
+        v.len() == 10
+            && v.bytes().enumerate().all(|(i, b)| {
+                if i == 4 || i == 7 {
+                    b == b'-'
+                } else {
+                    b.is_ascii_digit()
+                }
+            })
+    });
+    let signature = params
+        .get("sig")
+        .and_then(|sig| STANDARD.decode(percent_decode(sig)).ok())
+        .is_some_and(|bytes| bytes.len() == 32);
+    version && signature && (params.contains_key("sp") || params.contains_key("si"))
+}
+
+/// Decode `%XX` escapes, leaving malformed ones as they are
+pub(crate) fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Whether `token` is an Azure DevOps personal access token
+///
+/// Legacy tokens are 32 random bytes in lowercase base32, so their 52nd
+/// character carries a single bit and is `a` or `q`. Current tokens are 84
+/// alphanumeric characters with the `JQQJ9` signature of Microsoft's
+/// identifiable keys at offset 52 and the `AZDO` provider tag at offset 76.
+pub fn azure_devops_pat(token: &str) -> bool {
+    match token.len() {
+        52 => {
+            token
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'z' | b'2'..=b'7'))
+                && token.ends_with(['a', 'q'])
+        }
+        84 => {
+            token.bytes().all(|b| b.is_ascii_alphanumeric())
+                && &token[52..57] == "JQQJ9"
+                && &token[76..80] == "AZDO"
+        }
+        _ => false,
+    }
+}
@@ -3,28 +3,1366 @@
 //!  Efficiently scans files and directories for secret patterns
 //! using regex matching with performance optimizations.
 
-use anyhow::Result;
-use regex::Regex;
+use anyhow::{bail, Context, Result};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use regex::Captures;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
-use walkdir::WalkDir;
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::rules::{Rule, RuleSeverity};
+use crate::archive::{scan_archive_file, ArchiveKind, ArchiveOptions};
+use crate::cache::ScanCache;
+use crate::confidence::{self, Confidence};
+use crate::decode::{self, DecodeOptions};
+use crate::detector::{self, Detector, DetectorRegistry};
+use crate::encoding;
+use crate::entropy::{
+    scan_line_entropy, shannon_entropy, EntropyOptions, ENTROPY_RULE_ID, ENTROPY_RULE_NAME,
+};
+use crate::envfile::VariableTracker;
+use crate::git::CommitInfo;
+use crate::image::LayerInfo;
+use crate::kubernetes;
+use crate::lockfile::{self, LockfileMode, LockfileOptions};
+use crate::metadata;
+use crate::normalize;
+use crate::notebook::{self, NotebookCell};
+use crate::profile::RuleProfile;
+use crate::rules::{
+    load_builtin_rules, CompiledRule, CompiledRuleSet, CustomRules, CustomValidator,
+    CustomValidators, Rule, RuleOverride, RuleSeverity,
+};
+use crate::scope::{self, RuleScope};
+use crate::sourcemap::{self, OriginalLocation};
+#[cfg(feature = "syntax")]
+use crate::syntax;
+use crate::terraform;
+use crate::verify::VerificationStatus;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Severity of a finding, ordered from `Low` to `High`
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum FindingSeverity {
-    High,
-    Medium,
+    #[default]
     Low,
+    Medium,
+    High,
+}
+
+impl FindingSeverity {
+    /// Lowercase name, as in reports and config files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FindingSeverity::Low => "low",
+            FindingSeverity::Medium => "medium",
+            FindingSeverity::High => "high",
+        }
+    }
+}
+
+impl std::str::FromStr for FindingSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "high" => Ok(FindingSeverity::High),
+            "medium" => Ok(FindingSeverity::Medium),
+            "low" => Ok(FindingSeverity::Low),
+            _ => Err(format!("Unknown severity: {}", s)),
+        }
+    }
+}
+
+/// Number of findings at each severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SeverityCounts {
+    pub low: usize,
+    pub medium: usize,
+    pub high: usize,
+}
+
+impl SeverityCounts {
+    /// Tally a list of findings
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        let mut counts = SeverityCounts::default();
+        for f in findings {
+            counts.add(f.severity);
+        }
+        counts
+    }
+
+    /// Count one more finding of the given severity
+    pub fn add(&mut self, severity: FindingSeverity) {
+        match severity {
+            FindingSeverity::Low => self.low += 1,
+            FindingSeverity::Medium => self.medium += 1,
+            FindingSeverity::High => self.high += 1,
+        }
+    }
+
+    /// Number of findings with exactly this severity
+    pub fn get(&self, severity: FindingSeverity) -> usize {
+        match severity {
+            FindingSeverity::Low => self.low,
+            FindingSeverity::Medium => self.medium,
+            FindingSeverity::High => self.high,
+        }
+    }
+
+    /// Number of findings at or above this severity
+    pub fn at_or_above(&self, severity: FindingSeverity) -> usize {
+        [
+            FindingSeverity::Low,
+            FindingSeverity::Medium,
+            FindingSeverity::High,
+        ]
+        .into_iter()
+        .filter(|&s| s >= severity)
+        .map(|s| self.get(s))
+        .sum()
+    }
+
+    /// Total number of findings
+    pub fn total(&self) -> usize {
+        self.low + self.medium + self.high
+    }
+}
+
+impl std::ops::AddAssign for SeverityCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.low += other.low;
+        self.medium += other.medium;
+        self.high += other.high;
+    }
+}
+
+/// Parse a human-readable size such as `512`, `64K`, `10MB`, or `1GiB`
+///
+/// Suffixes are binary multiples (`K` = 1024 bytes) and case-insensitive.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, unit) = s.split_at(split);
+    let number: u64 = digits.parse().map_err(|_| format!("Invalid size: {}", s))?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return Err(format!("Invalid size: {}", s)),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Size too large: {}", s))
+}
+
+/// Severity threshold at which a scan should fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum FailOn {
+    /// Fail when any finding is at or above this severity
+    Severity(FindingSeverity),
+    /// Never fail, whatever is found
+    Never,
+}
+
+impl FailOn {
+    /// Whether findings with these counts should fail the scan
+    pub fn should_fail(&self, counts: &SeverityCounts) -> bool {
+        match self {
+            FailOn::Severity(threshold) => counts.at_or_above(*threshold) > 0,
+            FailOn::Never => false,
+        }
+    }
+}
+
+impl std::str::FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("never") {
+            Ok(FailOn::Never)
+        } else {
+            s.parse().map(FailOn::Severity)
+        }
+    }
+}
+
+impl TryFrom<String> for FailOn {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Order to list findings in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// By path, then line and column
+    #[default]
+    File,
+    /// Highest severity first, then by path
+    Severity,
+    /// By rule id, then by path
+    Rule,
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(SortOrder::File),
+            "severity" => Ok(SortOrder::Severity),
+            "rule" => Ok(SortOrder::Rule),
+            _ => Err(format!("Unknown sort order: {}", s)),
+        }
+    }
+}
+
+/// What to group findings by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    File,
+    Rule,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(GroupBy::File),
+            "rule" => Ok(GroupBy::Rule),
+            _ => Err(format!("Unknown grouping: {}", s)),
+        }
+    }
+}
+
+/// How to collapse findings that repeat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Dedup {
+    /// One finding per rule and location
+    #[default]
+    ByLocation,
+    /// One finding per distinct secret, listing every place it appears
+    BySecret,
+}
+
+impl std::str::FromStr for Dedup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "by-location" => Ok(Dedup::ByLocation),
+            "by-secret" => Ok(Dedup::BySecret),
+            _ => Err(format!("Unknown dedup mode: {}", s)),
+        }
+    }
+}
+
+/// Another place a deduplicated secret appears
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecretLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// Commit of the location, when scanning git history
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-pub struct Finding {
-    pub file: String,
-    pub line: usize,
-    pub snippet: String,
-    pub rule_name: String,
-    pub severity: FindingSeverity,
+/// A line shown around a finding, with `--context`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContextLine {
+    pub line: usize,
+    /// The line's text, with every secret found in the file masked
+    pub text: String,
+}
+
+impl std::fmt::Display for SecretLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)?;
+        if let Some(sha) = &self.commit {
+            write!(f, " @ {}", &sha[..sha.len().min(8)])?;
+        }
+        Ok(())
+    }
+}
+
+/// Collapse repeated findings, keeping the first of each in place
+///
+/// `ByLocation` drops findings of the same rule at the same place (and
+/// commit). `BySecret` keeps one finding per distinct secret, whichever
+/// rule matched it: the most severe, with the places of the others in its
+/// `other_locations`.
+pub fn dedup_findings(findings: Vec<Finding>, mode: Dedup) -> Vec<Finding> {
+    let mut kept: Vec<Finding> = Vec::with_capacity(findings.len());
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for finding in findings {
+        let key = match mode {
+            Dedup::ByLocation => format!(
+                "{}\0{}\0{}\0{}\0{}",
+                finding.rule_id,
+                finding.file,
+                finding.line,
+                finding.column,
+                finding.commit.as_ref().map_or("", |c| c.sha.as_str())
+            ),
+            Dedup::BySecret => {
+                let secret = normalize_secret(&finding.secret);
+                if secret.is_empty() {
+                    kept.push(finding);
+                    continue;
+                }
+                format!("{:x}", Sha256::digest(secret.as_bytes()))
+            }
+        };
+        let Some(&i) = index.get(&key) else {
+            index.insert(key, kept.len());
+            kept.push(finding);
+            continue;
+        };
+        if mode == Dedup::ByLocation {
+            continue;
+        }
+
+        let canonical = &mut kept[i];
+        let mut duplicate = finding;
+        let promoted = duplicate.severity > canonical.severity;
+        if promoted {
+            std::mem::swap(canonical, &mut duplicate);
+            canonical.other_locations = std::mem::take(&mut duplicate.other_locations);
+        }
+        let location = SecretLocation {
+            file: duplicate.file,
+            line: duplicate.line,
+            column: duplicate.column,
+            commit: duplicate.commit.map(|c| c.sha),
+        };
+        let same_place = |l: &SecretLocation| {
+            l.file == location.file && l.line == location.line && l.commit == location.commit
+        };
+        let canonical_commit = canonical.commit.as_ref().map(|c| &c.sha);
+        let is_canonical = canonical.file == location.file
+            && canonical.line == location.line
+            && canonical_commit == location.commit.as_ref();
+        if !is_canonical && !canonical.other_locations.iter().any(same_place) {
+            // Locations stay in the order they were found
+            let at = if promoted {
+                0
+            } else {
+                canonical.other_locations.len()
+            };
+            canonical.other_locations.insert(at, location);
+        }
+    }
+    kept
+}
+
+/// Sort findings into a stable order
+///
+/// Every order breaks ties by path, line, column, rule id, and commit, so
+/// the same findings always come out the same way regardless of the order
+/// files were walked in.
+pub fn sort_findings(findings: &mut [Finding], order: SortOrder) {
+    let location = |f: &Finding| {
+        (
+            f.relative_file.clone().unwrap_or_else(|| f.file.clone()),
+            f.line,
+            f.column,
+            f.rule_id.clone(),
+            f.commit.as_ref().map(|c| c.sha.clone()),
+        )
+    };
+    match order {
+        SortOrder::File => findings.sort_by_cached_key(location),
+        SortOrder::Severity => {
+            findings.sort_by_cached_key(|f| (std::cmp::Reverse(f.severity), location(f)))
+        }
+        SortOrder::Rule => findings.sort_by_cached_key(|f| (f.rule_id.clone(), location(f))),
+    }
+}
+
+/// Split findings into groups, keeping their order within each group
+///
+/// Groups are listed in the order their first finding appears, so sorting
+/// first decides the group order too.
+pub fn group_findings(findings: &[Finding], by: GroupBy) -> Vec<(String, Vec<&Finding>)> {
+    let mut groups: Vec<(String, Vec<&Finding>)> = Vec::new();
+    let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+    for f in findings {
+        let key = match by {
+            GroupBy::File => f.relative_file.as_deref().unwrap_or(&f.file),
+            GroupBy::Rule => &f.rule_id,
+        };
+        let i = *index.entry(key).or_insert_with(|| {
+            groups.push((key.to_string(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[i].1.push(f);
+    }
+    groups
+}
+
+/// Limits on how many findings are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindingCaps {
+    /// Most findings shown per file
+    pub per_file: Option<usize>,
+    /// Most findings shown per rule
+    pub per_rule: Option<usize>,
+}
+
+/// Findings left out by [`cap_findings`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SuppressedFindings {
+    /// Findings over the per-file cap, by file
+    pub by_file: BTreeMap<String, usize>,
+    /// Findings over the per-rule cap (but not the per-file one), by rule id
+    pub by_rule: BTreeMap<String, usize>,
+    /// Findings left out at each severity
+    pub severity_counts: SeverityCounts,
+}
+
+impl SuppressedFindings {
+    /// Number of findings left out
+    pub fn total(&self) -> usize {
+        self.severity_counts.total()
+    }
+}
+
+/// Keep at most `caps.per_file` findings of each file and `caps.per_rule`
+/// of each rule, in their original order
+///
+/// The most severe findings of a file or rule are kept first, so a flood of
+/// low-severity matches never hides a high-severity one.
+pub fn cap_findings(
+    findings: Vec<Finding>,
+    caps: FindingCaps,
+) -> (Vec<Finding>, SuppressedFindings) {
+    let mut suppressed = SuppressedFindings::default();
+    if caps == FindingCaps::default() {
+        return (findings, suppressed);
+    }
+
+    let mut order: Vec<usize> = (0..findings.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(findings[i].severity));
+    let mut keep = vec![false; findings.len()];
+    let mut per_file: HashMap<&str, usize> = HashMap::new();
+    let mut per_rule: HashMap<&str, usize> = HashMap::new();
+    for i in order {
+        let f = &findings[i];
+        let file = f.relative_file.as_deref().unwrap_or(&f.file);
+        let in_file = per_file.entry(file).or_default();
+        if caps.per_file.is_some_and(|cap| *in_file >= cap) {
+            *suppressed.by_file.entry(file.to_string()).or_default() += 1;
+            suppressed.severity_counts.add(f.severity);
+            continue;
+        }
+        let in_rule = per_rule.entry(&f.rule_id).or_default();
+        if caps.per_rule.is_some_and(|cap| *in_rule >= cap) {
+            *suppressed.by_rule.entry(f.rule_id.clone()).or_default() += 1;
+            suppressed.severity_counts.add(f.severity);
+            continue;
+        }
+        *in_file += 1;
+        *in_rule += 1;
+        keep[i] = true;
+    }
+
+    let kept = findings
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(f, keep)| keep.then_some(f))
+        .collect();
+    (kept, suppressed)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub file: String,
+    pub line: usize,
+    /// 1-based column where the secret starts
+    pub column: usize,
+    /// 1-based column just past the end of the secret
+    pub end_column: usize,
+    pub snippet: String,
+    /// Lines before and after the finding's line, with `--context`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<ContextLine>,
+    /// Stable id of the rule that matched
+    pub rule_id: String,
+    /// Display name of the rule that matched
+    #[serde(rename = "rule")]
+    pub rule_name: String,
+    pub severity: FindingSeverity,
+    /// How likely the match is to be a real secret
+    pub confidence: Confidence,
+    /// How to rotate or revoke the secret, from the rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+    /// Where the rule comes from, e.g. `builtin/aws` or a custom rules file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_source: Option<String>,
+    /// Version of the rule's definition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_version: Option<String>,
+    /// Salted SHA-256 of the secret, for correlating the same credential
+    /// across scans without storing it; set by [`hash_secrets`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_sha256: Option<String>,
+    /// The matched text; never serialized so reports don't re-leak it
+    #[serde(skip)]
+    pub secret: String,
+    /// Commit that introduced the secret, when scanning git history
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<CommitInfo>,
+    /// `file` relative to the scan root, when it differs from `file`
+    #[serde(skip)]
+    pub relative_file: Option<String>,
+    /// Result of checking the secret with its provider, when `--verify` ran
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<VerificationStatus>,
+    /// Image layer the file came from, when scanning a container image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<LayerInfo>,
+    /// Notebook cell the secret is in, when scanning a Jupyter notebook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<NotebookCell>,
+    /// Where the secret was written, when a source map resolves the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<OriginalLocation>,
+    /// Other places the same secret appears, with `Dedup::BySecret`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub other_locations: Vec<SecretLocation>,
+    /// Environment variable or config key the secret is assigned to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    /// Provider-specific context, e.g. `aws_account_id` or `jwt_issuer`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Finding {
+    /// Stable identifier for this finding
+    ///
+    /// Hex-encoded SHA-256 over the rule id, the normalized secret, and the
+    /// file path relative to the scan root. It survives the secret moving to
+    /// a different line, re-wrapping, or scanning the repository from a
+    /// different directory.
+    pub fn fingerprint(&self) -> String {
+        let path = self.relative_file.as_deref().unwrap_or(&self.file);
+        let path = path.replace('\\', "/");
+        let path = path.trim_start_matches("./");
+
+        let mut hasher = Sha256::new();
+        hasher.update(self.rule_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(normalize_secret(&self.secret).as_bytes());
+        hasher.update([0]);
+        hasher.update(path.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Hex-encoded SHA-256 of `salt`, a NUL byte, and the normalized secret
+///
+/// The same secret hashes the same in every file and scan that uses the
+/// same salt, so reports can be correlated without holding the secret.
+pub fn secret_sha256(secret: &str, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update([0]);
+    hasher.update(normalize_secret(secret).as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Set `secret_sha256` on every finding, see [`secret_sha256`]
+pub fn hash_secrets(findings: &mut [Finding], salt: &str) {
+    for finding in findings {
+        finding.secret_sha256 = Some(secret_sha256(&finding.secret, salt));
+    }
+}
+
+/// Strip whitespace and surrounding quotes from a secret
+///
+/// Makes fingerprints insensitive to line wrapping (PEM bodies), CRLF line
+/// endings, and quoting style.
+fn normalize_secret(secret: &str) -> String {
+    let trimmed = secret.trim().trim_matches(|c| c == '"' || c == '\'');
+    trimmed.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Options controlling how a scan is performed
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Rule ids (or display names) to skip
+    pub ignore_rules: Vec<String>,
+    /// Only run rules carrying at least one of these tags
+    pub only_tags: Vec<String>,
+    /// Skip rules carrying any of these tags
+    pub skip_tags: Vec<String>,
+    /// Show more detail in reports, such as fingerprints in text output;
+    /// the scan logs each match as a debug-level [`tracing`] event either way
+    pub verbose: bool,
+    /// Number of worker threads (0 = one per CPU)
+    pub threads: usize,
+    /// Run the entropy engine alongside the regex rules
+    pub entropy: Option<EntropyOptions>,
+    /// Don't honor .gitignore, .ignore, or .fastsecretignore files
+    pub no_ignore: bool,
+    /// Mask secrets in finding snippets
+    pub redact: bool,
+    /// Drop findings below this severity
+    pub min_severity: FindingSeverity,
+    /// Drop findings below this confidence
+    pub min_confidence: Confidence,
+    /// Extra directory names to skip, on top of the built-in list
+    pub skip_dirs: Vec<String>,
+    /// Gitignore-style globs, relative to the scan root; when any are
+    /// given, only matching files are scanned
+    pub include: Vec<String>,
+    /// Gitignore-style globs, relative to the scan root, to skip
+    pub exclude: Vec<String>,
+    /// Follow symbolic links to files and directories instead of skipping
+    /// them; loops are detected and each file is scanned once
+    pub follow_symlinks: bool,
+    /// How many directories deep to descend below the scan root
+    pub max_depth: Option<usize>,
+    /// Skip files larger than this many bytes
+    ///
+    /// Inside archives, applies to each entry's uncompressed size.
+    pub max_file_size: Option<u64>,
+    /// Look inside zip and tar archives instead of skipping them
+    pub archives: Option<ArchiveOptions>,
+    /// Match rules against the printable strings in binary files instead
+    /// of skipping them
+    pub scan_binary: bool,
+    /// Decode base64 and hex blobs and scan the decoded text too
+    pub decode: Option<DecodeOptions>,
+    /// Match lines longer than this many bytes in overlapping windows of
+    /// this size, as in minified files
+    pub max_line_length: Option<usize>,
+    /// Resolve findings in minified files to their original source through
+    /// the file's source map
+    pub source_maps: bool,
+    /// Lines of context to keep before and after each finding
+    pub context: usize,
+    /// Parse supported source files and keep only matches inside string
+    /// literals; needs the `syntax` feature and is ignored without it
+    pub syntax: bool,
+    /// Which files are lockfiles or vendored code, and how to scan them
+    pub lockfiles: LockfileOptions,
+    /// Rules ignored or added for files under some paths
+    pub rule_scopes: Vec<RuleScope>,
+    /// Read files through memory maps instead of heap buffers, falling back
+    /// to buffered reads for files that can't be mapped; needs the `mmap`
+    /// feature and is ignored without it
+    pub mmap: bool,
+    /// Detectors run over each text file alongside the rules
+    pub detectors: DetectorRegistry,
+    /// Collect per-rule timings while scanning
+    pub profile: Option<Arc<RuleProfile>>,
+    /// Directory for the result cache; files unchanged since a clean scan
+    /// are skipped
+    pub cache_dir: Option<PathBuf>,
+    /// Leading part of scanned file names that stands for the scan root,
+    /// such as `./`; [`Scanner`] sets it for each path it scans
+    ///
+    /// Path hints such as test directories are judged on names with it
+    /// stripped, so the directories above the root don't count.
+    pub root_prefix: Option<String>,
+}
+
+/// Receives updates while a directory scan runs
+///
+/// Methods are called from the scanning threads, so implementations must be
+/// thread-safe. All methods do nothing by default.
+pub trait ScanProgress: Send + Sync {
+    /// The files to scan have been listed
+    fn start(&self, _total_files: usize) {}
+
+    /// A file has been scanned or skipped
+    fn file_done(&self, _path: &Path) {}
+
+    /// A scanned file had findings; called before [`file_done`](Self::file_done)
+    fn findings(&self, _path: &Path, _findings: &[Finding]) {}
+
+    /// Every file has been scanned
+    fn finish(&self) {}
+}
+
+impl ScanOptions {
+    /// The options for the file at `path`, relative to the scan root, with
+    /// the ignore lists of the rule scopes resolved for it
+    ///
+    /// The result has no scopes left, so resolving it again changes
+    /// nothing.
+    pub fn for_path(&self, path: &str) -> Cow<'_, ScanOptions> {
+        if self.rule_scopes.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut options = self.clone();
+        options
+            .ignore_rules
+            .extend(scope::ignored_rules(&self.rule_scopes, path));
+        options.rule_scopes.clear();
+        Cow::Owned(options)
+    }
+
+    /// These options for scanning the tree under `base`
+    pub(crate) fn rooted_at(&self, base: &Path) -> ScanOptions {
+        let mut prefix = base.display().to_string();
+        if !prefix.is_empty() && !prefix.ends_with(std::path::MAIN_SEPARATOR) {
+            prefix.push(std::path::MAIN_SEPARATOR);
+        }
+        ScanOptions {
+            root_prefix: Some(prefix),
+            ..self.clone()
+        }
+    }
+
+    /// `name` relative to the scan root, or unchanged if it lies outside
+    pub(crate) fn relative<'a>(&self, name: &'a str) -> &'a str {
+        self.root_prefix
+            .as_deref()
+            .and_then(|prefix| name.strip_prefix(prefix))
+            .unwrap_or(name)
+    }
+
+    /// Whether `rule` survives the ignore list and tag filters
+    pub fn rule_enabled(&self, rule: &Rule) -> bool {
+        !self.ignore_rules.iter().any(|key| rule.is_named(key))
+            && (self.only_tags.is_empty() || rule.has_any_tag(&self.only_tags))
+            && !rule.has_any_tag(&self.skip_tags)
+    }
+}
+
+/// Statistics collected during a scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanStats {
+    /// Files that were read and scanned
+    pub files_scanned: usize,
+    /// Files skipped as binary, oversized, unreadable, or not UTF-8
+    pub files_skipped: usize,
+    /// Scanned files whose clean result came from the cache
+    pub files_cached: usize,
+    /// Bytes read from scanned files
+    pub bytes_scanned: u64,
+    /// Lines read from scanned files
+    pub lines_scanned: usize,
+    /// Wall-clock time spent scanning
+    pub duration: Duration,
+    /// Findings at each severity
+    pub severity_counts: SeverityCounts,
+    /// Findings left out of the report by [`ScanReport::cap`], at each
+    /// severity; `severity_counts` still counts them
+    pub suppressed: SeverityCounts,
+}
+
+impl ScanStats {
+    fn record(&mut self, file: Option<FileStats>) {
+        match file {
+            Some(f) => {
+                self.files_scanned += 1;
+                self.bytes_scanned += f.bytes;
+                self.lines_scanned += f.lines;
+            }
+            None => self.files_skipped += 1,
+        }
+    }
+}
+
+/// Why a file or directory couldn't be scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScanErrorKind {
+    /// Reading it was not permitted
+    PermissionDenied,
+    /// It couldn't be opened or read
+    Unreadable,
+    /// Its content couldn't be decoded, as in a corrupt archive
+    Decode,
+    /// It is larger than the maximum file size
+    TooLarge,
+    /// A custom detector failed on it; the rest of the file was scanned
+    Detector,
+}
+
+impl ScanErrorKind {
+    /// Kebab-case name, as in JSON reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanErrorKind::PermissionDenied => "permission-denied",
+            ScanErrorKind::Unreadable => "unreadable",
+            ScanErrorKind::Decode => "decode",
+            ScanErrorKind::TooLarge => "too-large",
+            ScanErrorKind::Detector => "detector",
+        }
+    }
+}
+
+/// A file or directory a scan had to skip
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanError {
+    pub file: String,
+    pub kind: ScanErrorKind,
+    pub message: String,
+}
+
+impl ScanError {
+    fn from_io(path: &Path, e: &io::Error) -> Self {
+        let kind = match e.kind() {
+            io::ErrorKind::PermissionDenied => ScanErrorKind::PermissionDenied,
+            io::ErrorKind::InvalidData => ScanErrorKind::Decode,
+            _ => ScanErrorKind::Unreadable,
+        };
+        ScanError {
+            file: path.display().to_string(),
+            kind,
+            message: e.to_string(),
+        }
+    }
+
+    /// An error from walking a directory, or `None` for symlink loops,
+    /// which are skipped on purpose
+    fn from_walk(e: &ignore::Error) -> Option<Self> {
+        let mut err = e;
+        let mut path = None;
+        loop {
+            match err {
+                ignore::Error::WithPath {
+                    path: p,
+                    err: inner,
+                } => {
+                    path = path.or(Some(p));
+                    err = inner;
+                }
+                ignore::Error::WithDepth { err: inner, .. }
+                | ignore::Error::WithLineNumber { err: inner, .. } => err = inner,
+                ignore::Error::Loop { .. } => return None,
+                _ => break,
+            }
+        }
+        let kind = match err.io_error().map(io::Error::kind) {
+            Some(io::ErrorKind::PermissionDenied) => ScanErrorKind::PermissionDenied,
+            _ => ScanErrorKind::Unreadable,
+        };
+        Some(ScanError {
+            file: path.map(|p| p.display().to_string()).unwrap_or_default(),
+            kind,
+            message: err.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+/// Findings from a scan together with its statistics
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    pub findings: Vec<Finding>,
+    pub stats: ScanStats,
+    /// Files and directories that couldn't be scanned
+    pub errors: Vec<ScanError>,
+}
+
+impl ScanReport {
+    fn new(
+        findings: Vec<Finding>,
+        mut stats: ScanStats,
+        errors: Vec<ScanError>,
+        started: Instant,
+    ) -> Self {
+        stats.severity_counts = SeverityCounts::from_findings(&findings);
+        stats.duration = started.elapsed();
+        ScanReport {
+            findings,
+            stats,
+            errors,
+        }
+    }
+
+    /// Sort the findings into a stable order
+    pub fn sort(&mut self, order: SortOrder) {
+        sort_findings(&mut self.findings, order);
+    }
+
+    /// Collapse repeated findings, see [`dedup_findings`]
+    pub fn dedup(&mut self, mode: Dedup) {
+        self.findings = dedup_findings(std::mem::take(&mut self.findings), mode);
+    }
+
+    /// Keep at most `caps` findings per file and per rule, see
+    /// [`cap_findings`]
+    ///
+    /// The left-out findings are still counted in the stats and summary.
+    pub fn cap(&mut self, caps: FindingCaps) -> SuppressedFindings {
+        let (findings, suppressed) = cap_findings(std::mem::take(&mut self.findings), caps);
+        self.findings = findings;
+        self.stats.suppressed += suppressed.severity_counts;
+        suppressed
+    }
+
+    /// The findings split into groups by file or rule
+    pub fn groups(&self, by: GroupBy) -> Vec<(String, Vec<&Finding>)> {
+        group_findings(&self.findings, by)
+    }
+
+    /// Counts of the report's findings, including any left out by
+    /// [`cap`](Self::cap), and scanned files
+    pub fn summary(&self) -> ScanSummary {
+        let mut counts = SeverityCounts::from_findings(&self.findings);
+        counts += self.stats.suppressed;
+        ScanSummary {
+            files_scanned: Some(self.stats.files_scanned),
+            ..ScanSummary::from_counts(counts)
+        }
+    }
+}
+
+/// Finding counts of a scan, for scripts
+///
+/// Displays as one line of space-separated `key=value` pairs:
+/// `findings=3 high=1 medium=2 low=0 files=120`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ScanSummary {
+    pub findings: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    /// Files scanned, when the scan read files from disk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files_scanned: Option<usize>,
+}
+
+impl ScanSummary {
+    /// Summarize a list of findings
+    pub fn from_findings(findings: &[Finding]) -> Self {
+        Self::from_counts(SeverityCounts::from_findings(findings))
+    }
+
+    fn from_counts(counts: SeverityCounts) -> Self {
+        ScanSummary {
+            findings: counts.total(),
+            high: counts.high,
+            medium: counts.medium,
+            low: counts.low,
+            files_scanned: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ScanSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "findings={} high={} medium={} low={}",
+            self.findings, self.high, self.medium, self.low
+        )?;
+        if let Some(files) = self.files_scanned {
+            write!(f, " files={}", files)?;
+        }
+        Ok(())
+    }
+}
+
+/// Amount of input read from a single file or stream
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileStats {
+    pub(crate) bytes: u64,
+    pub(crate) lines: usize,
+    /// Problems that didn't stop it from being scanned, such as a failing
+    /// detector
+    pub(crate) errors: Vec<ScanError>,
+}
+
+/// A configured scanner that can be reused across many scans
+///
+/// ```no_run
+/// use fastsecret::Scanner;
+///
+/// let scanner = Scanner::builder()
+///     .ignore(["jwt"])
+///     .max_file_size(10 << 20)
+///     .threads(4)
+///     .build()?;
+/// let report = scanner.scan_path(".")?;
+/// println!(
+///     "{} finding(s) in {} file(s)",
+///     report.findings.len(),
+///     report.stats.files_scanned
+/// );
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    rule_set: CompiledRuleSet,
+    options: ScanOptions,
+}
+
+impl Scanner {
+    /// Start building a scanner with the built-in rules and default options
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder::default()
+    }
+
+    /// Create a scanner from an already compiled rule set
+    pub fn new(rule_set: CompiledRuleSet, options: ScanOptions) -> Self {
+        Scanner { rule_set, options }
+    }
+
+    /// The compiled rules this scanner uses
+    pub fn rule_set(&self) -> &CompiledRuleSet {
+        &self.rule_set
+    }
+
+    /// The options this scanner uses
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    /// Per-rule timings of the scans so far, when profiling is on
+    pub fn profile(&self) -> Option<&RuleProfile> {
+        self.options.profile.as_deref()
+    }
+
+    /// Custom detectors run alongside the rules
+    pub fn detectors(&self) -> &DetectorRegistry {
+        &self.options.detectors
+    }
+
+    /// Scan a file or directory
+    pub fn scan_path(&self, root: impl AsRef<Path>) -> Result<ScanReport> {
+        scan_root(root.as_ref(), &self.rule_set, &self.options, None)
+    }
+
+    /// The files a scan of `root` would read, and the directories it
+    /// couldn't list
+    pub fn files(&self, root: impl AsRef<Path>) -> Result<(Vec<PathBuf>, Vec<ScanError>)> {
+        let root = root.as_ref();
+        let mut errors = Vec::new();
+        let files = if root.is_file() {
+            vec![root.to_path_buf()]
+        } else if root.is_dir() {
+            collect_files(root, &self.options, &mut errors)?
+        } else {
+            Vec::new()
+        };
+        Ok((files, errors))
+    }
+
+    /// Scan a file or directory, reporting each file to `progress`
+    pub fn scan_path_with_progress(
+        &self,
+        root: impl AsRef<Path>,
+        progress: &dyn ScanProgress,
+    ) -> Result<ScanReport> {
+        scan_root(root.as_ref(), &self.rule_set, &self.options, Some(progress))
+    }
+
+    /// Scan a single file
+    ///
+    /// Binary and oversized files are skipped and counted in the stats.
+    pub fn scan_file(&self, path: impl AsRef<Path>) -> Result<ScanReport> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            bail!("'{}' is not a file", path.display());
+        }
+        scan_root(path, &self.rule_set, &self.options, None)
+    }
+
+    /// Scan in-memory content, labelling findings with `name`
+    pub fn scan_bytes(&self, name: &str, bytes: &[u8]) -> Result<ScanReport> {
+        self.scan_reader(name, bytes)
+    }
+
+    /// Scan text from any reader, labelling findings with `name`
+    pub fn scan_reader<R: Read>(&self, name: &str, reader: R) -> Result<ScanReport> {
+        let started = Instant::now();
+        let mut findings = Vec::new();
+        let mut file = scan_lines(
+            BufReader::new(reader),
+            name,
+            &self.rule_set,
+            &self.options.for_path(name),
+            &mut findings,
+        )
+        .with_context(|| format!("failed to scan '{}'", name))?;
+
+        let errors = std::mem::take(&mut file.errors);
+        let mut stats = ScanStats::default();
+        stats.record(Some(file));
+        Ok(ScanReport::new(findings, stats, errors, started))
+    }
+}
+
+/// Builder for [`Scanner`]
+#[derive(Debug, Clone, Default)]
+pub struct ScannerBuilder {
+    rules: Option<Vec<Rule>>,
+    extra_rules: Vec<Rule>,
+    overrides: Vec<RuleOverride>,
+    validators: CustomValidators,
+    options: ScanOptions,
+    profile_rules: bool,
+}
+
+impl ScannerBuilder {
+    /// Use these rules instead of the built-in ones
+    pub fn rules(mut self, rules: Vec<Rule>) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Add rules on top of the built-in (or previously set) ones
+    pub fn add_rules(mut self, rules: impl IntoIterator<Item = Rule>) -> Self {
+        self.extra_rules.extend(rules);
+        self
+    }
+
+    /// Add the rules from a custom rules file, applying its overrides to the
+    /// built-in (or previously set) rules
+    pub fn add_rule_file(mut self, custom: CustomRules) -> Self {
+        self.extra_rules.extend(custom.rules);
+        self.overrides.extend(custom.overrides);
+        self
+    }
+
+    /// Run a custom detector over each text file alongside the rules
+    pub fn detector(mut self, detector: impl Detector + 'static) -> Self {
+        self.options.detectors.register(detector);
+        self
+    }
+
+    /// Register a function for rules declaring `validator: custom:NAME`
+    ///
+    /// Matches are kept only when it returns `true` for the secret.
+    pub fn validator(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.validators
+            .insert(name.into(), CustomValidator::new(check));
+        self
+    }
+
+    /// Skip rules by id or display name
+    pub fn ignore<I, S>(mut self, rules: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options
+            .ignore_rules
+            .extend(rules.into_iter().map(Into::into));
+        self
+    }
+
+    /// Skip files larger than this many bytes
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.options.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Follow symbolic links while walking directories
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.options.follow_symlinks = follow;
+        self
+    }
+
+    /// Descend at most this many directories below the scan root
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.options.max_depth = Some(depth);
+        self
+    }
+
+    /// Match rules against the printable strings in binary files
+    pub fn scan_binary(mut self, scan_binary: bool) -> Self {
+        self.options.scan_binary = scan_binary;
+        self
+    }
+
+    /// Decode base64 and hex blobs and scan the decoded text too
+    pub fn decode(mut self, decode: DecodeOptions) -> Self {
+        self.options.decode = Some(decode);
+        self
+    }
+
+    /// Match lines longer than this many bytes in overlapping windows
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.options.max_line_length = Some(bytes);
+        self
+    }
+
+    /// Resolve findings in minified files through their source maps
+    pub fn source_maps(mut self, source_maps: bool) -> Self {
+        self.options.source_maps = source_maps;
+        self
+    }
+
+    /// Keep `lines` lines before and after each finding, with secrets masked
+    pub fn context(mut self, lines: usize) -> Self {
+        self.options.context = lines;
+        self
+    }
+
+    /// Keep only matches inside string literals of supported source files
+    pub fn syntax(mut self, syntax: bool) -> Self {
+        self.options.syntax = syntax;
+        self
+    }
+
+    /// Read files through memory maps instead of heap buffers
+    pub fn mmap(mut self, mmap: bool) -> Self {
+        self.options.mmap = mmap;
+        self
+    }
+
+    /// Time each rule while scanning; read the results with
+    /// [`Scanner::profile`]
+    pub fn profile_rules(mut self, profile: bool) -> Self {
+        self.profile_rules = profile;
+        self
+    }
+
+    /// Ignore or add rules for files under some paths
+    pub fn rule_scope(mut self, scope: RuleScope) -> Self {
+        self.options.rule_scopes.push(scope);
+        self
+    }
+
+    /// How to scan lockfiles and vendored dependencies
+    pub fn lockfiles(mut self, lockfiles: LockfileOptions) -> Self {
+        self.options.lockfiles = lockfiles;
+        self
+    }
+
+    /// Number of worker threads (0 = one per CPU)
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = threads;
+        self
+    }
+
+    /// Drop findings below this severity
+    pub fn min_severity(mut self, severity: FindingSeverity) -> Self {
+        self.options.min_severity = severity;
+        self
+    }
+
+    /// Run the entropy engine alongside the regex rules
+    pub fn entropy(mut self, entropy: EntropyOptions) -> Self {
+        self.options.entropy = Some(entropy);
+        self
+    }
+
+    /// Mask secrets in finding snippets
+    pub fn redact(mut self, redact: bool) -> Self {
+        self.options.redact = redact;
+        self
+    }
+
+    /// Only run rules carrying at least one of these tags
+    pub fn only_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options
+            .only_tags
+            .extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Skip rules carrying any of these tags
+    pub fn skip_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options
+            .skip_tags
+            .extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only scan files matching these gitignore-style globs
+    pub fn include<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options
+            .include
+            .extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Skip files matching these gitignore-style globs
+    pub fn exclude<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options
+            .exclude
+            .extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Don't honor .gitignore, .ignore, or .fastsecretignore files
+    pub fn no_ignore(mut self, no_ignore: bool) -> Self {
+        self.options.no_ignore = no_ignore;
+        self
+    }
+
+    /// Replace all options at once
+    pub fn options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Compile the rules and create the scanner
+    ///
+    /// Fails if an override from [`add_rule_file`](Self::add_rule_file)
+    /// names a rule that isn't loaded.
+    pub fn build(self) -> Result<Scanner> {
+        let mut rules = self.rules.unwrap_or_else(load_builtin_rules);
+        for rule_override in &self.overrides {
+            if !rule_override.apply(&mut rules) {
+                bail!("cannot override unknown rule '{}'", rule_override.target);
+            }
+        }
+        rules.extend(self.extra_rules);
+        let rule_set = CompiledRuleSet::with_validators(&rules, &self.validators)?;
+        let mut options = self.options;
+        if self.profile_rules {
+            options.profile = Some(Arc::new(RuleProfile::new(&rule_set)));
+        }
+        Ok(Scanner::new(rule_set, options))
+    }
 }
 
 /// Scan a file or directory for secrets
@@ -34,91 +1372,1173 @@ pub fn scan_path(
     ignore_rules: &[String],
     verbose: bool,
 ) -> Result<Vec<Finding>> {
-    let mut findings = Vec::new();
-    let path = Path::new(root);
+    let options = ScanOptions {
+        ignore_rules: ignore_rules.to_vec(),
+        verbose,
+        ..ScanOptions::default()
+    };
+    scan_path_with_options(root, rules, &options)
+}
+
+/// Scan a file or directory for secrets using the given options
+pub fn scan_path_with_options(
+    root: &str,
+    rules: &[Rule],
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let rule_set = CompiledRuleSet::new(rules)?;
+    scan_path_with_rule_set(root, &rule_set, options)
+}
+
+/// Scan a file or directory with a pre-compiled rule set
+///
+/// Files are scanned concurrently on a rayon thread pool. Findings are
+/// merged back in traversal order, so the output is deterministic
+/// regardless of the thread count.
+pub fn scan_path_with_rule_set(
+    root: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    Ok(scan_root(Path::new(root), rule_set, options, None)?.findings)
+}
 
-    if path.is_file() {
-        scan_file(path, rules, ignore_rules, &mut findings, verbose)?;
+/// Scan a file or directory, collecting statistics along the way
+#[tracing::instrument(
+    name = "scan",
+    level = "info",
+    skip_all,
+    fields(root = %path.display(), files = tracing::field::Empty)
+)]
+fn scan_root(
+    path: &Path,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    progress: Option<&dyn ScanProgress>,
+) -> Result<ScanReport> {
+    let started = Instant::now();
+
+    let mut errors = Vec::new();
+    let files: Vec<PathBuf> = if path.is_file() {
+        vec![path.to_path_buf()]
     } else if path.is_dir() {
-        for entry in WalkDir::new(path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| !should_skip_dir(e.path()))
-        {
-            if entry.path().is_file() {
-                scan_file(entry.path(), rules, ignore_rules, &mut findings, verbose)?;
+        collect_files(path, options, &mut errors)?
+    } else {
+        Vec::new()
+    };
+    let span = tracing::Span::current();
+    span.record("files", files.len());
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()?;
+
+    // Fingerprints use paths relative to the scanned directory (or to the
+    // parent of a single scanned file)
+    let base = if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+    let options = &options.rooted_at(base);
+
+    let cache = options
+        .cache_dir
+        .as_deref()
+        .map(|dir| ScanCache::load(dir, path, rule_set, options));
+
+    if let Some(progress) = progress {
+        progress.start(files.len());
+    }
+    let per_file: Vec<_> = pool.install(|| {
+        files
+            .par_iter()
+            .map(|file| {
+                // Worker threads don't inherit the current span
+                let _enter = span.enter();
+                let mut findings = Vec::new();
+                let key = file.strip_prefix(base).unwrap_or(file).to_string_lossy();
+                let cached = cache.as_ref().and_then(|c| c.lookup(&key, file));
+                let was_cached = cached.is_some();
+                let stats = match cached {
+                    Some(stats) => Ok(Some(stats)),
+                    None => {
+                        let options = options.for_path(&key);
+                        let stats = scan_file(file, rule_set, &options, &mut findings);
+                        if let (Some(cache), Ok(Some(stats))) = (&cache, &stats) {
+                            if findings.is_empty() && stats.errors.is_empty() {
+                                cache.record_clean(&key, file, stats);
+                            }
+                        }
+                        stats
+                    }
+                };
+                set_relative_paths(base, file, &mut findings);
+                if let Some(progress) = progress {
+                    if !findings.is_empty() {
+                        progress.findings(file, &findings);
+                    }
+                    progress.file_done(file);
+                }
+                (findings, stats, was_cached)
+            })
+            .collect()
+    });
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    let mut findings = Vec::new();
+    let mut stats = ScanStats::default();
+    for (file_findings, file_stats, cached) in per_file {
+        findings.extend(file_findings);
+        stats.record(match file_stats {
+            Ok(file) => file.map(|mut file| {
+                errors.append(&mut file.errors);
+                file
+            }),
+            Err(e) => {
+                errors.push(e);
+                None
             }
+        });
+        stats.files_cached += usize::from(cached);
+    }
+
+    if let Some(cache) = cache {
+        if let Err(e) = cache.save() {
+            tracing::warn!("Failed to write scan cache: {}", e);
         }
     }
+    Ok(ScanReport::new(findings, stats, errors, started))
+}
 
-    Ok(findings)
+/// Record each finding's path relative to `base`, for fingerprints
+pub(crate) fn set_relative_paths(base: &Path, file: &Path, findings: &mut [Finding]) {
+    if let Ok(relative) = file.strip_prefix(base) {
+        // Archive entries keep their `!inner/path` suffix
+        let display = file.display().to_string();
+        let relative = relative.to_string_lossy();
+        for finding in findings {
+            let suffix = finding.file.strip_prefix(&display).unwrap_or_default();
+            finding.relative_file = Some(format!("{}{}", relative, suffix));
+        }
+    }
+}
+
+/// Name of the fastsecret-specific ignore file
+pub const IGNORE_FILE_NAME: &str = ".fastsecretignore";
+
+/// Walk a directory and list the files to scan, in a stable order
+///
+/// Honors `.gitignore`, `.ignore`, and `.fastsecretignore` files unless
+/// `options.no_ignore` is set. Hidden files are always included since
+/// secrets commonly live in dotfiles like `.env`.
+///
+/// Symbolic links are skipped unless `options.follow_symlinks` is set.
+/// When following them, links that lead back into a directory being walked
+/// are not descended into, and a file reachable through several links is
+/// listed once, under the first path found.
+///
+/// Directories that can't be read are added to `errors`.
+#[tracing::instrument(name = "walk", level = "info", skip_all, fields(root = %root.display()))]
+pub(crate) fn collect_files(
+    root: &Path,
+    options: &ScanOptions,
+    errors: &mut Vec<ScanError>,
+) -> Result<Vec<PathBuf>> {
+    let skip_dirs = options.skip_dirs.clone();
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(!options.no_ignore)
+        .hidden(false)
+        .require_git(false)
+        .follow_links(options.follow_symlinks)
+        .max_depth(options.max_depth.map(|depth| depth + 1))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |e| {
+            !(e.file_type().is_some_and(|t| t.is_dir()) && should_skip_dir(e.path(), &skip_dirs))
+        });
+    if !options.no_ignore {
+        builder.add_custom_ignore_filename(IGNORE_FILE_NAME);
+    }
+
+    // Excludes go to the walker, so an excluded directory is pruned without
+    // being read and a negation in an ignore file can't bring a file back.
+    // Includes only narrow down the files left afterwards: as walker
+    // overrides they would take precedence over the ignore files.
+    if !options.exclude.is_empty() {
+        let mut overrides = OverrideBuilder::new(root);
+        for glob in &options.exclude {
+            overrides.add(&format!("!{}", glob))?;
+            // `dir/**` only matches what's inside; also match `dir` itself
+            if let Some(dir) = glob.strip_suffix("/**") {
+                overrides.add(&format!("!{}", dir))?;
+            }
+        }
+        builder.overrides(overrides.build()?);
+    }
+    let include = if options.include.is_empty() {
+        None
+    } else {
+        let mut include = OverrideBuilder::new(root);
+        for glob in &options.include {
+            include.add(glob)?;
+        }
+        Some(include.build()?)
+    };
+
+    let mut seen = HashSet::new();
+    Ok(builder
+        .build()
+        .filter_map(|entry| match entry {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                // Symlink loops and unreadable directories
+                match ScanError::from_walk(&e) {
+                    Some(error) => errors.push(error),
+                    None => tracing::info!("Skipping: {}", e),
+                }
+                None
+            }
+        })
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter(|e| {
+            include
+                .as_ref()
+                .is_none_or(|include| include.matched(e.path(), false).is_whitelist())
+        })
+        .filter(|e| {
+            !options.follow_symlinks
+                || fs::canonicalize(e.path()).map_or(true, |real| seen.insert(real))
+        })
+        .map(|e| e.into_path())
+        .collect())
 }
 
-/// Scan a single file for secret matches
-fn scan_file(
+/// Scan one file, returning how much was read, `None` if it was skipped as
+/// binary, or why it couldn't be scanned
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+pub(crate) fn scan_file(
     path: &Path,
-    rules: &[Rule],
-    ignore_rules: &[String],
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
     findings: &mut Vec<Finding>,
-    verbose: bool,
-) -> Result<()> {
-    // Skip binary files
-    if is_binary_file(path) {
-        return Ok(());
+) -> Result<Option<FileStats>, ScanError> {
+    let error = |e: io::Error| ScanError::from_io(path, &e);
+    if options
+        .lockfiles
+        .mode_for(options.relative(&path.to_string_lossy()))
+        == LockfileMode::Skip
+    {
+        tracing::info!(file = %path.display(), "Skipping lockfile {}", path.display());
+        return Ok(None);
+    }
+    if let Some(limits) = &options.archives {
+        if let Some(kind) = ArchiveKind::from_name(&path.to_string_lossy()) {
+            let start = findings.len();
+            let stats = scan_archive_file(path, kind, rule_set, options, limits, findings);
+            if stats.is_err() {
+                findings.truncate(start);
+            }
+            return stats.map(Some).map_err(error);
+        }
+    }
+
+    if let Some(max) = options.max_file_size {
+        let size = fs::metadata(path).map_err(error)?.len();
+        if size > max {
+            return Err(ScanError {
+                file: path.display().to_string(),
+                kind: ScanErrorKind::TooLarge,
+                message: format!("{} bytes exceeds max file size of {} bytes", size, max),
+            });
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    if options.mmap {
+        if let Some(stats) = scan_mapped(path, rule_set, options, findings) {
+            return stats;
+        }
+    }
+
+    let mut file = fs::File::open(path).map_err(error)?;
+    let mut sample = Vec::with_capacity(SNIFF_LEN);
+    (&mut file)
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut sample)
+        .map_err(error)?;
+
+    let name = path.display().to_string();
+    let binary_extension = has_binary_extension(path);
+    let detected = encoding::detect(&sample, binary_extension);
+    if detected.is_none() && looks_binary(&sample, binary_extension) {
+        if !options.scan_binary {
+            return Ok(None);
+        }
+        let mut bytes = sample;
+        file.read_to_end(&mut bytes).map_err(error)?;
+        return Ok(Some(scan_binary(
+            &bytes, &name, rule_set, options, findings,
+        )));
+    }
+    if let Some(encoding) = detected {
+        tracing::debug!("Decoding {} as {}", name, encoding.name());
     }
 
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return Ok(()), // Skip files we can't read
+    let start = findings.len();
+    let mut stats = scan_lines(
+        encoding::text_reader(sample, file, detected),
+        &name,
+        rule_set,
+        options,
+        findings,
+    );
+    // Text that stops being UTF-8 past the sniffed sample is read again
+    // as Latin-1
+    if detected.is_none()
+        && stats
+            .as_ref()
+            .is_err_and(|e| e.kind() == io::ErrorKind::InvalidData)
+    {
+        findings.truncate(start);
+        tracing::debug!("Decoding {} as {}", name, encoding_rs::WINDOWS_1252.name());
+        let file = fs::File::open(path).map_err(error)?;
+        stats = scan_lines(
+            encoding::text_reader(Vec::new(), file, Some(encoding_rs::WINDOWS_1252)),
+            &name,
+            rule_set,
+            options,
+            findings,
+        );
+    }
+    if stats.is_err() {
+        // Unreadable or not a text file after all
+        findings.truncate(start);
+    }
+    stats.map(Some).map_err(error)
+}
+
+/// Scan the file at `path` through a memory map instead of a heap buffer
+///
+/// Returns `None` for files that should be read normally instead: ones that
+/// can't be mapped (empty files, pipes, some network filesystems), ones in
+/// an encoding other than UTF-8, and ones that turn out not to be UTF-8
+/// past the sniffed sample, which the buffered path reads as Latin-1.
+#[cfg(feature = "mmap")]
+fn scan_mapped(
+    path: &Path,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) -> Option<Result<Option<FileStats>, ScanError>> {
+    let file = fs::File::open(path).ok()?;
+    // SAFETY: the map is only read, and dropped before this returns. If
+    // another process truncates the file meanwhile, reading past its new
+    // end faults; that is the usual cost of scanning through mmap and why
+    // it's opt-in.
+    let map = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(map) if !map.is_empty() => map,
+        Ok(_) => return None,
+        Err(e) => {
+            tracing::debug!(
+                "Couldn't map {} ({}); reading it instead",
+                path.display(),
+                e
+            );
+            return None;
+        }
     };
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
 
-    let path_str = path.display().to_string();
+    let sample = &map[..map.len().min(SNIFF_LEN)];
+    let binary_extension = has_binary_extension(path);
+    if encoding::detect(sample, binary_extension).is_some() {
+        return None;
+    }
+    let name = path.display().to_string();
+    if looks_binary(sample, binary_extension) {
+        if !options.scan_binary {
+            return Some(Ok(None));
+        }
+        return Some(Ok(Some(scan_binary(
+            &map, &name, rule_set, options, findings,
+        ))));
+    }
 
-    for (line_idx, line) in content.lines().enumerate() {
-        for rule in rules {
-            // Skip ignored rules
-            if ignore_rules.contains(&rule.name) {
+    let start = findings.len();
+    let stats = scan_lines(&map[..], &name, rule_set, options, findings);
+    if stats.is_err() {
+        findings.truncate(start);
+    }
+    match stats {
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => None,
+        stats => Some(stats.map(Some).map_err(|e| ScanError::from_io(path, &e))),
+    }
+}
+
+/// Scan text from any reader, labelling findings with `name`
+///
+/// Used for stdin and other content that doesn't live in a file. Fails if
+/// the input can't be read or isn't valid UTF-8.
+pub fn scan_reader<R: Read>(
+    reader: R,
+    name: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    scan_lines(
+        BufReader::new(reader),
+        name,
+        rule_set,
+        &options.for_path(name),
+        &mut findings,
+    )
+    .with_context(|| format!("failed to scan '{}'", name))?;
+    Ok(findings)
+}
+
+/// Scan a stream line by line, appending to `findings`
+///
+/// Only one line is held in memory at a time unless multi-line rules,
+/// detectors, or context lines need the whole content buffered. Callers
+/// resolve rule scopes with [`ScanOptions::for_path`] first.
+pub(crate) fn scan_lines<R: BufRead>(
+    mut reader: R,
+    path_str: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) -> io::Result<FileStats> {
+    let file_start = findings.len();
+    let mut stats = FileStats::default();
+    let lockfile = options.lockfiles.mode_for(options.relative(path_str));
+    if lockfile == LockfileMode::Skip {
+        return Ok(stats);
+    }
+    let mut buf = Vec::new();
+    let mut line = String::new();
+    let mut prev_line = String::new();
+    let mut line_no = 0;
+    let mut multiline = rule_set.has_multiline().then(MultilineBuffer::default);
+    let mut variables = VariableTracker::for_path(path_str);
+    // Terraform files are parsed as a whole once read
+    let mut terraform = terraform::FileKind::from_name(path_str).map(|kind| (kind, String::new()));
+    let mut notebook = notebook::is_notebook(path_str).then(String::new);
+    #[cfg(feature = "syntax")]
+    let mut source = syntax::Language::for_path(path_str)
+        .filter(|_| options.syntax)
+        .map(|language| (language, String::new()));
+    let mut buffered = (!options.detectors.is_empty() || options.context > 0).then(String::new);
+    let mut map_url = None;
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        line_no += 1;
+        stats.bytes += buf.len() as u64;
+
+        let bytes = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        std::mem::swap(&mut line, &mut prev_line);
+        line.clear();
+        line.push_str(std::str::from_utf8(bytes).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid UTF-8 on line {}", line_no),
+            )
+        })?);
+        buf.clear();
+
+        let start = findings.len();
+        scan_line(path_str, line_no, &line, rule_set, options, findings);
+        if options.decode.is_some() {
+            decode::scan_encoded(path_str, line_no, &line, &[], rule_set, options, findings);
+        }
+        normalize::scan_normalized(path_str, line_no, &line, rule_set, options, findings);
+        if variables.as_ref().is_some_and(|t| t.is_yaml()) && line.contains("{{") {
+            kubernetes::scan_helm_line(
+                path_str, line_no, &line, start, rule_set, options, findings,
+            );
+        }
+        let prev = (line_no > 1).then_some(prev_line.as_str());
+        drop_suppressed(findings, start, &line, prev);
+
+        if let Some(buffer) = &mut multiline {
+            buffer.push(line_no, &line);
+            buffer.slide(path_str, rule_set, options, findings);
+        }
+        if let Some(tracker) = &mut variables {
+            tracker.feed(line_no, &line);
+        }
+        if options.source_maps {
+            if let Some(url) = sourcemap::mapping_url(&line) {
+                map_url = Some(url.to_string());
+            }
+        }
+        if let Some(content) = terraform.as_mut().map(|(_, c)| c).or(notebook.as_mut()) {
+            content.push_str(&line);
+            content.push('\n');
+        }
+        #[cfg(feature = "syntax")]
+        if let Some((_, content)) = &mut source {
+            content.push_str(&line);
+            content.push('\n');
+        }
+        if let Some(content) = &mut buffered {
+            content.push_str(&line);
+            content.push('\n');
+        }
+    }
+
+    if let Some(buffer) = &multiline {
+        buffer.scan(path_str, rule_set, options, findings);
+        findings[file_start..].sort_by_key(|f| f.line);
+    }
+    if let Some(tracker) = &variables {
+        tracker.annotate(&mut findings[file_start..]);
+        kubernetes::scan_secret_data(tracker, file_start, path_str, rule_set, options, findings);
+        findings[file_start..].sort_by_key(|f| (f.line, f.column));
+    }
+    if let Some((kind, content)) = &terraform {
+        terraform::scan_file(*kind, content, file_start, path_str, options, findings);
+        findings[file_start..].sort_by_key(|f| (f.line, f.column));
+    }
+    if let Some(content) = &notebook {
+        notebook::scan_notebook(content, file_start, path_str, rule_set, options, findings);
+        findings[file_start..].sort_by_key(|f| (f.line, f.column));
+    }
+    #[cfg(feature = "syntax")]
+    if let Some((language, content)) = &source {
+        syntax::scan_file(*language, content, file_start, path_str, options, findings);
+    }
+    if let Some(content) = &buffered {
+        if !options.detectors.is_empty() {
+            detector::scan_file(
+                content,
+                file_start,
+                path_str,
+                options,
+                findings,
+                &mut stats.errors,
+            );
+        }
+    }
+    if lockfile == LockfileMode::Restricted {
+        lockfile::restrict(findings, file_start, rule_set);
+    }
+    if let Some(content) = buffered.as_deref().filter(|_| options.context > 0) {
+        attach_context(content, options.context, &mut findings[file_start..]);
+    }
+    if options.source_maps {
+        sourcemap::resolve(path_str, map_url.as_deref(), &mut findings[file_start..]);
+    }
+
+    stats.lines = line_no;
+    Ok(stats)
+}
+
+/// Marker for inline suppression comments
+const SUPPRESS_MARKER: &str = "fastsecret:ignore";
+
+/// Rules silenced by a `fastsecret:ignore` comment on a line
+///
+/// Returns `None` when the line has no directive, an empty list for a bare
+/// `fastsecret:ignore` (all rules), or the bracketed rule list for
+/// `fastsecret:ignore[rule-a, rule-b]`.
+fn parse_suppression(line: &str) -> Option<Vec<&str>> {
+    let rest = &line[line.find(SUPPRESS_MARKER)? + SUPPRESS_MARKER.len()..];
+    let rules = rest
+        .strip_prefix('[')
+        .and_then(|r| r.split_once(']'))
+        .map(|(list, _)| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(rules)
+}
+
+/// Apply a rule's entropy, allowlist, and severity filters to a match
+///
+/// Returns the byte span of the secret (the rule's secret group, or the
+/// whole match) and the finding severity, or `None` if the match is dropped.
+fn accept_match(
+    compiled: &CompiledRule,
+    caps: &Captures,
+    path_str: &str,
+    options: &ScanOptions,
+) -> Option<(Range<usize>, FindingSeverity, Confidence)> {
+    let rule = &compiled.rule;
+    let secret = caps
+        .get(rule.secret_group.unwrap_or(1))
+        .or_else(|| caps.get(0))?;
+
+    if !compiled.within_max_len(caps.get(0).map_or(0, |m| m.len())) {
+        return None;
+    }
+    if let Some(min_entropy) = rule.entropy {
+        if shannon_entropy(secret.as_str()) < min_entropy {
+            return None;
+        }
+    }
+    if !compiled.is_valid(secret.as_str())
+        || compiled.is_allowed(options.relative(path_str), secret.as_str())
+    {
+        return None;
+    }
+
+    let severity = convert_severity(rule.severity);
+    if severity < options.min_severity {
+        return None;
+    }
+    let confidence = confidence::score(Some(rule), secret.as_str(), options.relative(path_str));
+    if confidence < options.min_confidence {
+        return None;
+    }
+    Some((secret.range(), severity, confidence))
+}
+
+/// Bytes of text a multi-line match may span and still be found in a
+/// stream; a PEM private key takes a few kilobytes
+pub(crate) const MULTILINE_WINDOW: usize = 64 << 10;
+
+/// Buffered lines of one file, for rules that match across lines
+///
+/// Callers streaming a file call [`slide`](Self::slide) after each line,
+/// so no more than twice [`MULTILINE_WINDOW`] is held at once.
+#[derive(Debug, Default)]
+pub(crate) struct MultilineBuffer {
+    content: String,
+    /// Byte offset and line number of each buffered line
+    lines: Vec<(usize, usize)>,
+}
+
+impl MultilineBuffer {
+    /// Append a line (without its terminator)
+    pub(crate) fn push(&mut self, line_no: usize, line: &str) {
+        self.lines.push((self.content.len(), line_no));
+        self.content.push_str(line);
+        self.content.push('\n');
+    }
+
+    /// Line number of the last buffered line
+    pub(crate) fn last_line(&self) -> Option<usize> {
+        self.lines.last().map(|&(_, no)| no)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.content.clear();
+        self.lines.clear();
+    }
+
+    /// Text of the buffered line at `idx`, without its newline
+    fn line(&self, idx: usize) -> &str {
+        let start = self.lines[idx].0;
+        let end = self
+            .lines
+            .get(idx + 1)
+            .map_or(self.content.len(), |&(off, _)| off);
+        &self.content[start..end - 1]
+    }
+
+    /// Once twice [`MULTILINE_WINDOW`] is buffered, report the matches
+    /// starting before the last window and drop the lines they start in
+    ///
+    /// Matches starting later are left for a later scan, which sees them
+    /// with the lines that follow.
+    pub(crate) fn slide(
+        &mut self,
+        path_str: &str,
+        rule_set: &CompiledRuleSet,
+        options: &ScanOptions,
+        findings: &mut Vec<Finding>,
+    ) {
+        if self.content.len() < 2 * MULTILINE_WINDOW {
+            return;
+        }
+        let window_start = self.content.len() - MULTILINE_WINDOW;
+        let keep = self.lines.partition_point(|&(off, _)| off < window_start);
+        // A single line longer than the window stays whole
+        let Some(&(cut, _)) = self.lines.get(keep).filter(|_| keep > 0) else {
+            return;
+        };
+        self.scan_until(cut, path_str, rule_set, options, findings);
+        self.content.drain(..cut);
+        self.lines.drain(..keep);
+        for (off, _) in &mut self.lines {
+            *off -= cut;
+        }
+    }
+
+    /// Run the multi-line rules over the buffered content
+    ///
+    /// Each finding is attributed to the line and column where its match
+    /// starts. Inline suppressions on that line or the one before it apply.
+    pub(crate) fn scan(
+        &self,
+        path_str: &str,
+        rule_set: &CompiledRuleSet,
+        options: &ScanOptions,
+        findings: &mut Vec<Finding>,
+    ) {
+        self.scan_until(usize::MAX, path_str, rule_set, options, findings);
+    }
+
+    /// [`scan`](Self::scan), keeping only matches that start before byte
+    /// `end` of the buffered content
+    fn scan_until(
+        &self,
+        end: usize,
+        path_str: &str,
+        rule_set: &CompiledRuleSet,
+        options: &ScanOptions,
+        findings: &mut Vec<Finding>,
+    ) {
+        for compiled in rule_set.multiline_rules() {
+            let rule = &compiled.rule;
+            if !options.rule_enabled(rule) || !compiled.applies_to(options.relative(path_str)) {
                 continue;
             }
 
-            // Compile regex and check for matches
-            match Regex::new(&rule.pattern) {
-                Ok(regex) => {
-                    if let Some(_mat) = regex.find(line) {
-                        let severity = convert_severity(rule.severity);
-
-                        let snippet = if line.len() > 100 {
-                            format!("{}...", &line[..97])
-                        } else {
-                            line.to_string()
-                        };
-
-                        findings.push(Finding {
-                            file: path_str.clone(),
-                            line: line_idx + 1,
-                            snippet: snippet.trim().to_string(),
-                            rule_name: rule.name.clone(),
-                            severity,
-                        });
-
-                        if verbose {
-                            eprintln!(
-                                "  ✓ Matched '{}' at {}:{}",
-                                rule.name,
-                                path_str,
-                                line_idx + 1
-                            );
-                        }
-                    }
+            let started = options.profile.as_ref().map(|_| Instant::now());
+            let mut matches = 0;
+            for caps in compiled.regex.captures_iter(&self.content) {
+                let mat = caps.get(0).expect("group 0 always matches");
+                if mat.start() >= end {
+                    break;
+                }
+                matches += 1;
+                let Some((secret, severity, confidence)) =
+                    accept_match(compiled, &caps, path_str, options)
+                else {
+                    continue;
+                };
+
+                let idx = self.lines.partition_point(|&(off, _)| off <= mat.start()) - 1;
+                let (offset, line_no) = self.lines[idx];
+                let line = self.line(idx);
+                let clip = |r: Range<usize>| {
+                    let start = r.start.clamp(offset, offset + line.len()) - offset;
+                    let end = r.end.clamp(offset, offset + line.len()) - offset;
+                    start..end.max(start)
+                };
+
+                // Columns cover the match on its first line; the snippet
+                // masks whatever part of the secret appears there
+                let (column, end_column, _) = locate(line, clip(mat.range()), false);
+                let (_, _, snippet) = locate(line, clip(secret.clone()), options.redact);
+
+                let start = findings.len();
+                findings.push(Finding {
+                    file: path_str.to_string(),
+                    line: line_no,
+                    column,
+                    end_column,
+                    snippet,
+                    context: Vec::new(),
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    severity,
+                    confidence,
+                    remediation: rule.remediation.clone(),
+                    rule_source: rule.source.clone(),
+                    rule_version: rule.version.clone(),
+                    secret_sha256: None,
+                    secret: self.content[secret.clone()].to_string(),
+                    commit: None,
+                    relative_file: None,
+                    verification: None,
+                    layer: None,
+                    cell: None,
+                    original: None,
+                    other_locations: Vec::new(),
+                    variable: None,
+                    metadata: metadata::extract(&self.content[secret]),
+                });
+
+                let prev = idx
+                    .checked_sub(1)
+                    .filter(|&p| self.lines[p].1 + 1 == line_no)
+                    .map(|p| self.line(p));
+                drop_suppressed(findings, start, line, prev);
+
+                if findings.len() > start {
+                    tracing::debug!(
+                        rule = %rule.id,
+                        "✓ Matched '{}' at {}:{}",
+                        rule.name,
+                        path_str,
+                        line_no
+                    );
                 }
-                Err(e) => {
-                    eprintln!("⚠️  Invalid regex in rule '{}': {}", rule.name, e);
+            }
+            if let (Some(profile), Some(started)) = (&options.profile, started) {
+                profile.record(&rule.id, started.elapsed(), matches, path_str);
+            }
+        }
+    }
+}
+
+/// Remove findings from `findings[start..]` silenced by an inline comment
+/// on the matched line or the line before it
+pub(crate) fn drop_suppressed(
+    findings: &mut Vec<Finding>,
+    start: usize,
+    line: &str,
+    prev_line: Option<&str>,
+) {
+    if findings.len() == start {
+        return;
+    }
+
+    let directives: Vec<Vec<&str>> = [Some(line), prev_line]
+        .into_iter()
+        .flatten()
+        .filter_map(parse_suppression)
+        .collect();
+    if directives.is_empty() {
+        return;
+    }
+
+    let candidates = findings.split_off(start);
+    findings.extend(candidates.into_iter().filter(|f| {
+        !directives.iter().any(|rules| {
+            rules.is_empty() || rules.iter().any(|&r| r == f.rule_id || r == f.rule_name)
+        })
+    }));
+}
+
+/// Match a single line against the rule set
+///
+/// `line_no` is 1-based. Every match of every rule is reported, so two
+/// secrets on one line give two findings. Any findings are appended to
+/// `findings`, ordered by column.
+///
+/// Lines longer than `options.max_line_length` are matched in overlapping
+/// windows of that size; columns still count from the start of the line.
+pub(crate) fn scan_line(
+    path_str: &str,
+    line_no: usize,
+    line: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    let start = findings.len();
+    for (offset, text) in line_windows(line, options.max_line_length) {
+        if let Some(profile) = &options.profile {
+            profile.time_line(rule_set, text, options.relative(path_str));
+        }
+        let _span = tracing::trace_span!("match_rules", line = line_no).entered();
+        for compiled in rule_set.matching_file(text, options.relative(path_str)) {
+            let rule = &compiled.rule;
+
+            // Skip ignored rules
+            if !options.rule_enabled(rule) {
+                continue;
+            }
+
+            let started = options.profile.as_ref().map(|_| Instant::now());
+            let mut matches = 0;
+            for caps in compiled.regex.captures_iter(text) {
+                matches += 1;
+                let Some((span, severity, confidence)) =
+                    accept_match(compiled, &caps, path_str, options)
+                else {
+                    continue;
+                };
+                let span = offset + span.start..offset + span.end;
+                let secret = &line[span.clone()];
+                let (column, end_column, snippet) = locate(line, span, options.redact);
+                // A secret in the overlap of two windows is seen twice
+                if offset > 0
+                    && findings[start..]
+                        .iter()
+                        .any(|f| f.rule_id == rule.id && f.column == column)
+                {
+                    continue;
                 }
+
+                findings.push(Finding {
+                    file: path_str.to_string(),
+                    line: line_no,
+                    column,
+                    end_column,
+                    snippet,
+                    context: Vec::new(),
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    severity,
+                    confidence,
+                    remediation: rule.remediation.clone(),
+                    rule_source: rule.source.clone(),
+                    rule_version: rule.version.clone(),
+                    secret_sha256: None,
+                    secret: secret.to_string(),
+                    commit: None,
+                    relative_file: None,
+                    verification: None,
+                    layer: None,
+                    cell: None,
+                    original: None,
+                    other_locations: Vec::new(),
+                    variable: None,
+                    metadata: metadata::extract(secret),
+                });
+
+                tracing::debug!(
+                    rule = %rule.id,
+                    "✓ Matched '{}' at {}:{}:{}",
+                    rule.name, path_str, line_no, column
+                );
+            }
+            if let (Some(profile), Some(started)) = (&options.profile, started) {
+                profile.record(&rule.id, started.elapsed(), matches, path_str);
             }
         }
     }
 
-    Ok(())
+    if let Some(entropy) = &options.entropy {
+        // Entropy findings are always low severity
+        let ignored = options.min_severity > FindingSeverity::Low
+            || options
+                .ignore_rules
+                .iter()
+                .any(|r| r == ENTROPY_RULE_ID || r == ENTROPY_RULE_NAME);
+        if !ignored {
+            scan_line_entropy(path_str, line_no, line, entropy, options, findings);
+        }
+    }
+
+    let line_findings = &mut findings[start..];
+    if line_findings.len() > 1 {
+        line_findings.sort_by_key(|f| f.column);
+        if options.redact {
+            mask_snippets(line, line_findings);
+        }
+    }
+}
+
+/// Bytes shared by neighbouring windows of a long line, so a secret on a
+/// window boundary is still seen whole
+const LINE_WINDOW_OVERLAP: usize = 512;
+
+/// Split a line longer than `max_len` bytes into overlapping windows, each
+/// with its byte offset in the line
+fn line_windows(line: &str, max_len: Option<usize>) -> Vec<(usize, &str)> {
+    let Some(max_len) = max_len.filter(|&max| max > 0 && line.len() > max) else {
+        return vec![(0, line)];
+    };
+    let floor = |mut i: usize| {
+        while !line.is_char_boundary(i) {
+            i -= 1;
+        }
+        i
+    };
+    let step = max_len - LINE_WINDOW_OVERLAP.min(max_len / 2);
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = floor((start + max_len).min(line.len()));
+        windows.push((start, &line[start..end]));
+        if end == line.len() {
+            return windows;
+        }
+        // Always advance, even past a wide character
+        start = floor(start + step).max(start + 1);
+        start = (start..line.len())
+            .find(|&i| line.is_char_boundary(i))
+            .unwrap_or(line.len());
+    }
+}
+
+/// Rebuild the snippets of several findings on one line so that each masks
+/// every secret on the line, not just its own
+fn mask_snippets(line: &str, findings: &mut [Finding]) {
+    // Byte offset of each 1-based column, plus the end of the line
+    let offsets: Vec<usize> = line
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([line.len()])
+        .collect();
+    let byte = |column: usize| offsets[(column - 1).min(offsets.len() - 1)];
+    let spans: Vec<Range<usize>> = findings
+        .iter()
+        .map(|f| byte(f.column)..byte(f.end_column))
+        .collect();
+
+    for (i, f) in findings.iter_mut().enumerate() {
+        f.snippet = masked_snippet(line, &spans, i);
+    }
+}
+
+/// Column range and display snippet for a secret at byte range `span`
+///
+/// Returns the 1-based start column, the exclusive end column, and the
+/// trimmed line truncated to a readable length. With `redact`, the secret
+/// is masked before truncation so no part of it leaks.
+pub(crate) fn locate(line: &str, span: Range<usize>, redact: bool) -> (usize, usize, String) {
+    let column = line[..span.start].chars().count() + 1;
+    let end_column = column + line[span.clone()].chars().count();
+
+    let snippet = if redact {
+        masked_snippet(line, std::slice::from_ref(&span), 0)
+    } else {
+        truncate_snippet(line, span)
+    };
+    (column, end_column, snippet)
+}
+
+/// Fill in each finding's context from the content of its file
+///
+/// Every secret found in the file is masked in the context lines, whether
+/// or not snippets are redacted, so the extra lines never show more than
+/// the snippets do.
+fn attach_context(content: &str, lines: usize, findings: &mut [Finding]) {
+    let text: Vec<&str> = content.lines().collect();
+    let secrets: Vec<String> = findings
+        .iter()
+        .flat_map(|f| f.secret.lines().map(str::trim))
+        .filter(|part| !part.is_empty())
+        .map(String::from)
+        .collect();
+    for finding in findings {
+        if finding.line == 0 || finding.line > text.len() {
+            continue;
+        }
+        let first = finding.line.saturating_sub(lines).max(1);
+        let last = (finding.line + lines).min(text.len());
+        finding.context = (first..=last)
+            .filter(|&no| no != finding.line)
+            .map(|no| ContextLine {
+                line: no,
+                text: context_text(text[no - 1], &secrets),
+            })
+            .collect();
+    }
+}
+
+/// A context line with `secrets` masked, cut to the snippet width
+fn context_text(line: &str, secrets: &[String]) -> String {
+    let spans: Vec<Range<usize>> = secrets
+        .iter()
+        .flat_map(|secret| {
+            line.match_indices(secret.as_str())
+                .map(|(at, m)| at..at + m.len())
+        })
+        .collect();
+    let display = if spans.is_empty() {
+        line.trim_end().to_string()
+    } else {
+        mask_spans(line, &spans, 0).0.trim_end().to_string()
+    };
+    if display.chars().count() <= SNIPPET_WIDTH {
+        return display;
+    }
+    let cut: String = display.chars().take(SNIPPET_WIDTH - 3).collect();
+    format!("{}...", cut)
+}
+
+/// Snippet of `line` with the secrets at byte ranges `spans` masked,
+/// centered on `spans[focus]` when the line is too long to show whole
+///
+/// Overlapping spans are masked as one.
+fn masked_snippet(line: &str, spans: &[Range<usize>], focus: usize) -> String {
+    let (display, window) = mask_spans(line, spans, focus);
+    truncate_snippet(&display, window)
+}
+
+/// `line` with the byte ranges `spans` masked, and where `spans[focus]`
+/// ends up in the result
+fn mask_spans(line: &str, spans: &[Range<usize>], focus: usize) -> (String, Range<usize>) {
+    let focused = spans[focus].clone();
+    let mut spans = spans.to_vec();
+    spans.sort_by_key(|s| s.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start < last.end => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+
+    let mut display = String::with_capacity(line.len());
+    let mut pos = 0;
+    let mut window = 0..0;
+    for span in merged {
+        display.push_str(&line[pos..span.start]);
+        let start = display.len();
+        display.push_str(&redact_secret(&line[span.clone()]));
+        if span.start <= focused.start && focused.start < span.end.max(span.start + 1) {
+            window = start..display.len();
+        }
+        pos = span.end;
+    }
+    display.push_str(&line[pos..]);
+    (display, window)
+}
+
+/// Longest snippet shown, in characters
+const SNIPPET_WIDTH: usize = 100;
+
+/// Characters of context kept before the secret when a long line is cut
+/// down to a window around it
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Trim a line and cut it to a readable length
+///
+/// Long lines, such as minified bundles, are shown as a window around the
+/// byte range `focus` so the secret stays in view.
+fn truncate_snippet(display: &str, focus: Range<usize>) -> String {
+    let total = display.chars().count();
+    if total <= SNIPPET_WIDTH {
+        return display.trim().to_string();
+    }
+    let focus_start = display[..focus.start.min(display.len())].chars().count();
+    let focus_end = focus_start + display[focus.clone()].chars().count();
+    let first = if focus_end <= SNIPPET_WIDTH - 3 {
+        0
+    } else {
+        focus_start.saturating_sub(SNIPPET_CONTEXT)
+    };
+    let width = if first == 0 {
+        SNIPPET_WIDTH - 3
+    } else {
+        SNIPPET_WIDTH - 6
+    };
+    let last = (first + width).min(total);
+
+    let byte = |chars: usize| {
+        display
+            .char_indices()
+            .nth(chars)
+            .map_or(display.len(), |(i, _)| i)
+    };
+    let mut snippet = String::new();
+    if first > 0 {
+        snippet.push_str("...");
+    }
+    snippet.push_str(&display[byte(first)..byte(last)]);
+    if last < total {
+        snippet.push_str("...");
+    }
+    snippet.trim().to_string()
+}
+
+/// Mask all but the first and last 4 characters of a secret
+///
+/// Secrets of 8 characters or fewer are masked entirely.
+pub fn redact_secret(secret: &str) -> String {
+    let len = secret.chars().count();
+    if len <= 8 {
+        return "*".repeat(len);
+    }
+    let head: String = secret.chars().take(4).collect();
+    let tail: String = secret.chars().skip(len - 4).collect();
+    format!("{}{}{}", head, "*".repeat(len - 8), tail)
 }
 
 /// Convert RuleSeverity to FindingSeverity
@@ -131,20 +2551,20 @@ fn convert_severity(sev: RuleSeverity) -> FindingSeverity {
 }
 
 /// Directories to skip during traversal
-fn should_skip_dir(path: &Path) -> bool {
+fn should_skip_dir(path: &Path, extra: &[String]) -> bool {
     let skip_names = [
-        ". git",
+        ".git",
         ".github",
         "node_modules",
-        ". venv",
+        ".venv",
         "venv",
         "__pycache__",
         "target",
-        ". idea",
+        ".idea",
         ".vscode",
         "dist",
         "build",
-        ". next",
+        ".next",
         ".nuxt",
         ".cargo",
         "site-packages",
@@ -152,12 +2572,91 @@ fn should_skip_dir(path: &Path) -> bool {
 
     path.file_name()
         .and_then(|n| n.to_str())
-        .map(|n| skip_names.contains(&n))
+        .map(|n| skip_names.contains(&n) || extra.iter().any(|e| e == n))
         .unwrap_or(false)
 }
 
-/// Detect binary files by extension
-fn is_binary_file(path: &Path) -> bool {
+/// Bytes read from the start of a file to decide whether it is binary
+pub(crate) const SNIFF_LEN: usize = 8 << 10;
+
+/// Whether the start of a file looks binary
+///
+/// Content with a NUL byte or invalid UTF-8 is binary; a character cut off
+/// at the end of the sample doesn't count. Files with a binary extension
+/// such as `.png` must also be free of other control characters to count as
+/// text, so a `.env.jpg` holding plain text is still scanned.
+pub(crate) fn looks_binary(sample: &[u8], binary_extension: bool) -> bool {
+    if sample.contains(&0) {
+        return true;
+    }
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return true,
+    };
+    binary_extension
+        && text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+}
+
+/// Shortest run of printable ASCII matched in a binary file
+const MIN_BINARY_STRING: usize = 8;
+
+/// Match the rules against the printable ASCII strings in binary content
+///
+/// Like `strings`, every run of at least eight printable bytes is scanned
+/// as a line of its own. Line numbers count `\n` bytes and columns are byte
+/// offsets within that line. Multi-line rules and inline suppressions don't
+/// apply.
+pub(crate) fn scan_binary(
+    bytes: &[u8],
+    path_str: &str,
+    rule_set: &CompiledRuleSet,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) -> FileStats {
+    let printable = |b: u8| b == b'\t' || (0x20..0x7f).contains(&b);
+    let mut line_no = 1;
+    let mut line_start = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos] == b'\n' {
+            line_no += 1;
+            line_start = pos + 1;
+        }
+        if !printable(bytes[pos]) {
+            pos += 1;
+            continue;
+        }
+
+        let run_start = pos;
+        while pos < bytes.len() && printable(bytes[pos]) {
+            pos += 1;
+        }
+        if pos - run_start < MIN_BINARY_STRING {
+            continue;
+        }
+        let run = std::str::from_utf8(&bytes[run_start..pos]).expect("printable ASCII");
+        let start = findings.len();
+        scan_line(path_str, line_no, run, rule_set, options, findings);
+        let shift = run_start - line_start;
+        for f in &mut findings[start..] {
+            f.column += shift;
+            f.end_column += shift;
+        }
+    }
+    FileStats {
+        bytes: bytes.len() as u64,
+        lines: line_no,
+        errors: Vec::new(),
+    }
+}
+
+/// Whether a file's extension belongs to a binary format
+pub(crate) fn has_binary_extension(path: &Path) -> bool {
     let skip_exts = [
         "jpg", "jpeg", "png", "gif", "bmp", "svg", "ico", "webp", "zip", "tar", "gz", "rar", "7z",
         "exe", "dll", "so", "dylib", "bin", "o", "a", "lib", "pdf", "doc", "docx", "xls", "xlsx",
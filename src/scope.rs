@@ -0,0 +1,84 @@
+//! Rule settings scoped to parts of the tree
+//!
+//! `[[overrides]]` sections of `fastsecret.toml` give different parts of a
+//! monorepo different rules in a single scan:
+//!
+//! ```toml
+//! [[overrides]]
+//! paths = ["infra/**"]
+//! ignore_rules = ["Generic High-Entropy Secret"]
+//! extra_rules = "infra-rules.yaml"
+//! ```
+//!
+//! Files matching `paths` skip the `ignore_rules`, and the rules loaded
+//! from `extra_rules` run only in those files. A file covered by several
+//! scopes gets the settings of all of them.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Rule settings for the files under some paths
+#[derive(Debug, Clone)]
+pub struct RuleScope {
+    paths: Vec<String>,
+    globs: GlobSet,
+    /// Rules, by id or name, that don't run in these files
+    pub ignore_rules: Vec<String>,
+    /// Ids of rules that run only in the files of this scope (or of
+    /// another scope listing them)
+    pub extra_rules: Vec<String>,
+}
+
+impl RuleScope {
+    /// A scope covering the files matching `paths`, globs relative to the
+    /// scan root
+    pub fn new(
+        paths: &[String],
+        ignore_rules: Vec<String>,
+        extra_rules: Vec<String>,
+    ) -> Result<Self> {
+        let mut globs = GlobSetBuilder::new();
+        for glob in paths {
+            globs.add(
+                Glob::new(glob.trim_start_matches("./"))
+                    .with_context(|| format!("invalid override path '{}'", glob))?,
+            );
+        }
+        Ok(RuleScope {
+            paths: paths.to_vec(),
+            globs: globs.build()?,
+            ignore_rules,
+            extra_rules,
+        })
+    }
+
+    /// The globs the scope was created with
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// Whether the scope applies to `path`, relative to the scan root
+    pub fn covers(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        self.globs.is_match(path.trim_start_matches("./"))
+    }
+}
+
+/// Rules to skip in the file at `path`: those ignored by a scope covering
+/// it, and the extra rules of scopes that don't
+pub(crate) fn ignored_rules(scopes: &[RuleScope], path: &str) -> Vec<String> {
+    let (covering, others): (Vec<&RuleScope>, Vec<&RuleScope>) =
+        scopes.iter().partition(|scope| scope.covers(path));
+    let mut ignored: Vec<String> = covering
+        .iter()
+        .flat_map(|scope| scope.ignore_rules.iter().cloned())
+        .collect();
+    ignored.extend(
+        others
+            .iter()
+            .flat_map(|scope| &scope.extra_rules)
+            .filter(|id| !covering.iter().any(|scope| scope.extra_rules.contains(id)))
+            .cloned(),
+    );
+    ignored
+}
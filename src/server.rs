@@ -0,0 +1,255 @@
+//! HTTP scanning service
+//!
+//! `fastsecret serve` keeps one compiled [`Scanner`] in memory and answers
+//! scan requests over HTTP, so services that scan often don't pay for
+//! process startup and rule compilation on every call:
+//!
+//! - `POST /scan` scans the request body — text, or a tarball (`.tar` or
+//!   gzipped) — and returns `{"findings": [...], "summary": {...}}`, with
+//!   findings in the same shape as `--format json`. `?name=` labels the
+//!   findings' file.
+//! - `GET /rules` lists the loaded rules as `fastsecret rules list --format
+//!   json` does.
+//! - `GET /health` reports that the server is up and how many rules it has.
+//! - `GET /metrics` exports scan counters in the Prometheus text format
+//!   (see [`metrics`](crate::metrics)).
+//!
+//! Errors are returned as `{"error": "..."}` with a 4xx or 5xx status.
+
+use std::io::{Cursor, Read};
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::archive::{scan_tar_stream, ArchiveKind};
+use crate::metrics::{self, Metrics};
+use crate::report::json::JsonFinding;
+use crate::rules::validators::percent_decode;
+use crate::rules::Rule;
+use crate::scanner::{Finding, ScanSummary, Scanner};
+
+/// Label of scanned bodies without a `?name=`
+pub const DEFAULT_NAME: &str = "request";
+
+/// Settings of the HTTP service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerOptions {
+    /// Requests handled at once
+    pub workers: usize,
+    /// Largest request body accepted, in bytes
+    pub max_body_size: u64,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        ServerOptions {
+            workers: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            max_body_size: 64 << 20,
+        }
+    }
+}
+
+/// A scanner listening for HTTP requests
+pub struct Server {
+    http: tiny_http::Server,
+    scanner: Scanner,
+    options: ServerOptions,
+    metrics: Metrics,
+}
+
+/// Response body of `POST /scan`
+#[derive(Serialize)]
+struct ScanResponse<'a> {
+    findings: Vec<JsonFinding<'a>>,
+    summary: ScanSummary,
+}
+
+/// A failed request, answered with `status` and `{"error": message}`
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        HttpError {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl Server {
+    /// Listen on `addr`, such as `127.0.0.1:8080` (port 0 picks a free one)
+    pub fn bind(addr: &str, scanner: Scanner, options: ServerOptions) -> Result<Self> {
+        let http = tiny_http::Server::http(addr)
+            .map_err(|e| anyhow!("failed to listen on {}: {}", addr, e))?;
+        Ok(Server {
+            http,
+            scanner,
+            options,
+            metrics: Metrics::new(),
+        })
+    }
+
+    /// Counters served at `GET /metrics`
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Handle requests until [`shutdown`](Self::shutdown) is called
+    pub fn run(&self) {
+        std::thread::scope(|scope| {
+            for _ in 0..self.options.workers.max(1) {
+                scope.spawn(|| {
+                    while let Ok(request) = self.http.recv() {
+                        self.handle(request);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Make [`run`](Self::run) return once the requests in flight finish
+    pub fn shutdown(&self) {
+        for _ in 0..self.options.workers.max(1) {
+            self.http.unblock();
+        }
+    }
+
+    fn handle(&self, mut request: Request) {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        if (request.method(), path) == (&Method::Get, "/metrics") {
+            let content_type = Header::from_bytes("Content-Type", metrics::CONTENT_TYPE)
+                .expect("static header is valid");
+            let response = Response::from_string(self.metrics.render()).with_header(content_type);
+            let _ = request.respond(response);
+            return;
+        }
+        let result = match (request.method(), path) {
+            (Method::Get, "/health") => Ok(json!({
+                "status": "ok",
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": self.scanner.rule_set().rules().len(),
+            })),
+            (Method::Get, "/rules") => {
+                let rules: Vec<&Rule> = self
+                    .scanner
+                    .rule_set()
+                    .rules()
+                    .iter()
+                    .map(|c| &c.rule)
+                    .collect();
+                serde_json::to_value(rules).map_err(|e| HttpError::new(500, e.to_string()))
+            }
+            (Method::Post, "/scan") => self
+                .scan(&mut request, query)
+                .inspect_err(|_| self.metrics.record_error("request")),
+            (_, "/health" | "/metrics" | "/rules" | "/scan") => {
+                Err(HttpError::new(405, "method not allowed"))
+            }
+            _ => Err(HttpError::new(404, format!("no such endpoint: {}", path))),
+        };
+        let (status, body) = match result {
+            Ok(body) => (200, body),
+            Err(e) => (e.status, json!({ "error": e.message })),
+        };
+        let content_type =
+            Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+        // The client hung up; nothing left to tell it
+        let _ = request.respond(response);
+    }
+
+    fn scan(&self, request: &mut Request, query: &str) -> Result<serde_json::Value, HttpError> {
+        let name = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("name="))
+            .map(|name| String::from_utf8_lossy(&percent_decode(name)).into_owned())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| DEFAULT_NAME.to_string());
+        let content_type = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Content-Type"))
+            .map(|h| h.value.as_str().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let limit = self.options.max_body_size;
+        let mut body = Vec::new();
+        request
+            .as_reader()
+            .take(limit.saturating_add(1))
+            .read_to_end(&mut body)
+            .map_err(|e| HttpError::new(400, format!("failed to read body: {}", e)))?;
+        if body.len() as u64 > limit {
+            return Err(HttpError::new(413, format!("body exceeds {} bytes", limit)));
+        }
+
+        let started = Instant::now();
+        let findings = self.scan_body(&name, &content_type, &body)?;
+        self.metrics.record_scan(started.elapsed(), &findings);
+        let response = ScanResponse {
+            findings: findings.iter().map(JsonFinding::from).collect(),
+            summary: ScanSummary::from_findings(&findings),
+        };
+        serde_json::to_value(response).map_err(|e| HttpError::new(500, e.to_string()))
+    }
+
+    fn scan_body(
+        &self,
+        name: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Vec<Finding>, HttpError> {
+        let gzip = body.starts_with(&[0x1f, 0x8b]);
+        let tar = body.get(257..262) == Some(b"ustar".as_slice());
+        let archive = ArchiveKind::from_name(name).filter(|k| *k != ArchiveKind::Zip);
+        if !(gzip || tar || archive.is_some() || content_type.contains("tar")) {
+            return self
+                .scanner
+                .scan_bytes(name, body)
+                .map(|report| report.findings)
+                .map_err(|e| HttpError::new(422, format!("{:#}", e)));
+        }
+
+        let options = self.scanner.options();
+        let limits = options.archives.unwrap_or_default();
+        let mut findings = Vec::new();
+        let result = if gzip {
+            scan_tar_stream(
+                GzDecoder::new(body),
+                name,
+                self.scanner.rule_set(),
+                options,
+                &limits,
+                &mut findings,
+            )
+        } else {
+            scan_tar_stream(
+                Cursor::new(body),
+                name,
+                self.scanner.rule_set(),
+                options,
+                &limits,
+                &mut findings,
+            )
+        };
+        result
+            .map(|_| findings)
+            .map_err(|e| HttpError::new(422, format!("failed to read tarball: {}", e)))
+    }
+}
@@ -0,0 +1,233 @@
+//! Source map resolution for minified files
+//!
+//! A secret found in `app.min.js` is more useful reported against the file
+//! it was written in. With source maps enabled, findings in a file that
+//! names its map in a `sourceMappingURL` comment (or has a `.map` file next
+//! to it) are resolved through the map's `mappings` to the original source
+//! file, line, and column.
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::scanner::Finding;
+
+/// Largest source map that is read
+const MAX_MAP_SIZE: u64 = 64 << 20;
+
+/// Where a finding in generated code was written originally
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OriginalLocation {
+    /// Source file, as named by the source map
+    pub file: String,
+    /// 1-based line in the source file
+    pub line: usize,
+    /// 1-based column in the source file
+    pub column: usize,
+}
+
+impl std::fmt::Display for OriginalLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// One mapping: a generated column and the source position it came from
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    column: usize,
+    source: usize,
+    line: usize,
+    source_column: usize,
+}
+
+/// A parsed version 3 source map
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// Segments of each generated line, ordered by column
+    lines: Vec<Vec<Segment>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Parse the JSON of a source map
+    ///
+    /// Index maps (with `sections`) aren't supported.
+    pub fn parse(json: &str) -> Result<Self> {
+        let raw: RawMap = serde_json::from_str(json).context("invalid source map")?;
+        if raw.version != 3 {
+            bail!("unsupported source map version {}", raw.version);
+        }
+        let root = raw
+            .source_root
+            .filter(|r| !r.is_empty())
+            .map(|r| format!("{}/", r.trim_end_matches('/')));
+        let sources = raw
+            .sources
+            .into_iter()
+            .map(|s| {
+                let s = s.unwrap_or_default();
+                match &root {
+                    Some(root) => format!("{}{}", root, s),
+                    None => s,
+                }
+            })
+            .collect();
+
+        // Fields after the first are deltas, carried across lines except
+        // the generated column
+        let (mut source, mut line, mut source_column) = (0i64, 0i64, 0i64);
+        let mut lines = Vec::new();
+        for generated in raw.mappings.split(';') {
+            let mut column = 0i64;
+            let mut segments = Vec::new();
+            for segment in generated.split(',').filter(|s| !s.is_empty()) {
+                let fields = decode_vlq(segment).context("invalid source map mappings")?;
+                column += fields[0];
+                if fields.len() < 4 {
+                    continue;
+                }
+                source += fields[1];
+                line += fields[2];
+                source_column += fields[3];
+                if column < 0 || source < 0 || line < 0 || source_column < 0 {
+                    bail!("invalid source map mappings");
+                }
+                segments.push(Segment {
+                    column: column as usize,
+                    source: source as usize,
+                    line: line as usize,
+                    source_column: source_column as usize,
+                });
+            }
+            segments.sort_by_key(|s| s.column);
+            lines.push(segments);
+        }
+        Ok(SourceMap { sources, lines })
+    }
+
+    /// The source position of a 1-based generated line and column
+    pub fn lookup(&self, line: usize, column: usize) -> Option<OriginalLocation> {
+        let segments = self.lines.get(line.checked_sub(1)?)?;
+        let idx = segments.partition_point(|s| s.column < column);
+        let segment = segments.get(idx.checked_sub(1)?)?;
+        Some(OriginalLocation {
+            file: self.sources.get(segment.source)?.clone(),
+            line: segment.line + 1,
+            column: segment.source_column + 1 + (column - 1 - segment.column),
+        })
+    }
+}
+
+/// Decode one base64 VLQ segment into its fields
+fn decode_vlq(segment: &str) -> Option<Vec<i64>> {
+    let mut fields = Vec::new();
+    let mut value = 0i64;
+    let mut shift = 0;
+    for c in segment.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as i64;
+        if shift > 60 {
+            return None;
+        }
+        value |= (digit & 31) << shift;
+        shift += 5;
+        if digit & 32 == 0 {
+            let magnitude = value >> 1;
+            fields.push(if value & 1 == 1 {
+                -magnitude
+            } else {
+                magnitude
+            });
+            value = 0;
+            shift = 0;
+        }
+    }
+    (shift == 0 && !fields.is_empty()).then_some(fields)
+}
+
+/// The map named by a `sourceMappingURL` comment on `line`, if any
+pub(crate) fn mapping_url(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let rest = line
+        .strip_prefix("//# sourceMappingURL=")
+        .or_else(|| line.strip_prefix("//@ sourceMappingURL="))
+        .or_else(|| {
+            line.strip_prefix("/*# sourceMappingURL=")
+                .and_then(|r| r.strip_suffix("*/"))
+        })?;
+    Some(rest.trim()).filter(|url| !url.is_empty())
+}
+
+/// Load the source map of the file at `path`, named by `url` or else found
+/// next to it as `<file>.map`
+fn load(path: &Path, url: Option<&str>) -> Result<SourceMap> {
+    if let Some(data) = url.and_then(|u| u.strip_prefix("data:")) {
+        let Some((_, encoded)) = data.split_once(";base64,") else {
+            bail!("unsupported source map data URL");
+        };
+        let json = STANDARD
+            .decode(encoded)
+            .context("invalid source map data URL")?;
+        return SourceMap::parse(&String::from_utf8_lossy(&json));
+    }
+    let map_path = match url {
+        Some(url) if url.contains("://") => bail!("remote source map '{}' not fetched", url),
+        Some(url) => path.with_file_name(url),
+        None => {
+            let mut name = path.file_name().unwrap_or_default().to_os_string();
+            name.push(".map");
+            path.with_file_name(name)
+        }
+    };
+    let size = fs::metadata(&map_path)
+        .with_context(|| format!("no source map at '{}'", map_path.display()))?
+        .len();
+    if size > MAX_MAP_SIZE {
+        bail!("source map '{}' is too large", map_path.display());
+    }
+    SourceMap::parse(&fs::read_to_string(&map_path)?)
+}
+
+/// Resolve findings in the file at `path_str` to their original location
+///
+/// `url` is the file's `sourceMappingURL`, if it has one. A file without a
+/// usable map is left alone, and the reason logged if it named one.
+pub(crate) fn resolve(path_str: &str, url: Option<&str>, findings: &mut [Finding]) {
+    if findings.is_empty() {
+        return;
+    }
+    let map = match load(Path::new(path_str), url) {
+        Ok(map) => map,
+        Err(e) => {
+            // Most files have no map; only say why a named one failed
+            if url.is_some() {
+                tracing::info!(file = path_str, "Source map for {}: {:#}", path_str, e);
+            }
+            return;
+        }
+    };
+    for finding in findings {
+        finding.original = map.lookup(finding.line, finding.column);
+    }
+}
@@ -0,0 +1,337 @@
+//! Language-aware filtering of source files
+//!
+//! Behind the `syntax` feature, Python, JavaScript, TypeScript, Go, and Rust
+//! sources are parsed with tree-sitter once read. A credential in code is a
+//! string literal, so matches elsewhere — identifiers that happen to look
+//! random, hashes in comments, generated constants — are dropped. Findings
+//! inside a literal assigned to a name are tagged with that name, and
+//! literals assigned to names like `db_password` or `apiToken` are reported
+//! even when no rule matched them.
+//!
+//! Files that don't parse cleanly are left alone.
+
+use std::ops::Range;
+use std::path::Path;
+
+use tree_sitter::{Node, Parser};
+
+use crate::confidence::{self, PLACEHOLDER_MARKERS};
+use crate::entropy::shannon_entropy;
+use crate::metadata;
+use crate::rules::{Rule, RuleSeverity};
+use crate::scanner::{drop_suppressed, locate, Finding, FindingSeverity, ScanOptions};
+use crate::terraform::is_sensitive_name;
+
+/// Rule id reported for string literals assigned to credential-like names
+pub const ASSIGNMENT_RULE_ID: &str = "secret-assignment";
+
+/// Name reported for string literals assigned to credential-like names
+pub const ASSIGNMENT_RULE_NAME: &str = "Hardcoded Secret Assignment";
+
+/// Remediation reported for string literals assigned to credential-like names
+pub const ASSIGNMENT_REMEDIATION: &str = "Rotate the value, then load it from the environment \
+    or a secrets manager at runtime instead of committing it";
+
+/// The rule literals assigned to credential-like names are reported under,
+/// see [`parser_rules`](crate::rules::parser_rules)
+pub fn rule() -> Rule {
+    Rule {
+        id: ASSIGNMENT_RULE_ID.to_string(),
+        name: ASSIGNMENT_RULE_NAME.to_string(),
+        severity: RuleSeverity::Medium,
+        tags: vec!["generic".to_string()],
+        description: Some(
+            "Random-looking string literal assigned to a credential-like name in parsed source"
+                .to_string(),
+        ),
+        file_patterns: [
+            "*.py", "*.pyw", "*.js", "*.mjs", "*.cjs", "*.jsx", "*.ts", "*.mts", "*.cts", "*.tsx",
+            "*.go", "*.rs",
+        ]
+        .map(String::from)
+        .into(),
+        remediation: Some(ASSIGNMENT_REMEDIATION.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Shortest literal reported for a credential-like name
+const MIN_ASSIGNED_LEN: usize = 8;
+
+/// Least entropy, in bits per character, of a literal reported for a
+/// credential-like name; rules out words like `"password"`
+const MIN_ASSIGNED_ENTROPY: f64 = 3.0;
+
+/// Fields naming the target of an assignment, declaration, or key-value pair
+const NAME_FIELDS: &[&str] = &["left", "name", "key", "pattern", "field"];
+
+/// How many ancestors of a literal are searched for the name it is
+/// assigned to, enough to see through parentheses and expression lists
+const MAX_ASSIGNMENT_DEPTH: usize = 2;
+
+/// Languages whose sources are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Language {
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    Rust,
+}
+
+impl Language {
+    pub(crate) fn for_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "py" | "pyw" => Some(Language::Python),
+            "js" | "mjs" | "cjs" | "jsx" => Some(Language::JavaScript),
+            "ts" | "mts" | "cts" => Some(Language::TypeScript),
+            "tsx" => Some(Language::Tsx),
+            "go" => Some(Language::Go),
+            "rs" => Some(Language::Rust),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Language::Go => tree_sitter_go::LANGUAGE.into(),
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+        }
+    }
+
+    fn is_string(self, kind: &str) -> bool {
+        match self {
+            Language::Python => kind == "string",
+            Language::JavaScript | Language::TypeScript | Language::Tsx => {
+                matches!(kind, "string" | "template_string")
+            }
+            Language::Go => matches!(kind, "interpreted_string_literal" | "raw_string_literal"),
+            Language::Rust => matches!(kind, "string_literal" | "raw_string_literal"),
+        }
+    }
+}
+
+/// A string literal in the file
+#[derive(Debug)]
+struct Literal {
+    /// Byte range of the literal, quotes included
+    span: Range<usize>,
+    /// Name the literal is assigned to, if any
+    name: Option<String>,
+}
+
+/// Drop the findings in `findings[file_start..]` outside string literals,
+/// tag the rest with the name their literal is assigned to, and report
+/// literals assigned to credential-like names
+pub(crate) fn scan_file(
+    language: Language,
+    content: &str,
+    file_start: usize,
+    path_str: &str,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    let mut parser = Parser::new();
+    if parser.set_language(&language.grammar()).is_err() {
+        return;
+    }
+    let Some(tree) = parser.parse(content, None) else {
+        return;
+    };
+    if tree.root_node().has_error() {
+        tracing::debug!("Couldn't parse {}; skipping syntax checks", path_str);
+        return;
+    }
+
+    let mut literals = Vec::new();
+    collect_literals(tree.root_node(), language, content, &mut literals);
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_at = |idx: usize| {
+        content[line_starts[idx]..]
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('\r')
+    };
+
+    // Byte offset in the file of each finding, from its line and column
+    let candidates = findings.split_off(file_start);
+    let mut reported = Vec::new();
+    for mut finding in candidates {
+        let offset = finding.line.checked_sub(1).and_then(|idx| {
+            let start = *line_starts.get(idx)?;
+            let column = line_at(idx)
+                .char_indices()
+                .nth(finding.column.saturating_sub(1))
+                .map_or(0, |(i, _)| i);
+            Some(start + column)
+        });
+        let literal = offset.and_then(|offset| {
+            literals
+                .iter()
+                .find(|l| l.span.start < offset && offset < l.span.end)
+        });
+        match literal {
+            Some(literal) => {
+                if finding.variable.is_none() {
+                    finding.variable = literal.name.clone();
+                }
+                reported.push(literal.span.start);
+                findings.push(finding);
+            }
+            None if offset.is_none() => findings.push(finding),
+            None => tracing::debug!(
+                rule = %finding.rule_id,
+                "Dropped '{}' at {}:{} outside a string literal",
+                finding.rule_name, path_str, finding.line
+            ),
+        }
+    }
+
+    let ignored = options.min_severity > FindingSeverity::Medium || !options.rule_enabled(&rule());
+    if ignored {
+        return;
+    }
+    for literal in &literals {
+        let Some(name) = &literal.name else {
+            continue;
+        };
+        if reported.contains(&literal.span.start) || !is_sensitive_name(&snake_case(name)) {
+            continue;
+        }
+        let value = unquote(&content[literal.span.clone()]);
+        if !is_secret_like(value) {
+            continue;
+        }
+        let confidence = confidence::score(None, value, options.relative(path_str));
+        if confidence < options.min_confidence {
+            continue;
+        }
+
+        let value_start = literal.span.start
+            + content[literal.span.clone()]
+                .find(value)
+                .unwrap_or_default();
+        let line_idx = line_starts.partition_point(|&s| s <= value_start) - 1;
+        let offset = line_starts[line_idx];
+        let line = line_at(line_idx);
+        let span = value_start - offset..(value_start - offset + value.len()).min(line.len());
+        let (column, end_column, snippet) = locate(line, span, options.redact);
+        let start = findings.len();
+        findings.push(Finding {
+            file: path_str.to_string(),
+            line: line_idx + 1,
+            column,
+            end_column,
+            snippet,
+            context: Vec::new(),
+            rule_id: ASSIGNMENT_RULE_ID.to_string(),
+            rule_name: ASSIGNMENT_RULE_NAME.to_string(),
+            severity: FindingSeverity::Medium,
+            confidence,
+            remediation: Some(ASSIGNMENT_REMEDIATION.to_string()),
+            rule_source: None,
+            rule_version: None,
+            secret_sha256: None,
+            secret: value.to_string(),
+            commit: None,
+            relative_file: None,
+            verification: None,
+            layer: None,
+            cell: None,
+            original: None,
+            other_locations: Vec::new(),
+            variable: Some(name.clone()),
+            metadata: metadata::extract(value),
+        });
+        let prev = line_idx.checked_sub(1).map(line_at);
+        drop_suppressed(findings, start, line, prev);
+    }
+    findings[file_start..].sort_by_key(|f| (f.line, f.column));
+}
+
+/// Every string literal under `node`, outermost first, with the name each
+/// is assigned to
+fn collect_literals(node: Node, language: Language, content: &str, out: &mut Vec<Literal>) {
+    if language.is_string(node.kind()) {
+        out.push(Literal {
+            span: node.byte_range(),
+            name: assigned_name(node, content),
+        });
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_literals(child, language, content, out);
+    }
+}
+
+/// The name a literal is the value of, from the nearest assignment,
+/// declaration, keyword argument, or key-value pair around it
+fn assigned_name(literal: Node, content: &str) -> Option<String> {
+    let mut node = literal;
+    for _ in 0..MAX_ASSIGNMENT_DEPTH {
+        node = node.parent()?;
+        for field in NAME_FIELDS {
+            let Some(target) = node.child_by_field_name(field) else {
+                continue;
+            };
+            // The literal is the key itself, not the value
+            if target.byte_range().contains(&literal.start_byte()) {
+                return None;
+            }
+            // The last identifier of `self.api_key` or `env["API_KEY"]`
+            let text = target.utf8_text(content.as_bytes()).ok()?;
+            return text
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .rfind(|part| !part.is_empty())
+                .map(str::to_string);
+        }
+    }
+    None
+}
+
+/// Text of a literal without its prefix, quotes, and raw-string hashes
+fn unquote(text: &str) -> &str {
+    text.trim_start_matches(|c: char| c.is_ascii_alphabetic() || c == '#')
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`' | '#'))
+}
+
+/// `apiToken` and `API-TOKEN` as `api_token`, so camelCase names are
+/// recognized like snake_case ones
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(if c == '-' {
+            '_'
+        } else {
+            c.to_ascii_lowercase()
+        });
+    }
+    out
+}
+
+/// Whether a literal assigned to a credential-like name could be one
+fn is_secret_like(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    value.len() >= MIN_ASSIGNED_LEN
+        && !value.chars().any(char::is_whitespace)
+        && !value.contains("${")
+        && !value.contains("{{")
+        && !value.contains("://")
+        && !PLACEHOLDER_MARKERS.iter().any(|m| lower.contains(m))
+        && shannon_entropy(value) >= MIN_ASSIGNED_ENTROPY
+}
@@ -0,0 +1,76 @@
+//! OpenTelemetry export of scan traces
+//!
+//! The scan pipeline is instrumented with [`tracing`] spans, which cost next
+//! to nothing until a subscriber is installed:
+//!
+//! - `scan` (info): one per scanned directory or file, with the file count
+//! - `walk` (info): listing the files under a directory
+//! - `scan_file` (debug): one per file
+//! - `match_rules` (trace): the candidate rules run against one line
+//!
+//! [`Telemetry::layer`] sends the spans to an OTLP collector, so large
+//! scheduled scans can be profiled in an existing tracing backend. Install it
+//! with [`logging::init`](crate::logging::init), or in a subscriber of your
+//! own.
+
+use anyhow::{Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Path the collector receives traces on
+const TRACES_PATH: &str = "/v1/traces";
+
+/// An OTLP exporter; dropping it flushes the spans still queued
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+impl Telemetry {
+    /// Export spans to the OTLP/HTTP collector at `endpoint`, such as
+    /// `http://localhost:4318`
+    pub fn new(endpoint: &str) -> Result<Self> {
+        let endpoint = endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with(TRACES_PATH) {
+            endpoint.to_string()
+        } else {
+            format!("{}{}", endpoint, TRACES_PATH)
+        };
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(url)
+            .build()
+            .context("failed to create the OTLP exporter")?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(env!("CARGO_PKG_NAME"))
+                    .build(),
+            )
+            .build();
+        Ok(Telemetry { provider })
+    }
+
+    /// A subscriber layer passing spans at `level` and above to the exporter
+    pub fn layer<S>(&self, level: Level) -> impl Layer<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        tracing_opentelemetry::layer()
+            .with_tracer(self.provider.tracer(env!("CARGO_PKG_NAME")))
+            .with_filter(LevelFilter::from_level(level))
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        // An unreachable collector shouldn't fail the scan
+        let _ = self.provider.shutdown();
+    }
+}
@@ -0,0 +1,591 @@
+//! Terraform state and variable files
+//!
+//! `terraform.tfstate` holds every resource attribute in plain text,
+//! including database passwords and provider-issued keys, and `.tfvars`
+//! files often assign credentials inside nested objects or heredocs that a
+//! line regex can't tie to a name. These files are parsed structurally:
+//! string values that Terraform marks sensitive, or whose attribute name
+//! says they are credentials, are reported with their resource address
+//! (`module.db.aws_db_instance.main.password`, `db.password`). Findings the
+//! rules already made on such a value are tagged with the address instead.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::confidence;
+use crate::metadata;
+use crate::rules::{Rule, RuleSeverity};
+use crate::scanner::{drop_suppressed, locate, Finding, FindingSeverity, ScanOptions};
+
+/// Rule id reported for sensitive values found by parsing Terraform files
+pub const TERRAFORM_RULE_ID: &str = "terraform-sensitive-value";
+
+/// Name reported for sensitive values found by parsing Terraform files
+pub const TERRAFORM_RULE_NAME: &str = "Terraform Sensitive Value";
+
+/// Remediation reported for sensitive values found by parsing Terraform files
+pub const TERRAFORM_REMEDIATION: &str = "Rotate the value with its provider, then keep state in \
+    an encrypted remote backend and pass secrets through TF_VAR_ environment variables instead \
+    of committed files";
+
+/// The rule sensitive values found by parsing Terraform files are reported
+/// under, see [`parser_rules`](crate::rules::parser_rules)
+pub fn rule() -> Rule {
+    Rule {
+        id: TERRAFORM_RULE_ID.to_string(),
+        name: TERRAFORM_RULE_NAME.to_string(),
+        severity: RuleSeverity::High,
+        tags: vec!["generic".to_string(), "iac".to_string()],
+        description: Some(
+            "Value Terraform marks sensitive, or a credential attribute, in state or variable files"
+                .to_string(),
+        ),
+        file_patterns: vec![
+            "*.tfstate".to_string(),
+            "*.tfstate.backup".to_string(),
+            "*.tfvars".to_string(),
+            "*.tfvars.json".to_string(),
+        ],
+        remediation: Some(TERRAFORM_REMEDIATION.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Words in an attribute name that mark its value as a credential
+const SENSITIVE_WORDS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "private_key",
+    "access_key",
+    "api_key",
+    "apikey",
+    "credential",
+];
+
+/// Name suffixes for attributes that describe a credential without
+/// holding it, like `secret_arn` or `password_length`
+const DESCRIPTIVE_SUFFIXES: &[&str] = &[
+    "_arn", "_id", "_ids", "_name", "_path", "_file", "_length", "_ttl", "_version", "_type",
+    "_policy", "_enabled", "_count",
+];
+
+/// Kinds of Terraform file that are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileKind {
+    /// `terraform.tfstate` and its `.backup`
+    State,
+    /// HCL `.tfvars`
+    Vars,
+    /// `.tfvars.json`
+    VarsJson,
+}
+
+impl FileKind {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let file_name = Path::new(name).file_name()?.to_str()?.to_ascii_lowercase();
+        if file_name.ends_with(".tfstate") || file_name.ends_with(".tfstate.backup") {
+            Some(FileKind::State)
+        } else if file_name.ends_with(".tfvars.json") {
+            Some(FileKind::VarsJson)
+        } else if file_name.ends_with(".tfvars") {
+            Some(FileKind::Vars)
+        } else {
+            None
+        }
+    }
+}
+
+/// A string value in the file and where it is
+#[derive(Debug)]
+struct Located {
+    address: String,
+    value: String,
+    /// Byte range of the value's text in the file
+    span: Range<usize>,
+    /// Whether to report the value even if no rule matched it
+    sensitive: bool,
+    /// Marked sensitive by Terraform itself, not just by name
+    marked: bool,
+}
+
+/// Whether an attribute called `name` holds a credential
+pub fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_WORDS.iter().any(|w| name.contains(w))
+        && !DESCRIPTIVE_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+/// Values worth reporting; references and interpolations aren't secrets
+fn is_literal(value: &str) -> bool {
+    value.trim().len() >= 4 && !value.contains("${")
+}
+
+/// Report the sensitive values of a Terraform file and tag the findings in
+/// `findings[file_start..]` that lie inside any value with its address
+pub(crate) fn scan_file(
+    kind: FileKind,
+    content: &str,
+    file_start: usize,
+    path_str: &str,
+    options: &ScanOptions,
+    findings: &mut Vec<Finding>,
+) {
+    // Values holding a secret some rule already found are located too, so
+    // those findings get an address
+    let found: Vec<String> = findings[file_start..]
+        .iter()
+        .map(|f| f.secret.clone())
+        .collect();
+    let wanted = |name: &str, value: &str, marked: bool| {
+        marked || is_sensitive_name(name) || found.iter().any(|s| value.contains(s.as_str()))
+    };
+    let values = match kind {
+        FileKind::State => state_values(content, &wanted),
+        FileKind::VarsJson => vars_json_values(content, &wanted),
+        FileKind::Vars => vars_values(content, &wanted),
+    };
+
+    let ignored = !options.rule_enabled(&rule());
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    for value in values {
+        let line_idx = line_starts.partition_point(|&s| s <= value.span.start) - 1;
+        let line_no = line_idx + 1;
+        let offset = line_starts[line_idx];
+        let line = content[offset..]
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('\r');
+
+        let mut tagged = false;
+        for finding in &mut findings[file_start..] {
+            if finding.line == line_no && value.value.contains(finding.secret.as_str()) {
+                finding.variable = Some(value.address.clone());
+                tagged = true;
+            }
+        }
+        if tagged || !value.sensitive || ignored {
+            continue;
+        }
+
+        let severity = if value.marked {
+            FindingSeverity::High
+        } else {
+            FindingSeverity::Medium
+        };
+        let confidence = confidence::score(None, &value.value, options.relative(path_str));
+        if severity < options.min_severity || confidence < options.min_confidence {
+            continue;
+        }
+        let span = value.span.start - offset..(value.span.end - offset).min(line.len());
+        let (column, end_column, snippet) = locate(line, span, options.redact);
+        let start = findings.len();
+        findings.push(Finding {
+            file: path_str.to_string(),
+            line: line_no,
+            column,
+            end_column,
+            snippet,
+            context: Vec::new(),
+            rule_id: TERRAFORM_RULE_ID.to_string(),
+            rule_name: TERRAFORM_RULE_NAME.to_string(),
+            severity,
+            confidence,
+            remediation: Some(TERRAFORM_REMEDIATION.to_string()),
+            rule_source: None,
+            rule_version: None,
+            secret_sha256: None,
+            secret: value.value.clone(),
+            commit: None,
+            relative_file: None,
+            verification: None,
+            layer: None,
+            cell: None,
+            original: None,
+            other_locations: Vec::new(),
+            variable: Some(value.address),
+            metadata: metadata::extract(&value.value),
+        });
+        let prev = line_idx
+            .checked_sub(1)
+            .map(|i| content[line_starts[i]..offset].trim_end_matches(['\n', '\r']));
+        drop_suppressed(findings, start, line, prev);
+    }
+}
+
+/// Sensitive values of a state file: resource attributes and sensitive
+/// outputs
+fn state_values(content: &str, wanted: &dyn Fn(&str, &str, bool) -> bool) -> Vec<Located> {
+    let Ok(state) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    let mut strings = Vec::new();
+
+    if let Some(outputs) = state["outputs"].as_object() {
+        for (name, output) in outputs {
+            let marked = output["sensitive"].as_bool().unwrap_or(false);
+            if let Some(value) = output["value"].as_str() {
+                if wanted(name, value, marked) {
+                    strings.push(Found {
+                        address: format!("output.{}", name),
+                        key: "value".to_string(),
+                        value: value.to_string(),
+                        marked,
+                    });
+                }
+            }
+        }
+    }
+
+    for resource in state["resources"].as_array().into_iter().flatten() {
+        let mut address = String::new();
+        if let Some(module) = resource["module"].as_str() {
+            address.push_str(module);
+            address.push('.');
+        }
+        if resource["mode"] == "data" {
+            address.push_str("data.");
+        }
+        address.push_str(resource["type"].as_str().unwrap_or_default());
+        address.push('.');
+        address.push_str(resource["name"].as_str().unwrap_or_default());
+
+        for instance in resource["instances"].as_array().into_iter().flatten() {
+            let mut instance_address = address.clone();
+            match &instance["index_key"] {
+                Value::Number(n) => instance_address.push_str(&format!("[{}]", n)),
+                Value::String(s) => instance_address.push_str(&format!("[{:?}]", s)),
+                _ => {}
+            }
+            let marked = sensitive_paths(&instance["sensitive_attributes"]);
+            walk(
+                &instance["attributes"],
+                &instance_address,
+                "",
+                &marked,
+                wanted,
+                &mut strings,
+            );
+        }
+    }
+    locate_json(content, strings)
+}
+
+/// Attribute paths listed in an instance's `sensitive_attributes`, in the
+/// form [`walk`] builds (`password`, `connection[0].secret`)
+fn sensitive_paths(attributes: &Value) -> HashSet<String> {
+    let mut paths = HashSet::new();
+    for steps in attributes.as_array().into_iter().flatten() {
+        let mut path = String::new();
+        for step in steps.as_array().into_iter().flatten() {
+            match (step["type"].as_str(), &step["value"]) {
+                (Some("get_attr"), Value::String(name)) => push_key(&mut path, name),
+                (Some("index"), index) => match &index["value"] {
+                    Value::Number(n) => path.push_str(&format!("[{}]", n)),
+                    Value::String(key) => push_key(&mut path, key),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        paths.insert(path);
+    }
+    paths
+}
+
+fn push_key(path: &mut String, key: &str) {
+    if !path.is_empty() && !key.starts_with('[') {
+        path.push('.');
+    }
+    path.push_str(key);
+}
+
+/// A string value found in parsed JSON, before it is located in the text
+struct Found {
+    address: String,
+    /// Object key the string is stored under
+    key: String,
+    value: String,
+    marked: bool,
+}
+
+/// Collect the wanted string leaves below `value`
+fn walk(
+    value: &Value,
+    address: &str,
+    path: &str,
+    marked: &HashSet<String>,
+    wanted: &dyn Fn(&str, &str, bool) -> bool,
+    out: &mut Vec<Found>,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let mut child_path = path.to_string();
+                push_key(&mut child_path, key);
+                walk(child, address, &child_path, marked, wanted, out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let child_path = format!("{}[{}]", path, i);
+                walk(child, address, &child_path, marked, wanted, out);
+            }
+        }
+        Value::String(s) => {
+            let key = path
+                .rsplit('.')
+                .next()
+                .unwrap_or(path)
+                .split('[')
+                .next()
+                .unwrap_or_default();
+            let is_marked = marked.contains(path);
+            if !s.is_empty() && wanted(key, s, is_marked) {
+                let address = match (address.is_empty(), path.is_empty()) {
+                    (true, _) => path.to_string(),
+                    (false, true) => address.to_string(),
+                    (false, false) => format!("{}.{}", address, path),
+                };
+                out.push(Found {
+                    address,
+                    key: key.to_string(),
+                    value: s.clone(),
+                    marked: is_marked,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Wanted values of a `.tfvars.json` file
+fn vars_json_values(content: &str, wanted: &dyn Fn(&str, &str, bool) -> bool) -> Vec<Located> {
+    let Ok(vars) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    let mut strings = Vec::new();
+    walk(&vars, "", "", &HashSet::new(), wanted, &mut strings);
+    locate_json(content, strings)
+}
+
+/// Find each JSON string in the text, preferring an occurrence right after
+/// its key; each occurrence is claimed once
+fn locate_json(content: &str, strings: Vec<Found>) -> Vec<Located> {
+    let mut claimed = HashSet::new();
+    let mut located = Vec::new();
+    for found in strings {
+        let literals = json_literals(&found.value);
+        let keyed = literals.iter().flat_map(|lit| {
+            let key = serde_json::to_string(&found.key).unwrap_or_default();
+            [format!("{}: {}", key, lit), format!("{}:{}", key, lit)]
+                .into_iter()
+                .map(move |needle| (needle.len() - lit.len(), needle))
+        });
+        let bare = literals.iter().map(|lit| (0, lit.clone()));
+        let span = keyed.chain(bare).find_map(|(skip, needle)| {
+            content
+                .match_indices(&needle)
+                .map(|(i, _)| i + skip)
+                .find(|i| !claimed.contains(i))
+                .map(|i| i + 1..i + needle.len() - skip - 1)
+        });
+        let Some(span) = span else {
+            continue;
+        };
+        claimed.insert(span.start - 1);
+        located.push(Located {
+            sensitive: is_literal(&found.value) && (found.marked || is_sensitive_name(&found.key)),
+            marked: found.marked,
+            address: found.address,
+            value: found.value,
+            span,
+        });
+    }
+    located
+}
+
+/// Ways a string may be written in JSON: as serde writes it, and with the
+/// HTML-safe escapes Terraform uses for `<`, `>`, and `&`
+fn json_literals(value: &str) -> Vec<String> {
+    let plain = serde_json::to_string(value).unwrap_or_default();
+    let escaped = plain
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026");
+    if escaped == plain {
+        vec![plain]
+    } else {
+        vec![plain, escaped]
+    }
+}
+
+/// Wanted values of an HCL `.tfvars` file
+///
+/// Understands assignments, nested objects and lists written one entry per
+/// line, and heredocs, which covers how variable files are written in
+/// practice; it is not a full HCL parser.
+fn vars_values(content: &str, wanted: &dyn Fn(&str, &str, bool) -> bool) -> Vec<Located> {
+    let lines: Vec<(usize, &str)> = content
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end_matches(['\n', '\r'])))
+        })
+        .collect();
+    // Open objects and lists, with the next index of a list
+    let mut path: Vec<(String, Option<usize>)> = Vec::new();
+    let mut located = Vec::new();
+    let mut push = |address: String, key: &str, value: String, span: Range<usize>| {
+        if wanted(key, &value, false) {
+            located.push(Located {
+                sensitive: is_literal(&value) && is_sensitive_name(key),
+                marked: false,
+                address,
+                value,
+                span,
+            });
+        }
+    };
+
+    let mut i = 0;
+    while i < lines.len() {
+        let (offset, line) = lines[i];
+        i += 1;
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+        if trimmed.starts_with(['}', ']']) {
+            path.pop();
+            continue;
+        }
+
+        // An element of the enclosing list
+        if let Some((key, Some(index))) = path.last() {
+            let (key, element) = (key.clone(), format!("[{}]", index));
+            if let Some((_, Some(index))) = path.last_mut() {
+                *index += 1;
+            }
+            if trimmed.trim_end() == "{" {
+                path.push((element, None));
+            } else if let Some((value, span)) = quoted(trimmed) {
+                let start = offset + indent;
+                push(
+                    address_of(&path, &element),
+                    &key,
+                    value,
+                    start + span.start..start + span.end,
+                );
+            }
+            continue;
+        }
+
+        let Some((key, rest, rest_start)) = assignment(trimmed) else {
+            continue;
+        };
+        let address = address_of(&path, &key);
+        let rest_offset = offset + indent + rest_start;
+        match rest.trim_end() {
+            "{" => path.push((key, None)),
+            "[" => path.push((key, Some(0))),
+            heredoc if heredoc.starts_with("<<") => {
+                let delimiter = heredoc.trim_start_matches("<<").trim_start_matches('-');
+                let body_start = i;
+                while i < lines.len() && lines[i].1.trim() != delimiter {
+                    i += 1;
+                }
+                let body = &lines[body_start..i];
+                i += 1;
+                let Some(&(first_offset, first)) = body.first() else {
+                    continue;
+                };
+                let value = body
+                    .iter()
+                    .map(|(_, l)| l.trim())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                // Point at the first line of the body
+                let lead = first.len() - first.trim_start().len();
+                let span = first_offset + lead..first_offset + first.trim_end().len();
+                push(address, &key, value, span);
+            }
+            _ => {
+                if let Some((value, span)) = quoted(rest) {
+                    push(
+                        address,
+                        &key,
+                        value,
+                        rest_offset + span.start..rest_offset + span.end,
+                    );
+                }
+            }
+        }
+    }
+    located
+}
+
+/// `key = rest` or `"key": rest`, with the byte offset of `rest`
+fn assignment(line: &str) -> Option<(String, &str, usize)> {
+    let (key, after) = match line.strip_prefix('"') {
+        Some(quoted) => {
+            let end = quoted.find('"')?;
+            (&quoted[..end], &quoted[end + 1..])
+        }
+        None => {
+            let end = line
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-')))
+                .unwrap_or(line.len());
+            (&line[..end], &line[end..])
+        }
+    };
+    if key.is_empty() {
+        return None;
+    }
+    let rest = after.trim_start().strip_prefix(['=', ':'])?;
+    let value = rest.trim_start();
+    Some((key.to_string(), value, line.len() - value.len()))
+}
+
+/// The string literal at the start of `text`, unescaped, and the byte range
+/// of its contents
+fn quoted(text: &str) -> Option<(String, Range<usize>)> {
+    let body = text.strip_prefix('"')?;
+    let mut value = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, 1..i + 1)),
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                value.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    other => other,
+                });
+            }
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Dotted address of `key` inside the open objects and lists of `path`
+fn address_of(path: &[(String, Option<usize>)], key: &str) -> String {
+    let mut address = String::new();
+    for (name, _) in path {
+        push_key(&mut address, name);
+    }
+    push_key(&mut address, key);
+    address
+}
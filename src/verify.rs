@@ -0,0 +1,271 @@
+//! Secret verification
+//!
+//! Checks matched credentials against their provider so findings can be
+//! tagged as verified (live), invalid (revoked or fake), or unknown. Rules
+//! opt in by naming a verifier in their `verifier` field.
+//!
+//! The verification driver and the [`Verifier`] trait are always available
+//! so library users can plug in their own checks. The built-in verifiers for
+//! GitHub, Stripe, and AWS make network calls and are only compiled with the
+//! `verify` cargo feature.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::rules::CompiledRuleSet;
+use crate::scanner::Finding;
+
+/// Outcome of checking a credential against its provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationStatus {
+    /// The provider accepted the credential
+    Verified,
+    /// The provider rejected the credential
+    Invalid,
+    /// The check could not be completed
+    Unknown,
+}
+
+/// A way of checking one kind of credential
+pub trait Verifier: Send + Sync {
+    /// Name rules use in their `verifier` field
+    fn name(&self) -> &str;
+
+    /// Check a finding's secret
+    ///
+    /// `related` holds the other findings from the same file, for
+    /// credentials that come in parts (such as an AWS key id and secret).
+    fn verify(&self, finding: &Finding, related: &[&Finding]) -> VerificationStatus;
+}
+
+/// Settings for a verification run
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyOptions {
+    /// Upper bound on provider requests per second
+    pub requests_per_second: f64,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            requests_per_second: 5.0,
+        }
+    }
+}
+
+/// Verify every finding whose rule names one of `verifiers`
+///
+/// Requests are made one at a time and spaced out to respect
+/// `options.requests_per_second`. Each distinct secret is checked once per
+/// set of related secrets it appears with, since those can decide the
+/// result; checks that end `Unknown` are tried again for the next finding.
+pub fn verify_findings(
+    findings: &mut [Finding],
+    rule_set: &CompiledRuleSet,
+    verifiers: &[Box<dyn Verifier>],
+    options: &VerifyOptions,
+) {
+    let verifier_for_rule: HashMap<&str, &dyn Verifier> = rule_set
+        .rules()
+        .iter()
+        .filter_map(|c| {
+            let name = c.rule.verifier.as_deref()?;
+            let verifier = verifiers.iter().find(|v| v.name() == name)?;
+            Some((c.rule.id.as_str(), verifier.as_ref()))
+        })
+        .collect();
+
+    let interval = Duration::from_secs_f64(1.0 / options.requests_per_second.max(0.001));
+    let mut last_request: Option<Instant> = None;
+    let mut cache: HashMap<(String, String, Vec<String>), VerificationStatus> = HashMap::new();
+    let mut results = Vec::new();
+
+    for (idx, finding) in findings.iter().enumerate() {
+        let Some(verifier) = verifier_for_rule.get(finding.rule_id.as_str()) else {
+            continue;
+        };
+
+        let related: Vec<&Finding> = findings
+            .iter()
+            .filter(|f| f.file == finding.file && !std::ptr::eq(*f, finding))
+            .collect();
+        let mut related_secrets: Vec<String> = related.iter().map(|f| f.secret.clone()).collect();
+        related_secrets.sort();
+        related_secrets.dedup();
+        let key = (
+            verifier.name().to_string(),
+            finding.secret.clone(),
+            related_secrets,
+        );
+        let status = match cache.get(&key) {
+            Some(status) => *status,
+            None => {
+                if let Some(last) = last_request {
+                    let elapsed = last.elapsed();
+                    if elapsed < interval {
+                        thread::sleep(interval - elapsed);
+                    }
+                }
+                last_request = Some(Instant::now());
+
+                let status = verifier.verify(finding, &related);
+                if status != VerificationStatus::Unknown {
+                    cache.insert(key, status);
+                }
+                status
+            }
+        };
+        results.push((idx, status));
+    }
+
+    for (idx, status) in results {
+        findings[idx].verification = Some(status);
+    }
+}
+
+/// Verifiers for the providers fastsecret knows how to check
+#[cfg(feature = "verify")]
+pub fn builtin_verifiers(timeout: Duration) -> Vec<Box<dyn Verifier>> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .user_agent(concat!("fastsecret/", env!("CARGO_PKG_VERSION")))
+        .build();
+    vec![
+        Box::new(providers::GitHub {
+            agent: agent.clone(),
+        }),
+        Box::new(providers::Stripe {
+            agent: agent.clone(),
+        }),
+        Box::new(providers::Aws { agent }),
+    ]
+}
+
+#[cfg(feature = "verify")]
+mod providers {
+    use std::time::SystemTime;
+
+    use super::{VerificationStatus, Verifier};
+    use crate::scanner::Finding;
+    use crate::sigv4::{self, amz_date};
+
+    /// Map an HTTP result to a status: 2xx verified, listed codes invalid
+    fn status_of(
+        result: Result<ureq::Response, ureq::Error>,
+        invalid: &[u16],
+    ) -> VerificationStatus {
+        match result {
+            Ok(_) => VerificationStatus::Verified,
+            Err(ureq::Error::Status(code, _)) if invalid.contains(&code) => {
+                VerificationStatus::Invalid
+            }
+            Err(_) => VerificationStatus::Unknown,
+        }
+    }
+
+    /// GitHub tokens, checked with `GET /user`
+    pub struct GitHub {
+        pub agent: ureq::Agent,
+    }
+
+    impl Verifier for GitHub {
+        fn name(&self) -> &str {
+            "github"
+        }
+
+        fn verify(&self, finding: &Finding, _related: &[&Finding]) -> VerificationStatus {
+            let result = self
+                .agent
+                .get("https://api.github.com/user")
+                .set("Authorization", &format!("token {}", finding.secret))
+                .set("Accept", "application/vnd.github+json")
+                .call();
+            status_of(result, &[401])
+        }
+    }
+
+    /// Stripe keys, checked with `GET /v1/account`
+    ///
+    /// A 403 means the key is live but restricted, so it counts as verified.
+    pub struct Stripe {
+        pub agent: ureq::Agent,
+    }
+
+    impl Verifier for Stripe {
+        fn name(&self) -> &str {
+            "stripe"
+        }
+
+        fn verify(&self, finding: &Finding, _related: &[&Finding]) -> VerificationStatus {
+            let result = self
+                .agent
+                .get("https://api.stripe.com/v1/account")
+                .set("Authorization", &format!("Bearer {}", finding.secret))
+                .call();
+            match result {
+                Err(ureq::Error::Status(403, _)) => VerificationStatus::Verified,
+                other => status_of(other, &[401]),
+            }
+        }
+    }
+
+    /// AWS access keys, checked with STS `GetCallerIdentity`
+    ///
+    /// Needs the matching secret access key from the same file; without it
+    /// the status is unknown.
+    pub struct Aws {
+        pub agent: ureq::Agent,
+    }
+
+    impl Verifier for Aws {
+        fn name(&self) -> &str {
+            "aws"
+        }
+
+        fn verify(&self, finding: &Finding, related: &[&Finding]) -> VerificationStatus {
+            let Some(secret_key) = related
+                .iter()
+                .find(|f| f.rule_id == "aws-secret-access-key")
+                .map(|f| f.secret.as_str())
+            else {
+                return VerificationStatus::Unknown;
+            };
+
+            let body = "Action=GetCallerIdentity&Version=2011-06-15";
+            let amz_date = amz_date(SystemTime::now());
+            let authorization = sigv4_authorization(&finding.secret, secret_key, &amz_date, body);
+            let result = self
+                .agent
+                .post("https://sts.amazonaws.com/")
+                .set("Content-Type", "application/x-www-form-urlencoded")
+                .set("X-Amz-Date", &amz_date)
+                .set("Authorization", &authorization)
+                .send_string(body);
+            status_of(result, &[403])
+        }
+    }
+
+    /// Signature Version 4 `Authorization` header for an STS POST request
+    fn sigv4_authorization(key_id: &str, secret: &str, amz_date: &str, body: &str) -> String {
+        let credentials = sigv4::Credentials {
+            key_id: key_id.to_string(),
+            secret: secret.to_string(),
+            session_token: None,
+        };
+        let request = sigv4::Request {
+            method: "POST",
+            path: "/",
+            query: "",
+            headers: &[
+                ("content-type", "application/x-www-form-urlencoded"),
+                ("host", "sts.amazonaws.com"),
+                ("x-amz-date", amz_date),
+            ],
+            payload_hash: &sigv4::hex_sha256(body.as_bytes()),
+        };
+        sigv4::authorization(&credentials, "us-east-1", "sts", amz_date, &request)
+    }
+}
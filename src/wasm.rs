@@ -0,0 +1,80 @@
+//! JavaScript bindings for wasm32 builds
+//!
+//! Exposes in-memory scanning to browsers and Node through wasm-bindgen, so
+//! web-based review tools run exactly the rules the CLI does. Nothing here
+//! touches the filesystem or spawns threads; build with
+//! `wasm-pack build --target web -- --no-default-features --features wasm`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::report::json::JsonFinding;
+use crate::rules::{load_builtin_rules, parse_rule_file, CompiledRuleSet};
+use crate::scanner::{scan_lines, ScanOptions};
+
+/// A scanner with its rules compiled once, for scanning many buffers
+#[wasm_bindgen]
+pub struct WasmScanner {
+    rule_set: CompiledRuleSet,
+    options: ScanOptions,
+}
+
+#[wasm_bindgen]
+impl WasmScanner {
+    /// Compile the built-in rules plus any custom rules given as YAML (the
+    /// same format as a `--rules` file)
+    #[wasm_bindgen(constructor)]
+    pub fn new(rules: Option<String>) -> Result<WasmScanner, JsError> {
+        let mut all = load_builtin_rules();
+        if let Some(yaml) = rules.filter(|y| !y.trim().is_empty()) {
+            let custom = parse_rule_file(&yaml).map_err(to_js_error)?;
+            if let Some(target) = custom.merge_into(&mut all).first() {
+                return Err(JsError::new(&format!(
+                    "cannot override unknown rule '{}'",
+                    target
+                )));
+            }
+        }
+        Ok(WasmScanner {
+            rule_set: CompiledRuleSet::new(&all).map_err(to_js_error)?,
+            options: ScanOptions {
+                redact: true,
+                ..ScanOptions::default()
+            },
+        })
+    }
+
+    /// Scan `content`, reporting findings under `filename` (default
+    /// `<input>`); returns an array of finding objects
+    pub fn scan(&self, content: &str, filename: Option<String>) -> Result<JsValue, JsError> {
+        let name = filename.as_deref().unwrap_or("<input>");
+        let mut findings = Vec::new();
+        scan_lines(
+            content.as_bytes(),
+            name,
+            &self.rule_set,
+            &self.options,
+            &mut findings,
+        )
+        .map_err(to_js_error)?;
+
+        // Same shape as the CLI's JSON output
+        let findings: Vec<JsonFinding> = findings.iter().map(JsonFinding::from).collect();
+        serde_wasm_bindgen::to_value(&findings).map_err(to_js_error)
+    }
+}
+
+/// Scan `content` with the built-in rules plus optional custom YAML rules
+#[wasm_bindgen]
+pub fn scan_text(content: &str, rules: Option<String>) -> Result<JsValue, JsError> {
+    WasmScanner::new(rules)?.scan(content, None)
+}
+
+/// Every built-in rule, as an array of rule objects
+#[wasm_bindgen]
+pub fn builtin_rules() -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(&load_builtin_rules()).map_err(to_js_error)
+}
+
+fn to_js_error(e: impl std::fmt::Display) -> JsError {
+    JsError::new(&e.to_string())
+}
@@ -0,0 +1,178 @@
+//! Watch mode
+//!
+//! Scans a directory once, then listens for filesystem changes and rescans
+//! only the files that changed. Each rescan is compared with the file's
+//! previous findings (by fingerprint), so callers hear about secrets as they
+//! are introduced and removed rather than about the whole tree again.
+//! [`watch_with_metrics`] also counts the scans in a
+//! [`Metrics`](crate::metrics::Metrics) for export to Prometheus.
+
+use anyhow::{bail, Context, Result};
+use notify::RecursiveMode;
+use notify_debouncer_mini::new_debouncer;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::metrics::Metrics;
+use crate::scanner::{
+    collect_files, scan_file, set_relative_paths, Finding, ScanReport, Scanner, IGNORE_FILE_NAME,
+};
+
+/// Something that happened while watching
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// The initial scan of the whole directory finished
+    Initial(ScanReport),
+    /// A file was created, modified, or removed and its findings changed
+    Changed {
+        path: PathBuf,
+        /// Findings that weren't in the file before
+        new: Vec<Finding>,
+        /// Earlier findings that are gone now
+        resolved: Vec<Finding>,
+    },
+}
+
+/// Scan `root`, then rescan files as they change until `on_event` returns
+/// `false`
+///
+/// Changes are batched for `debounce` before being rescanned, so an editor
+/// saving a file in several steps produces one event. Only files a full scan
+/// would include are rescanned; ignore files and skipped directories are
+/// honored as usual.
+pub fn watch(
+    scanner: &Scanner,
+    root: &Path,
+    debounce: Duration,
+    on_event: impl FnMut(WatchEvent) -> bool,
+) -> Result<()> {
+    watch_with_metrics(scanner, root, debounce, &Metrics::new(), on_event)
+}
+
+/// [`watch`], counting the initial scan and each batch of rescans in
+/// `metrics`
+pub fn watch_with_metrics(
+    scanner: &Scanner,
+    root: &Path,
+    debounce: Duration,
+    metrics: &Metrics,
+    mut on_event: impl FnMut(WatchEvent) -> bool,
+) -> Result<()> {
+    if !root.is_dir() {
+        bail!("'{}' is not a directory", root.display());
+    }
+    let options = &scanner.options().rooted_at(root);
+    let canonical_root = root
+        .canonicalize()
+        .with_context(|| format!("failed to watch '{}'", root.display()))?;
+
+    // Subscribe before the initial scan so no change slips through
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(debounce, tx).context("failed to start file watcher")?;
+    debouncer
+        .watcher()
+        .watch(&canonical_root, RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch '{}'", root.display()))?;
+
+    let report = scanner.scan_path(root)?;
+    metrics.record_scan(report.stats.duration, &report.findings);
+    for error in &report.errors {
+        metrics.record_error(error.kind.as_str());
+    }
+    let mut known: HashMap<PathBuf, Vec<Finding>> = HashMap::new();
+    for finding in &report.findings {
+        let path = PathBuf::from(finding.file.split('!').next().unwrap_or(&finding.file));
+        known.entry(path).or_default().push(finding.clone());
+    }
+    let mut files: HashSet<PathBuf> = collect_files(root, options, &mut Vec::new())?
+        .into_iter()
+        .collect();
+    if !on_event(WatchEvent::Initial(report)) {
+        return Ok(());
+    }
+
+    for batch in rx {
+        let Ok(events) = batch else {
+            continue;
+        };
+
+        // Map notification paths back onto `root` so findings read the
+        // same as in the initial scan
+        let mut changed: Vec<PathBuf> = events
+            .iter()
+            .filter_map(|e| e.path.strip_prefix(&canonical_root).ok())
+            .map(|relative| root.join(relative))
+            .collect();
+        changed.sort();
+        changed.dedup();
+
+        // New files and edited ignore rules change what a full scan covers
+        let rewalk = changed.iter().any(|p| {
+            (p.is_file() && !files.contains(p))
+                || p.file_name()
+                    .is_some_and(|n| n == ".gitignore" || n == ".ignore" || n == IGNORE_FILE_NAME)
+        });
+        if rewalk {
+            files = collect_files(root, options, &mut Vec::new())?
+                .into_iter()
+                .collect();
+        }
+
+        // Rescan first so the batch is timed apart from the callbacks
+        let started = Instant::now();
+        let mut rescanned = Vec::new();
+        for path in changed {
+            let mut current = Vec::new();
+            if files.contains(&path) && path.is_file() {
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy();
+                let options = options.for_path(&relative);
+                if let Err(e) = scan_file(&path, scanner.rule_set(), &options, &mut current) {
+                    metrics.record_error(e.kind.as_str());
+                }
+                set_relative_paths(root, &path, &mut current);
+            } else if !known.contains_key(&path) {
+                continue;
+            }
+            rescanned.push((path, current));
+        }
+        if !rescanned.is_empty() {
+            let findings: Vec<Finding> = rescanned
+                .iter()
+                .flat_map(|(_, current)| current.iter().cloned())
+                .collect();
+            metrics.record_scan(started.elapsed(), &findings);
+        }
+
+        for (path, current) in rescanned {
+            let previous = known.remove(&path).unwrap_or_default();
+            let before: HashSet<String> = previous.iter().map(Finding::fingerprint).collect();
+            let after: HashSet<String> = current.iter().map(Finding::fingerprint).collect();
+            let new: Vec<Finding> = current
+                .iter()
+                .filter(|f| !before.contains(&f.fingerprint()))
+                .cloned()
+                .collect();
+            let resolved: Vec<Finding> = previous
+                .into_iter()
+                .filter(|f| !after.contains(&f.fingerprint()))
+                .collect();
+            if !current.is_empty() {
+                known.insert(path.clone(), current);
+            }
+
+            if new.is_empty() && resolved.is_empty() {
+                continue;
+            }
+            if !on_event(WatchEvent::Changed {
+                path,
+                new,
+                resolved,
+            }) {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
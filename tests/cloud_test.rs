@@ -0,0 +1,138 @@
+#![cfg(feature = "cloud")]
+
+use fastsecret::cloud::{scan_bucket, AwsCredentials, BucketOptions, BucketUrl, Provider};
+use fastsecret::rules::{load_builtin_rules, CompiledRuleSet};
+use fastsecret::scanner::{ScanErrorKind, ScanOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+const LISTING: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>dumps</Name><Prefix></Prefix><KeyCount>4</KeyCount><IsTruncated>false</IsTruncated>
+  <Contents><Key>config/</Key><Size>0</Size></Contents>
+  <Contents><Key>config/app &amp; db.env</Key><Size>42</Size></Contents>
+  <Contents><Key>logs/huge.log</Key><Size>999999999</Size></Contents>
+  <Contents><Key>logo.png</Key><Size>4</Size></Contents>
+</ListBucketResult>"#;
+
+/// Paths requested, and whether each request was signed
+type Requests = Arc<Mutex<Vec<(String, bool)>>>;
+
+/// Serve a fake S3 bucket at the returned endpoint
+fn serve_bucket(key: String) -> (String, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut reader = BufReader::new(stream.unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut signed = false;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                signed |= line
+                    .to_lowercase()
+                    .starts_with("authorization: aws4-hmac-sha256");
+            }
+            let path = request_line.split(' ').nth(1).unwrap().to_string();
+            let body: Vec<u8> = if path.starts_with("/dumps?") {
+                LISTING.as_bytes().to_vec()
+            } else if path == "/dumps/config/app%20%26%20db.env" {
+                format!("STRIPE_KEY={}\n", key).into_bytes()
+            } else if path == "/dumps/logo.png" {
+                b"\x89PNG\0\0".to_vec()
+            } else {
+                Vec::new()
+            };
+            let status = if body.is_empty() {
+                "404 Not Found"
+            } else {
+                "200 OK"
+            };
+            let stream = reader.get_mut();
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+            seen.lock().unwrap().push((path, signed));
+        }
+    });
+    (endpoint, requests)
+}
+
+#[test]
+fn test_bucket_url() {
+    let url = BucketUrl::parse("s3://dumps/backups/2024").unwrap();
+    assert_eq!(url.provider, Provider::S3);
+    assert_eq!(
+        (url.bucket.as_str(), url.prefix.as_str()),
+        ("dumps", "backups/2024")
+    );
+    assert_eq!(url.object_url("a.env"), "s3://dumps/a.env");
+
+    let url = BucketUrl::parse("gs://assets").unwrap();
+    assert_eq!((url.provider, url.prefix.as_str()), (Provider::Gcs, ""));
+    assert!(BucketUrl::parse("s3://").is_none());
+    assert!(BucketUrl::parse("./dumps").is_none());
+}
+
+#[test]
+fn test_scan_s3_bucket() -> anyhow::Result<()> {
+    let key = format!("sk_live_{}", "a1B2c3D4e5".repeat(3));
+    let (endpoint, requests) = serve_bucket(key.clone());
+    let bucket = BucketOptions {
+        endpoint: Some(endpoint),
+        aws_credentials: Some(AwsCredentials {
+            key_id: "AKIDEXAMPLE".into(),
+            secret: "secret".into(),
+            session_token: None,
+        }),
+        ..Default::default()
+    };
+    let rule_set = CompiledRuleSet::new(&load_builtin_rules())?;
+    let options = ScanOptions {
+        threads: 1,
+        ..Default::default()
+    };
+    let url = BucketUrl::parse("s3://dumps").unwrap();
+
+    let scan = scan_bucket(&url, &bucket, &rule_set, &options)?;
+    let stripe = scan
+        .findings
+        .iter()
+        .find(|f| f.rule_id == "stripe-live-secret-key")
+        .expect("stripe finding");
+    assert_eq!(stripe.file, "s3://dumps/config/app & db.env");
+    assert_eq!(stripe.secret, key);
+    // The placeholder is dropped; the huge log and the image are skipped
+    assert_eq!((scan.objects_scanned, scan.objects_skipped), (1, 2));
+    // Only the huge log is an error; skipping binary files is on purpose
+    assert_eq!(scan.errors.len(), 1);
+    assert_eq!(scan.errors[0].file, "s3://dumps/logs/huge.log");
+    assert_eq!(scan.errors[0].kind, ScanErrorKind::TooLarge);
+    assert!(!scan.truncated);
+
+    let requests = requests.lock().unwrap().clone();
+    assert!(requests.iter().all(|(_, signed)| *signed));
+    assert!(!requests.iter().any(|(path, _)| path.contains("huge")));
+
+    let limited = BucketOptions {
+        max_objects: 1,
+        ..bucket
+    };
+    let scan = scan_bucket(&url, &limited, &rule_set, &options)?;
+    assert!(scan.truncated);
+    assert_eq!(scan.objects_scanned, 1);
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use fastsecret::corpus::{Corpus, CorpusOptions};
+use fastsecret::scanner::Scanner;
+
+#[test]
+fn test_corpus_is_deterministic_and_secrets_are_found() {
+    let options = CorpusOptions {
+        files: 14,
+        file_size: 4 << 10,
+        secrets_per_file: 2,
+        seed: 7,
+    };
+    let corpus = Corpus::generate(&options);
+    assert_eq!(corpus.files.len(), 14);
+    assert_eq!(corpus.secrets.len(), 28);
+    assert!(corpus.bytes() >= 14 * 4096);
+    assert_eq!(corpus.files, Corpus::generate(&options).files);
+    assert_ne!(
+        corpus.files,
+        Corpus::generate(&CorpusOptions { seed: 8, ..options }).files
+    );
+
+    let scanner = Scanner::builder().build().unwrap();
+    let mut found = HashSet::new();
+    for (name, content) in &corpus.files {
+        let report = scanner.scan_bytes(name, content.as_bytes()).unwrap();
+        found.extend(report.findings.into_iter().map(|f| f.secret));
+    }
+    for secret in &corpus.secrets {
+        assert!(found.contains(secret), "missed planted secret {}", secret);
+    }
+}
+
+#[test]
+fn test_corpus_write_to() {
+    let dir = tempfile::tempdir().unwrap();
+    let corpus = Corpus::generate(&CorpusOptions {
+        files: 3,
+        file_size: 1024,
+        ..CorpusOptions::default()
+    });
+    corpus.write_to(dir.path()).unwrap();
+    for (name, content) in &corpus.files {
+        assert_eq!(
+            &std::fs::read_to_string(dir.path().join(name)).unwrap(),
+            content
+        );
+    }
+}
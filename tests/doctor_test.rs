@@ -0,0 +1,74 @@
+use std::process::Command;
+
+fn doctor(dir: &std::path::Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_fastsecret"))
+        .arg("doctor")
+        .arg(dir)
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("fastsecret should run");
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn test_doctor_reports_a_healthy_setup() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(
+        dir.path().join("fastsecret.toml"),
+        "[rules]\ncustom_rules = \"team.yml\"\n",
+    )?;
+    std::fs::write(
+        dir.path().join("team.yml"),
+        "- id: myco-token\n  name: MyCo Token\n  pattern: 'myco_[a-z0-9]{8}'\n  version: \"1.0\"\n",
+    )?;
+    std::fs::write(dir.path().join("app.env"), "name=app\n")?;
+
+    let (ok, out) = doctor(dir.path(), &[]);
+    assert!(ok, "{}", out);
+    assert!(out.contains("fastsecret.toml is valid"), "{}", out);
+    assert!(
+        out.contains("team.yml: 1 rule(s), 0 override(s)"),
+        "{}",
+        out
+    );
+    assert!(out.contains("builtin/aws"), "{}", out);
+    assert!(out.contains("3 file(s)"), "{}", out);
+    assert!(out.contains("Ready to scan"), "{}", out);
+    Ok(())
+}
+
+#[test]
+fn test_doctor_fails_on_broken_rules_and_baseline() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(
+        dir.path().join("team.yml"),
+        "- id: broken\n  name: Broken\n  pattern: 'token_([a-z'\n",
+    )?;
+    std::fs::write(dir.path().join("baseline.json"), "{\"version\": 1}")?;
+    let rules = dir.path().join("team.yml");
+    let baseline = dir.path().join("baseline.json");
+
+    let (ok, out) = doctor(
+        dir.path(),
+        &[
+            "--no-config",
+            "--rules",
+            rules.to_str().unwrap(),
+            "--baseline",
+            baseline.to_str().unwrap(),
+        ],
+    );
+    assert!(!ok, "{}", out);
+    assert!(out.contains("broken"), "{}", out);
+    assert!(out.contains("invalid baseline file"), "{}", out);
+    assert!(
+        out.contains("skipped until the problems above are fixed"),
+        "{}",
+        out
+    );
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use fastsecret::entropy::{candidate_tokens, shannon_entropy, EntropyOptions, ENTROPY_RULE_NAME};
+use fastsecret::rules::load_builtin_rules;
+use fastsecret::scanner::{scan_path_with_options, ScanOptions};
+
+#[test]
+fn test_shannon_entropy() {
+    assert_eq!(shannon_entropy(""), 0.0);
+    assert_eq!(shannon_entropy("aaaaaaaa"), 0.0);
+    assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+    assert!(shannon_entropy("wJalrXUtnFEMI/K7MDENG/bPxRfiCYzQ8kLp2vN") > 4.5);
+}
+
+#[test]
+fn test_candidate_tokens() {
+    let tokens = candidate_tokens("key = \"abcdefghijklmnopqrstuvwxyz\"; short", 20);
+    assert_eq!(tokens, vec![(7, "abcdefghijklmnopqrstuvwxyz")]);
+}
+
+#[test]
+fn test_entropy_engine_reports_random_tokens() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("settings.ini");
+    std::fs::write(
+        &file,
+        "name = this_is_a_plain_identifier_name\nsecret = q8Zr2XvN0pLw7KsT4bYh9JmC1fDg6EoA\n",
+    )?;
+
+    let options = ScanOptions {
+        entropy: Some(EntropyOptions::default()),
+        ..ScanOptions::default()
+    };
+    let findings = scan_path_with_options(file.to_str().unwrap(), &[], &options)?;
+
+    let lines: Vec<usize> = findings
+        .iter()
+        .filter(|f| f.rule_name == ENTROPY_RULE_NAME)
+        .map(|f| f.line)
+        .collect();
+    assert_eq!(lines, vec![2]);
+
+    Ok(())
+}
+
+#[test]
+fn test_rule_entropy_filters_low_entropy_values() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("app.cfg");
+    std::fs::write(
+        &file,
+        "password = aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\ntoken = q8Zr2XvN0pLw7KsT4bYh9JmC1fDg6EoA\n",
+    )?;
+
+    let findings = scan_path_with_options(
+        file.to_str().unwrap(),
+        &load_builtin_rules(),
+        &ScanOptions::default(),
+    )?;
+    let lines: Vec<usize> = findings
+        .iter()
+        .filter(|f| f.rule_name == "Generic High-Entropy Secret")
+        .map(|f| f.line)
+        .collect();
+    assert_eq!(lines, vec![2], "Repetitive values should not match");
+
+    Ok(())
+}
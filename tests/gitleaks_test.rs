@@ -0,0 +1,73 @@
+use fastsecret::rules::{load_gitleaks_config, load_rules, RulesFormat};
+use fastsecret::scanner::scan_path;
+
+const GITLEAKS_TOML: &str = r#"
+title = "team rules"
+
+[allowlist]
+paths = ['''(^|/)vendor/''']
+
+[[rules]]
+id = "acme-api-key"
+description = "Acme API Key"
+regex = '''(?i)acme[_-]?key\s*=\s*['"]?(acme_[a-z0-9]{16})'''
+secretGroup = 1
+keywords = ["acme"]
+entropy = 2.5
+
+[rules.allowlist]
+stopwords = ["acme_0000"]
+regexes = ['''example''']
+
+[[rules]]
+id = "pkcs12-file"
+description = "PKCS12 file"
+path = '''\.p12$'''
+"#;
+
+#[test]
+fn test_load_gitleaks_config() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("gitleaks.toml");
+    std::fs::write(&path, GITLEAKS_TOML)?;
+
+    let rules = load_gitleaks_config(path.to_str().unwrap())?;
+    assert_eq!(rules.len(), 1, "Path-only rules should be skipped");
+
+    let rule = &rules[0];
+    assert_eq!(rule.id, "acme-api-key");
+    assert_eq!(rule.name, "Acme API Key");
+    assert_eq!(rule.secret_group, Some(1));
+    assert_eq!(rule.keywords, vec!["acme"]);
+    assert_eq!(rule.entropy, Some(2.5));
+    assert_eq!(rule.allowlist.path_regexes, vec!["(^|/)vendor/"]);
+    assert!(rule.allowlist.regexes.contains(&"example".to_string()));
+    assert!(rule.allowlist.regexes.contains(&"acme_0000".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn test_gitleaks_rules_scan() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let rules_path = dir.path().join("gitleaks.toml");
+    std::fs::write(&rules_path, GITLEAKS_TOML)?;
+    let rules = load_rules(rules_path.to_str().unwrap(), RulesFormat::Gitleaks)?;
+
+    let src = dir.path().join("src");
+    let vendor = dir.path().join("vendor");
+    std::fs::create_dir_all(&src)?;
+    std::fs::create_dir_all(&vendor)?;
+    std::fs::write(
+        src.join("settings.py"),
+        "ACME_KEY = 'acme_k3y9x2m7q1w8e4r6'\nacme_key=acme_0000111122223333\n",
+    )?;
+    std::fs::write(vendor.join("lib.py"), "acme_key=acme_k3y9x2m7q1w8e4r6\n")?;
+
+    let findings = scan_path(dir.path().to_str().unwrap(), &rules, &[], false)?;
+    assert_eq!(findings.len(), 1);
+    assert!(findings[0].file.ends_with("settings.py"));
+    assert_eq!(findings[0].secret, "acme_k3y9x2m7q1w8e4r6");
+
+    Ok(())
+}